@@ -0,0 +1,30 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mavlink::{common::MavMessage, MavFrame, MavlinkVersion, Message};
+
+// Decoding arbitrary input must fail with an error instead of panicking
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, input)) = data.split_first() else {
+        return;
+    };
+    let version = if selector & 1 == 0 {
+        MavlinkVersion::V1
+    } else {
+        MavlinkVersion::V2
+    };
+
+    if let Ok(frame) = MavFrame::<MavMessage>::deser(version, input) {
+        // a decoded frame must encode to a frame that decodes again
+        let mut buf = [0u8; mavlink::MAX_FRAME_SIZE];
+        let len = frame
+            .ser(&mut buf)
+            .expect("failed to serialize decoded frame");
+        MavFrame::<MavMessage>::deser(version, &buf[..len])
+            .expect("failed to decode encoded frame");
+    }
+
+    if let Some((id, payload)) = input.split_first_chunk::<4>() {
+        let _ = MavMessage::parse(version, u32::from_le_bytes(*id), payload);
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mavlink::{
+    common::MavMessage,
+    frame_parser::{FrameParser, ParseEvent},
+    ReadVersion,
+};
+
+// Every byte fed to the parser is part of a frame, discarded or still buffered, regardless of chunking
+fuzz_target!(|data: &[u8]| {
+    let Some((&chunk_size, input)) = data.split_first() else {
+        return;
+    };
+    let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+    let mut consumed = 0;
+    for chunk in input.chunks(usize::from(chunk_size).max(1)) {
+        for event in parser.feed(chunk) {
+            match event {
+                ParseEvent::Frame(frame) => consumed += frame.raw_bytes().len(),
+                ParseEvent::Discarded(count) => consumed += count,
+            }
+        }
+    }
+    assert_eq!(consumed + parser.buffered(), input.len());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mavlink::{common::MavMessage, error::MessageReadError, peek_reader::PeekReader};
+
+// Reading from an untrusted stream must end with an error instead of panicking
+fuzz_target!(|data: &[u8]| {
+    let mut reader = PeekReader::new(data);
+    loop {
+        match mavlink::read_any_msg::<MavMessage, _>(&mut reader) {
+            Ok(_) | Err(MessageReadError::Parse(_)) => {}
+            Err(MessageReadError::Io(_)) => break,
+        }
+    }
+});
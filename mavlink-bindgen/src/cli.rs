@@ -2,7 +2,8 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use mavlink_bindgen::{
-    emit_cargo_build_messages, format_generated_code, generate, BindGenError, XmlDefinitions,
+    emit_cargo_build_messages, format_generated_code, generate_with_options, BindGenError,
+    GeneratorOptions, XmlDefinitions,
 };
 
 #[derive(Parser)]
@@ -18,13 +19,31 @@ struct Cli {
     /// prints cargo build messages indicating when the code has to be rebuild
     #[arg(long)]
     emit_cargo_build_messages: bool,
+    /// box messages with a payload longer than this many bytes in the generated MavMessage enum
+    #[arg(long, value_name = "BYTES")]
+    box_threshold: Option<usize>,
+    /// emit borrowed views of the message payloads
+    #[arg(long)]
+    emit_views: bool,
+    /// emit the schemas of the messages and enums
+    #[arg(long)]
+    emit_schemas: bool,
+    /// prints the payload length of the generated messages of each dialect
+    #[arg(long)]
+    payload_report: bool,
 }
 
 pub fn main() -> Result<(), BindGenError> {
     let args = Cli::parse();
-    let result = generate(
+    let options = GeneratorOptions {
+        box_threshold: args.box_threshold,
+        emit_views: args.emit_views,
+        emit_schemas: args.emit_schemas,
+    };
+    let result = generate_with_options(
         XmlDefinitions::Directory(args.definitions_dir),
         args.destination_dir,
+        &options,
     )?;
 
     if args.format_generated_code {
@@ -35,5 +54,11 @@ pub fn main() -> Result<(), BindGenError> {
         emit_cargo_build_messages(&result);
     }
 
+    if args.payload_report {
+        for binding in &result.bindings {
+            print!("{}", binding.payload_report);
+        }
+    }
+
     Ok(())
 }
@@ -1,4 +1,5 @@
 pub use crate::error::BindGenError;
+use std::fmt;
 use std::fs::{read_dir, File};
 use std::io::{self, BufWriter};
 use std::ops::Deref;
@@ -15,6 +16,7 @@ pub struct GeneratedBinding {
     pub module_name: String,
     pub mavlink_xml: PathBuf,
     pub rust_module: PathBuf,
+    pub payload_report: PayloadReport,
 }
 
 #[derive(Debug)]
@@ -23,6 +25,77 @@ pub struct GeneratedBindings {
     pub mod_rs: PathBuf,
 }
 
+/// Options controlling the generated code.
+#[derive(Debug, Clone, Default)]
+pub struct GeneratorOptions {
+    /// Box the `MavMessage` variants of messages with a payload longer than this many bytes.
+    ///
+    /// The size of the `MavMessage` enum is set by its largest variant, boxing large messages shrinks it
+    /// considerably at the cost of an allocation per large message. The generated code uses `::alloc::boxed::Box`,
+    /// so the crate including it has to declare `extern crate alloc;`.
+    pub box_threshold: Option<usize>,
+    /// Emit a borrowed `<Message>View<'a>` per message, reading its fields directly from the payload bytes.
+    pub emit_views: bool,
+    /// Emit the schemas of the messages and enums and the dynamic field access of `MavMessage`, see
+    /// `mavlink_core::schema`.
+    pub emit_schemas: bool,
+}
+
+/// Payload lengths of the messages of a generated `MavMessage` enum.
+///
+/// The in-memory size of the generated types is only known to the compiler, use `core::mem::size_of` on the
+/// generated `MavMessage` or `*_DATA` types for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadReport {
+    pub dialect_name: String,
+    /// Messages ordered by decreasing payload length.
+    pub messages: Vec<MessagePayload>,
+}
+
+/// Payload length of a single message in a [`PayloadReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessagePayload {
+    pub name: String,
+    /// Maximum payload length in bytes.
+    pub payload_len: usize,
+    /// Whether the `MavMessage` variant of the message is boxed.
+    pub boxed: bool,
+}
+
+impl PayloadReport {
+    /// Largest payload length of a variant that is stored inline in the `MavMessage` enum.
+    pub fn largest_inline_len(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|message| !message.boxed)
+            .map(|message| message.payload_len)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl fmt::Display for PayloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let boxed = self.messages.iter().filter(|message| message.boxed).count();
+        writeln!(
+            f,
+            "{}: {} messages, {boxed} boxed, largest inline payload {} bytes",
+            self.dialect_name,
+            self.messages.len(),
+            self.largest_inline_len(),
+        )?;
+        for message in &self.messages {
+            let boxed = if message.boxed { " (boxed)" } else { "" };
+            writeln!(
+                f,
+                "  {:<40} {:>3} bytes{boxed}",
+                message.name, message.payload_len
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Specifies the source(s) of MAVLink XML definition files used for generating
 /// Rust MAVLink dialect bindings.
 pub enum XmlDefinitions<T: AsRef<Path>> {
@@ -39,6 +112,19 @@ pub enum XmlDefinitions<T: AsRef<Path>> {
 pub fn generate<P1: AsRef<Path>, P2: AsRef<Path>>(
     xml_definitions: XmlDefinitions<P1>,
     destination_dir: P2,
+) -> Result<GeneratedBindings, BindGenError> {
+    generate_with_options(
+        xml_definitions,
+        destination_dir,
+        &GeneratorOptions::default(),
+    )
+}
+
+/// Generate Rust MAVLink dialect binding like [`generate`], using the given `options`.
+pub fn generate_with_options<P1: AsRef<Path>, P2: AsRef<Path>>(
+    xml_definitions: XmlDefinitions<P1>,
+    destination_dir: P2,
+    options: &GeneratorOptions,
 ) -> Result<GeneratedBindings, BindGenError> {
     let destination_dir = destination_dir.as_ref();
 
@@ -61,7 +147,7 @@ pub fn generate<P1: AsRef<Path>, P2: AsRef<Path>>(
             for file in files {
                 let file = file.as_ref();
 
-                bindings.push(generate_single_file(file, destination_dir)?);
+                bindings.push(generate_single_file(file, destination_dir, options)?);
             }
         }
         XmlDefinitions::Directory(definitions_dir) => {
@@ -98,7 +184,11 @@ pub fn generate<P1: AsRef<Path>, P2: AsRef<Path>>(
                     continue;
                 }
 
-                bindings.push(generate_single_file(entry.path(), destination_dir)?);
+                bindings.push(generate_single_file(
+                    entry.path(),
+                    destination_dir,
+                    options,
+                )?);
             }
         }
     }
@@ -133,6 +223,7 @@ pub fn generate<P1: AsRef<Path>, P2: AsRef<Path>>(
 fn generate_single_file<P1: AsRef<Path>, P2: AsRef<Path>>(
     source_file: P1,
     destination_dir: P2,
+    options: &GeneratorOptions,
 ) -> Result<GeneratedBinding, BindGenError> {
     let source_file = source_file.as_ref();
     let destination_dir = destination_dir.as_ref();
@@ -173,12 +264,14 @@ fn generate_single_file<P1: AsRef<Path>, P2: AsRef<Path>>(
     })?);
 
     // codegen
-    parser::generate(definitions_dir, &definition_filename, &mut outf)?;
+    let payload_report =
+        parser::generate_with_options(definitions_dir, &definition_filename, &mut outf, options)?;
 
     Ok(GeneratedBinding {
         module_name,
         mavlink_xml: source_file.to_owned(),
         rust_module: dest_path,
+        payload_report,
     })
 }
 
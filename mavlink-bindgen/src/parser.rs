@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::BindGenError;
 use crate::util;
+use crate::{GeneratorOptions, MessagePayload, PayloadReport};
 
 lazy_static! {
     static ref URL_REGEX: Regex = {
@@ -123,17 +124,17 @@ impl MavProfile {
 
     /// Emit rust messages
     #[inline(always)]
-    fn emit_msgs(&self) -> Vec<TokenStream> {
+    fn emit_msgs(&self, options: &GeneratorOptions) -> Vec<TokenStream> {
         self.messages
             .values()
-            .map(|d| d.emit_rust(self.version.is_some()))
+            .map(|d| d.emit_rust(self.version.is_some(), options))
             .collect()
     }
 
     /// Emit rust enums
     #[inline(always)]
-    fn emit_enums(&self) -> Vec<TokenStream> {
-        self.enums.values().map(|d| d.emit_rust()).collect()
+    fn emit_enums(&self, options: &GeneratorOptions) -> Vec<TokenStream> {
+        self.enums.values().map(|d| d.emit_rust(options)).collect()
     }
 
     #[inline(always)]
@@ -170,36 +171,110 @@ impl MavProfile {
             .collect()
     }
 
-    fn emit_rust(&self, dialect_name: &str) -> TokenStream {
+    /// Whether the `MavMessage` variant of the given message is boxed
+    fn is_boxed(msg: &MavMessage, options: &GeneratorOptions) -> bool {
+        options
+            .box_threshold
+            .is_some_and(|threshold| msg.payload_len() > threshold)
+    }
+
+    /// Emit the types held by the `MavMessage` variants
+    #[inline(always)]
+    fn emit_variant_types(&self, options: &GeneratorOptions) -> Vec<TokenStream> {
+        self.messages
+            .values()
+            .map(|msg| {
+                let struct_name = msg.emit_struct_name();
+                if Self::is_boxed(msg, options) {
+                    quote!(::alloc::boxed::Box<#struct_name>)
+                } else {
+                    struct_name
+                }
+            })
+            .collect()
+    }
+
+    /// Emit expressions wrapping the given values into `MavMessage` variants
+    #[inline(always)]
+    fn emit_variant_values(
+        &self,
+        options: &GeneratorOptions,
+        value: impl Fn(&TokenStream) -> TokenStream,
+    ) -> Vec<TokenStream> {
+        self.messages
+            .values()
+            .map(|msg| {
+                let name = format_ident!("{}", msg.name);
+                let value = value(&msg.emit_struct_name());
+                if Self::is_boxed(msg, options) {
+                    quote!(Self::#name(::alloc::boxed::Box::new(#value)))
+                } else {
+                    quote!(Self::#name(#value))
+                }
+            })
+            .collect()
+    }
+
+    /// Payload lengths of the messages, ordered by decreasing payload length
+    fn payload_report(&self, dialect_name: &str, options: &GeneratorOptions) -> PayloadReport {
+        let mut messages: Vec<MessagePayload> = self
+            .messages
+            .values()
+            .map(|msg| MessagePayload {
+                name: msg.name.clone(),
+                payload_len: msg.payload_len(),
+                boxed: Self::is_boxed(msg, options),
+            })
+            .collect();
+        messages.sort_by_key(|message| std::cmp::Reverse(message.payload_len));
+        PayloadReport {
+            dialect_name: dialect_name.to_string(),
+            messages,
+        }
+    }
+
+    fn emit_rust(&self, dialect_name: &str, options: &GeneratorOptions) -> TokenStream {
         //TODO verify that id_width of u8 is OK even in mavlink v1
         let id_width = format_ident!("u32");
 
         let comment = self.emit_comments(dialect_name);
         let mav_minor_version = self.emit_minor_version();
         let mav_dialect_number = self.emit_dialect_number();
-        let msgs = self.emit_msgs();
+        let msgs = self.emit_msgs(options);
         let deprecations = self.emit_deprecations();
         let enum_names = self.emit_enum_names();
         let struct_names = self.emit_struct_names();
-        let enums = self.emit_enums();
+        let variant_types = self.emit_variant_types(options);
+        let enums = self.emit_enums(options);
 
         let variant_docs = self.emit_variant_description();
 
-        let mav_message =
-            self.emit_mav_message(&variant_docs, &deprecations, &enum_names, &struct_names);
+        let mav_message = self.emit_mav_message(
+            &variant_docs,
+            &deprecations,
+            &enum_names,
+            &variant_types,
+            options,
+        );
         let mav_message_all_ids = self.emit_mav_message_all_ids();
-        let mav_message_parse = self.emit_mav_message_parse(&enum_names, &struct_names);
+        let mav_message_parse = self.emit_mav_message_parse(&struct_names, options);
         let mav_message_crc = self.emit_mav_message_crc(&id_width, &struct_names);
         let mav_message_name = self.emit_mav_message_name(&enum_names, &struct_names);
         let mav_message_id = self.emit_mav_message_id(&enum_names, &struct_names);
         let mav_message_id_from_name = self.emit_mav_message_id_from_name(&struct_names);
         let mav_message_default_from_id =
-            self.emit_mav_message_default_from_id(&enum_names, &struct_names);
+            self.emit_mav_message_default_from_id(&struct_names, options);
         let mav_message_random_from_id =
-            self.emit_mav_message_random_from_id(&enum_names, &struct_names);
+            self.emit_mav_message_random_from_id(&struct_names, options);
         let mav_message_serialize = self.emit_mav_message_serialize(&enum_names);
         let mav_message_target_system_id = self.emit_mav_message_target_system_id();
         let mav_message_target_component_id = self.emit_mav_message_target_component_id();
+        let mav_message_from_data = self.emit_mav_message_from_data(&struct_names, options);
+        let mav_message_fields = if options.emit_schemas {
+            self.emit_mav_message_fields(&enum_names, &struct_names)
+        } else {
+            quote!()
+        };
 
         quote! {
             #comment
@@ -249,24 +324,90 @@ impl MavProfile {
                 #mav_message_target_system_id
                 #mav_message_target_component_id
             }
+
+            #(#mav_message_from_data)*
+
+            #mav_message_fields
         }
     }
 
+    /// Emit the schema lookup and dynamic field access of `MavMessage`
+    #[inline(always)]
+    fn emit_mav_message_fields(
+        &self,
+        enums: &[TokenStream],
+        structs: &[TokenStream],
+    ) -> TokenStream {
+        quote! {
+            impl ::mavlink_core::schema::MessageFields for MavMessage {
+                fn schemas() -> &'static [::mavlink_core::schema::MessageSchema] {
+                    &[#(<#structs as ::mavlink_core::schema::MessageDataSchema>::SCHEMA,)*]
+                }
+
+                fn schema(&self) -> &'static ::mavlink_core::schema::MessageSchema {
+                    match self {
+                        #(Self::#enums(..) => &<#structs as ::mavlink_core::schema::MessageDataSchema>::SCHEMA,)*
+                    }
+                }
+
+                fn for_each_field(
+                    &self,
+                    f: &mut dyn FnMut(&'static ::mavlink_core::schema::FieldSchema, ::mavlink_core::schema::FieldValue<'_>),
+                ) {
+                    match self {
+                        #(Self::#enums(body) => body.for_each_field(f),)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emit conversions from the message structs into `MavMessage`, which also box large messages
+    #[inline(always)]
+    fn emit_mav_message_from_data(
+        &self,
+        structs: &[TokenStream],
+        options: &GeneratorOptions,
+    ) -> Vec<TokenStream> {
+        let messages = self.emit_variant_values(options, |_| quote!(data));
+        structs
+            .iter()
+            .zip(messages)
+            .map(|(struct_name, message)| {
+                quote! {
+                    impl From<#struct_name> for MavMessage {
+                        fn from(data: #struct_name) -> Self {
+                            #message
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
     #[inline(always)]
     fn emit_mav_message(
         &self,
         docs: &[TokenStream],
         deprecations: &[TokenStream],
         enums: &[TokenStream],
-        structs: &[TokenStream],
+        variant_types: &[TokenStream],
+        options: &GeneratorOptions,
     ) -> TokenStream {
+        let boxed_doc = options.box_threshold.map(|threshold| {
+            let doc = format!(
+                "Messages with a payload longer than {threshold} bytes are boxed to limit the size of the enum."
+            );
+            quote!(#[doc = #doc])
+        });
         quote! {
+            #boxed_doc
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             #[cfg_attr(feature = "serde", serde(tag = "type"))]
             #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
             #[repr(u32)]
             pub enum MavMessage {
-                #(#docs #deprecations #enums(#structs),)*
+                #(#docs #deprecations #enums(#variant_types),)*
             }
         }
     }
@@ -327,15 +468,27 @@ impl MavProfile {
     #[inline(always)]
     fn emit_mav_message_parse(
         &self,
-        enums: &[TokenStream],
         structs: &[TokenStream],
+        options: &GeneratorOptions,
     ) -> TokenStream {
         let id_width = format_ident!("u32");
+        let variants = self
+            .messages
+            .values()
+            .map(|msg| {
+                let name = format_ident!("{}", msg.name);
+                if Self::is_boxed(msg, options) {
+                    quote!(map(::alloc::boxed::Box::new).map(Self::#name))
+                } else {
+                    quote!(map(Self::#name))
+                }
+            })
+            .collect::<Vec<TokenStream>>();
 
         quote! {
             fn parse(version: MavlinkVersion, id: #id_width, payload: &[u8]) -> Result<Self, ::mavlink_core::error::ParserError> {
                 match id {
-                    #(#structs::ID => #structs::deser(version, payload).map(Self::#enums),)*
+                    #(#structs::ID => #structs::deser(version, payload).#variants,)*
                     _ => {
                         Err(::mavlink_core::error::ParserError::UnknownMessage { id })
                     },
@@ -398,13 +551,26 @@ impl MavProfile {
     #[inline(always)]
     fn emit_mav_message_default_from_id(
         &self,
-        enums: &[TokenStream],
         structs: &[TokenStream],
+        options: &GeneratorOptions,
     ) -> TokenStream {
+        let messages = self
+            .messages
+            .values()
+            .map(|msg| {
+                let name = format_ident!("{}", msg.name);
+                let struct_name = msg.emit_struct_name();
+                if Self::is_boxed(msg, options) {
+                    quote!(Self::#name(::alloc::boxed::Box::default()))
+                } else {
+                    quote!(Self::#name(#struct_name::default()))
+                }
+            })
+            .collect::<Vec<TokenStream>>();
         quote! {
             fn default_message_from_id(id: u32) -> Option<Self> {
                 match id {
-                    #(#structs::ID => Some(Self::#enums(#structs::default())),)*
+                    #(#structs::ID => Some(#messages),)*
                     _ => {
                         None
                     }
@@ -416,14 +582,16 @@ impl MavProfile {
     #[inline(always)]
     fn emit_mav_message_random_from_id(
         &self,
-        enums: &[TokenStream],
         structs: &[TokenStream],
+        options: &GeneratorOptions,
     ) -> TokenStream {
+        let messages =
+            self.emit_variant_values(options, |struct_name| quote!(#struct_name::random(rng)));
         quote! {
             #[cfg(feature = "arbitrary")]
             fn random_message_from_id<R: rand::RngCore>(id: u32, rng: &mut R) -> Option<Self> {
                 match id {
-                    #(#structs::ID => Some(Self::#enums(#structs::random(rng))),)*
+                    #(#structs::ID => Some(#messages),)*
                     _ => None,
                 }
             }
@@ -515,11 +683,28 @@ impl MavEnum {
         }
     }
 
-    fn emit_defs(&self) -> Vec<TokenStream> {
+    /// Values of the entries, entries without a value follow the largest value before them
+    fn entry_values(&self) -> Vec<u32> {
         let mut cnt = 0u32;
         self.entries
             .iter()
             .map(|enum_entry| {
+                if let Some(value) = enum_entry.value {
+                    cnt = cnt.max(value);
+                    value
+                } else {
+                    cnt += 1;
+                    cnt
+                }
+            })
+            .collect()
+    }
+
+    fn emit_defs(&self) -> Vec<TokenStream> {
+        self.entries
+            .iter()
+            .zip(self.entry_values())
+            .map(|(enum_entry, entry_value)| {
                 let name = format_ident!("{}", enum_entry.name.clone());
                 let value;
 
@@ -532,14 +717,11 @@ impl MavEnum {
                     quote!()
                 };
 
-                if enum_entry.value.is_none() {
-                    cnt += 1;
-                    value = quote!(#cnt);
-                } else {
-                    let tmp_value = enum_entry.value.unwrap();
-                    cnt = cnt.max(tmp_value);
-                    let tmp = TokenStream::from_str(&tmp_value.to_string()).unwrap();
+                if enum_entry.value.is_some() {
+                    let tmp = TokenStream::from_str(&entry_value.to_string()).unwrap();
                     value = quote!(#tmp);
+                } else {
+                    value = quote!(#entry_value);
                 }
                 if self.primitive.is_some() {
                     quote! {
@@ -578,10 +760,37 @@ impl MavEnum {
             .unwrap_or_default()
     }
 
-    fn emit_rust(&self) -> TokenStream {
+    /// Emit the schema of the enum, listing its entries
+    fn emit_schema(&self) -> TokenStream {
+        let name = &self.name;
+        let bitmask = self.bitmask;
+        let entries = self
+            .entries
+            .iter()
+            .zip(self.entry_values())
+            .map(|(entry, value)| {
+                let entry_name = &entry.name;
+                let value = u64::from(value);
+                quote!(::mavlink_core::schema::EnumEntry { name: #entry_name, value: #value },)
+            });
+        quote! {
+            pub const SCHEMA: ::mavlink_core::schema::EnumSchema = ::mavlink_core::schema::EnumSchema {
+                name: #name,
+                bitmask: #bitmask,
+                entries: &[#(#entries)*],
+            };
+        }
+    }
+
+    fn emit_rust(&self, options: &GeneratorOptions) -> TokenStream {
         let defs = self.emit_defs();
         let enum_name = self.emit_name();
         let const_default = self.emit_const_default();
+        let schema = if options.emit_schemas {
+            self.emit_schema()
+        } else {
+            quote!()
+        };
 
         let deprecated = self.emit_deprecation();
 
@@ -627,6 +836,7 @@ impl MavEnum {
 
             impl #enum_name {
                 #const_default
+                #schema
             }
 
             impl Default for #enum_name {
@@ -669,6 +879,11 @@ pub struct MavMessage {
 }
 
 impl MavMessage {
+    /// Maximum length of the payload in bytes
+    fn payload_len(&self) -> usize {
+        self.fields.iter().map(|field| field.mavtype.len()).sum()
+    }
+
     /// Return Token of "MESSAGE_NAME_DATA
     /// for mavlink struct data
     fn emit_struct_name(&self) -> TokenStream {
@@ -835,7 +1050,7 @@ impl MavMessage {
         quote!(pub const DEFAULT: Self = Self { #(#initializers)* };)
     }
 
-    fn emit_rust(&self, dialect_has_version: bool) -> TokenStream {
+    fn emit_rust(&self, dialect_has_version: bool, options: &GeneratorOptions) -> TokenStream {
         let msg_name = self.emit_struct_name();
         let id = self.id;
         let name = self.name.clone();
@@ -850,11 +1065,22 @@ impl MavMessage {
         let serialize_vars = self.emit_serialize_vars();
         let const_default = self.emit_const_default(dialect_has_version);
         let default_impl = self.emit_default_impl();
+        let (schema, for_each_field) = if options.emit_schemas {
+            (self.emit_schema_impl(), self.emit_for_each_field())
+        } else {
+            (quote!(), quote!())
+        };
 
         let deprecation = self.emit_deprecation();
 
         let description = self.emit_description();
 
+        let view = if options.emit_views {
+            self.emit_view()
+        } else {
+            quote!()
+        };
+
         quote! {
             #deprecation
             #description
@@ -877,6 +1103,8 @@ impl MavMessage {
                     let mut unstructured = Unstructured::new(&buf);
                     Self::arbitrary(&mut unstructured).unwrap_or_default()
                 }
+
+                #for_each_field
             }
 
             #default_impl
@@ -897,6 +1125,103 @@ impl MavMessage {
                     #serialize_vars
                 }
             }
+
+            #schema
+
+            #view
+        }
+    }
+
+    /// Emit the schema of the message, listing its fields in serialization order
+    fn emit_schema_impl(&self) -> TokenStream {
+        let msg_name = self.emit_struct_name();
+        let id = self.id;
+        let name = &self.name;
+        let fields = self.fields.iter().map(|field| field.emit_schema());
+        quote! {
+            impl ::mavlink_core::schema::MessageDataSchema for #msg_name {
+                const SCHEMA: ::mavlink_core::schema::MessageSchema = ::mavlink_core::schema::MessageSchema {
+                    id: #id,
+                    name: #name,
+                    fields: &[#(#fields)*],
+                };
+            }
+        }
+    }
+
+    /// Emit the visitor of the field values, matching the fields of the schema
+    fn emit_for_each_field(&self) -> TokenStream {
+        let visits = self.fields.iter().enumerate().map(|(index, field)| {
+            let value = field.emit_field_value();
+            quote!(f(&fields[#index], #value);)
+        });
+        let body = if self.fields.is_empty() {
+            quote!(let _ = f;)
+        } else {
+            quote! {
+                let fields = <Self as ::mavlink_core::schema::MessageDataSchema>::SCHEMA.fields;
+                #(#visits)*
+            }
+        };
+        quote! {
+            /// Call `f` with every field of the message, in the order of the fields of its schema
+            pub fn for_each_field(
+                &self,
+                f: &mut dyn FnMut(&'static ::mavlink_core::schema::FieldSchema, ::mavlink_core::schema::FieldValue<'_>),
+            ) {
+                #body
+            }
+        }
+    }
+
+    /// Return Token of "MessageNameView"
+    /// for the borrowed view of the message payload
+    fn emit_view_name(&self) -> TokenStream {
+        let name = format_ident!("{}View", to_pascal_case(&self.name));
+        quote!(#name)
+    }
+
+    /// Emit the borrowed view of the message payload with an accessor per field
+    fn emit_view(&self) -> TokenStream {
+        let msg_name = self.emit_struct_name();
+        let view_name = self.emit_view_name();
+        let deprecation = self.emit_deprecation();
+        let doc = format!("Borrowed view of the payload of a [`{msg_name}`] message");
+
+        let mut offset = 0;
+        let accessors = self
+            .fields
+            .iter()
+            .map(|field| {
+                let accessor = field.emit_view_accessor(offset);
+                offset += field.mavtype.len();
+                accessor
+            })
+            .collect::<Vec<TokenStream>>();
+
+        quote! {
+            #deprecation
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct #view_name<'a> {
+                payload: &'a [u8],
+            }
+
+            impl<'a> #view_name<'a> {
+                #(#accessors)*
+            }
+
+            impl<'a> ::mavlink_core::MessageView<'a> for #view_name<'a> {
+                type Data = #msg_name;
+
+                fn new(payload: &'a [u8]) -> Self {
+                    Self { payload }
+                }
+
+                fn payload_bytes(&self) -> &'a [u8] {
+                    self.payload
+                }
+            }
         }
     }
 
@@ -1054,6 +1379,148 @@ impl MavField {
         }
     }
 
+    /// Emit accessor of the field in the borrowed view of the message payload
+    fn emit_view_accessor(&self, offset: usize) -> TokenStream {
+        let name = self.emit_name();
+        let description = self.emit_description();
+
+        if let MavType::Array(t, size) = &self.mavtype {
+            // enum arrays are decoded as primitive arrays, like in the message struct
+            let rt = TokenStream::from_str(&self.mavtype.rust_type()).unwrap();
+            let elem = TokenStream::from_str(&t.rust_type()).unwrap();
+            let elem_len = t.len();
+            if elem_len == 1 {
+                let bytes_name = format_ident!("{}_bytes", self.name);
+                let bytes_doc = format!(
+                    "Bytes of `{}` present in the payload, trailing zeros may be truncated",
+                    self.name
+                );
+                let end = offset + size;
+                let bytes =
+                    quote!(::mavlink_core::utils::get_padded::<#size>(self.payload, #offset));
+                let value = if t.rust_type() == "u8" {
+                    bytes
+                } else {
+                    quote!(#bytes.map(|byte| #elem::from_le_bytes([byte])))
+                };
+                return quote! {
+                    #description
+                    #[inline]
+                    pub fn #name(&self) -> #rt {
+                        #value
+                    }
+
+                    #[doc = #bytes_doc]
+                    #[inline]
+                    pub fn #bytes_name(&self) -> &'a [u8] {
+                        let len = self.payload.len();
+                        &self.payload[len.min(#offset)..len.min(#end)]
+                    }
+                };
+            }
+            return quote! {
+                #description
+                #[inline]
+                pub fn #name(&self) -> #rt {
+                    let mut __array = [#elem::default(); #size];
+                    for (i, v) in __array.iter_mut().enumerate() {
+                        *v = #elem::from_le_bytes(::mavlink_core::utils::get_padded::<#elem_len>(
+                            self.payload,
+                            #offset + i * #elem_len,
+                        ));
+                    }
+                    __array
+                }
+            };
+        }
+
+        let len = self.mavtype.len();
+        let rt = TokenStream::from_str(&self.mavtype.rust_type()).unwrap();
+        let value = quote! {
+            #rt::from_le_bytes(::mavlink_core::utils::get_padded::<#len>(self.payload, #offset))
+        };
+        let Some(enum_name) = &self.enumtype else {
+            return quote! {
+                #description
+                #[inline]
+                pub fn #name(&self) -> #rt {
+                    #value
+                }
+            };
+        };
+
+        let ty = self.emit_type();
+        let decode = if let Some(dsp) = &self.display {
+            if dsp == "bitmask" {
+                quote! {
+                    #ty::from_bits(tmp & #ty::all().bits())
+                        .ok_or(::mavlink_core::error::ParserError::InvalidFlag { flag_type: #enum_name, value: tmp as u32 })
+                }
+            } else {
+                panic!("Display option not implemented");
+            }
+        } else {
+            let val = format_ident!("from_{}", &self.mavtype.rust_type());
+            quote! {
+                FromPrimitive::#val(tmp)
+                    .ok_or(::mavlink_core::error::ParserError::InvalidEnum { enum_type: #enum_name, value: tmp as u32 })
+            }
+        };
+        quote! {
+            #description
+            #[inline]
+            pub fn #name(&self) -> Result<#ty, ::mavlink_core::error::ParserError> {
+                let tmp = #value;
+                #decode
+            }
+        }
+    }
+
+    /// Emit the schema of the field
+    fn emit_schema(&self) -> TokenStream {
+        let name = &self.name;
+        let (field_type, array_length) = match &self.mavtype {
+            MavType::Array(t, size) => (t.schema_type(), quote!(Some(#size))),
+            t => (t.schema_type(), quote!(None)),
+        };
+        let enum_schema = match &self.enumtype {
+            Some(enum_name) => {
+                let enum_name = TokenStream::from_str(enum_name).unwrap();
+                quote!(Some(&#enum_name::SCHEMA))
+            }
+            None => quote!(None),
+        };
+        let extension = self.is_extension;
+        quote! {
+            ::mavlink_core::schema::FieldSchema {
+                name: #name,
+                field_type: ::mavlink_core::schema::FieldType::#field_type,
+                array_length: #array_length,
+                enum_schema: #enum_schema,
+                extension: #extension,
+            },
+        }
+    }
+
+    /// Emit the `FieldValue` of the field, enums are converted to the value of their entry
+    fn emit_field_value(&self) -> TokenStream {
+        let name = self.emit_name();
+        if let MavType::Array(t, _) = &self.mavtype {
+            let variant = format_ident!("{}Array", t.schema_type().to_string());
+            return quote!(::mavlink_core::schema::FieldValue::#variant(&self.#name));
+        }
+        let variant = self.mavtype.schema_type();
+        let value = match (&self.enumtype, &self.display) {
+            (Some(_), Some(_)) => quote!(self.#name.bits()),
+            (Some(_), None) => {
+                let rt = TokenStream::from_str(&self.mavtype.rust_type()).unwrap();
+                quote!(self.#name as #rt)
+            }
+            (None, _) => quote!(self.#name),
+        };
+        quote!(::mavlink_core::schema::FieldValue::#variant(#value))
+    }
+
     #[inline(always)]
     fn emit_default_initializer(&self, dialect_has_version: bool) -> TokenStream {
         let field = self.emit_name();
@@ -1124,18 +1591,18 @@ impl MavType {
     pub fn rust_reader(&self, val: &TokenStream, buf: Ident) -> TokenStream {
         use self::MavType::*;
         match self {
-            Char => quote! {#val = #buf.get_u8();},
-            UInt8 => quote! {#val = #buf.get_u8();},
-            UInt16 => quote! {#val = #buf.get_u16_le();},
-            UInt32 => quote! {#val = #buf.get_u32_le();},
-            UInt64 => quote! {#val = #buf.get_u64_le();},
-            UInt8MavlinkVersion => quote! {#val = #buf.get_u8();},
-            Int8 => quote! {#val = #buf.get_i8();},
-            Int16 => quote! {#val = #buf.get_i16_le();},
-            Int32 => quote! {#val = #buf.get_i32_le();},
-            Int64 => quote! {#val = #buf.get_i64_le();},
-            Float => quote! {#val = #buf.get_f32_le();},
-            Double => quote! {#val = #buf.get_f64_le();},
+            Char => quote! {#val = #buf.get_u8()?;},
+            UInt8 => quote! {#val = #buf.get_u8()?;},
+            UInt16 => quote! {#val = #buf.get_u16_le()?;},
+            UInt32 => quote! {#val = #buf.get_u32_le()?;},
+            UInt64 => quote! {#val = #buf.get_u64_le()?;},
+            UInt8MavlinkVersion => quote! {#val = #buf.get_u8()?;},
+            Int8 => quote! {#val = #buf.get_i8()?;},
+            Int16 => quote! {#val = #buf.get_i16_le()?;},
+            Int32 => quote! {#val = #buf.get_i32_le()?;},
+            Int64 => quote! {#val = #buf.get_i64_le()?;},
+            Float => quote! {#val = #buf.get_f32_le()?;},
+            Double => quote! {#val = #buf.get_f64_le()?;},
             Array(t, _) => {
                 let r = t.rust_reader(&quote!(let val), buf);
                 quote! {
@@ -1267,6 +1734,25 @@ impl MavType {
         }
     }
 
+    /// Return the `FieldType` variant of a MavType, or of its elements if it is an array
+    pub fn schema_type(&self) -> Ident {
+        use self::MavType::*;
+        match self {
+            UInt8 | UInt8MavlinkVersion => format_ident!("UInt8"),
+            Int8 => format_ident!("Int8"),
+            Char => format_ident!("Char"),
+            UInt16 => format_ident!("UInt16"),
+            Int16 => format_ident!("Int16"),
+            UInt32 => format_ident!("UInt32"),
+            Int32 => format_ident!("Int32"),
+            Float => format_ident!("Float"),
+            UInt64 => format_ident!("UInt64"),
+            Int64 => format_ident!("Int64"),
+            Double => format_ident!("Double"),
+            Array(t, _) => t.schema_type(),
+        }
+    }
+
     /// Return rust equivalent of the primitive type of a MavType. The primitive
     /// type is the type itself for all except arrays, in which case it is the
     /// element type.
@@ -1802,16 +2288,33 @@ pub fn generate<W: Write>(
     definition_file: &Path,
     output_rust: &mut W,
 ) -> Result<(), BindGenError> {
+    generate_with_options(
+        definitions_dir,
+        definition_file,
+        output_rust,
+        &GeneratorOptions::default(),
+    )?;
+    Ok(())
+}
+
+/// Generate rust representation of mavlink message set using the given options
+/// and return the payload lengths of the generated messages
+pub fn generate_with_options<W: Write>(
+    definitions_dir: &Path,
+    definition_file: &Path,
+    output_rust: &mut W,
+    options: &GeneratorOptions,
+) -> Result<PayloadReport, BindGenError> {
     let mut parsed_files: HashSet<PathBuf> = HashSet::new();
     let profile = parse_profile(definitions_dir, definition_file, &mut parsed_files)?;
 
     let dialect_name = util::to_dialect_name(definition_file);
 
     // rust file
-    let rust_tokens = profile.emit_rust(&dialect_name);
+    let rust_tokens = profile.emit_rust(&dialect_name, options);
     writeln!(output_rust, "{rust_tokens}").unwrap();
 
-    Ok(())
+    Ok(profile.payload_report(&dialect_name, options))
 }
 
 /// CRC operates over names of the message and names of its fields
@@ -2056,7 +2559,7 @@ mod tests {
         profile.add_message(&msg_with_targets);
         profile.add_message(&msg_without_targets);
 
-        let tokens = profile.emit_rust("common");
+        let tokens = profile.emit_rust("common", &GeneratorOptions::default());
         let mut code = tokens.to_string();
         code.retain(|c| !c.is_whitespace());
 
@@ -2,7 +2,9 @@ use std::fs;
 use std::path::PathBuf;
 
 use insta::{self, assert_snapshot, glob};
-use mavlink_bindgen::{format_generated_code, generate, XmlDefinitions};
+use mavlink_bindgen::{
+    format_generated_code, generate, generate_with_options, GeneratorOptions, XmlDefinitions,
+};
 use tempfile::TempDir;
 
 fn definitions_dir() -> PathBuf {
@@ -27,6 +29,53 @@ fn run_snapshot(def_file: &str) {
     });
 }
 
+fn run_snapshot_boxed(def_file: &str, box_threshold: usize) {
+    let defs = definitions_dir();
+    let tmp = TempDir::new().expect("tmp dir");
+    let out_dir = tmp.path();
+
+    let xml = defs.join(def_file);
+    let options = GeneratorOptions {
+        box_threshold: Some(box_threshold),
+        ..GeneratorOptions::default()
+    };
+    let result = generate_with_options(XmlDefinitions::Files(vec![xml]), out_dir, &options)
+        .expect("generate ok");
+
+    format_generated_code(&result);
+
+    let report = &result.bindings[0].payload_report;
+    assert!(report
+        .messages
+        .iter()
+        .all(|message| message.boxed == (message.payload_len > box_threshold)));
+    assert!(report.largest_inline_len() <= box_threshold);
+
+    let name = format!("{def_file}_boxed");
+    glob!(out_dir, "**/*.rs", |path| {
+        let contents = fs::read_to_string(path).expect("read generated file");
+        assert_snapshot!(name.as_str(), contents);
+    });
+}
+
+fn run_snapshot_with_options(def_file: &str, suffix: &str, options: GeneratorOptions) {
+    let defs = definitions_dir();
+    let tmp = TempDir::new().expect("tmp dir");
+    let out_dir = tmp.path();
+
+    let xml = defs.join(def_file);
+    let result = generate_with_options(XmlDefinitions::Files(vec![xml]), out_dir, &options)
+        .expect("generate ok");
+
+    format_generated_code(&result);
+
+    let name = format!("{def_file}_{suffix}");
+    glob!(out_dir, "**/*.rs", |path| {
+        let contents = fs::read_to_string(path).expect("read generated file");
+        assert_snapshot!(name.as_str(), contents);
+    });
+}
+
 #[test]
 fn snapshot_heartbeat() {
     run_snapshot("heartbeat.xml");
@@ -46,3 +95,26 @@ fn snapshot_deprecated() {
 fn snapshot_no_field_description() {
     run_snapshot("no_field_description.xml");
 }
+
+#[test]
+fn snapshot_parameters_boxed() {
+    run_snapshot_boxed("parameters.xml", 20);
+}
+
+#[test]
+fn snapshot_parameters_views() {
+    let options = GeneratorOptions {
+        emit_views: true,
+        ..GeneratorOptions::default()
+    };
+    run_snapshot_with_options("parameters.xml", "views", options);
+}
+
+#[test]
+fn snapshot_parameters_schemas() {
+    let options = GeneratorOptions {
+        emit_schemas: true,
+        ..GeneratorOptions::default()
+    };
+    run_snapshot_with_options("parameters.xml", "schemas", options);
+}
@@ -0,0 +1,162 @@
+//! [`Stream`] and [`Sink`] adapters for async MAVLink connections
+
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::stream::FusedStream;
+use futures::{FutureExt, Sink, Stream};
+
+use super::AsyncMavConnection;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::{MavFrame, Message};
+
+/// A [`Stream`] of the frames received on an [`AsyncMavConnection`]
+///
+/// Errors are yielded as items and the stream keeps on receiving afterwards, except for I/O errors that are not
+/// transient, like an unexpected end of file or a reset connection, after which the stream ends.
+///
+/// The connection is shared through an [`Arc`], so the same connection can also back a [`MavFrameSink`].
+///
+/// # Example
+/// ```ignore
+/// use futures::StreamExt;
+/// use mavlink::MavFrameStream;
+///
+/// let connection = Arc::new(AsyncStreamConnection::new(reader, writer));
+/// let mut heartbeats = MavFrameStream::<MavMessage, _>::new(connection)
+///     .filter_map(|frame| async move { frame.ok() })
+///     .filter(|frame| futures::future::ready(matches!(frame.msg, MavMessage::HEARTBEAT(_))));
+/// ```
+pub struct MavFrameStream<M: Message, C: ?Sized> {
+    connection: Arc<C>,
+    pending: Option<BoxFuture<'static, Result<MavFrame<M>, MessageReadError>>>,
+    terminated: bool,
+}
+
+impl<M, C> MavFrameStream<M, C>
+where
+    M: Message + Sync + Send + 'static,
+    C: AsyncMavConnection<M> + Sync + Send + ?Sized + 'static,
+{
+    /// Create a stream receiving frames from the given connection
+    pub fn new(connection: Arc<C>) -> Self {
+        Self {
+            connection,
+            pending: None,
+            terminated: false,
+        }
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &Arc<C> {
+        &self.connection
+    }
+}
+
+impl<M, C> Stream for MavFrameStream<M, C>
+where
+    M: Message + Sync + Send + 'static,
+    C: AsyncMavConnection<M> + Sync + Send + ?Sized + 'static,
+{
+    type Item = Result<MavFrame<M>, MessageReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.terminated {
+            return Poll::Ready(None);
+        }
+
+        let pending = this.pending.get_or_insert_with(|| {
+            let connection = this.connection.clone();
+            async move { connection.recv_frame().await }.boxed()
+        });
+        let result = futures::ready!(pending.poll_unpin(cx));
+        this.pending = None;
+
+        if let Err(MessageReadError::Io(e)) = &result {
+            // retrying after other I/O errors, like a reset connection, fails the same way
+            this.terminated = !matches!(
+                e.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut
+            );
+        }
+        Poll::Ready(Some(result))
+    }
+}
+
+impl<M, C> FusedStream for MavFrameStream<M, C>
+where
+    M: Message + Sync + Send + 'static,
+    C: AsyncMavConnection<M> + Sync + Send + ?Sized + 'static,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+/// A [`Sink`] sending frames over an [`AsyncMavConnection`]
+///
+/// Frames are sent one at a time, using the header of each frame. Like [`AsyncMavConnection::send`]
+/// the sequence number is assigned by the connection and the protocol version of the connection is used.
+pub struct MavFrameSink<M, C: ?Sized> {
+    connection: Arc<C>,
+    pending: Option<BoxFuture<'static, Result<usize, MessageWriteError>>>,
+    _message: PhantomData<fn(M)>,
+}
+
+impl<M, C> MavFrameSink<M, C>
+where
+    M: Message + Sync + Send + 'static,
+    C: AsyncMavConnection<M> + Sync + Send + ?Sized + 'static,
+{
+    /// Create a sink sending frames to the given connection
+    pub fn new(connection: Arc<C>) -> Self {
+        Self {
+            connection,
+            pending: None,
+            _message: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &Arc<C> {
+        &self.connection
+    }
+}
+
+impl<M, C> Sink<MavFrame<M>> for MavFrameSink<M, C>
+where
+    M: Message + Sync + Send + 'static,
+    C: AsyncMavConnection<M> + Sync + Send + ?Sized + 'static,
+{
+    type Error = MessageWriteError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: MavFrame<M>) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        let connection = this.connection.clone();
+        this.pending = Some(async move { connection.send_frame(&item).await }.boxed());
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if let Some(pending) = this.pending.as_mut() {
+            let result = futures::ready!(pending.poll_unpin(cx));
+            this.pending = None;
+            result?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
@@ -1,106 +1,272 @@
 //! Async File MAVLINK connection
-use core::ops::DerefMut;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
 use std::io;
-use std::path::PathBuf;
+#[cfg(feature = "signing")]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::{AsyncConnectable, AsyncMavConnection};
-use crate::connection::file::config::FileConfig;
+use super::{
+    AsyncConnectable, AsyncHalvesConnection, AsyncMavConnection, AsyncMavReceiveHalf,
+    AsyncMavSendHalf,
+};
+use crate::connection::file::config::{FileConfig, FileFormat};
+use crate::connection::file::{write_only, FileOutput};
+use crate::connection::ConnectionHalf;
 use crate::error::{MessageReadError, MessageWriteError};
+use crate::frame_parser::FrameCheck;
+use crate::tlog::{check_record, parse_raw, TIMESTAMP_SIZE};
 use crate::{
     async_peek_reader::AsyncPeekReader, MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message,
-    ReadVersion,
+    ReadVersion, SigningData, MAX_FRAME_SIZE,
 };
 
 use async_trait::async_trait;
-use futures::lock::Mutex;
+use futures::AsyncRead;
 use tokio::fs::File;
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
 
 #[cfg(not(feature = "signing"))]
 use crate::{read_raw_versioned_msg_async, read_versioned_msg_async};
 
 #[cfg(feature = "signing")]
-use crate::{
-    read_raw_versioned_msg_async_signed, read_versioned_msg_async_signed, SigningConfig,
-    SigningData,
-};
+use crate::{read_raw_versioned_msg_async_signed, read_versioned_msg_async_signed};
+
+/// Time to wait before checking whether a followed file has grown
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(50);
+
+const TLOG_BUFFER_SIZE: usize = TIMESTAMP_SIZE + MAX_FRAME_SIZE;
 
-pub async fn open(file_path: &PathBuf) -> io::Result<AsyncFileConnection> {
-    let file = File::open(file_path).await?;
-    Ok(AsyncFileConnection {
-        file: Mutex::new(AsyncPeekReader::new(file)),
+/// Open the input and output files of a file address
+pub async fn open(config: &FileConfig) -> io::Result<AsyncFileConnection> {
+    let file = match config.input() {
+        Some(path) => Some(FileInput::new(File::open(path).await?, config)),
+        None => None,
+    };
+    let output = config
+        .output()
+        .map(|path| FileOutput::open(path, config.format))
+        .transpose()?;
+    let receive_half = AsyncFileReceiveHalf {
+        file,
         protocol_version: MavlinkVersion::V2,
         recv_any_version: false,
         #[cfg(feature = "signing")]
         signing_data: None,
-    })
+    };
+    let send_half = AsyncFileSendHalf {
+        output,
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(AsyncHalvesConnection::from_halves(receive_half, send_half))
 }
 
-pub struct AsyncFileConnection {
-    file: Mutex<AsyncPeekReader<File>>,
-    protocol_version: MavlinkVersion,
-    recv_any_version: bool,
-    #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+/// Reader that waits for more data at the end of a file that is still being written, instead of ending
+struct Follow {
+    file: Compat<File>,
+    follow: bool,
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
 }
 
-#[async_trait::async_trait]
-impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
-    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut file = self.file.lock().await;
-        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+impl AsyncRead for Follow {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
         loop {
-            #[cfg(not(feature = "signing"))]
-            let result = read_raw_versioned_msg_async::<M, _>(file.deref_mut(), version).await;
-            #[cfg(feature = "signing")]
-            let result = read_raw_versioned_msg_async_signed::<M, _>(
-                file.deref_mut(),
-                version,
-                self.signing_data.as_ref(),
-            )
-            .await;
-            match result {
-                ok @ Ok(..) => {
-                    return ok;
-                }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
+            if let Some(sleep) = &mut self.sleep {
+                ready!(sleep.as_mut().poll(cx));
+                self.sleep = None;
+            }
+            let read = ready!(Pin::new(&mut self.file).poll_read(cx, buf))?;
+            if read > 0 || buf.is_empty() || !self.follow {
+                return Poll::Ready(Ok(read));
+            }
+            self.sleep = Some(Box::pin(tokio::time::sleep(FOLLOW_INTERVAL)));
+        }
+    }
+}
+
+enum InputReader {
+    Raw(AsyncPeekReader<Follow>),
+    Tlog(AsyncPeekReader<Follow, TLOG_BUFFER_SIZE>),
+}
+
+/// Input file of an async file connection
+struct FileInput {
+    reader: InputReader,
+    // whether frames of unknown messages are accepted, see `TlogReader`
+    synced: bool,
+    throttle: bool,
+    // log time that is received at the instant
+    anchor: Option<(Instant, u64)>,
+}
+
+impl FileInput {
+    fn new(file: File, config: &FileConfig) -> Self {
+        let file = Follow {
+            file: file.compat(),
+            follow: config.follow,
+            sleep: None,
+        };
+        let reader = match config.format {
+            FileFormat::Raw => InputReader::Raw(AsyncPeekReader::new(file)),
+            FileFormat::Tlog => InputReader::Tlog(AsyncPeekReader::new(file)),
+        };
+        Self {
+            reader,
+            synced: true,
+            throttle: config.throttle,
+            anchor: None,
+        }
+    }
+
+    async fn recv_raw<M: Message>(
+        &mut self,
+        version: ReadVersion,
+        signing_data: Option<&SigningData>,
+    ) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let reader = match &mut self.reader {
+            InputReader::Raw(reader) => loop {
+                #[cfg(not(feature = "signing"))]
+                let result = read_raw_versioned_msg_async::<M, _>(reader, version).await;
+                #[cfg(feature = "signing")]
+                let result =
+                    read_raw_versioned_msg_async_signed::<M, _>(reader, version, signing_data)
+                        .await;
+                match result {
+                    ok @ Ok(..) => {
+                        return ok;
+                    }
+                    Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
                         return Err(MessageReadError::Io(e));
                     }
+                    _ => {}
                 }
-                _ => {}
+            },
+            InputReader::Tlog(reader) => reader,
+        };
+        loop {
+            let (timestamp, raw) = read_record::<M>(reader, &mut self.synced, signing_data).await?;
+            if !version.accepts_stx(raw.raw_bytes()[0]) {
+                continue;
             }
+            if self.throttle {
+                let (instant, anchor) = *self.anchor.get_or_insert((Instant::now(), timestamp));
+                let delay = Duration::from_micros(timestamp.saturating_sub(anchor));
+                tokio::time::sleep_until((instant + delay).into()).await;
+            }
+            return Ok(raw);
         }
     }
 
-    async fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().await;
-        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
-        loop {
-            #[cfg(not(feature = "signing"))]
-            let result = read_versioned_msg_async(file.deref_mut(), version).await;
-            #[cfg(feature = "signing")]
-            let result = read_versioned_msg_async_signed(
-                file.deref_mut(),
-                version,
-                self.signing_data.as_ref(),
-            )
-            .await;
-            match result {
-                ok @ Ok(..) => {
-                    return ok;
-                }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
+    async fn recv<M: Message>(
+        &mut self,
+        version: ReadVersion,
+        signing_data: Option<&SigningData>,
+    ) -> Result<(MavHeader, M), MessageReadError> {
+        if let InputReader::Raw(reader) = &mut self.reader {
+            loop {
+                #[cfg(not(feature = "signing"))]
+                let result = read_versioned_msg_async(reader, version).await;
+                #[cfg(feature = "signing")]
+                let result = read_versioned_msg_async_signed(reader, version, signing_data).await;
+                match result {
+                    ok @ Ok(..) => {
+                        return ok;
+                    }
+                    Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
                         return Err(MessageReadError::Io(e));
                     }
+                    _ => {}
+                }
+            }
+        }
+        loop {
+            // skip frames of unknown messages
+            let raw = self.recv_raw::<M>(version, signing_data).await?;
+            if let Ok(message) = parse_raw(&raw) {
+                return Ok(message);
+            }
+        }
+    }
+}
+
+/// Read the next valid record of a tlog, skipping corrupt bytes
+async fn read_record<M: Message>(
+    reader: &mut AsyncPeekReader<Follow, TLOG_BUFFER_SIZE>,
+    synced: &mut bool,
+    signing_data: Option<&SigningData>,
+) -> Result<(u64, MAVLinkMessageRaw), MessageReadError> {
+    loop {
+        let mut needed = TIMESTAMP_SIZE + 1;
+        let record = loop {
+            let bytes = reader.peek_exact(needed).await?;
+            match check_record::<M>(&bytes[TIMESTAMP_SIZE..], *synced, signing_data) {
+                FrameCheck::Incomplete(len) => needed = TIMESTAMP_SIZE + len,
+                FrameCheck::Valid(frame) => {
+                    let timestamp = u64::from_be_bytes(bytes[..TIMESTAMP_SIZE].try_into().unwrap());
+                    break Some((timestamp, frame));
                 }
-                _ => {}
+                FrameCheck::Invalid | FrameCheck::Rejected(_) => break None,
+            }
+        };
+        match record {
+            Some((timestamp, (frame, checked))) => {
+                reader.consume(TIMESTAMP_SIZE + frame.raw_bytes().len());
+                *synced |= checked;
+                return Ok((timestamp, frame));
+            }
+            None => {
+                *synced = false;
+                reader.consume(1);
             }
         }
     }
+}
+
+/// Async file connection, reading frames from a file and optionally appending sent frames to a file
+///
+/// Unlike the blocking connection, the input file can be followed while it is written, and a tlog can be received
+/// at the time it was recorded at. Sent frames are appended with blocking writes.
+pub type AsyncFileConnection = AsyncHalvesConnection<AsyncFileReceiveHalf, AsyncFileSendHalf>;
+
+/// The receiving half of an [`AsyncFileConnection`]
+pub struct AsyncFileReceiveHalf {
+    file: Option<FileInput>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
 
-    async fn send(&self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
-        Ok(0)
+impl AsyncFileReceiveHalf {
+    /// The input file and the signing data to check its frames with
+    fn input(&mut self) -> Result<(&mut FileInput, Option<&SigningData>), MessageReadError> {
+        let file = self.file.as_mut().ok_or_else(write_only)?;
+        #[cfg(feature = "signing")]
+        return Ok((file, self.signing_data.as_deref()));
+        #[cfg(not(feature = "signing"))]
+        Ok((file, None))
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavReceiveHalf<M> for AsyncFileReceiveHalf {
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        let (file, signing_data) = self.input()?;
+        file.recv_raw::<M>(version, signing_data).await
+    }
+
+    async fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        let (file, signing_data) = self.input()?;
+        file.recv(version, signing_data).await
     }
 
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
@@ -118,10 +284,59 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
     fn allow_recv_any_version(&self) -> bool {
         self.recv_any_version
     }
+}
+
+impl ConnectionHalf for AsyncFileReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of an [`AsyncFileConnection`], writing to it does nothing unless the connection has an output
+/// file
+pub struct AsyncFileSendHalf {
+    output: Option<FileOutput>,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavSendHalf<M> for AsyncFileSendHalf {
+    async fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let Some(output) = &mut self.output else {
+            return Ok(0);
+        };
+        output.send(
+            self.protocol_version,
+            header,
+            data,
+            #[cfg(feature = "signing")]
+            self.signing_data.as_deref(),
+        )
+    }
+
+    async fn send_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let Some(output) = &mut self.output else {
+            return Ok(0);
+        };
+        output.send_raw(frame)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
 
+impl ConnectionHalf for AsyncFileSendHalf {
     #[cfg(feature = "signing")]
-    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
 }
 
@@ -131,6 +346,6 @@ impl AsyncConnectable for FileConfig {
     where
         M: Message + Sync + Send,
     {
-        Ok(Box::new(open(&self.address).await?))
+        Ok(Box::new(open(self).await?))
     }
 }
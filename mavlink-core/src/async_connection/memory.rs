@@ -0,0 +1,134 @@
+//! Async in-memory MAVLink connection
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncWrite};
+use tokio::time::Sleep;
+
+use super::{AsyncConnectable, AsyncMavConnection, AsyncStreamConnection};
+use crate::connection::impairment::Impairments;
+use crate::connection::memory::config::MemoryConfig;
+use crate::connection::memory::{endpoints, named_endpoint, Endpoint, Link, Next};
+use crate::Message;
+
+/// Reads the frames of a memory link as a byte stream
+pub struct AsyncMemoryRead {
+    link: Arc<Link>,
+    buffer: VecDeque<u8>,
+    // wakes the reader when a delayed frame is due
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl AsyncRead for AsyncMemoryRead {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        while self.buffer.is_empty() {
+            match self.link.poll_next(cx.waker())? {
+                Next::Frame(frame) => self.buffer.extend(frame),
+                Next::At(at) => {
+                    let at = at.into();
+                    let delay = self
+                        .delay
+                        .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(at)));
+                    if delay.deadline() != at {
+                        delay.as_mut().reset(at);
+                    }
+                    if delay.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                }
+                Next::Empty => return Poll::Pending,
+            }
+        }
+        Poll::Ready(self.buffer.read(buf))
+    }
+}
+
+/// Sends the bytes written since the last flush as one frame
+pub struct AsyncMemoryWrite {
+    link: Arc<Link>,
+    buffer: Vec<u8>,
+}
+
+impl AsyncWrite for AsyncMemoryWrite {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.buffer.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.buffer.is_empty() {
+            let frame = core::mem::take(&mut self.buffer);
+            self.link.push(frame);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl Drop for AsyncMemoryWrite {
+    fn drop(&mut self) {
+        self.link.close();
+    }
+}
+
+/// Async connection to another connection in the same process
+///
+/// The async counterpart of [`MemoryConnection`](crate::MemoryConnection), delayed frames require a tokio runtime
+/// with the time driver enabled.
+pub type AsyncMemoryConnection = AsyncStreamConnection<AsyncMemoryRead, AsyncMemoryWrite>;
+
+impl AsyncMemoryConnection {
+    /// Create two async connections that are linked to each other
+    pub fn pair() -> (Self, Self) {
+        Self::pair_with(Impairments::default(), Impairments::default())
+    }
+
+    /// Create two linked async connections with impaired links
+    ///
+    /// `a_to_b` impairs the frames sent by the first connection, `b_to_a` the frames sent by the second one.
+    pub fn pair_with(a_to_b: Impairments, b_to_a: Impairments) -> (Self, Self) {
+        let (a, b) = endpoints(a_to_b, b_to_a);
+        (from_endpoint(a), from_endpoint(b))
+    }
+}
+
+fn from_endpoint(endpoint: Endpoint) -> AsyncMemoryConnection {
+    AsyncStreamConnection::new(
+        AsyncMemoryRead {
+            link: endpoint.incoming,
+            buffer: VecDeque::new(),
+            delay: None,
+        },
+        AsyncMemoryWrite {
+            link: endpoint.outgoing,
+            buffer: Vec::new(),
+        },
+    )
+}
+
+#[async_trait]
+impl AsyncConnectable for MemoryConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        Ok(Box::new(from_endpoint(named_endpoint(&self.name))))
+    }
+}
@@ -1,24 +1,62 @@
+#[cfg(feature = "tokio-1")]
 use async_trait::async_trait;
+#[cfg(feature = "tokio-1")]
 use std::io;
 
-use crate::{
-    connectable::ConnectionAddress, MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message,
-};
-#[cfg(feature = "tcp")]
+use std::sync::Arc;
+
+use async_lock::{RwLock, RwLockReadGuard};
+use futures::lock::Mutex;
+
+#[cfg(feature = "tokio-1")]
+use crate::connectable::ConnectionAddress;
+use crate::connection::{parse_frame, ConnectionHalf};
+use crate::{MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message};
+
+mod adapters;
+pub use adapters::{MavFrameSink, MavFrameStream};
+
+mod stream;
+pub use stream::AsyncStreamConnection;
+
+mod recording;
+pub use recording::{AsyncRecordingConnection, AsyncRecordingReceiveHalf, AsyncRecordingSendHalf};
+
+#[cfg(all(feature = "tokio-1", feature = "tcp"))]
 mod tcp;
 
-#[cfg(feature = "udp")]
+#[cfg(all(feature = "tokio-1", feature = "udp"))]
 mod udp;
 
-#[cfg(feature = "direct-serial")]
+#[cfg(all(feature = "tokio-1", feature = "direct-serial"))]
 mod direct_serial;
 
+#[cfg(all(feature = "tokio-1", feature = "unix", unix))]
+mod unix;
+
+#[cfg(all(feature = "tokio-1", feature = "websocket"))]
+mod websocket;
+
+#[cfg(feature = "tokio-1")]
 mod file;
 
+#[cfg(feature = "tokio-1")]
+mod memory;
+#[cfg(feature = "tokio-1")]
+pub use memory::AsyncMemoryConnection;
+
+#[cfg(feature = "tokio-1")]
+mod replay;
+#[cfg(feature = "tokio-1")]
+pub use replay::{AsyncReplayConnection, AsyncReplayReceiveHalf, AsyncReplaySendHalf};
+
 #[cfg(feature = "signing")]
-use crate::SigningConfig;
+use crate::{SigningConfig, SigningData};
 
 /// An async MAVLink connection
+///
+/// The connection is independent of the async runtime, [`AsyncStreamConnection`] implements it for any pair of
+/// [`futures::AsyncRead`] and [`futures::AsyncWrite`] streams. The transports returned by `connect_async` use tokio.
 #[async_trait::async_trait]
 pub trait AsyncMavConnection<M: Message + Sync + Send> {
     /// Receive a mavlink message.
@@ -38,6 +76,18 @@ pub trait AsyncMavConnection<M: Message + Sync + Send> {
         data: &M,
     ) -> Result<usize, crate::error::MessageWriteError>;
 
+    /// Send a raw MAVLink frame.
+    ///
+    /// The connections of this crate send the bytes of the frame unchanged, keeping its sequence number, version and
+    /// signature. By default the message of the frame is parsed and sent with [`send`](Self::send) instead.
+    async fn send_raw(
+        &self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let (header, data) = parse_frame(frame)?;
+        self.send(&header, &data).await
+    }
+
     /// Sets the MAVLink version to use for receiving (when `allow_recv_any_version()` is `false`) and sending messages.
     fn set_protocol_version(&mut self, version: MavlinkVersion);
     /// Gets the currently used MAVLink version
@@ -78,6 +128,327 @@ pub trait AsyncMavConnection<M: Message + Sync + Send> {
     /// Setup secret key used for message signing, or disable message signing
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>);
+
+    /// Split the connection into independently owned receive and send halves.
+    ///
+    /// Both halves keep the protocol version and signing configuration of the connection at the time of the split.
+    /// Dropping both halves closes the connection.
+    ///
+    /// By default both halves share the connection, so changing the protocol version on one half changes it for
+    /// the other half as well, once a call in progress on the other half completes. The connections of this crate
+    /// hand out halves that are independent of each other.
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn AsyncMavReceiveHalf<M> + Send>,
+        Box<dyn AsyncMavSendHalf<M> + Send>,
+    )
+    where
+        Self: Send + Sync + 'static,
+    {
+        let settings = Settings {
+            protocol_version: self.protocol_version(),
+            recv_any_version: self.allow_recv_any_version(),
+            changed: false,
+        };
+        let shared = Arc::new(SharedConnection {
+            settings: std::sync::Mutex::new(settings),
+            connection: RwLock::new(self),
+        });
+        (
+            Box::new(SharedHalf {
+                shared: shared.clone(),
+            }),
+            Box::new(SharedHalf { shared }),
+        )
+    }
+}
+
+/// The receiving half of an [`AsyncMavConnection`], created by [`AsyncMavConnection::split`]
+#[async_trait::async_trait]
+pub trait AsyncMavReceiveHalf<M: Message + Sync + Send> {
+    /// Receive a mavlink message.
+    ///
+    /// Yield until a valid frame is received, ignoring invalid messages.
+    async fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError>;
+
+    /// Receive a raw, unparsed mavlink message.
+    ///
+    /// Yield until a valid frame is received, ignoring invalid messages.
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError>;
+
+    /// Sets the MAVLink version to use for receiving when `allow_recv_any_version()` is `false`.
+    fn set_protocol_version(&mut self, version: MavlinkVersion);
+    /// Gets the currently used MAVLink version
+    fn protocol_version(&self) -> MavlinkVersion;
+
+    /// Set wether MAVLink messages of either version may be received.
+    fn set_allow_recv_any_version(&mut self, allow: bool);
+    /// Wether messages of any MAVLink version may be received
+    fn allow_recv_any_version(&self) -> bool;
+
+    /// Read whole frame
+    async fn recv_frame(&mut self) -> Result<MavFrame<M>, crate::error::MessageReadError> {
+        let (header, msg) = self.recv().await?;
+        let protocol_version = self.protocol_version();
+        Ok(MavFrame {
+            header,
+            msg,
+            protocol_version,
+        })
+    }
+}
+
+/// The sending half of an [`AsyncMavConnection`], created by [`AsyncMavConnection::split`]
+#[async_trait::async_trait]
+pub trait AsyncMavSendHalf<M: Message + Sync + Send> {
+    /// Send a mavlink message
+    async fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError>;
+
+    /// Send a raw MAVLink frame, see [`AsyncMavConnection::send_raw`]
+    async fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let (header, data) = parse_frame(frame)?;
+        self.send(&header, &data).await
+    }
+
+    /// Sets the MAVLink version to use for sending messages.
+    fn set_protocol_version(&mut self, version: MavlinkVersion);
+    /// Gets the currently used MAVLink version
+    fn protocol_version(&self) -> MavlinkVersion;
+
+    /// Write whole frame
+    async fn send_frame(
+        &mut self,
+        frame: &MavFrame<M>,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.send(&frame.header, &frame.msg).await
+    }
+
+    /// Send a message with default header
+    async fn send_default(&mut self, data: &M) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader::default();
+        self.send(&header, data).await
+    }
+}
+
+/// Settings of a connection split by the default [`AsyncMavConnection::split`]
+#[derive(Clone, Copy)]
+struct Settings {
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    // whether the settings still have to be applied to the connection
+    changed: bool,
+}
+
+struct SharedConnection<C: ?Sized> {
+    settings: std::sync::Mutex<Settings>,
+    connection: RwLock<Box<C>>,
+}
+
+impl<C: ?Sized> SharedConnection<C> {
+    /// The connection, with the settings changed on either half applied to it
+    async fn connection<M>(&self) -> RwLockReadGuard<'_, Box<C>>
+    where
+        M: Message + Sync + Send,
+        C: AsyncMavConnection<M>,
+    {
+        if self.settings.lock().unwrap().changed {
+            // the settings can only be applied once calls in progress on the other half complete
+            let mut connection = self.connection.write().await;
+            let mut settings = self.settings.lock().unwrap();
+            if settings.changed {
+                connection.set_protocol_version(settings.protocol_version);
+                connection.set_allow_recv_any_version(settings.recv_any_version);
+                settings.changed = false;
+            }
+        }
+        self.connection.read().await
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Settings)) {
+        let mut settings = self.settings.lock().unwrap();
+        f(&mut settings);
+        settings.changed = true;
+    }
+
+    fn settings(&self) -> Settings {
+        *self.settings.lock().unwrap()
+    }
+}
+
+/// A half of a connection that is split by the default [`AsyncMavConnection::split`]
+struct SharedHalf<C: ?Sized> {
+    shared: Arc<SharedConnection<C>>,
+}
+
+#[async_trait::async_trait]
+impl<M, C> AsyncMavReceiveHalf<M> for SharedHalf<C>
+where
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + Send + Sync + ?Sized,
+{
+    async fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.shared.connection().await.recv().await
+    }
+
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.shared.connection().await.recv_raw().await
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.shared
+            .update(|settings| settings.protocol_version = version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.shared.settings().protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.shared
+            .update(|settings| settings.recv_any_version = allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.shared.settings().recv_any_version
+    }
+}
+
+#[async_trait::async_trait]
+impl<M, C> AsyncMavSendHalf<M> for SharedHalf<C>
+where
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + Send + Sync + ?Sized,
+{
+    async fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.shared.connection().await.send(header, data).await
+    }
+
+    async fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.shared.connection().await.send_raw(frame).await
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.shared
+            .update(|settings| settings.protocol_version = version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.shared.settings().protocol_version
+    }
+}
+
+/// An async MAVLink connection made of a receive and a send half
+///
+/// Receiving and sending each lock their own half, so a task can send while another one waits for a message.
+/// [`AsyncMavConnection::split`] hands out the halves themselves.
+pub struct AsyncHalvesConnection<R, S> {
+    receive_half: Mutex<R>,
+    send_half: Mutex<S>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+}
+
+impl<R, S> AsyncHalvesConnection<R, S> {
+    /// Join halves that use MAVLink 2 and receive only MAVLink 2 messages
+    pub(crate) fn from_halves(receive_half: R, send_half: S) -> Self {
+        Self {
+            receive_half: Mutex::new(receive_half),
+            send_half: Mutex::new(send_half),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M, R, S> AsyncMavConnection<M> for AsyncHalvesConnection<R, S>
+where
+    M: Message + Sync + Send,
+    R: AsyncMavReceiveHalf<M> + ConnectionHalf + Send,
+    S: AsyncMavSendHalf<M> + ConnectionHalf + Send,
+{
+    async fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.receive_half.lock().await.recv().await
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.receive_half.lock().await.recv_raw().await
+    }
+
+    async fn send(
+        &self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.send_half.lock().await.send(header, data).await
+    }
+
+    async fn send_raw(
+        &self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.send_half.lock().await.send_raw(frame).await
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+        self.receive_half.get_mut().set_protocol_version(version);
+        self.send_half.get_mut().set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+        self.receive_half
+            .get_mut()
+            .set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        let signing_data = signing_data.map(|config| Arc::new(SigningData::from_config(config)));
+        self.receive_half
+            .get_mut()
+            .setup_signing(signing_data.clone());
+        self.send_half.get_mut().setup_signing(signing_data);
+    }
+
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn AsyncMavReceiveHalf<M> + Send>,
+        Box<dyn AsyncMavSendHalf<M> + Send>,
+    )
+    where
+        Self: Send + Sync + 'static,
+    {
+        (
+            Box::new(self.receive_half.into_inner()),
+            Box::new(self.send_half.into_inner()),
+        )
+    }
 }
 
 /// Connect asynchronously to a MAVLink node by address string.
@@ -90,10 +461,24 @@ pub trait AsyncMavConnection<M: Message + Sync + Send> {
 ///  * `udpout:<addr>:<port>` to create a UDP client
 ///  * `udpbcast:<addr>:<port>` to create a UDP broadcast
 ///  * `serial:<port>:<baudrate>` to create a serial connection
+///  * `unixin:<path>` to create a Unix domain stream socket server, listening for an incoming connection
+///  * `unix:<path>` to create a Unix domain stream socket client
+///  * `unixgramin:<path>` to create a Unix domain datagram socket server, listening for incoming packets
+///  * `unixgram:<path>` to create a Unix domain datagram socket client
+///  * `wsin:<addr>:<port>` to create a WebSocket server, listening for an incoming connection
+///  * `ws://<host>:<port>/<path>` or `wss://<host>:<port>/<path>` to create a WebSocket client
+///  * `memory:<name>` to create an in-memory connection, linked to the next connection with the same name
 ///  * `file:<path>` to extract file data, writing to such a connection does nothing
+///  * `file:<path>?mode=write&format=<raw|tlog>` to append sent frames to a file, optionally with tlog timestamps
+///  * `file:<path>?mode=readwrite&output=<path>` to extract file data, appending sent frames to the output
+///  * `file:<path>?format=tlog&follow=true&throttle=true` to read a tlog as it is written, at the time it was
+///    recorded at, following and throttling is only supported by async connections
+///  * `replay:<path>?speed=<factor>&loop=<bool>` to replay a tlog with its recorded timing, optionally at a scaled
+///    speed and starting over at its end
 ///
 /// The type of the connection is determined at runtime based on the address type, so the
 /// connection is returned as a trait object.
+#[cfg(feature = "tokio-1")]
 pub async fn connect_async<M: Message + Sync + Send>(
     address: &str,
 ) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>> {
@@ -103,17 +488,17 @@ pub async fn connect_async<M: Message + Sync + Send>(
 }
 
 /// Returns the socket address for the given address.
-#[cfg(any(feature = "tcp", feature = "udp"))]
+#[cfg(all(
+    feature = "tokio-1",
+    any(feature = "tcp", feature = "udp", feature = "websocket")
+))]
 pub(crate) fn get_socket_addr<T: std::net::ToSocketAddrs>(
     address: T,
 ) -> Result<std::net::SocketAddr, io::Error> {
     let addr = match address.to_socket_addrs()?.next() {
         Some(addr) => addr,
         None => {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Host address lookup failed",
-            ));
+            return Err(io::Error::other("Host address lookup failed"));
         }
     };
     Ok(addr)
@@ -122,6 +507,7 @@ pub(crate) fn get_socket_addr<T: std::net::ToSocketAddrs>(
 /// A MAVLink connection address that can be connected to, establishing an [`AsyncMavConnection`]
 ///
 /// This is the `async` version of `Connectable`.
+#[cfg(feature = "tokio-1")]
 #[async_trait]
 pub trait AsyncConnectable {
     /// Attempt to establish an asynchronous MAVLink connection
@@ -130,6 +516,7 @@ pub trait AsyncConnectable {
         M: Message + Sync + Send;
 }
 
+#[cfg(feature = "tokio-1")]
 #[async_trait]
 impl AsyncConnectable for ConnectionAddress {
     async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
@@ -143,7 +530,13 @@ impl AsyncConnectable for ConnectionAddress {
             Self::Udp(connectable) => connectable.connect_async::<M>().await,
             #[cfg(feature = "direct-serial")]
             Self::Serial(connectable) => connectable.connect_async::<M>().await,
+            #[cfg(all(feature = "unix", unix))]
+            Self::Unix(connectable) => connectable.connect_async::<M>().await,
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(connectable) => connectable.connect_async::<M>().await,
+            Self::Memory(connectable) => connectable.connect_async::<M>().await,
             Self::File(connectable) => connectable.connect_async::<M>().await,
+            Self::Replay(connectable) => connectable.connect_async::<M>().await,
         }
     }
 }
@@ -0,0 +1,190 @@
+//! Async connection wrapper recording frames into a tlog
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::{AsyncMavConnection, AsyncMavReceiveHalf, AsyncMavSendHalf};
+use crate::connection::FrameEncoder;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::tlog::{parse_raw, record_raw, TlogSink};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
+
+#[cfg(feature = "signing")]
+use crate::SigningConfig;
+
+/// Async connection wrapper recording every frame sent and received over another connection into a tlog
+///
+/// The async counterpart of [`RecordingConnection`](crate::RecordingConnection). Records are written while the
+/// connection is polled, a [`TlogWriter`](crate::tlog::TlogWriter) should write to a buffered writer.
+pub struct AsyncRecordingConnection<M: Message + Sync + Send, S> {
+    connection: Box<dyn AsyncMavConnection<M> + Sync + Send>,
+    encoder: Mutex<FrameEncoder>,
+    sink: Arc<Mutex<S>>,
+}
+
+impl<M: Message + Sync + Send, S: TlogSink> AsyncRecordingConnection<M, S> {
+    /// Wrap `connection`, recording its frames into `sink`
+    pub fn new(connection: Box<dyn AsyncMavConnection<M> + Sync + Send>, sink: S) -> Self {
+        Self {
+            connection,
+            encoder: Mutex::new(FrameEncoder::new()),
+            sink: Arc::new(Mutex::new(sink)),
+        }
+    }
+
+    /// The wrapped connection
+    pub fn get_ref(&self) -> &(dyn AsyncMavConnection<M> + Sync + Send) {
+        self.connection.as_ref()
+    }
+
+    /// Flush the records written so far
+    pub fn flush_log(&self) -> io::Result<()> {
+        self.sink.lock().unwrap().flush()
+    }
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send + 'static, S: TlogSink + 'static> AsyncMavConnection<M>
+    for AsyncRecordingConnection<M, S>
+{
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let raw = self.recv_raw().await?;
+        Ok(parse_raw(&raw)?)
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let raw = self.connection.recv_raw().await?;
+        record_raw(&self.sink, &raw)?;
+        Ok(raw)
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self.encoder.lock().unwrap().encode(
+            self.connection.protocol_version(),
+            header,
+            data,
+        )?;
+        self.send_raw(&frame).await
+    }
+
+    async fn send_raw(&self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let len = self.connection.send_raw(frame).await?;
+        record_raw(&self.sink, frame)?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.connection.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.connection.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.connection.allow_recv_any_version()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.encoder
+            .get_mut()
+            .unwrap()
+            .setup_signing(signing_data.clone());
+        self.connection.setup_signing(signing_data);
+    }
+
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn AsyncMavReceiveHalf<M> + Send>,
+        Box<dyn AsyncMavSendHalf<M> + Send>,
+    ) {
+        let (receive_half, send_half) = self.connection.split();
+        let receive_half = AsyncRecordingReceiveHalf {
+            half: receive_half,
+            sink: self.sink.clone(),
+        };
+        let send_half = AsyncRecordingSendHalf {
+            half: send_half,
+            encoder: self.encoder.into_inner().unwrap(),
+            sink: self.sink,
+        };
+        (Box::new(receive_half), Box::new(send_half))
+    }
+}
+
+/// The receiving half of an [`AsyncRecordingConnection`], sharing the log with the sending half
+pub struct AsyncRecordingReceiveHalf<M: Message + Sync + Send, S> {
+    half: Box<dyn AsyncMavReceiveHalf<M> + Send>,
+    sink: Arc<Mutex<S>>,
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send, S: TlogSink> AsyncMavReceiveHalf<M>
+    for AsyncRecordingReceiveHalf<M, S>
+{
+    async fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        let raw = self.recv_raw().await?;
+        Ok(parse_raw(&raw)?)
+    }
+
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let raw = self.half.recv_raw().await?;
+        record_raw(&self.sink, &raw)?;
+        Ok(raw)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.half.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.half.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.half.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.half.allow_recv_any_version()
+    }
+}
+
+/// The sending half of an [`AsyncRecordingConnection`], sharing the log with the receiving half
+pub struct AsyncRecordingSendHalf<M: Message + Sync + Send, S> {
+    half: Box<dyn AsyncMavSendHalf<M> + Send>,
+    encoder: FrameEncoder,
+    sink: Arc<Mutex<S>>,
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send, S: TlogSink> AsyncMavSendHalf<M> for AsyncRecordingSendHalf<M, S> {
+    async fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self
+            .encoder
+            .encode(self.half.protocol_version(), header, data)?;
+        self.send_raw(&frame).await
+    }
+
+    async fn send_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let len = self.half.send_raw(frame).await?;
+        record_raw(&self.sink, frame)?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.half.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.half.protocol_version()
+    }
+}
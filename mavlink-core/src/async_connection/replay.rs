@@ -0,0 +1,156 @@
+//! Async replay of a tlog with its recorded timing
+
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{
+    AsyncConnectable, AsyncHalvesConnection, AsyncMavConnection, AsyncMavReceiveHalf,
+    AsyncMavSendHalf,
+};
+use crate::connection::replay::config::ReplayConfig;
+use crate::connection::replay::{Next, ReplayControl, Shared};
+use crate::connection::ConnectionHalf;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::tlog::parse_raw;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+use async_trait::async_trait;
+
+#[cfg(feature = "signing")]
+use crate::SigningData;
+
+/// Longest wait before changes of the replay controls are noticed
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+async fn recv_raw<M: Message>(
+    shared: &Shared,
+    version: ReadVersion,
+) -> Result<MAVLinkMessageRaw, MessageReadError> {
+    loop {
+        let next = shared.state.lock().unwrap().poll::<M>(Instant::now())?;
+        match next {
+            Next::Frame(raw) if version.accepts_stx(raw.raw_bytes()[0]) => return Ok(raw),
+            Next::Frame(_) => {}
+            Next::Wait(delay) => {
+                tokio::time::sleep(delay.map_or(POLL_INTERVAL, |delay| delay.min(POLL_INTERVAL)))
+                    .await;
+            }
+        }
+    }
+}
+
+async fn recv<M: Message>(
+    shared: &Shared,
+    version: ReadVersion,
+) -> Result<(MavHeader, M), MessageReadError> {
+    loop {
+        // skip frames of unknown messages
+        if let Ok(message) = parse_raw(&recv_raw::<M>(shared, version).await?) {
+            return Ok(message);
+        }
+    }
+}
+
+/// Async connection replaying the frames of a tlog, see [`ReplayConnection`](crate::ReplayConnection)
+pub type AsyncReplayConnection = AsyncHalvesConnection<AsyncReplayReceiveHalf, AsyncReplaySendHalf>;
+
+impl AsyncReplayConnection {
+    /// Open the tlog of a replay address
+    pub fn open(config: &ReplayConfig) -> io::Result<Self> {
+        let receive_half = AsyncReplayReceiveHalf {
+            shared: Shared::open(config)?,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+        };
+        let send_half = AsyncReplaySendHalf {
+            protocol_version: MavlinkVersion::V2,
+        };
+        Ok(Self::from_halves(receive_half, send_half))
+    }
+
+    /// Control over the replay, which stays usable after the connection is split
+    pub fn control(&mut self) -> ReplayControl {
+        ReplayControl::new(self.receive_half.get_mut().shared.clone())
+    }
+}
+
+/// The receiving half of an [`AsyncReplayConnection`]
+pub struct AsyncReplayReceiveHalf {
+    shared: Arc<Shared>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send> AsyncMavReceiveHalf<M> for AsyncReplayReceiveHalf {
+    async fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        recv(&self.shared, version).await
+    }
+
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        recv_raw::<M>(&self.shared, version).await
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for AsyncReplayReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, _signing_data: Option<Arc<SigningData>>) {}
+}
+
+/// The sending half of an [`AsyncReplayConnection`], writing to it does nothing
+pub struct AsyncReplaySendHalf {
+    protocol_version: MavlinkVersion,
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send> AsyncMavSendHalf<M> for AsyncReplaySendHalf {
+    async fn send(&mut self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
+        Ok(0)
+    }
+
+    async fn send_raw(&mut self, _frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        Ok(0)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for AsyncReplaySendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, _signing_data: Option<Arc<SigningData>>) {}
+}
+
+#[async_trait]
+impl AsyncConnectable for ReplayConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        Ok(Box::new(AsyncReplayConnection::open(self)?))
+    }
+}
@@ -0,0 +1,208 @@
+//! Async MAVLink connection over any [`futures::AsyncRead`]/[`futures::AsyncWrite`] stream
+
+#[cfg(feature = "signing")]
+use std::sync::Arc;
+
+use futures::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+use super::{AsyncHalvesConnection, AsyncMavReceiveHalf, AsyncMavSendHalf};
+use crate::async_peek_reader::AsyncPeekReader;
+use crate::connection::ConnectionHalf;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_raw_versioned_msg_async, read_versioned_msg_async, write_versioned_msg_async};
+#[cfg(feature = "signing")]
+use crate::{
+    read_raw_versioned_msg_async_signed, read_versioned_msg_async_signed,
+    write_versioned_msg_async_signed, SigningData,
+};
+
+/// Async MAVLink connection over a pair of [`futures::AsyncRead`] and [`futures::AsyncWrite`] halves.
+///
+/// This does not depend on a specific async runtime, so it can be used with e.g. async-std or smol streams.
+/// Tokio streams can be used through the compatibility layer of `tokio-util`.
+///
+/// # Example
+/// ```ignore
+/// use mavlink::{AsyncMavConnection, AsyncStreamConnection};
+///
+/// let stream = async_std::net::TcpStream::connect("127.0.0.1:5760").await?;
+/// let connection = AsyncStreamConnection::new(stream.clone(), stream);
+/// let (header, msg) = AsyncMavConnection::<MavMessage>::recv(&connection).await?;
+/// ```
+pub type AsyncStreamConnection<R, W> =
+    AsyncHalvesConnection<AsyncStreamReceiveHalf<R>, AsyncStreamSendHalf<W>>;
+
+struct StreamWrite<W> {
+    stream: W,
+    sequence: u8,
+}
+
+impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> AsyncStreamConnection<R, W> {
+    /// Create a MAVLink 2 connection from a reader and a writer
+    pub fn new(reader: R, writer: W) -> Self {
+        let receive_half = AsyncStreamReceiveHalf {
+            reader: AsyncPeekReader::new(reader),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        };
+        let send_half = AsyncStreamSendHalf {
+            writer: StreamWrite {
+                stream: writer,
+                sequence: 0,
+            },
+            protocol_version: MavlinkVersion::V2,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        };
+        Self::from_halves(receive_half, send_half)
+    }
+
+    /// Deconstruct the connection into its reader and writer
+    ///
+    /// Data that was already read from the reader but not yet parsed is lost.
+    pub fn into_inner(self) -> (R, W) {
+        (
+            self.receive_half.into_inner().reader.into_inner(),
+            self.send_half.into_inner().writer.stream,
+        )
+    }
+}
+
+/// The receiving half of an [`AsyncStreamConnection`]
+pub struct AsyncStreamReceiveHalf<R> {
+    reader: AsyncPeekReader<R>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+#[async_trait::async_trait]
+impl<M, R> AsyncMavReceiveHalf<M> for AsyncStreamReceiveHalf<R>
+where
+    M: Message + Sync + Send,
+    R: AsyncRead + Unpin + Send,
+{
+    async fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg_async(&mut self.reader, version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_msg_async_signed(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        )
+        .await;
+        result
+    }
+
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg_async::<M, _>(&mut self.reader, version).await;
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_async_signed::<M, _>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        )
+        .await;
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl<R> ConnectionHalf for AsyncStreamReceiveHalf<R> {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of an [`AsyncStreamConnection`]
+pub struct AsyncStreamSendHalf<W> {
+    writer: StreamWrite<W>,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+#[async_trait::async_trait]
+impl<M, W> AsyncMavSendHalf<M> for AsyncStreamSendHalf<W>
+where
+    M: Message + Sync + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    async fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.writer.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.writer.sequence = self.writer.sequence.wrapping_add(1);
+        #[cfg(not(feature = "signing"))]
+        let len =
+            write_versioned_msg_async(&mut self.writer.stream, self.protocol_version, header, data)
+                .await?;
+        #[cfg(feature = "signing")]
+        let len = write_versioned_msg_async_signed(
+            &mut self.writer.stream,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_deref(),
+        )
+        .await?;
+        self.writer.stream.flush().await?;
+        Ok(len)
+    }
+
+    async fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.writer.stream.write_all(frame.raw_bytes()).await?;
+        self.writer.stream.flush().await?;
+        Ok(frame.raw_bytes().len())
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl<W> ConnectionHalf for AsyncStreamSendHalf<W> {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
@@ -1,28 +1,33 @@
 //! Async UDP MAVLink connection
 
-use core::{ops::DerefMut, task::Poll};
+use core::task::Poll;
 use std::io;
-use std::{collections::VecDeque, io::Read, sync::Arc};
+use std::{
+    collections::VecDeque,
+    io::Read,
+    sync::{self, Arc},
+};
 
 use async_trait::async_trait;
-use futures::lock::Mutex;
-use tokio::{
-    io::{AsyncRead, ReadBuf},
-    net::UdpSocket,
-};
+use futures::AsyncRead;
+use tokio::{io::ReadBuf, net::UdpSocket};
 
 use crate::connection::udp::config::{UdpConfig, UdpMode};
 use crate::MAVLinkMessageRaw;
 use crate::{async_peek_reader::AsyncPeekReader, MavHeader, MavlinkVersion, Message, ReadVersion};
 
-use super::{get_socket_addr, AsyncConnectable, AsyncMavConnection};
+use super::{
+    get_socket_addr, AsyncConnectable, AsyncHalvesConnection, AsyncMavConnection,
+    AsyncMavReceiveHalf, AsyncMavSendHalf,
+};
+use crate::connection::ConnectionHalf;
 
 #[cfg(not(feature = "signing"))]
 use crate::{read_raw_versioned_msg_async, read_versioned_msg_async, write_versioned_msg_async};
 #[cfg(feature = "signing")]
 use crate::{
     read_raw_versioned_msg_async_signed, read_versioned_msg_async_signed,
-    write_versioned_msg_signed, SigningConfig, SigningData,
+    write_versioned_msg_signed, SigningData,
 };
 
 struct UdpRead {
@@ -36,8 +41,8 @@ impl AsyncRead for UdpRead {
     fn poll_read(
         mut self: core::pin::Pin<&mut Self>,
         cx: &mut core::task::Context<'_>,
-        buf: &mut ReadBuf<'_>,
-    ) -> Poll<io::Result<()>> {
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
         if self.buffer.is_empty() {
             let mut read_buffer = [0u8; MTU_SIZE];
             let mut read_buffer = ReadBuf::new(&mut read_buffer);
@@ -46,161 +51,194 @@ impl AsyncRead for UdpRead {
                 Poll::Ready(Ok(address)) => {
                     let n_buffer = read_buffer.filled().len();
 
-                    let n = (&read_buffer.filled()[0..n_buffer]).read(buf.initialize_unfilled())?;
-                    buf.advance(n);
+                    let n = (&read_buffer.filled()[0..n_buffer]).read(buf)?;
 
                     self.buffer.extend(&read_buffer.filled()[n..n_buffer]);
                     self.last_recv_address = Some(address);
-                    Poll::Ready(Ok(()))
+                    Poll::Ready(Ok(n))
                 }
                 Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
                 Poll::Pending => Poll::Pending,
             }
         } else {
-            let read_result = self.buffer.read(buf.initialize_unfilled());
-            let result = match read_result {
-                Ok(n) => {
-                    buf.advance(n);
-                    Ok(())
-                }
-                Err(err) => Err(err),
-            };
-            Poll::Ready(result)
+            Poll::Ready(self.buffer.read(buf))
         }
     }
 }
 
-struct UdpWrite {
-    socket: Arc<UdpSocket>,
+/// Async UDP MAVLink connection
+pub type AsyncUdpConnection = AsyncHalvesConnection<AsyncUdpReceiveHalf, AsyncUdpSendHalf>;
+
+fn connection(
+    socket: UdpSocket,
+    server: bool,
     dest: Option<std::net::SocketAddr>,
-    sequence: u8,
+) -> AsyncUdpConnection {
+    let socket = Arc::new(socket);
+    // a server replies to the last sender, so the receive half has to update the destination of the send half
+    let dest = Arc::new(sync::Mutex::new(dest));
+    let receive_half = AsyncUdpReceiveHalf {
+        reader: AsyncPeekReader::new(UdpRead {
+            socket: socket.clone(),
+            buffer: VecDeque::new(),
+            last_recv_address: None,
+        }),
+        server,
+        dest: dest.clone(),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = AsyncUdpSendHalf {
+        socket,
+        dest,
+        sequence: 0,
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    AsyncHalvesConnection::from_halves(receive_half, send_half)
 }
 
-pub struct AsyncUdpConnection {
-    reader: Mutex<AsyncPeekReader<UdpRead>>,
-    writer: Mutex<UdpWrite>,
+/// The receiving half of an [`AsyncUdpConnection`]
+pub struct AsyncUdpReceiveHalf {
+    reader: AsyncPeekReader<UdpRead>,
+    server: bool,
+    dest: Arc<sync::Mutex<Option<std::net::SocketAddr>>>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
-    server: bool,
     #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+    signing_data: Option<Arc<SigningData>>,
 }
 
-impl AsyncUdpConnection {
-    fn new(
-        socket: UdpSocket,
-        server: bool,
-        dest: Option<std::net::SocketAddr>,
-    ) -> io::Result<Self> {
-        let socket = Arc::new(socket);
-        Ok(Self {
-            server,
-            reader: Mutex::new(AsyncPeekReader::new(UdpRead {
-                socket: socket.clone(),
-                buffer: VecDeque::new(),
-                last_recv_address: None,
-            })),
-            writer: Mutex::new(UdpWrite {
-                socket,
-                dest,
-                sequence: 0,
-            }),
-            protocol_version: MavlinkVersion::V2,
-            recv_any_version: false,
-            #[cfg(feature = "signing")]
-            signing_data: None,
-        })
+impl AsyncUdpReceiveHalf {
+    fn update_dest(&mut self) {
+        if self.server {
+            if let addr @ Some(_) = self.reader.reader_ref().last_recv_address {
+                *self.dest.lock().unwrap() = addr;
+            }
+        }
     }
 }
 
 #[async_trait::async_trait]
-impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpConnection {
-    async fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().await;
-        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+impl<M: Message + Sync + Send> AsyncMavReceiveHalf<M> for AsyncUdpReceiveHalf {
+    async fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
         loop {
             #[cfg(not(feature = "signing"))]
-            let result = read_versioned_msg_async(reader.deref_mut(), version).await;
+            let result = read_versioned_msg_async(&mut self.reader, version).await;
             #[cfg(feature = "signing")]
             let result = read_versioned_msg_async_signed(
-                reader.deref_mut(),
+                &mut self.reader,
                 version,
-                self.signing_data.as_ref(),
+                self.signing_data.as_deref(),
             )
             .await;
-            if self.server {
-                if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                    self.writer.lock().await.dest = addr;
-                }
-            }
+            self.update_dest();
             if let ok @ Ok(..) = result {
                 return ok;
             }
         }
     }
 
-    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().await;
-        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
         loop {
             #[cfg(not(feature = "signing"))]
-            let result = read_raw_versioned_msg_async::<M, _>(reader.deref_mut(), version).await;
+            let result = read_raw_versioned_msg_async::<M, _>(&mut self.reader, version).await;
             #[cfg(feature = "signing")]
             let result = read_raw_versioned_msg_async_signed::<M, _>(
-                reader.deref_mut(),
+                &mut self.reader,
                 version,
-                self.signing_data.as_ref(),
+                self.signing_data.as_deref(),
             )
             .await;
-            if self.server {
-                if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                    self.writer.lock().await.dest = addr;
-                }
-            }
+            self.update_dest();
             if let ok @ Ok(..) = result {
                 return ok;
             }
         }
     }
 
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for AsyncUdpReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of an [`AsyncUdpConnection`]
+pub struct AsyncUdpSendHalf {
+    socket: Arc<UdpSocket>,
+    dest: Arc<sync::Mutex<Option<std::net::SocketAddr>>>,
+    sequence: u8,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavSendHalf<M> for AsyncUdpSendHalf {
     async fn send(
-        &self,
+        &mut self,
         header: &MavHeader,
         data: &M,
     ) -> Result<usize, crate::error::MessageWriteError> {
-        let mut guard = self.writer.lock().await;
-        let state = &mut *guard;
-
         let header = MavHeader {
-            sequence: state.sequence,
+            sequence: self.sequence,
             system_id: header.system_id,
             component_id: header.component_id,
         };
 
-        state.sequence = state.sequence.wrapping_add(1);
+        self.sequence = self.sequence.wrapping_add(1);
 
-        let len = if let Some(addr) = state.dest {
+        let dest = *self.dest.lock().unwrap();
+        let len = if let Some(addr) = dest {
             let mut buf = Vec::new();
             #[cfg(not(feature = "signing"))]
-            write_versioned_msg_async(
-                &mut buf,
-                self.protocol_version,
-                header,
-                data,
-                #[cfg(feature = "signing")]
-                self.signing_data.as_ref(),
-            )
-            .await?;
+            write_versioned_msg_async(&mut buf, self.protocol_version, header, data).await?;
             #[cfg(feature = "signing")]
             write_versioned_msg_signed(
                 &mut buf,
                 self.protocol_version,
                 header,
                 data,
-                #[cfg(feature = "signing")]
-                self.signing_data.as_ref(),
+                self.signing_data.as_deref(),
             )?;
-            state.socket.send_to(&buf, addr).await?
+            self.socket.send_to(&buf, addr).await?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    async fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let dest = *self.dest.lock().unwrap();
+        let len = if let Some(addr) = dest {
+            self.socket.send_to(frame.raw_bytes(), addr).await?
         } else {
             0
         };
@@ -215,18 +253,12 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpConnection {
     fn protocol_version(&self) -> MavlinkVersion {
         self.protocol_version
     }
+}
 
-    fn set_allow_recv_any_version(&mut self, allow: bool) {
-        self.recv_any_version = allow;
-    }
-
-    fn allow_recv_any_version(&self) -> bool {
-        self.recv_any_version
-    }
-
+impl ConnectionHalf for AsyncUdpSendHalf {
     #[cfg(feature = "signing")]
-    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
 }
 
@@ -244,14 +276,14 @@ impl AsyncConnectable for UdpConfig {
         if matches!(self.mode, UdpMode::Udpcast) {
             socket.set_broadcast(true)?;
         }
-        Ok(Box::new(AsyncUdpConnection::new(socket, server, dest)?))
+        Ok(Box::new(connection(socket, server, dest)))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::io::AsyncReadExt;
+    use futures::AsyncReadExt;
 
     #[tokio::test]
     async fn test_datagram_buffering() {
@@ -0,0 +1,330 @@
+//! Async Unix domain socket MAVLink connection
+
+use core::task::Poll;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::{
+    collections::VecDeque,
+    io::Read,
+    sync::{self, Arc},
+};
+
+use async_trait::async_trait;
+use futures::AsyncRead;
+use tokio::io::ReadBuf;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixDatagram, UnixListener, UnixStream};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use crate::connection::unix::config::{UnixConfig, UnixMode};
+use crate::connection::unix::BoundPath;
+use crate::MAVLinkMessageRaw;
+use crate::{async_peek_reader::AsyncPeekReader, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+use super::{
+    AsyncConnectable, AsyncHalvesConnection, AsyncMavConnection, AsyncMavReceiveHalf,
+    AsyncMavSendHalf, AsyncStreamConnection,
+};
+use crate::connection::ConnectionHalf;
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_raw_versioned_msg_async, read_versioned_msg_async, write_versioned_msg_async};
+#[cfg(feature = "signing")]
+use crate::{
+    read_raw_versioned_msg_async_signed, read_versioned_msg_async_signed,
+    write_versioned_msg_signed, SigningData,
+};
+
+pub type AsyncUnixConnection = AsyncStreamConnection<Compat<OwnedReadHalf>, Compat<OwnedWriteHalf>>;
+
+fn from_stream(socket: UnixStream) -> AsyncUnixConnection {
+    let (reader, writer) = socket.into_split();
+    AsyncStreamConnection::new(reader.compat(), writer.compat_write())
+}
+
+pub async fn unixout<P: AsRef<Path>>(path: P) -> io::Result<AsyncUnixConnection> {
+    let socket = UnixStream::connect(path).await?;
+
+    Ok(from_stream(socket))
+}
+
+pub async fn unixin<P: AsRef<Path>>(path: P) -> io::Result<AsyncUnixConnection> {
+    let listener = UnixListener::bind(&path)?;
+    // the socket file is only needed to accept the connection
+    let _path = BoundPath::new(path.as_ref().to_path_buf());
+
+    //For now we only accept one incoming stream: this yields until we get one
+    let (socket, _) = listener.accept().await?;
+
+    Ok(from_stream(socket))
+}
+
+pub async fn unixgramin<P: AsRef<Path>>(path: P) -> io::Result<AsyncUnixDatagramConnection> {
+    let socket = UnixDatagram::bind(&path)?;
+    let path = BoundPath::new(path.as_ref().to_path_buf());
+    Ok(datagram_connection(socket, path, true, None))
+}
+
+pub async fn unixgramout<P: AsRef<Path>>(path: P) -> io::Result<AsyncUnixDatagramConnection> {
+    let local = BoundPath::temporary();
+    let socket = UnixDatagram::bind(local.path())?;
+    Ok(datagram_connection(
+        socket,
+        local,
+        false,
+        Some(path.as_ref().to_path_buf()),
+    ))
+}
+
+struct DatagramRead {
+    socket: Arc<UnixDatagram>,
+    buffer: VecDeque<u8>,
+    last_recv_address: Option<PathBuf>,
+    // keeps the socket file of the bound path around as long as messages can be received
+    _path: BoundPath,
+}
+
+const MTU_SIZE: usize = 1500;
+impl AsyncRead for DatagramRead {
+    fn poll_read(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.buffer.is_empty() {
+            let mut read_buffer = [0u8; MTU_SIZE];
+            let mut read_buffer = ReadBuf::new(&mut read_buffer);
+
+            match self.socket.poll_recv_from(cx, &mut read_buffer) {
+                Poll::Ready(Ok(address)) => {
+                    let n_buffer = read_buffer.filled().len();
+
+                    let n = (&read_buffer.filled()[0..n_buffer]).read(buf)?;
+
+                    self.buffer.extend(&read_buffer.filled()[n..n_buffer]);
+                    // replies are only possible to senders bound to a path
+                    self.last_recv_address = address.as_pathname().map(Path::to_path_buf);
+                    Poll::Ready(Ok(n))
+                }
+                Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+                Poll::Pending => Poll::Pending,
+            }
+        } else {
+            Poll::Ready(self.buffer.read(buf))
+        }
+    }
+}
+
+/// Async Unix domain datagram socket MAVLink connection
+pub type AsyncUnixDatagramConnection =
+    AsyncHalvesConnection<AsyncUnixDatagramReceiveHalf, AsyncUnixDatagramSendHalf>;
+
+fn datagram_connection(
+    socket: UnixDatagram,
+    path: BoundPath,
+    server: bool,
+    dest: Option<PathBuf>,
+) -> AsyncUnixDatagramConnection {
+    let socket = Arc::new(socket);
+    // a server replies to the last sender, so the receive half has to update the destination of the send half
+    let dest = Arc::new(sync::Mutex::new(dest));
+    let receive_half = AsyncUnixDatagramReceiveHalf {
+        reader: AsyncPeekReader::new(DatagramRead {
+            socket: socket.clone(),
+            buffer: VecDeque::new(),
+            last_recv_address: None,
+            _path: path,
+        }),
+        server,
+        dest: dest.clone(),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = AsyncUnixDatagramSendHalf {
+        socket,
+        dest,
+        sequence: 0,
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    AsyncHalvesConnection::from_halves(receive_half, send_half)
+}
+
+/// The receiving half of an [`AsyncUnixDatagramConnection`]
+pub struct AsyncUnixDatagramReceiveHalf {
+    reader: AsyncPeekReader<DatagramRead>,
+    server: bool,
+    dest: Arc<sync::Mutex<Option<PathBuf>>>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl AsyncUnixDatagramReceiveHalf {
+    fn update_dest(&mut self) {
+        if self.server {
+            if let addr @ Some(_) = &self.reader.reader_ref().last_recv_address {
+                *self.dest.lock().unwrap() = addr.clone();
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavReceiveHalf<M> for AsyncUnixDatagramReceiveHalf {
+    async fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_msg_async(&mut self.reader, version).await;
+            #[cfg(feature = "signing")]
+            let result = read_versioned_msg_async_signed(
+                &mut self.reader,
+                version,
+                self.signing_data.as_deref(),
+            )
+            .await;
+            self.update_dest();
+            if let ok @ Ok(..) = result {
+                return ok;
+            }
+        }
+    }
+
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let result = read_raw_versioned_msg_async::<M, _>(&mut self.reader, version).await;
+            #[cfg(feature = "signing")]
+            let result = read_raw_versioned_msg_async_signed::<M, _>(
+                &mut self.reader,
+                version,
+                self.signing_data.as_deref(),
+            )
+            .await;
+            self.update_dest();
+            if let ok @ Ok(..) = result {
+                return ok;
+            }
+        }
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for AsyncUnixDatagramReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of an [`AsyncUnixDatagramConnection`]
+pub struct AsyncUnixDatagramSendHalf {
+    socket: Arc<UnixDatagram>,
+    dest: Arc<sync::Mutex<Option<PathBuf>>>,
+    sequence: u8,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavSendHalf<M> for AsyncUnixDatagramSendHalf {
+    async fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.sequence = self.sequence.wrapping_add(1);
+
+        let dest = self.dest.lock().unwrap().clone();
+        let len = if let Some(addr) = dest {
+            let mut buf = Vec::new();
+            #[cfg(not(feature = "signing"))]
+            write_versioned_msg_async(&mut buf, self.protocol_version, header, data).await?;
+            #[cfg(feature = "signing")]
+            write_versioned_msg_signed(
+                &mut buf,
+                self.protocol_version,
+                header,
+                data,
+                self.signing_data.as_deref(),
+            )?;
+            self.socket.send_to(&buf, addr).await?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    async fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let dest = self.dest.lock().unwrap().clone();
+        let len = if let Some(addr) = dest {
+            self.socket.send_to(frame.raw_bytes(), addr).await?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for AsyncUnixDatagramSendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+#[async_trait]
+impl AsyncConnectable for UnixConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        Ok(match self.mode {
+            UnixMode::StreamIn => Box::new(unixin(&self.path).await?),
+            UnixMode::StreamOut => Box::new(unixout(&self.path).await?),
+            UnixMode::DatagramIn => Box::new(unixgramin(&self.path).await?),
+            UnixMode::DatagramOut => Box::new(unixgramout(&self.path).await?),
+        })
+    }
+}
@@ -0,0 +1,173 @@
+//! Async WebSocket MAVLink connection
+
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use async_trait::async_trait;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{AsyncRead, AsyncWrite, SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::client::{uri_mode, IntoClientRequest};
+use tokio_tungstenite::tungstenite::stream::Mode;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+use super::{get_socket_addr, AsyncConnectable, AsyncMavConnection, AsyncStreamConnection};
+use crate::connection::websocket::config::{WebSocketConfig, WebSocketMode};
+use crate::connection::websocket::to_io_error;
+use crate::Message;
+
+/// Reads the binary messages of a WebSocket as a byte stream
+pub struct WsRead<S> {
+    stream: SplitStream<WebSocketStream<S>>,
+    buffer: VecDeque<u8>,
+}
+
+impl<S> AsyncRead for WsRead<S>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        while self.buffer.is_empty() {
+            match ready!(self.stream.poll_next_unpin(cx)) {
+                Some(Ok(WsMessage::Binary(data))) => self.buffer.extend(data),
+                Some(Ok(WsMessage::Close(_))) | None => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "WebSocket closed",
+                    )))
+                }
+                // text messages are ignored, control messages are handled by the WebSocket
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Poll::Ready(Err(to_io_error(e))),
+            }
+        }
+        Poll::Ready(self.buffer.read(buf))
+    }
+}
+
+/// Buffers written bytes and sends them as one binary message when flushed
+pub struct WsWrite<S> {
+    sink: SplitSink<WebSocketStream<S>, WsMessage>,
+    buffer: Vec<u8>,
+}
+
+impl<S> AsyncWrite for WsWrite<S>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.buffer.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.buffer.is_empty() {
+            ready!(self.sink.poll_ready_unpin(cx)).map_err(to_io_error)?;
+            let data = core::mem::take(&mut self.buffer);
+            self.sink
+                .start_send_unpin(WsMessage::Binary(data))
+                .map_err(to_io_error)?;
+        }
+        self.sink.poll_flush_unpin(cx).map_err(to_io_error)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        self.sink.poll_close_unpin(cx).map_err(to_io_error)
+    }
+}
+
+pub type AsyncWebSocketConnection<S> = AsyncStreamConnection<WsRead<S>, WsWrite<S>>;
+
+fn from_stream<S>(socket: WebSocketStream<S>) -> AsyncWebSocketConnection<S>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (sink, stream) = socket.split();
+    AsyncStreamConnection::new(
+        WsRead {
+            stream,
+            buffer: VecDeque::new(),
+        },
+        WsWrite {
+            sink,
+            buffer: Vec::new(),
+        },
+    )
+}
+
+pub async fn wsin<T: std::net::ToSocketAddrs>(
+    address: T,
+) -> io::Result<AsyncWebSocketConnection<TcpStream>> {
+    let addr = get_socket_addr(address)?;
+    let listener = TcpListener::bind(addr).await?;
+
+    //For now we only accept one incoming stream: this yields until we get one
+    let (stream, _) = listener.accept().await?;
+    let socket = tokio_tungstenite::accept_async(stream)
+        .await
+        .map_err(to_io_error)?;
+    Ok(from_stream(socket))
+}
+
+async fn wsout<M: Message + Sync + Send>(
+    url: &str,
+) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>> {
+    let request = url.into_client_request().map_err(to_io_error)?;
+    let mode = uri_mode(request.uri()).map_err(to_io_error)?;
+    let host = request.uri().host().ok_or(io::Error::new(
+        io::ErrorKind::AddrNotAvailable,
+        "Missing WebSocket host",
+    ))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = request.uri().port_u16().unwrap_or(match mode {
+        Mode::Plain => 80,
+        Mode::Tls => 443,
+    });
+    let stream = TcpStream::connect((host, port)).await?;
+
+    match mode {
+        Mode::Plain => {
+            let (socket, _response) = tokio_tungstenite::client_async(request, stream)
+                .await
+                .map_err(to_io_error)?;
+            Ok(Box::new(from_stream(socket)))
+        }
+        #[cfg(feature = "websocket-tls")]
+        Mode::Tls => {
+            let (socket, _response) = tokio_tungstenite::client_async_tls(request, stream)
+                .await
+                .map_err(to_io_error)?;
+            Ok(Box::new(from_stream(socket)))
+        }
+        #[cfg(not(feature = "websocket-tls"))]
+        Mode::Tls => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "wss:// requires the websocket-tls feature",
+        )),
+    }
+}
+
+#[async_trait]
+impl AsyncConnectable for WebSocketConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        match self.mode {
+            WebSocketMode::WsIn => Ok(Box::new(wsin(&self.address).await?)),
+            WebSocketMode::WsOut => wsout::<M>(&self.address).await,
+        }
+    }
+}
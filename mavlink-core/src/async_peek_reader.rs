@@ -3,26 +3,29 @@
 //! The purpose of the buffered/peekable reader is to allow for backtracking parsers.
 //!
 //! This is the async version of [`crate::peek_reader::PeekReader`].
-//! A reader implementing the tokio library's [`tokio::io::AsyncBufRead`]/[`tokio::io::AsyncBufReadExt`] traits seems like a good fit, but
+//! A reader implementing the futures library's [`futures::AsyncBufRead`]/[`futures::AsyncBufReadExt`] traits seems like a good fit, but
 //! it does not allow for peeking a specific number of bytes, so it provides no way to request
 //! more data from the underlying reader without consuming the existing data.
 //!
-//! This API still tries to adhere to the [`tokio::io::AsyncBufRead`]'s trait philosophy.
+//! This API still tries to adhere to the [`futures::AsyncBufRead`]'s trait philosophy.
 //!
-//! The main type [`AsyncPeekReader`] does not implement [`tokio::io::AsyncBufReadExt`] itself, as there is no added benefit
+//! The main type [`AsyncPeekReader`] does not implement [`futures::AsyncBufReadExt`] itself, as there is no added benefit
 //! in doing so.
 //!
+//! Readers of other runtimes can be used through their `futures` compatibility layer, e.g. tokio readers
+//! via `tokio_util::compat::TokioAsyncReadCompatExt::compat`.
+//!
 
 #[cfg(doc)]
 use std::io::ErrorKind;
 
-use tokio::io::AsyncReadExt;
+use futures::AsyncReadExt;
 
 use crate::error::MessageReadError;
 
 /// A buffered/peekable reader
 ///
-/// This reader wraps a type implementing [`tokio::io::AsyncRead`] and adds buffering via an internal buffer.
+/// This reader wraps a type implementing [`futures::AsyncRead`] and adds buffering via an internal buffer.
 ///
 /// It allows the user to `peek` a specified number of bytes (without consuming them),
 /// to `read` bytes (consuming them), or to `consume` them after `peek`ing.
@@ -42,7 +45,7 @@ pub struct AsyncPeekReader<R, const BUFFER_SIZE: usize = 280> {
 }
 
 impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFER_SIZE> {
-    /// Instantiates a new [`AsyncPeekReader`], wrapping the provided [`tokio::io::AsyncReadExt`] and using the default chunk size
+    /// Instantiates a new [`AsyncPeekReader`], wrapping the provided [`futures::AsyncReadExt`] and using the default chunk size
     pub fn new(reader: R) -> Self {
         Self {
             buffer: [0; BUFFER_SIZE],
@@ -55,7 +58,7 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
     /// Peeks an exact amount of bytes from the internal buffer
     ///
     /// If the internal buffer does not contain enough data, this function will read
-    /// from the underlying [`tokio::io::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
+    /// from the underlying [`futures::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
     ///
     /// If an EOF occurs and the specified amount could not be read, this function will return an [`ErrorKind::UnexpectedEof`].
     ///
@@ -69,7 +72,7 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
     /// Reads a specified amount of bytes from the internal buffer
     ///
     /// If the internal buffer does not contain enough data, this function will read
-    /// from the underlying [`tokio::io::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
+    /// from the underlying [`futures::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
     ///
     /// If an EOF occurs and the specified amount could not be read, this function will return an [`ErrorKind::UnexpectedEof`].
     ///
@@ -82,7 +85,7 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
     /// Reads a byte from the internal buffer
     ///
     /// If the internal buffer does not contain enough data, this function will read
-    /// from the underlying [`tokio::io::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
+    /// from the underlying [`futures::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
     ///
     /// If an EOF occurs and the specified amount could not be read, this function will return an [`ErrorKind::UnexpectedEof`].
     ///
@@ -103,20 +106,27 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
         amount
     }
 
-    /// Returns an immutable reference to the underlying [`tokio::io::AsyncRead`]
+    /// Returns an immutable reference to the underlying [`futures::AsyncRead`]
     ///
     /// Reading directly from the underlying reader will cause data loss
     pub fn reader_ref(&mut self) -> &R {
         &self.reader
     }
 
-    /// Returns a mutable reference to the underlying [`tokio::io::AsyncRead`]
+    /// Returns a mutable reference to the underlying [`futures::AsyncRead`]
     ///
     /// Reading directly from the underlying reader will cause data loss
     pub fn reader_mut(&mut self) -> &mut R {
         &mut self.reader
     }
 
+    /// Consumes the [`AsyncPeekReader`], returning the underlying [`futures::AsyncRead`]
+    ///
+    /// Any buffered data is lost
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
     /// Internal function to fetch data from the internal buffer and/or reader
     async fn fetch(&mut self, amount: usize, consume: bool) -> Result<&[u8], MessageReadError> {
         let buffered = self.top - self.cursor;
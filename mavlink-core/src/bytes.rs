@@ -1,3 +1,5 @@
+use crate::error::ParserError;
+
 pub struct Bytes<'a> {
     data: &'a [u8],
     pos: usize,
@@ -19,116 +21,118 @@ impl<'a> Bytes<'a> {
     }
 
     #[inline]
-    fn check_remaining(&self, count: usize) {
-        assert!(
-            self.remaining() >= count,
-            "read buffer exhausted; remaining {} bytes, try read {count} bytes",
-            self.remaining(),
-        );
+    fn check_remaining(&self, count: usize) -> Result<(), ParserError> {
+        if self.remaining() < count {
+            return Err(ParserError::InvalidLength {
+                expected: self.pos + count,
+                actual: self.data.len(),
+            });
+        }
+        Ok(())
     }
 
     #[inline]
-    pub fn get_bytes(&mut self, count: usize) -> &[u8] {
-        self.check_remaining(count);
+    pub fn get_bytes(&mut self, count: usize) -> Result<&[u8], ParserError> {
+        self.check_remaining(count)?;
 
         let bytes = &self.data[self.pos..(self.pos + count)];
         self.pos += count;
-        bytes
+        Ok(bytes)
     }
 
     #[inline]
-    pub fn get_array<const SIZE: usize>(&mut self) -> [u8; SIZE] {
-        let bytes = self.get_bytes(SIZE);
+    pub fn get_array<const SIZE: usize>(&mut self) -> Result<[u8; SIZE], ParserError> {
+        let bytes = self.get_bytes(SIZE)?;
         let mut arr = [0u8; SIZE];
 
         arr.copy_from_slice(bytes);
 
         debug_assert_eq!(arr.as_slice(), bytes);
 
-        arr
+        Ok(arr)
     }
 
     #[inline]
-    pub fn get_u8(&mut self) -> u8 {
-        self.check_remaining(1);
+    pub fn get_u8(&mut self) -> Result<u8, ParserError> {
+        self.check_remaining(1)?;
 
         let val = self.data[self.pos];
         self.pos += 1;
-        val
+        Ok(val)
     }
 
     #[inline]
-    pub fn get_i8(&mut self) -> i8 {
-        self.check_remaining(1);
+    pub fn get_i8(&mut self) -> Result<i8, ParserError> {
+        self.check_remaining(1)?;
 
         let val = self.data[self.pos] as i8;
         self.pos += 1;
-        val
+        Ok(val)
     }
 
     #[inline]
-    pub fn get_u16_le(&mut self) -> u16 {
-        u16::from_le_bytes(self.get_array())
+    pub fn get_u16_le(&mut self) -> Result<u16, ParserError> {
+        Ok(u16::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_i16_le(&mut self) -> i16 {
-        i16::from_le_bytes(self.get_array())
+    pub fn get_i16_le(&mut self) -> Result<i16, ParserError> {
+        Ok(i16::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_u24_le(&mut self) -> u32 {
+    pub fn get_u24_le(&mut self) -> Result<u32, ParserError> {
         const SIZE: usize = 3;
-        self.check_remaining(SIZE);
+        self.check_remaining(SIZE)?;
 
         let mut val = [0u8; SIZE + 1];
         val[..3].copy_from_slice(&self.data[self.pos..self.pos + SIZE]);
         self.pos += SIZE;
 
         debug_assert_eq!(val[3], 0);
-        u32::from_le_bytes(val)
+        Ok(u32::from_le_bytes(val))
     }
 
     #[inline]
-    pub fn get_i24_le(&mut self) -> i32 {
+    pub fn get_i24_le(&mut self) -> Result<i32, ParserError> {
         const SIZE: usize = 3;
-        self.check_remaining(SIZE);
+        self.check_remaining(SIZE)?;
 
         let mut val = [0u8; SIZE + 1];
         val[..3].copy_from_slice(&self.data[self.pos..self.pos + SIZE]);
         self.pos += SIZE;
 
         debug_assert_eq!(val[3], 0);
-        i32::from_le_bytes(val)
+        Ok(i32::from_le_bytes(val))
     }
 
     #[inline]
-    pub fn get_u32_le(&mut self) -> u32 {
-        u32::from_le_bytes(self.get_array())
+    pub fn get_u32_le(&mut self) -> Result<u32, ParserError> {
+        Ok(u32::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_i32_le(&mut self) -> i32 {
-        i32::from_le_bytes(self.get_array())
+    pub fn get_i32_le(&mut self) -> Result<i32, ParserError> {
+        Ok(i32::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_u64_le(&mut self) -> u64 {
-        u64::from_le_bytes(self.get_array())
+    pub fn get_u64_le(&mut self) -> Result<u64, ParserError> {
+        Ok(u64::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_i64_le(&mut self) -> i64 {
-        i64::from_le_bytes(self.get_array())
+    pub fn get_i64_le(&mut self) -> Result<i64, ParserError> {
+        Ok(i64::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_f32_le(&mut self) -> f32 {
-        f32::from_le_bytes(self.get_array())
+    pub fn get_f32_le(&mut self) -> Result<f32, ParserError> {
+        Ok(f32::from_le_bytes(self.get_array()?))
     }
 
     #[inline]
-    pub fn get_f64_le(&mut self) -> f64 {
-        f64::from_le_bytes(self.get_array())
+    pub fn get_f64_le(&mut self) -> Result<f64, ParserError> {
+        Ok(f64::from_le_bytes(self.get_array()?))
     }
 }
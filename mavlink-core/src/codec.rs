@@ -0,0 +1,227 @@
+//! [`tokio_util::codec`] support for MAVLink frames
+//!
+//! The codecs allow using MAVLink with [`Framed`](tokio_util::codec::Framed) over any tokio
+//! [`AsyncRead`](tokio::io::AsyncRead)/[`AsyncWrite`](tokio::io::AsyncWrite), e.g. TCP or Unix sockets, pipes or
+//! in-memory duplex streams.
+//!
+//! # Example
+//! ```ignore
+//! use futures::{SinkExt, StreamExt};
+//! use mavlink::{MavlinkCodec, ReadVersion};
+//! use tokio_util::codec::Framed;
+//!
+//! let stream = tokio::net::TcpStream::connect("127.0.0.1:5760").await?;
+//! let mut framed = Framed::new(stream, MavlinkCodec::<MavMessage>::new(ReadVersion::Any));
+//! while let Some(frame) = framed.next().await {
+//!     let frame = frame?;
+//!     framed.send(frame).await?;
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use tokio_util::bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::error::{MessageReadError, MessageWriteError, ParserError};
+use crate::frame_parser::{check_any_frame, FrameCheck};
+use crate::{MAVLinkMessageRaw, MavFrame, MavHeader, Message, ReadVersion, MAX_FRAME_SIZE};
+
+#[cfg(feature = "signing")]
+use crate::{MavlinkVersion, SigningConfig, SigningData};
+
+// Decoding and encoding shared by the raw and parsing codec
+struct FrameCodec<M> {
+    version: ReadVersion,
+    discarded: usize,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<M: Message> FrameCodec<M> {
+    fn new(version: ReadVersion) -> Self {
+        Self {
+            version,
+            discarded: 0,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+            _message: PhantomData,
+        }
+    }
+
+    fn decode_raw(&mut self, src: &mut BytesMut) -> Option<MAVLinkMessageRaw> {
+        loop {
+            // search for the magic framing value indicating start of MAVLink message
+            let Some(start) = src.iter().position(|byte| self.version.accepts_stx(*byte)) else {
+                self.discarded += src.len();
+                src.clear();
+                return None;
+            };
+            self.discarded += start;
+            src.advance(start);
+
+            // like the readers, signing is ignored when decoding MAVLink 1 only
+            #[cfg(feature = "signing")]
+            let signing_data = self
+                .signing_data
+                .as_ref()
+                .filter(|_| self.version != ReadVersion::Single(MavlinkVersion::V1));
+            #[cfg(not(feature = "signing"))]
+            let signing_data = None;
+
+            match check_any_frame::<M>(src, signing_data) {
+                FrameCheck::Incomplete(len) => {
+                    src.reserve(len - src.len());
+                    return None;
+                }
+                FrameCheck::Valid(message) => {
+                    src.advance(message.raw_bytes().len());
+                    return Some(message);
+                }
+                FrameCheck::Rejected(len) => {
+                    self.discarded += len;
+                    src.advance(len);
+                }
+                // an STX byte may appear in the middle of a message, only skip the STX
+                FrameCheck::Invalid => {
+                    self.discarded += 1;
+                    src.advance(1);
+                }
+            }
+        }
+    }
+
+    fn encode(&self, frame: &MavFrame<M>, dst: &mut BytesMut) -> Result<(), MessageWriteError> {
+        let mut buf = [0u8; MAX_FRAME_SIZE];
+        #[cfg(feature = "signing")]
+        let len = frame.ser_signed(&mut buf, self.signing_data.as_ref())?;
+        #[cfg(not(feature = "signing"))]
+        let len = frame.ser(&mut buf)?;
+        dst.extend_from_slice(&buf[..len]);
+        Ok(())
+    }
+}
+
+macro_rules! impl_codec_config {
+    ($codec:ident) => {
+        impl<M: Message> $codec<M> {
+            /// Create a codec decoding frames of the given version(s)
+            pub fn new(version: ReadVersion) -> Self {
+                Self(FrameCodec::new(version))
+            }
+
+            /// Returns the version(s) of the decoded frames
+            pub fn version(&self) -> ReadVersion {
+                self.0.version
+            }
+
+            /// Set the version(s) of the decoded frames
+            ///
+            /// Encoded frames use the protocol version of the frame.
+            pub fn set_version(&mut self, version: ReadVersion) {
+                self.0.version = version;
+            }
+
+            /// Total number of bytes skipped while decoding, because they are not part of an accepted frame
+            pub fn discarded(&self) -> usize {
+                self.0.discarded
+            }
+
+            /// Setup secret key used for message signing, or disable message signing
+            ///
+            /// Encoded MAVLink 2 frames are signed and decoded frames with an invalid signature are skipped.
+            /// Signing is ignored when decoding [`ReadVersion::Single`]`(`[`MavlinkVersion::V1`]`)`.
+            #[cfg(feature = "signing")]
+            pub fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+                self.0.signing_data = signing_data.map(SigningData::from_config);
+            }
+        }
+
+        impl<M: Message> Encoder<MavFrame<M>> for $codec<M> {
+            type Error = MessageWriteError;
+
+            fn encode(
+                &mut self,
+                frame: MavFrame<M>,
+                dst: &mut BytesMut,
+            ) -> Result<(), Self::Error> {
+                self.0.encode(&frame, dst)
+            }
+        }
+
+        impl<M: Message> Encoder<&MavFrame<M>> for $codec<M> {
+            type Error = MessageWriteError;
+
+            fn encode(
+                &mut self,
+                frame: &MavFrame<M>,
+                dst: &mut BytesMut,
+            ) -> Result<(), Self::Error> {
+                self.0.encode(frame, dst)
+            }
+        }
+    };
+}
+
+/// [`Decoder`] and [`Encoder`] of parsed MAVLink frames
+///
+/// Decoding skips bytes that are not part of a valid frame and frames of messages that are not part of the dialect,
+/// like the readers of this crate do. A frame of a known message that can not be parsed results in an error, which
+/// ends the stream of a [`Framed`](tokio_util::codec::Framed).
+pub struct MavlinkCodec<M>(FrameCodec<M>);
+
+impl_codec_config!(MavlinkCodec);
+
+impl<M: Message> Decoder for MavlinkCodec<M> {
+    type Item = MavFrame<M>;
+    type Error = MessageReadError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            let Some(message) = self.0.decode_raw(src) else {
+                return Ok(None);
+            };
+            let msg = match M::parse(message.version(), message.message_id(), message.payload()) {
+                Ok(msg) => msg,
+                Err(ParserError::UnknownMessage { .. }) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            return Ok(Some(MavFrame {
+                header: MavHeader {
+                    sequence: message.sequence(),
+                    system_id: message.system_id(),
+                    component_id: message.component_id(),
+                },
+                msg,
+                protocol_version: message.version(),
+            }));
+        }
+    }
+}
+
+/// [`Decoder`] of raw MAVLink frames, which also encodes parsed and raw frames
+///
+/// Raw frames are encoded unchanged, so they keep their sequence number and signature, which makes this codec
+/// suitable for forwarding frames.
+pub struct RawMavlinkCodec<M>(FrameCodec<M>);
+
+impl_codec_config!(RawMavlinkCodec);
+
+impl<M: Message> Decoder for RawMavlinkCodec<M> {
+    type Item = MAVLinkMessageRaw;
+    type Error = MessageReadError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Ok(self.0.decode_raw(src))
+    }
+}
+
+impl<M: Message> Encoder<MAVLinkMessageRaw> for RawMavlinkCodec<M> {
+    type Error = MessageWriteError;
+
+    fn encode(&mut self, frame: MAVLinkMessageRaw, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(frame.raw_bytes());
+        Ok(())
+    }
+}
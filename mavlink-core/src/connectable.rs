@@ -4,11 +4,17 @@ use std::path::PathBuf;
 
 #[cfg(feature = "direct-serial")]
 use crate::connection::direct_serial::config::SerialConfig;
-use crate::connection::file::config::FileConfig;
+use crate::connection::file::config::{FileConfig, FileFormat, FileMode};
+use crate::connection::memory::config::MemoryConfig;
+use crate::connection::replay::config::{valid_speed, ReplayConfig};
 #[cfg(feature = "tcp")]
 use crate::connection::tcp::config::{TcpConfig, TcpMode};
 #[cfg(feature = "udp")]
 use crate::connection::udp::config::{UdpConfig, UdpMode};
+#[cfg(all(feature = "unix", unix))]
+use crate::connection::unix::config::{UnixConfig, UnixMode};
+#[cfg(feature = "websocket")]
+use crate::connection::websocket::config::{WebSocketConfig, WebSocketMode};
 
 /// A parsed MAVLink connection address
 pub enum ConnectionAddress {
@@ -21,8 +27,18 @@ pub enum ConnectionAddress {
     /// Serial port address
     #[cfg(feature = "direct-serial")]
     Serial(SerialConfig),
+    /// Unix domain stream or datagram socket client or server address
+    #[cfg(all(feature = "unix", unix))]
+    Unix(UnixConfig),
+    /// WebSocket client or server address
+    #[cfg(feature = "websocket")]
+    WebSocket(WebSocketConfig),
+    /// Named in-memory connection address
+    Memory(MemoryConfig),
     /// File input address
     File(FileConfig),
+    /// Tlog replay address
+    Replay(ReplayConfig),
 }
 
 #[cfg(feature = "tcp")]
@@ -46,12 +62,38 @@ impl From<SerialConfig> for ConnectionAddress {
     }
 }
 
+#[cfg(all(feature = "unix", unix))]
+impl From<UnixConfig> for ConnectionAddress {
+    fn from(value: UnixConfig) -> Self {
+        Self::Unix(value)
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl From<WebSocketConfig> for ConnectionAddress {
+    fn from(value: WebSocketConfig) -> Self {
+        Self::WebSocket(value)
+    }
+}
+
+impl From<MemoryConfig> for ConnectionAddress {
+    fn from(value: MemoryConfig) -> Self {
+        Self::Memory(value)
+    }
+}
+
 impl From<FileConfig> for ConnectionAddress {
     fn from(value: FileConfig) -> Self {
         Self::File(value)
     }
 }
 
+impl From<ReplayConfig> for ConnectionAddress {
+    fn from(value: ReplayConfig) -> Self {
+        Self::Replay(value)
+    }
+}
+
 impl Display for ConnectionAddress {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -61,7 +103,13 @@ impl Display for ConnectionAddress {
             Self::Udp(connectable) => write!(f, "{connectable}"),
             #[cfg(feature = "direct-serial")]
             Self::Serial(connectable) => write!(f, "{connectable}"),
+            #[cfg(all(feature = "unix", unix))]
+            Self::Unix(connectable) => write!(f, "{connectable}"),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(connectable) => write!(f, "{connectable}"),
+            Self::Memory(connectable) => write!(f, "{connectable}"),
             Self::File(connectable) => write!(f, "{connectable}"),
+            Self::Replay(connectable) => write!(f, "{connectable}"),
         }
     }
 }
@@ -77,7 +125,23 @@ impl ConnectionAddress {
     ///  * `udpout:<addr>:<port>` to create a UDP client
     ///  * `udpbcast:<addr>:<port>` to create a UDP broadcast
     ///  * `serial:<port>:<baudrate>` to create a serial connection
+    ///  * `unixin:<path>` to create a Unix domain stream socket server, listening for an incoming connection
+    ///  * `unix:<path>` to create a Unix domain stream socket client
+    ///  * `unixgramin:<path>` to create a Unix domain datagram socket server, listening for incoming packets
+    ///  * `unixgram:<path>` to create a Unix domain datagram socket client
+    ///  * `wsin:<addr>:<port>` to create a WebSocket server, listening for an incoming connection
+    ///  * `ws://<host>:<port>/<path>` or `wss://<host>:<port>/<path>` to create a WebSocket client
+    ///  * `memory:<name>` to create an in-memory connection, linked to the next connection with the same name
     ///  * `file:<path>` to extract file data, writing to such a connection does nothing
+    ///  * `file:<path>?mode=write&format=<raw|tlog>` to append sent frames to a file, optionally with tlog timestamps
+    ///  * `file:<path>?mode=readwrite&output=<path>` to extract file data, appending sent frames to the output
+    ///  * `file:<path>?format=tlog&follow=true&throttle=true` to read a tlog as it is written, at the time it was
+    ///    recorded at, following and throttling is only supported by async connections
+    ///  * `replay:<path>?speed=<factor>&loop=<bool>` to replay a tlog with its recorded timing, optionally at a scaled
+    ///    speed and starting over at its end
+    ///
+    ///  For `file:` and `replay:`, a `?` in the path is only taken to start the parameters when it is followed by
+    ///  known parameters alone.
     pub fn parse_address(address: &str) -> Result<Self, io::Error> {
         let (protocol, address) = address.split_once(':').ok_or(io::Error::new(
             io::ErrorKind::AddrNotAvailable,
@@ -116,7 +180,123 @@ impl ConnectionAddress {
                     _ => unreachable!(),
                 },
             )),
-            "file" => Self::File(FileConfig::new(PathBuf::from(address))),
+            #[cfg(all(feature = "unix", unix))]
+            "unixin" | "unix" | "unixgramin" | "unixgram" => Self::Unix(UnixConfig::new(
+                PathBuf::from(address),
+                match protocol {
+                    "unixin" => UnixMode::StreamIn,
+                    "unix" => UnixMode::StreamOut,
+                    "unixgramin" => UnixMode::DatagramIn,
+                    "unixgram" => UnixMode::DatagramOut,
+                    _ => unreachable!(),
+                },
+            )),
+            #[cfg(feature = "websocket")]
+            "wsin" => Self::WebSocket(WebSocketConfig::new(
+                address.to_string(),
+                WebSocketMode::WsIn,
+            )),
+            #[cfg(feature = "websocket")]
+            "ws" | "wss" => {
+                // the URL scheme is part of the address
+                let url = match address.strip_prefix("//") {
+                    Some(_) => format!("{protocol}:{address}"),
+                    None => format!("{protocol}://{address}"),
+                };
+                Self::WebSocket(WebSocketConfig::new(url, WebSocketMode::WsOut))
+            }
+            "memory" => Self::Memory(MemoryConfig::new(address.to_string())),
+            "file" => {
+                let (path, params) =
+                    split_path_query(address, &["mode", "follow", "throttle", "output", "format"]);
+                let mut mode = None;
+                let mut output = None;
+                let mut format = FileFormat::Raw;
+                let mut follow = false;
+                let mut throttle = false;
+                for (key, value) in params {
+                    match key {
+                        "mode" => mode = Some(value),
+                        "follow" => follow = parse_flag(key, value)?,
+                        "throttle" => throttle = parse_flag(key, value)?,
+                        "output" => output = Some(PathBuf::from(value)),
+                        "format" => {
+                            format = match value {
+                                "raw" => FileFormat::Raw,
+                                "tlog" => FileFormat::Tlog,
+                                _ => {
+                                    return Err(io::Error::new(
+                                        io::ErrorKind::AddrNotAvailable,
+                                        "Invalid file format",
+                                    ))
+                                }
+                            };
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                let default_mode = if output.is_some() {
+                    "readwrite"
+                } else {
+                    "read"
+                };
+                let mode = match (mode.unwrap_or(default_mode), output) {
+                    ("read", None) => FileMode::Read,
+                    ("write", None) => FileMode::Write,
+                    ("readwrite", Some(output)) => FileMode::ReadWrite(output),
+                    ("readwrite", None) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AddrNotAvailable,
+                            "Missing output file",
+                        ))
+                    }
+                    ("read" | "write", Some(_)) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AddrNotAvailable,
+                            "Output file requires the readwrite mode",
+                        ))
+                    }
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AddrNotAvailable,
+                            "Invalid file mode",
+                        ))
+                    }
+                };
+                if throttle && format != FileFormat::Tlog {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrNotAvailable,
+                        "Throttling requires the tlog format",
+                    ));
+                }
+                Self::File(
+                    FileConfig::with_mode(PathBuf::from(path), mode, format)
+                        .follow(follow)
+                        .throttle(throttle),
+                )
+            }
+            "replay" => {
+                let (path, params) = split_path_query(address, &["speed", "loop"]);
+                let mut speed = 1.0;
+                let mut looping = false;
+                for (key, value) in params {
+                    match key {
+                        "speed" => {
+                            speed = value
+                                .parse()
+                                .ok()
+                                .filter(|speed| valid_speed(*speed))
+                                .ok_or(io::Error::new(
+                                    io::ErrorKind::AddrNotAvailable,
+                                    "Invalid replay speed",
+                                ))?;
+                        }
+                        "loop" => looping = parse_flag(key, value)?,
+                        _ => unreachable!(),
+                    }
+                }
+                Self::Replay(ReplayConfig::new(PathBuf::from(path), speed, looping))
+            }
             _ => {
                 return Err(io::Error::new(
                     io::ErrorKind::AddrNotAvailable,
@@ -127,3 +307,42 @@ impl ConnectionAddress {
         Ok(conn)
     }
 }
+
+/// Split a path address at the first `?` followed by parameters with one of `keys` only, as paths may contain `?`
+pub(crate) fn split_path_query<'a>(
+    address: &'a str,
+    keys: &[&str],
+) -> (&'a str, impl Iterator<Item = (&'a str, &'a str)>) {
+    let is_query = |query: &str| {
+        query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .all(|param| {
+                param
+                    .split_once('=')
+                    .is_some_and(|(key, _)| keys.contains(&key))
+            })
+    };
+    let (address, query) = address
+        .match_indices('?')
+        .map(|(i, _)| (&address[..i], &address[i + 1..]))
+        .find(|(_, query)| is_query(query))
+        .unwrap_or((address, ""));
+    (address, query_params(query))
+}
+
+fn query_params(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| param.split_once('=').unwrap_or((param, "")))
+}
+
+fn parse_flag(key: &str, value: &str) -> Result<bool, io::Error> {
+    value.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("Invalid value of address parameter `{key}`"),
+        )
+    })
+}
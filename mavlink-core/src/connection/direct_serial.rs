@@ -1,14 +1,15 @@
 //! Serial MAVLINK connection
 
-use crate::connection::MavConnection;
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
 use crate::error::{MessageReadError, MessageWriteError};
 use crate::peek_reader::PeekReader;
 use crate::Connectable;
 use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
-use core::ops::DerefMut;
-use core::sync::atomic::{self, AtomicU8};
 use std::io;
-use std::sync::Mutex;
+#[cfg(feature = "signing")]
+use std::sync::Arc;
 
 use serialport::{DataBits, FlowControl, Parity, SerialPort, StopBits};
 
@@ -17,127 +18,158 @@ use crate::{read_raw_versioned_msg, read_versioned_msg, write_versioned_msg};
 #[cfg(feature = "signing")]
 use crate::{
     read_raw_versioned_msg_signed, read_versioned_msg_signed, write_versioned_msg_signed,
-    SigningConfig, SigningData,
+    SigningData,
 };
 
 pub mod config;
 
 use config::SerialConfig;
 
-pub struct SerialConnection {
-    // Separate ports for reading and writing as it's safe to use concurrently.
-    // See the official ref: https://github.com/serialport/serialport-rs/blob/321f85e1886eaa1302aef8a600a631bc1c88703a/examples/duplex.rs
-    read_port: Mutex<PeekReader<Box<dyn SerialPort>>>,
-    write_port: Mutex<Box<dyn SerialPort>>,
-    sequence: AtomicU8,
+/// Serial MAVLink connection
+pub type SerialConnection = HalvesConnection<SerialReceiveHalf, SerialSendHalf>;
+
+/// The receiving half of a [`SerialConnection`]
+pub struct SerialReceiveHalf {
+    port: PeekReader<Box<dyn SerialPort>>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
     #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+    signing_data: Option<Arc<SigningData>>,
 }
 
-impl<M: Message> MavConnection<M> for SerialConnection {
-    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
-        let mut port = self.read_port.lock().unwrap();
+impl<M: Message> MavReceiveHalf<M> for SerialReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
         loop {
-            let version = ReadVersion::from_conn_cfg::<_, M>(self);
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
             #[cfg(not(feature = "signing"))]
-            let result = read_versioned_msg(port.deref_mut(), version);
+            let result = read_versioned_msg(&mut self.port, version);
             #[cfg(feature = "signing")]
             let result =
-                read_versioned_msg_signed(port.deref_mut(), version, self.signing_data.as_ref());
+                read_versioned_msg_signed(&mut self.port, version, self.signing_data.as_deref());
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
         }
     }
 
-    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
-        let mut port = self.read_port.lock().unwrap();
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
         loop {
-            let version = ReadVersion::from_conn_cfg::<_, M>(self);
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
             #[cfg(not(feature = "signing"))]
-            let result = read_raw_versioned_msg::<M, _>(port.deref_mut(), version);
+            let result = read_raw_versioned_msg::<M, _>(&mut self.port, version);
             #[cfg(feature = "signing")]
             let result = read_raw_versioned_msg_signed::<M, _>(
-                port.deref_mut(),
+                &mut self.port,
                 version,
-                self.signing_data.as_ref(),
+                self.signing_data.as_deref(),
             );
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
         }
     }
 
-    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
-        let mut port = self.read_port.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+    fn try_recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
 
         #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(port.deref_mut(), version);
+        let result = read_versioned_msg(&mut self.port, version);
 
         #[cfg(feature = "signing")]
         let result =
-            read_versioned_msg_signed(port.deref_mut(), version, self.signing_data.as_ref());
+            read_versioned_msg_signed(&mut self.port, version, self.signing_data.as_deref());
 
         result
     }
 
-    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
-        let mut port = self.write_port.lock().unwrap();
-
-        let sequence = self.sequence.fetch_add(
-            1,
-            // Safety:
-            //
-            // We are using `Ordering::Relaxed` here because:
-            // - We only need a unique sequence number per message
-            // - `Mutex` on `self.write_port` already makes sure the rest of the code is synchronized
-            // - No other thread reads or writes `self.sequence` without going through this `Mutex`
-            //
-            // Warning:
-            //
-            // If we later change this code to access `self.sequence` without locking `self.write_port` with the `Mutex`,
-            // then we should upgrade this ordering to `Ordering::SeqCst`.
-            atomic::Ordering::Relaxed,
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(&mut self.port, version);
+
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_signed::<M, _>(
+            &mut self.port,
+            version,
+            self.signing_data.as_deref(),
         );
 
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for SerialReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of a [`SerialConnection`]
+pub struct SerialSendHalf {
+    port: Box<dyn SerialPort>,
+    sequence: u8,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for SerialSendHalf {
+    fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
         let header = MavHeader {
-            sequence,
+            sequence: self.sequence,
             system_id: header.system_id,
             component_id: header.component_id,
         };
 
+        self.sequence = self.sequence.wrapping_add(1);
         #[cfg(not(feature = "signing"))]
-        let result = write_versioned_msg(port.deref_mut(), self.protocol_version, header, data);
+        let result = write_versioned_msg(&mut self.port, self.protocol_version, header, data);
         #[cfg(feature = "signing")]
         let result = write_versioned_msg_signed(
-            port.deref_mut(),
+            &mut self.port,
             self.protocol_version,
             header,
             data,
-            self.signing_data.as_ref(),
+            self.signing_data.as_deref(),
         );
         result
     }
 
+    fn send_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.port.write_all(frame.raw_bytes())?;
+        Ok(frame.raw_bytes().len())
+    }
+
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
         self.protocol_version = version;
     }
@@ -145,18 +177,12 @@ impl<M: Message> MavConnection<M> for SerialConnection {
     fn protocol_version(&self) -> MavlinkVersion {
         self.protocol_version
     }
+}
 
-    fn set_allow_recv_any_version(&mut self, allow: bool) {
-        self.recv_any_version = allow;
-    }
-
-    fn allow_recv_any_version(&self) -> bool {
-        self.recv_any_version
-    }
-
+impl ConnectionHalf for SerialSendHalf {
     #[cfg(feature = "signing")]
-    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
 }
 
@@ -169,16 +195,27 @@ impl Connectable for SerialConfig {
             .flow_control(FlowControl::None)
             .open()?;
 
+        // Separate ports for reading and writing as it's safe to use concurrently.
+        // See the official ref: https://github.com/serialport/serialport-rs/blob/321f85e1886eaa1302aef8a600a631bc1c88703a/examples/duplex.rs
         let write_port = read_port.try_clone()?;
 
-        Ok(Box::new(SerialConnection {
-            read_port: Mutex::new(PeekReader::new(read_port)),
-            write_port: Mutex::new(write_port),
-            sequence: AtomicU8::new(0),
+        let receive_half = SerialReceiveHalf {
+            port: PeekReader::new(read_port),
             protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
             #[cfg(feature = "signing")]
             signing_data: None,
-            recv_any_version: false,
-        }))
+        };
+        let send_half = SerialSendHalf {
+            port: write_port,
+            sequence: 0,
+            protocol_version: MavlinkVersion::V2,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        };
+        Ok(Box::new(SerialConnection::from_halves(
+            receive_half,
+            send_half,
+        )))
     }
 }
@@ -1,112 +1,203 @@
 //! File MAVLINK connection
 
-use crate::connection::MavConnection;
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
 use crate::error::{MessageReadError, MessageWriteError};
 use crate::peek_reader::PeekReader;
+use crate::tlog::timestamp_now;
 use crate::{Connectable, MAVLinkMessageRaw};
 use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
-use core::ops::DerefMut;
-use std::fs::File;
-use std::io;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+#[cfg(feature = "signing")]
+use std::sync::Arc;
 
 #[cfg(not(feature = "signing"))]
-use crate::{read_raw_versioned_msg, read_versioned_msg};
+use crate::{read_raw_versioned_msg, read_versioned_msg, write_versioned_msg};
 #[cfg(feature = "signing")]
-use crate::{read_raw_versioned_msg_signed, read_versioned_msg_signed, SigningConfig, SigningData};
+use crate::{
+    read_raw_versioned_msg_signed, read_versioned_msg_signed, write_versioned_msg_signed,
+    SigningData,
+};
 
 pub mod config;
 
-use config::FileConfig;
+use config::{FileConfig, FileFormat};
 
-pub fn open(file_path: &PathBuf) -> io::Result<FileConnection> {
-    let file = File::open(file_path)?;
+/// Open the input and output files of a file address
+pub fn open(config: &FileConfig) -> io::Result<FileConnection> {
+    if config.follow || config.throttle {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Following or throttling a file requires an async connection",
+        ));
+    }
+    let file = config.input().map(File::open).transpose()?;
+    let output = config
+        .output()
+        .map(|path| FileOutput::open(path, config.format))
+        .transpose()?;
 
-    Ok(FileConnection {
-        file: Mutex::new(PeekReader::new(file)),
+    let receive_half = FileReceiveHalf {
+        file: file.map(PeekReader::new),
         protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
         #[cfg(feature = "signing")]
         signing_data: None,
-        recv_any_version: false,
-    })
+    };
+    let send_half = FileSendHalf {
+        output,
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(FileConnection::from_halves(receive_half, send_half))
 }
 
-pub struct FileConnection {
-    file: Mutex<PeekReader<File>>,
+/// File sent frames are appended to
+pub(crate) struct FileOutput {
+    file: File,
+    format: FileFormat,
+    sequence: u8,
+}
+
+impl FileOutput {
+    pub(crate) fn open(path: &Path, format: FileFormat) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file,
+            format,
+            sequence: 0,
+        })
+    }
+
+    /// Append a frame of the message, returning the size of the frame
+    pub(crate) fn send<M: Message>(
+        &mut self,
+        version: MavlinkVersion,
+        header: &MavHeader,
+        data: &M,
+        #[cfg(feature = "signing")] signing_data: Option<&SigningData>,
+    ) -> Result<usize, MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+        self.sequence = self.sequence.wrapping_add(1);
+
+        // written with a single write, so records of writers appending to the same file are not interleaved
+        let mut record = Vec::new();
+        if self.format == FileFormat::Tlog {
+            record.extend_from_slice(&timestamp_now().to_be_bytes());
+        }
+        #[cfg(not(feature = "signing"))]
+        let len = write_versioned_msg(&mut record, version, header, data)?;
+        #[cfg(feature = "signing")]
+        let len = write_versioned_msg_signed(&mut record, version, header, data, signing_data)?;
+        self.file.write_all(&record)?;
+        Ok(len)
+    }
+
+    /// Append a raw frame unchanged, returning the size of the frame
+    pub(crate) fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, MessageWriteError> {
+        let mut record = Vec::new();
+        if self.format == FileFormat::Tlog {
+            record.extend_from_slice(&timestamp_now().to_be_bytes());
+        }
+        record.extend_from_slice(frame.raw_bytes());
+        self.file.write_all(&record)?;
+        Ok(frame.raw_bytes().len())
+    }
+}
+
+/// Error of receiving from a connection without an input file
+pub(crate) fn write_only() -> MessageReadError {
+    io::Error::new(io::ErrorKind::Unsupported, "File connection is write only").into()
+}
+
+/// File connection, reading frames from a file and optionally appending sent frames to a file
+pub type FileConnection = HalvesConnection<FileReceiveHalf, FileSendHalf>;
+
+/// The receiving half of a [`FileConnection`]
+pub struct FileReceiveHalf {
+    file: Option<PeekReader<File>>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
     #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+    signing_data: Option<Arc<SigningData>>,
 }
 
-impl<M: Message> MavConnection<M> for FileConnection {
-    fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().unwrap();
-
+impl<M: Message> MavReceiveHalf<M> for FileReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let file = self.file.as_mut().ok_or_else(write_only)?;
         loop {
-            let version = ReadVersion::from_conn_cfg::<_, M>(self);
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
             #[cfg(not(feature = "signing"))]
-            let result = read_versioned_msg(file.deref_mut(), version);
+            let result = read_versioned_msg(file, version);
             #[cfg(feature = "signing")]
-            let result =
-                read_versioned_msg_signed(file.deref_mut(), version, self.signing_data.as_ref());
+            let result = read_versioned_msg_signed(file, version, self.signing_data.as_deref());
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
         }
     }
 
-    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut file = self.file.lock().unwrap();
-
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let file = self.file.as_mut().ok_or_else(write_only)?;
         loop {
-            let version = ReadVersion::from_conn_cfg::<_, M>(self);
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
             #[cfg(not(feature = "signing"))]
-            let result = read_raw_versioned_msg::<M, _>(file.deref_mut(), version);
+            let result = read_raw_versioned_msg::<M, _>(file, version);
             #[cfg(feature = "signing")]
-            let result = read_raw_versioned_msg_signed::<M, _>(
-                file.deref_mut(),
-                version,
-                self.signing_data.as_ref(),
-            );
+            let result =
+                read_raw_versioned_msg_signed::<M, _>(file, version, self.signing_data.as_deref());
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
         }
     }
 
-    fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let file = self.file.as_mut().ok_or_else(write_only)?;
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
 
         #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(file.deref_mut(), version);
+        let result = read_versioned_msg(file, version);
         #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_signed(file.deref_mut(), version, self.signing_data.as_ref());
+        let result = read_versioned_msg_signed(file, version, self.signing_data.as_deref());
 
         result
     }
 
-    fn send(&self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
-        Ok(0)
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let file = self.file.as_mut().ok_or_else(write_only)?;
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(file, version);
+        #[cfg(feature = "signing")]
+        let result =
+            read_raw_versioned_msg_signed::<M, _>(file, version, self.signing_data.as_deref());
+
+        result
     }
 
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
@@ -124,15 +215,62 @@ impl<M: Message> MavConnection<M> for FileConnection {
     fn allow_recv_any_version(&self) -> bool {
         self.recv_any_version
     }
+}
+
+impl ConnectionHalf for FileReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of a [`FileConnection`], writing to it does nothing unless the connection has an output file
+pub struct FileSendHalf {
+    output: Option<FileOutput>,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for FileSendHalf {
+    fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let Some(output) = &mut self.output else {
+            return Ok(0);
+        };
+        output.send(
+            self.protocol_version,
+            header,
+            data,
+            #[cfg(feature = "signing")]
+            self.signing_data.as_deref(),
+        )
+    }
+
+    fn send_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let Some(output) = &mut self.output else {
+            return Ok(0);
+        };
+        output.send_raw(frame)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
 
+impl ConnectionHalf for FileSendHalf {
     #[cfg(feature = "signing")]
-    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
 }
 
 impl Connectable for FileConfig {
     fn connect<M: Message>(&self) -> io::Result<Box<dyn MavConnection<M> + Sync + Send>> {
-        Ok(Box::new(open(&self.address)?))
+        Ok(Box::new(open(self)?))
     }
 }
@@ -1,32 +1,140 @@
 use core::fmt::Display;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// MAVLink connection address for a file input
+/// Direction of a file connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileMode {
+    /// Read frames from the file, sending does nothing
+    Read,
+    /// Append sent frames to the file, receiving is not supported
+    Write,
+    /// Read frames from the file and append sent frames to the given output file
+    ReadWrite(PathBuf),
+}
+
+/// Format of the frames in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileFormat {
+    /// Frames only, as they would be sent over a link
+    #[default]
+    Raw,
+    /// Frames preceded by the time they were sent or received at, see [`tlog`](crate::tlog)
+    Tlog,
+}
+
+/// MAVLink connection address for a file input or output
 ///
 /// # Example
 ///
 /// ```ignore
-/// use mavlink::{Connectable, FileConfig};
+/// use mavlink::{Connectable, FileConfig, FileFormat, FileMode};
 /// use std::path::PathBuf;
 ///
 /// let config = FileConfig::new(PathBuf::from("/some/path"));
 /// config
 ///   .connect::<mavlink::ardupilotmega::MavMessage>()
 ///   .unwrap();
+///
+/// // replay the input of a test while capturing its output
+/// let config = FileConfig::with_mode(
+///     PathBuf::from("input.bin"),
+///     FileMode::ReadWrite(PathBuf::from("output.tlog")),
+///     FileFormat::Tlog,
+/// );
+///
+/// // follow a tlog that is still being recorded
+/// let config = FileConfig::with_mode(PathBuf::from("flight.tlog"), FileMode::Read, FileFormat::Tlog)
+///     .follow(true);
+/// config
+///   .connect_async::<mavlink::ardupilotmega::MavMessage>()
+///   .await
+///   .unwrap();
 /// ```
 #[derive(Debug, Clone)]
 pub struct FileConfig {
     pub(crate) address: PathBuf,
+    pub(crate) mode: FileMode,
+    pub(crate) format: FileFormat,
+    pub(crate) follow: bool,
+    pub(crate) throttle: bool,
 }
 
 impl FileConfig {
     /// Creates a file input address from a file path string.
     pub fn new(address: PathBuf) -> Self {
-        Self { address }
+        Self::with_mode(address, FileMode::Read, FileFormat::Raw)
+    }
+
+    /// Creates a file address with the given mode, frames are read and appended in the given format.
+    pub fn with_mode(address: PathBuf, mode: FileMode, format: FileFormat) -> Self {
+        Self {
+            address,
+            mode,
+            format,
+            follow: false,
+            throttle: false,
+        }
+    }
+
+    /// Set whether to wait for more frames at the end of the input file, like `tail -f`.
+    ///
+    /// Only supported by async connections.
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Set whether frames are received at the time they were recorded at, rather than as fast as they are read.
+    ///
+    /// Only supported by async connections reading a file in the tlog format.
+    pub fn throttle(mut self, throttle: bool) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// File frames are read from
+    pub(crate) fn input(&self) -> Option<&Path> {
+        match self.mode {
+            FileMode::Read | FileMode::ReadWrite(_) => Some(&self.address),
+            FileMode::Write => None,
+        }
+    }
+
+    /// File sent frames are appended to
+    pub(crate) fn output(&self) -> Option<&Path> {
+        match &self.mode {
+            FileMode::Read => None,
+            FileMode::Write => Some(&self.address),
+            FileMode::ReadWrite(output) => Some(output),
+        }
     }
 }
+
 impl Display for FileConfig {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "file:{}", self.address.display())
+        write!(f, "file:{}", self.address.display())?;
+        let mut separator = '?';
+        match &self.mode {
+            FileMode::Read => {}
+            FileMode::Write => {
+                write!(f, "?mode=write")?;
+                separator = '&';
+            }
+            FileMode::ReadWrite(output) => {
+                write!(f, "?mode=readwrite&output={}", output.display())?;
+                separator = '&';
+            }
+        }
+        for (enabled, param) in [
+            (self.format == FileFormat::Tlog, "format=tlog"),
+            (self.follow, "follow=true"),
+            (self.throttle, "throttle=true"),
+        ] {
+            if enabled {
+                write!(f, "{separator}{param}")?;
+                separator = '&';
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,730 @@
+//! Simulated network impairments
+
+use core::ops::DerefMut;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::connection::{to_read_error, FrameEncoder, MavConnection, MavReceiveHalf, MavSendHalf};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::peek_reader::PeekReader;
+use crate::{
+    read_raw_versioned_msg, MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion,
+};
+
+#[cfg(feature = "signing")]
+use crate::SigningConfig;
+
+/// How long to wait before polling the wrapped connection again while frames are delayed
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Impairments applied to the frames sent over a simulated link
+///
+/// All random decisions are taken from a pseudo random generator seeded with `seed`, so the same frames sent with
+/// the same impairments are always impaired the same way.
+///
+/// # Example
+///
+/// ```ignore
+/// use std::time::Duration;
+/// use mavlink::{GilbertElliott, Impairments};
+///
+/// let impairments = Impairments {
+///     latency: Duration::from_millis(50),
+///     loss: 0.01,
+///     burst_loss: Some(GilbertElliott::new(0.05, 0.3)),
+///     seed: 42,
+///     ..Impairments::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Impairments {
+    /// Fixed delay of every frame
+    pub latency: Duration,
+    /// Maximum random delay added to the latency, frames with different delays may arrive out of order
+    pub jitter: Duration,
+    /// Probability that a frame is dropped
+    pub loss: f64,
+    /// Burst loss model, frames are dropped by this model in addition to the fixed `loss` probability
+    pub burst_loss: Option<GilbertElliott>,
+    /// Probability that a frame is delivered twice
+    pub duplication: f64,
+    /// Probability that a frame is held back and delivered after the next frame
+    pub reorder: f64,
+    /// Probability that a single bit of a frame is flipped
+    pub corruption: f64,
+    /// Seed of the pseudo random generator
+    pub seed: u64,
+}
+
+/// Gilbert-Elliott burst loss model
+///
+/// The link switches between a good and a bad state after every frame, and drops frames with a different
+/// probability in each state. This simulates the bursts of lost frames of a fading radio link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GilbertElliott {
+    /// Probability of switching from the good to the bad state
+    pub good_to_bad: f64,
+    /// Probability of switching from the bad to the good state
+    pub bad_to_good: f64,
+    /// Probability that a frame is dropped in the good state
+    pub good_loss: f64,
+    /// Probability that a frame is dropped in the bad state
+    pub bad_loss: f64,
+}
+
+impl GilbertElliott {
+    /// Simple Gilbert model, dropping every frame in the bad state and none in the good state
+    ///
+    /// The mean burst length is `1 / bad_to_good` frames.
+    pub fn new(good_to_bad: f64, bad_to_good: f64) -> Self {
+        Self {
+            good_to_bad,
+            bad_to_good,
+            good_loss: 0.0,
+            bad_loss: 1.0,
+        }
+    }
+}
+
+/// Counters of the frames passed through an impaired link
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImpairmentStats {
+    /// Frames sent over the link
+    pub frames: u64,
+    /// Frames passed on to the receiver, including duplicates
+    pub delivered: u64,
+    /// Dropped frames
+    pub lost: u64,
+    /// Frames that were delivered twice
+    pub duplicated: u64,
+    /// Frames with a flipped bit
+    pub corrupted: u64,
+    /// Frames held back and delivered after the next frame
+    pub reordered: u64,
+}
+
+/// SplitMix64 pseudo random generator, good enough for simulations and without dependencies
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed value in `[0, 1)`
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+
+    /// Uniformly distributed value in `[0, n)`
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A frame that can be impaired
+pub(crate) trait Frame: Clone {
+    /// The bytes of the frame, which corruption flips a bit of
+    fn bytes_mut(&mut self) -> &mut [u8];
+}
+
+impl Frame for Vec<u8> {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl Frame for MAVLinkMessageRaw {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        let len = self.raw_bytes().len();
+        match self {
+            Self::V1(frame) => &mut frame.as_mut_slice()[..len],
+            Self::V2(frame) => &mut frame.as_mut_slice()[..len],
+        }
+    }
+}
+
+/// Applies [`Impairments`] to a sequence of frames
+#[derive(Debug, Clone)]
+pub(crate) struct Impairer<F> {
+    impairments: Impairments,
+    rng: Rng,
+    held: Option<F>,
+    // state of the burst loss model
+    bad: bool,
+    stats: ImpairmentStats,
+}
+
+impl<F: Frame> Impairer<F> {
+    pub(crate) fn new(impairments: Impairments) -> Self {
+        Self {
+            rng: Rng::new(impairments.seed),
+            impairments,
+            held: None,
+            bad: false,
+            stats: ImpairmentStats::default(),
+        }
+    }
+
+    pub(crate) fn stats(&self) -> ImpairmentStats {
+        self.stats
+    }
+
+    /// Impair a frame, calling `deliver` with the delay and content of every frame that should be delivered now
+    pub(crate) fn process(&mut self, frame: F, mut deliver: impl FnMut(Duration, F)) {
+        self.stats.frames += 1;
+        if self.lose() {
+            self.stats.lost += 1;
+            return;
+        }
+        if self.rng.chance(self.impairments.duplication) {
+            self.stats.duplicated += 1;
+            self.impair(frame.clone(), &mut deliver);
+        }
+        self.impair(frame, &mut deliver);
+    }
+
+    fn lose(&mut self) -> bool {
+        let burst = match self.impairments.burst_loss {
+            Some(model) => {
+                let (loss, switch) = if self.bad {
+                    (model.bad_loss, model.bad_to_good)
+                } else {
+                    (model.good_loss, model.good_to_bad)
+                };
+                let lost = self.rng.chance(loss);
+                if self.rng.chance(switch) {
+                    self.bad = !self.bad;
+                }
+                lost
+            }
+            None => false,
+        };
+        self.rng.chance(self.impairments.loss) || burst
+    }
+
+    fn impair(&mut self, mut frame: F, deliver: &mut impl FnMut(Duration, F)) {
+        let bytes = frame.bytes_mut();
+        if !bytes.is_empty() && self.rng.chance(self.impairments.corruption) {
+            let bit = self.rng.below(bytes.len() * 8);
+            bytes[bit / 8] ^= 1 << (bit % 8);
+            self.stats.corrupted += 1;
+        }
+        if self.held.is_none() && self.rng.chance(self.impairments.reorder) {
+            self.held = Some(frame);
+            self.stats.reordered += 1;
+            return;
+        }
+        let delay = self.delay();
+        deliver(delay, frame);
+        self.stats.delivered += 1;
+        if let Some(held) = self.held.take() {
+            deliver(delay.max(self.delay()), held);
+            self.stats.delivered += 1;
+        }
+    }
+
+    fn delay(&mut self) -> Duration {
+        let jitter = self.impairments.jitter.mul_f64(self.rng.next_f64());
+        self.impairments.latency + jitter
+    }
+}
+
+/// Impaired frames waiting for their delivery time
+#[derive(Debug)]
+pub(crate) struct DelayQueue<F = Vec<u8>> {
+    // frames ordered by their delivery time, the counter keeps frames with the same time in order
+    frames: BTreeMap<(Instant, u64), F>,
+    counter: u64,
+    impairer: Impairer<F>,
+}
+
+impl<F: Frame> DelayQueue<F> {
+    pub(crate) fn new(impairments: Impairments) -> Self {
+        Self {
+            frames: BTreeMap::new(),
+            counter: 0,
+            impairer: Impairer::new(impairments),
+        }
+    }
+
+    /// Impair a frame and queue the frames to deliver
+    pub(crate) fn push(&mut self, frame: F) {
+        let now = Instant::now();
+        let Self {
+            frames,
+            counter,
+            impairer,
+        } = self;
+        impairer.process(frame, |delay, frame| {
+            frames.insert((now + delay, *counter), frame);
+            *counter += 1;
+        });
+    }
+
+    /// Delivery time of the next frame
+    pub(crate) fn next_due(&self) -> Option<Instant> {
+        self.frames.keys().next().map(|(at, _)| *at)
+    }
+
+    /// Take the next frame if it is due
+    pub(crate) fn pop_due(&mut self) -> Option<F> {
+        let entry = self.frames.first_entry()?;
+        if entry.key().0 <= Instant::now() {
+            Some(entry.remove())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn stats(&self) -> ImpairmentStats {
+        self.impairer.stats()
+    }
+}
+
+// corrupted frames fail their checksum and are discarded, like a receiver would do
+fn check<M: Message>(frame: MAVLinkMessageRaw) -> Option<MAVLinkMessageRaw> {
+    read_raw_versioned_msg::<M, _>(&mut PeekReader::new(frame.raw_bytes()), ReadVersion::Any).ok()
+}
+
+fn parse<M: Message>(frame: MAVLinkMessageRaw) -> Option<(MavHeader, M)> {
+    let frame = check::<M>(frame)?;
+    let header = MavHeader {
+        sequence: frame.sequence(),
+        system_id: frame.system_id(),
+        component_id: frame.component_id(),
+    };
+    let data = M::parse(frame.version(), frame.message_id(), frame.payload()).ok()?;
+    Some((header, data))
+}
+
+/// Send the due frames of `outgoing` unchanged, including corrupted ones
+fn flush(
+    outgoing: &mut DelayQueue<MAVLinkMessageRaw>,
+    mut send_raw: impl FnMut(&MAVLinkMessageRaw) -> Result<usize, MessageWriteError>,
+) -> Result<(), MessageWriteError> {
+    while let Some(frame) = outgoing.pop_due() {
+        send_raw(&frame)?;
+    }
+    Ok(())
+}
+
+/// Receive into `incoming` until a frame is due and parsed by `parse`
+///
+/// `recv_raw` receives from the wrapped connection, blocking if its argument is true. `pending` flushes the outgoing
+/// frames and returns the delivery time of the next one, which is waited for like delayed incoming frames.
+fn receive<T>(
+    incoming: &mut DelayQueue<MAVLinkMessageRaw>,
+    mut recv_raw: impl FnMut(bool) -> Result<MAVLinkMessageRaw, MessageReadError>,
+    mut pending: impl FnMut() -> Result<Option<Instant>, MessageWriteError>,
+    parse: impl Fn(MAVLinkMessageRaw) -> Option<T>,
+    blocking: bool,
+) -> Result<T, MessageReadError> {
+    loop {
+        let next_outgoing = pending().map_err(to_read_error)?;
+        while let Some(frame) = incoming.pop_due() {
+            if let Some(result) = parse(frame) {
+                return Ok(result);
+            }
+        }
+        let next_due = incoming.next_due().into_iter().chain(next_outgoing).min();
+
+        match recv_raw(blocking && next_due.is_none()) {
+            Ok(frame) => incoming.push(frame),
+            Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => match next_due
+            {
+                Some(at) if blocking => thread::sleep(
+                    at.saturating_duration_since(Instant::now())
+                        .min(POLL_INTERVAL),
+                ),
+                _ => return Err(MessageReadError::Io(e)),
+            },
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Connection wrapper impairing the frames sent and received over another connection
+///
+/// Sent frames are impaired before they are passed to the wrapped connection and received frames after they are
+/// received from it, so a mission or parameter client can be tested against a bad link in a local setup.
+///
+/// Frames are passed on with [`MavConnection::send_raw`] and received with [`MavConnection::recv_raw`], so they keep
+/// their version, sequence number and signature. Sent messages are numbered by the wrapper before they are impaired,
+/// so lost frames leave gaps in the sequence numbers. Corrupted frames are sent with the flipped bit, received
+/// corrupted frames fail their checksum and are discarded, like a receiver would do.
+///
+/// Delayed frames are handled without a background thread: they are sent and received during later calls to the
+/// connection, receiving polls the wrapped connection while frames are delayed.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{ImpairedConnection, Impairments};
+///
+/// let connection = mavlink::connect::<mavlink::common::MavMessage>("udpin:0.0.0.0:14550")?;
+/// let lossy = Impairments {
+///     loss: 0.2,
+///     seed: 1,
+///     ..Impairments::default()
+/// };
+/// let connection = ImpairedConnection::new(connection, lossy.clone(), lossy);
+/// ```
+pub struct ImpairedConnection<M: Message> {
+    connection: Box<dyn MavConnection<M> + Sync + Send>,
+    encoder: Mutex<FrameEncoder>,
+    outgoing: Mutex<DelayQueue<MAVLinkMessageRaw>>,
+    incoming: Mutex<DelayQueue<MAVLinkMessageRaw>>,
+}
+
+impl<M: Message> ImpairedConnection<M> {
+    /// Wrap `connection`, impairing sent frames with `outgoing` and received frames with `incoming`
+    pub fn new(
+        connection: Box<dyn MavConnection<M> + Sync + Send>,
+        outgoing: Impairments,
+        incoming: Impairments,
+    ) -> Self {
+        Self {
+            connection,
+            encoder: Mutex::new(FrameEncoder::new()),
+            outgoing: Mutex::new(DelayQueue::new(outgoing)),
+            incoming: Mutex::new(DelayQueue::new(incoming)),
+        }
+    }
+
+    /// The wrapped connection
+    pub fn get_ref(&self) -> &(dyn MavConnection<M> + Sync + Send) {
+        self.connection.as_ref()
+    }
+
+    /// Unwrap the connection, frames that are not delivered yet are discarded
+    pub fn into_inner(self) -> Box<dyn MavConnection<M> + Sync + Send> {
+        self.connection
+    }
+
+    /// Counters of the sent frames
+    pub fn outgoing_stats(&self) -> ImpairmentStats {
+        self.outgoing.lock().unwrap().stats()
+    }
+
+    /// Counters of the received frames
+    pub fn incoming_stats(&self) -> ImpairmentStats {
+        self.incoming.lock().unwrap().stats()
+    }
+
+    fn pending(&self) -> Result<Option<Instant>, MessageWriteError> {
+        let mut outgoing = self.outgoing.lock().unwrap();
+        flush(&mut outgoing, |frame| self.connection.send_raw(frame))?;
+        Ok(outgoing.next_due())
+    }
+
+    fn receive<T>(
+        &self,
+        parse: impl Fn(MAVLinkMessageRaw) -> Option<T>,
+        blocking: bool,
+    ) -> Result<T, MessageReadError> {
+        let mut incoming = self.incoming.lock().unwrap();
+        receive(
+            incoming.deref_mut(),
+            |blocking| {
+                if blocking {
+                    self.connection.recv_raw()
+                } else {
+                    self.connection.try_recv_raw()
+                }
+            },
+            || self.pending(),
+            parse,
+            blocking,
+        )
+    }
+
+    fn impair_raw(&self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.outgoing.lock().unwrap().push(*frame);
+        self.pending()?;
+        Ok(frame.raw_bytes().len())
+    }
+}
+
+impl<M: Message + 'static> MavConnection<M> for ImpairedConnection<M> {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.receive(parse, true)
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.receive(check::<M>, true)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.receive(parse, false)
+    }
+
+    fn try_recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.receive(check::<M>, false)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self.encoder.lock().unwrap().encode(
+            self.connection.protocol_version(),
+            header,
+            data,
+        )?;
+        self.impair_raw(&frame)
+    }
+
+    fn send_raw(&self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.impair_raw(frame)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.connection.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.connection.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.connection.allow_recv_any_version()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.encoder
+            .get_mut()
+            .unwrap()
+            .setup_signing(signing_data.clone());
+        self.connection.setup_signing(signing_data);
+    }
+
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn MavReceiveHalf<M> + Send>,
+        Box<dyn MavSendHalf<M> + Send>,
+    ) {
+        let (receive_half, send_half) = self.connection.split();
+        let receive_half = ImpairedReceiveHalf {
+            half: receive_half,
+            incoming: self.incoming.into_inner().unwrap(),
+        };
+        let send_half = ImpairedSendHalf {
+            half: send_half,
+            encoder: self.encoder.into_inner().unwrap(),
+            outgoing: self.outgoing.into_inner().unwrap(),
+        };
+        (Box::new(receive_half), Box::new(send_half))
+    }
+}
+
+/// The receiving half of an [`ImpairedConnection`]
+pub struct ImpairedReceiveHalf<M: Message> {
+    half: Box<dyn MavReceiveHalf<M> + Send>,
+    incoming: DelayQueue<MAVLinkMessageRaw>,
+}
+
+impl<M: Message> ImpairedReceiveHalf<M> {
+    fn receive<T>(
+        &mut self,
+        parse: impl Fn(MAVLinkMessageRaw) -> Option<T>,
+        blocking: bool,
+    ) -> Result<T, MessageReadError> {
+        let half = &mut self.half;
+        receive(
+            &mut self.incoming,
+            |blocking| {
+                if blocking {
+                    half.recv_raw()
+                } else {
+                    half.try_recv_raw()
+                }
+            },
+            || Ok(None),
+            parse,
+            blocking,
+        )
+    }
+}
+
+impl<M: Message> MavReceiveHalf<M> for ImpairedReceiveHalf<M> {
+    fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        self.receive(parse, true)
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.receive(check::<M>, true)
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        self.receive(parse, false)
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.receive(check::<M>, false)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.half.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.half.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.half.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.half.allow_recv_any_version()
+    }
+}
+
+/// The sending half of an [`ImpairedConnection`]
+///
+/// Without a receiving side to poll, delayed frames are sent during later calls to `send`.
+pub struct ImpairedSendHalf<M: Message> {
+    half: Box<dyn MavSendHalf<M> + Send>,
+    encoder: FrameEncoder,
+    outgoing: DelayQueue<MAVLinkMessageRaw>,
+}
+
+impl<M: Message> ImpairedSendHalf<M> {
+    fn impair_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.outgoing.push(*frame);
+        let half = &mut self.half;
+        flush(&mut self.outgoing, |frame| half.send_raw(frame))?;
+        Ok(frame.raw_bytes().len())
+    }
+}
+
+impl<M: Message> MavSendHalf<M> for ImpairedSendHalf<M> {
+    fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self
+            .encoder
+            .encode(self.half.protocol_version(), header, data)?;
+        self.impair_raw(&frame)
+    }
+
+    fn send_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.impair_raw(frame)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.half.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.half.protocol_version()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(impairments: Impairments) -> Vec<(Duration, Vec<u8>)> {
+        let mut impairer = Impairer::new(impairments);
+        let mut delivered = Vec::new();
+        for i in 0..100u8 {
+            impairer.process(vec![i; 4], |delay, frame| delivered.push((delay, frame)));
+        }
+        delivered
+    }
+
+    #[test]
+    fn test_no_impairments() {
+        let delivered = run(Impairments::default());
+        assert_eq!(delivered.len(), 100);
+        for (i, (delay, frame)) in delivered.into_iter().enumerate() {
+            assert_eq!(delay, Duration::ZERO);
+            assert_eq!(frame, vec![i as u8; 4]);
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let impairments = Impairments {
+            jitter: Duration::from_millis(10),
+            loss: 0.2,
+            reorder: 0.2,
+            corruption: 0.2,
+            seed: 7,
+            ..Impairments::default()
+        };
+        let delivered = run(impairments.clone());
+        assert_eq!(delivered, run(impairments.clone()));
+        assert_ne!(
+            delivered,
+            run(Impairments {
+                seed: 8,
+                ..impairments
+            })
+        );
+
+        let lost = 100 - delivered.len();
+        assert!(lost > 5 && lost < 40, "{lost} frames lost");
+        assert!(delivered
+            .iter()
+            .any(|(_, frame)| frame.iter().any(|byte| *byte != frame[1])));
+        assert!(delivered.windows(2).any(|w| w[0].1[1] > w[1].1[1]));
+    }
+
+    #[test]
+    fn test_burst_loss_and_duplication() {
+        let delivered = run(Impairments {
+            burst_loss: Some(GilbertElliott::new(0.1, 0.25)),
+            duplication: 0.1,
+            seed: 1,
+            ..Impairments::default()
+        });
+        let ids: Vec<u8> = delivered.iter().map(|(_, frame)| frame[0]).collect();
+        let duplicates = ids.windows(2).filter(|w| w[0] == w[1]).count();
+        let gaps: Vec<u8> = ids
+            .windows(2)
+            .map(|w| w[1].saturating_sub(w[0]))
+            .filter(|gap| *gap > 1)
+            .collect();
+        assert!(duplicates > 0);
+        // lost frames come in bursts
+        assert!(gaps.iter().any(|gap| *gap > 2), "{gaps:?}");
+
+        let mut impairer = Impairer::new(Impairments {
+            burst_loss: Some(GilbertElliott::new(0.1, 0.25)),
+            duplication: 0.1,
+            seed: 1,
+            ..Impairments::default()
+        });
+        for i in 0..100u8 {
+            impairer.process(vec![i; 4], |_, _| {});
+        }
+        let stats = impairer.stats();
+        assert_eq!(stats.frames, 100);
+        assert_eq!(stats.delivered as usize, delivered.len());
+        assert_eq!(stats.duplicated as usize, duplicates);
+        assert_eq!(
+            stats.frames - stats.lost + stats.duplicated,
+            stats.delivered
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! In-memory MAVLink connection
+
+use crate::connection::impairment::{DelayQueue, Impairments};
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
+use crate::peek_reader::PeekReader;
+#[cfg(not(feature = "signing"))]
+use crate::read_raw_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::read_raw_versioned_msg_signed;
+use crate::Connectable;
+use crate::MAVLinkMessageRaw;
+use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
+use core::task::Waker;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Instant;
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, write_versioned_msg};
+
+#[cfg(feature = "signing")]
+use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningData};
+
+pub mod config;
+
+use config::MemoryConfig;
+
+/// Result of polling a [`Link`] for the next frame
+pub(crate) enum Next {
+    Frame(Vec<u8>),
+    /// The next frame is delayed until the given instant
+    At(Instant),
+    Empty,
+}
+
+struct LinkState {
+    queue: DelayQueue,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// One direction of an in-memory connection
+pub(crate) struct Link {
+    state: Mutex<LinkState>,
+    available: Condvar,
+}
+
+impl Link {
+    fn new(impairments: Impairments) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(LinkState {
+                queue: DelayQueue::new(impairments),
+                closed: false,
+                waker: None,
+            }),
+            available: Condvar::new(),
+        })
+    }
+
+    pub(crate) fn push(&self, frame: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.queue.push(frame);
+        self.notify(&mut state);
+    }
+
+    /// Mark the link as closed once the sender is gone, frames that are already sent are still delivered
+    pub(crate) fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        self.notify(&mut state);
+    }
+
+    fn notify(&self, state: &mut LinkState) {
+        self.available.notify_all();
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn closed_error() -> io::Error {
+        io::Error::new(io::ErrorKind::ConnectionAborted, "Memory connection closed")
+    }
+
+    fn next(state: &mut LinkState) -> io::Result<Next> {
+        if let Some(frame) = state.queue.pop_due() {
+            return Ok(Next::Frame(frame));
+        }
+        match state.queue.next_due() {
+            Some(at) => Ok(Next::At(at)),
+            None if state.closed => Err(Self::closed_error()),
+            None => Ok(Next::Empty),
+        }
+    }
+
+    /// Take the next frame that is due, waiting for it if `blocking`
+    pub(crate) fn pop(&self, blocking: bool) -> io::Result<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            state = match Self::next(&mut state)? {
+                Next::Frame(frame) => return Ok(frame),
+                _ if !blocking => return Err(io::ErrorKind::WouldBlock.into()),
+                Next::At(at) => {
+                    let timeout = at.saturating_duration_since(Instant::now());
+                    self.available.wait_timeout(state, timeout).unwrap().0
+                }
+                Next::Empty => self.available.wait(state).unwrap(),
+            };
+        }
+    }
+
+    /// Take the next frame that is due, `waker` is woken when a new frame is sent
+    #[cfg(feature = "tokio-1")]
+    pub(crate) fn poll_next(&self, waker: &Waker) -> io::Result<Next> {
+        let mut state = self.state.lock().unwrap();
+        let next = Self::next(&mut state)?;
+        if !matches!(next, Next::Frame(_)) {
+            state.waker = Some(waker.clone());
+        }
+        Ok(next)
+    }
+}
+
+/// One end of a pair of linked connections
+pub(crate) struct Endpoint {
+    pub(crate) incoming: Arc<Link>,
+    pub(crate) outgoing: Arc<Link>,
+}
+
+pub(crate) fn endpoints(a_to_b: Impairments, b_to_a: Impairments) -> (Endpoint, Endpoint) {
+    let a_to_b = Link::new(a_to_b);
+    let b_to_a = Link::new(b_to_a);
+    (
+        Endpoint {
+            incoming: b_to_a.clone(),
+            outgoing: a_to_b.clone(),
+        },
+        Endpoint {
+            incoming: a_to_b,
+            outgoing: b_to_a,
+        },
+    )
+}
+
+/// Endpoint of the connection with the given name
+///
+/// The first connection to a name creates a pair and registers the other end, which is taken by the second
+/// connection to that name. Messages sent before the second connection is made are kept.
+pub(crate) fn named_endpoint(name: &str) -> Endpoint {
+    static WAITING: OnceLock<Mutex<HashMap<String, Endpoint>>> = OnceLock::new();
+    let mut waiting = WAITING.get_or_init(Default::default).lock().unwrap();
+    waiting.remove(name).unwrap_or_else(|| {
+        let (endpoint, other) = endpoints(Impairments::default(), Impairments::default());
+        waiting.insert(name.to_string(), other);
+        endpoint
+    })
+}
+
+struct MemoryRead {
+    link: Arc<Link>,
+    buffer: VecDeque<u8>,
+    blocking: bool,
+}
+
+impl Read for MemoryRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.is_empty() {
+            let frame = self.link.pop(self.blocking)?;
+            self.buffer.extend(frame);
+        }
+        self.buffer.read(buf)
+    }
+}
+
+struct MemoryWrite {
+    link: Arc<Link>,
+    sequence: u8,
+}
+
+impl Drop for MemoryWrite {
+    fn drop(&mut self) {
+        self.link.close();
+    }
+}
+
+/// Connection to another connection in the same process
+///
+/// Every frame is passed to the other end as a whole, optionally impaired by [`Impairments`] to simulate a bad link.
+/// Dropping a connection closes it, once all frames are received the other end fails to receive.
+pub type MemoryConnection = HalvesConnection<MemoryReceiveHalf, MemorySendHalf>;
+
+impl MemoryConnection {
+    /// Create two connections that are linked to each other
+    pub fn pair() -> (Self, Self) {
+        Self::pair_with(Impairments::default(), Impairments::default())
+    }
+
+    /// Create two linked connections with impaired links
+    ///
+    /// `a_to_b` impairs the frames sent by the first connection, `b_to_a` the frames sent by the second one.
+    pub fn pair_with(a_to_b: Impairments, b_to_a: Impairments) -> (Self, Self) {
+        let (a, b) = endpoints(a_to_b, b_to_a);
+        (connection(a), connection(b))
+    }
+}
+
+pub(crate) fn connection(endpoint: Endpoint) -> MemoryConnection {
+    let receive_half = MemoryReceiveHalf {
+        reader: PeekReader::new(MemoryRead {
+            link: endpoint.incoming,
+            buffer: VecDeque::new(),
+            blocking: true,
+        }),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = MemorySendHalf {
+        writer: MemoryWrite {
+            link: endpoint.outgoing,
+            sequence: 0,
+        },
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    HalvesConnection::from_halves(receive_half, send_half)
+}
+
+/// The receiving half of a [`MemoryConnection`]
+pub struct MemoryReceiveHalf {
+    reader: PeekReader<MemoryRead>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavReceiveHalf<M> for MemoryReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result =
+            read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+        result
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_signed::<M, _>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        );
+        result
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.reader.reader_mut().blocking = false;
+        let result = MavReceiveHalf::<M>::recv(self);
+        self.reader.reader_mut().blocking = true;
+        result
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.reader.reader_mut().blocking = false;
+        let result = MavReceiveHalf::<M>::recv_raw(self);
+        self.reader.reader_mut().blocking = true;
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for MemoryReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of a [`MemoryConnection`]
+pub struct MemorySendHalf {
+    writer: MemoryWrite,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for MemorySendHalf {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.writer.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.writer.sequence = self.writer.sequence.wrapping_add(1);
+
+        let mut buf = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        let len = write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        let len = write_versioned_msg_signed(
+            &mut buf,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_deref(),
+        )?;
+        self.writer.link.push(buf);
+        Ok(len)
+    }
+
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.writer.link.push(frame.raw_bytes().to_vec());
+        Ok(frame.raw_bytes().len())
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for MemorySendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+impl Connectable for MemoryConfig {
+    fn connect<M: Message>(&self) -> io::Result<Box<dyn MavConnection<M> + Sync + Send>> {
+        Ok(Box::new(connection(named_endpoint(&self.name))))
+    }
+}
@@ -0,0 +1,32 @@
+use core::fmt::Display;
+
+/// MAVLink address of a named in-memory connection
+///
+/// Two connections to the same name are linked to each other, see [`MemoryConnection`](crate::MemoryConnection).
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connectable, MemoryConfig};
+///
+/// let config = MemoryConfig::new("link".to_owned());
+/// let gcs = config.connect::<mavlink::common::MavMessage>().unwrap();
+/// let vehicle = config.connect::<mavlink::common::MavMessage>().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryConfig {
+    pub(crate) name: String,
+}
+
+impl MemoryConfig {
+    /// Creates an in-memory connection address.
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl Display for MemoryConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "memory:{}", self.name)
+    }
+}
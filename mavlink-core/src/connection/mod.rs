@@ -1,9 +1,11 @@
 use crate::{
-    connectable::ConnectionAddress, MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message,
+    connectable::ConnectionAddress, MAVLinkMessageRaw, MAVLinkV1MessageRaw, MAVLinkV2MessageRaw,
+    MavFrame, MavHeader, MavlinkVersion, Message,
 };
 
 use core::fmt::Display;
 use std::io::{self};
+use std::sync::{Arc, Mutex, RwLock};
 
 #[cfg(feature = "tcp")]
 pub mod tcp;
@@ -14,11 +16,25 @@ pub mod udp;
 #[cfg(feature = "direct-serial")]
 pub mod direct_serial;
 
+#[cfg(all(feature = "unix", unix))]
+pub mod unix;
+
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
 #[cfg(feature = "signing")]
-use crate::SigningConfig;
+use crate::{SigningConfig, SigningData};
 
 pub mod file;
 
+pub mod impairment;
+
+pub mod memory;
+
+pub mod recording;
+
+pub mod replay;
+
 /// A MAVLink connection
 pub trait MavConnection<M: Message> {
     /// Receive a MAVLink message.
@@ -37,9 +53,38 @@ pub trait MavConnection<M: Message> {
     /// if there is an error or no message is available.
     fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError>;
 
+    /// Try to receive a raw, unparsed MAVLink message.
+    ///
+    /// Non-blocking variant of `recv_raw()`. The connections of this crate return the frame as it was received. By
+    /// default the message received with [`try_recv`](Self::try_recv) is encoded again instead, using the protocol
+    /// version of the connection.
+    fn try_recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let (header, data) = self.try_recv()?;
+        encode_frame(
+            self.protocol_version(),
+            header,
+            &data,
+            #[cfg(feature = "signing")]
+            None,
+        )
+        .map_err(to_read_error)
+    }
+
     /// Send a MAVLink message
     fn send(&self, header: &MavHeader, data: &M) -> Result<usize, crate::error::MessageWriteError>;
 
+    /// Send a raw MAVLink frame.
+    ///
+    /// The connections of this crate send the bytes of the frame unchanged, keeping its sequence number, version and
+    /// signature. By default the message of the frame is parsed and sent with [`send`](Self::send) instead.
+    fn send_raw(
+        &self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let (header, data) = parse_frame(frame)?;
+        self.send(&header, &data)
+    }
+
     /// Sets the MAVLink version to use for receiving (when `allow_recv_any_version()` is `false`) and sending messages.
     fn set_protocol_version(&mut self, version: MavlinkVersion);
     /// Gets the currently used MAVLink version
@@ -77,6 +122,316 @@ pub trait MavConnection<M: Message> {
     /// Setup secret key used for message signing, or disable message signing
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>);
+
+    /// Split the connection into independently owned receive and send halves.
+    ///
+    /// Both halves keep the protocol version and signing configuration of the connection at the time of the split.
+    /// Dropping both halves closes the connection.
+    ///
+    /// By default both halves share the connection, so changing the protocol version on one half changes it for
+    /// the other half as well, and waits for a call in progress on the other half. The connections of this crate
+    /// hand out halves that are independent of each other.
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn MavReceiveHalf<M> + Send>,
+        Box<dyn MavSendHalf<M> + Send>,
+    )
+    where
+        Self: Send + Sync + 'static,
+    {
+        let connection = Arc::new(RwLock::new(self));
+        (
+            Box::new(SharedHalf {
+                connection: connection.clone(),
+            }),
+            Box::new(SharedHalf { connection }),
+        )
+    }
+}
+
+/// The receiving half of a [`MavConnection`], created by [`MavConnection::split`]
+pub trait MavReceiveHalf<M: Message> {
+    /// Receive a MAVLink message.
+    ///
+    /// Blocks until a valid frame is received, ignoring invalid messages.
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError>;
+
+    /// Receive a raw, unparsed MAVLink message.
+    ///
+    /// Blocks until a valid frame is received, ignoring invalid messages.
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError>;
+
+    /// Try to receive a MAVLink message.
+    ///
+    /// Non-blocking variant of `recv()`, returns immediately with a `MessageReadError`
+    /// if there is an error or no message is available.
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError>;
+
+    /// Try to receive a raw, unparsed MAVLink message, see [`MavConnection::try_recv_raw`]
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let (header, data) = self.try_recv()?;
+        encode_frame(
+            self.protocol_version(),
+            header,
+            &data,
+            #[cfg(feature = "signing")]
+            None,
+        )
+        .map_err(to_read_error)
+    }
+
+    /// Sets the MAVLink version to use for receiving when `allow_recv_any_version()` is `false`.
+    fn set_protocol_version(&mut self, version: MavlinkVersion);
+    /// Gets the currently used MAVLink version
+    fn protocol_version(&self) -> MavlinkVersion;
+
+    /// Set wether MAVLink messages of either version may be received.
+    fn set_allow_recv_any_version(&mut self, allow: bool);
+    /// Wether messages of any MAVLink version may be received
+    fn allow_recv_any_version(&self) -> bool;
+
+    /// Read whole frame
+    fn recv_frame(&mut self) -> Result<MavFrame<M>, crate::error::MessageReadError> {
+        let (header, msg) = self.recv()?;
+        let protocol_version = self.protocol_version();
+        Ok(MavFrame {
+            header,
+            msg,
+            protocol_version,
+        })
+    }
+}
+
+/// The sending half of a [`MavConnection`], created by [`MavConnection::split`]
+pub trait MavSendHalf<M: Message> {
+    /// Send a MAVLink message
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError>;
+
+    /// Send a raw MAVLink frame, see [`MavConnection::send_raw`]
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let (header, data) = parse_frame(frame)?;
+        self.send(&header, &data)
+    }
+
+    /// Sets the MAVLink version to use for sending messages.
+    fn set_protocol_version(&mut self, version: MavlinkVersion);
+    /// Gets the currently used MAVLink version
+    fn protocol_version(&self) -> MavlinkVersion;
+
+    /// Write whole frame
+    fn send_frame(
+        &mut self,
+        frame: &MavFrame<M>,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.send(&frame.header, &frame.msg)
+    }
+
+    /// Send a message with default header
+    fn send_default(&mut self, data: &M) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader::default();
+        self.send(&header, data)
+    }
+}
+
+/// A half of a connection that is split by the default [`MavConnection::split`]
+struct SharedHalf<C: ?Sized> {
+    connection: Arc<RwLock<Box<C>>>,
+}
+
+impl<M: Message, C: MavConnection<M> + ?Sized> MavReceiveHalf<M> for SharedHalf<C> {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.connection.read().unwrap().recv()
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.connection.read().unwrap().recv_raw()
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.connection.read().unwrap().try_recv()
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.connection.read().unwrap().try_recv_raw()
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.connection
+            .write()
+            .unwrap()
+            .set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.read().unwrap().protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.connection
+            .write()
+            .unwrap()
+            .set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.connection.read().unwrap().allow_recv_any_version()
+    }
+}
+
+impl<M: Message, C: MavConnection<M> + ?Sized> MavSendHalf<M> for SharedHalf<C> {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.connection.read().unwrap().send(header, data)
+    }
+
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.connection.read().unwrap().send_raw(frame)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.connection
+            .write()
+            .unwrap()
+            .set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.read().unwrap().protocol_version()
+    }
+}
+
+/// Signing setup of the halves a [`HalvesConnection`] or an `AsyncHalvesConnection` is made of
+pub(crate) trait ConnectionHalf {
+    /// Setup the signing data shared by both halves, or disable message signing
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>);
+}
+
+/// A MAVLink connection made of a receive and a send half
+///
+/// Receiving and sending each lock their own half, so one thread can send while another one waits for a message.
+/// [`MavConnection::split`] hands out the halves themselves.
+pub struct HalvesConnection<R, S> {
+    receive_half: Mutex<R>,
+    send_half: Mutex<S>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+}
+
+impl<R, S> HalvesConnection<R, S> {
+    /// Join halves that use MAVLink 2 and receive only MAVLink 2 messages
+    pub(crate) fn from_halves(receive_half: R, send_half: S) -> Self {
+        Self {
+            receive_half: Mutex::new(receive_half),
+            send_half: Mutex::new(send_half),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+        }
+    }
+}
+
+impl<M, R, S> MavConnection<M> for HalvesConnection<R, S>
+where
+    M: Message,
+    R: MavReceiveHalf<M> + ConnectionHalf + Send,
+    S: MavSendHalf<M> + ConnectionHalf + Send,
+{
+    fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.receive_half.lock().unwrap().recv()
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.receive_half.lock().unwrap().recv_raw()
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.receive_half.lock().unwrap().try_recv()
+    }
+
+    fn try_recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.receive_half.lock().unwrap().try_recv_raw()
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, crate::error::MessageWriteError> {
+        self.send_half.lock().unwrap().send(header, data)
+    }
+
+    fn send_raw(
+        &self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.send_half.lock().unwrap().send_raw(frame)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+        self.receive_half
+            .get_mut()
+            .unwrap()
+            .set_protocol_version(version);
+        self.send_half
+            .get_mut()
+            .unwrap()
+            .set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+        self.receive_half
+            .get_mut()
+            .unwrap()
+            .set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        let signing_data = signing_data.map(|config| Arc::new(SigningData::from_config(config)));
+        self.receive_half
+            .get_mut()
+            .unwrap()
+            .setup_signing(signing_data.clone());
+        self.send_half
+            .get_mut()
+            .unwrap()
+            .setup_signing(signing_data);
+    }
+
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn MavReceiveHalf<M> + Send>,
+        Box<dyn MavSendHalf<M> + Send>,
+    )
+    where
+        Self: Send + Sync + 'static,
+    {
+        (
+            Box::new(self.receive_half.into_inner().unwrap()),
+            Box::new(self.send_half.into_inner().unwrap()),
+        )
+    }
 }
 
 /// Connect to a MAVLink node by address string.
@@ -89,7 +444,20 @@ pub trait MavConnection<M: Message> {
 ///  * `udpout:<addr>:<port>` to create a UDP client
 ///  * `udpbcast:<addr>:<port>` to create a UDP broadcast
 ///  * `serial:<port>:<baudrate>` to create a serial connection
+///  * `unixin:<path>` to create a Unix domain stream socket server, listening for an incoming connection
+///  * `unix:<path>` to create a Unix domain stream socket client
+///  * `unixgramin:<path>` to create a Unix domain datagram socket server, listening for incoming packets
+///  * `unixgram:<path>` to create a Unix domain datagram socket client
+///  * `wsin:<addr>:<port>` to create a WebSocket server, listening for an incoming connection
+///  * `ws://<host>:<port>/<path>` or `wss://<host>:<port>/<path>` to create a WebSocket client
+///  * `memory:<name>` to create an in-memory connection, linked to the next connection with the same name
 ///  * `file:<path>` to extract file data, writing to such a connection does nothing
+///  * `file:<path>?mode=write&format=<raw|tlog>` to append sent frames to a file, optionally with tlog timestamps
+///  * `file:<path>?mode=readwrite&output=<path>` to extract file data, appending sent frames to the output
+///  * `file:<path>?format=tlog&follow=true&throttle=true` to read a tlog as it is written, at the time it was
+///    recorded at, following and throttling is only supported by async connections
+///  * `replay:<path>?speed=<factor>&loop=<bool>` to replay a tlog with its recorded timing, optionally at a scaled
+///    speed and starting over at its end
 ///
 /// The type of the connection is determined at runtime based on the address type, so the
 /// connection is returned as a trait object.
@@ -100,14 +468,112 @@ pub fn connect<M: Message + Sync + Send>(
 }
 
 /// Returns the socket address for the given address.
-#[cfg(any(feature = "tcp", feature = "udp"))]
+#[cfg(any(feature = "tcp", feature = "udp", feature = "websocket"))]
 pub(crate) fn get_socket_addr<T: std::net::ToSocketAddrs>(
     address: &T,
 ) -> Result<std::net::SocketAddr, io::Error> {
-    address.to_socket_addrs()?.next().ok_or(io::Error::new(
-        io::ErrorKind::Other,
-        "Host address lookup failed",
-    ))
+    address
+        .to_socket_addrs()?
+        .next()
+        .ok_or(io::Error::other("Host address lookup failed"))
+}
+
+/// Encode a message into a raw frame, signing it if `signing_data` signs outgoing messages
+pub(crate) fn encode_frame<M: Message>(
+    version: MavlinkVersion,
+    header: MavHeader,
+    data: &M,
+    #[cfg(feature = "signing")] signing_data: Option<&SigningData>,
+) -> Result<MAVLinkMessageRaw, crate::error::MessageWriteError> {
+    match version {
+        MavlinkVersion::V1 => {
+            if data.message_id() > u8::MAX.into() {
+                return Err(crate::error::MessageWriteError::MAVLink2Only);
+            }
+            let mut frame = MAVLinkV1MessageRaw::new();
+            frame.serialize_message(header, data);
+            Ok(MAVLinkMessageRaw::V1(frame))
+        }
+        MavlinkVersion::V2 => {
+            let mut frame = MAVLinkV2MessageRaw::new();
+            #[cfg(feature = "signing")]
+            if let Some(signing_data) = signing_data.filter(|data| data.config.sign_outgoing) {
+                frame.serialize_message_for_signing(header, data);
+                signing_data.sign_message(&mut frame);
+                return Ok(MAVLinkMessageRaw::V2(frame));
+            }
+            frame.serialize_message(header, data);
+            Ok(MAVLinkMessageRaw::V2(frame))
+        }
+    }
+}
+
+/// Encodes the messages sent by a connection wrapper into frames, which are passed on with `send_raw`
+///
+/// Like a connection, the encoder numbers the frames and signs them if signing is set up.
+pub(crate) struct FrameEncoder {
+    sequence: u8,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl FrameEncoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            sequence: 0,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    #[cfg(feature = "signing")]
+    pub(crate) fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+
+    pub(crate) fn encode<M: Message>(
+        &mut self,
+        version: MavlinkVersion,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<MAVLinkMessageRaw, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.sequence,
+            ..*header
+        };
+        self.sequence = self.sequence.wrapping_add(1);
+        encode_frame(
+            version,
+            header,
+            data,
+            #[cfg(feature = "signing")]
+            self.signing_data.as_ref(),
+        )
+    }
+}
+
+/// Parse the header and message of a raw frame to send it again
+pub(crate) fn parse_frame<M: Message>(
+    frame: &MAVLinkMessageRaw,
+) -> Result<(MavHeader, M), crate::error::MessageWriteError> {
+    let header = MavHeader {
+        sequence: frame.sequence(),
+        system_id: frame.system_id(),
+        component_id: frame.component_id(),
+    };
+    let data = M::parse(frame.version(), frame.message_id(), frame.payload())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((header, data))
+}
+
+/// Convert an error of a write done while receiving, like recording a received frame
+pub(crate) fn to_read_error(
+    error: crate::error::MessageWriteError,
+) -> crate::error::MessageReadError {
+    match error {
+        crate::error::MessageWriteError::Io(e) => crate::error::MessageReadError::Io(e),
+        error => io::Error::other(error.to_string()).into(),
+    }
 }
 
 /// A MAVLink connection address that can be connected to, establishing a [`MavConnection`]
@@ -128,7 +594,13 @@ impl Connectable for ConnectionAddress {
             Self::Udp(config) => config.connect::<M>(),
             #[cfg(feature = "direct-serial")]
             Self::Serial(config) => config.connect::<M>(),
+            #[cfg(all(feature = "unix", unix))]
+            Self::Unix(config) => config.connect::<M>(),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(config) => config.connect::<M>(),
+            Self::Memory(config) => config.connect::<M>(),
             Self::File(config) => config.connect::<M>(),
+            Self::Replay(config) => config.connect::<M>(),
         }
     }
 }
@@ -0,0 +1,220 @@
+//! Connection wrapper recording frames into a tlog
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use crate::connection::{FrameEncoder, MavConnection, MavReceiveHalf, MavSendHalf};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::tlog::{parse_raw, record_raw, TlogSink};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
+
+#[cfg(feature = "signing")]
+use crate::SigningConfig;
+
+/// Connection wrapper recording every frame sent and received over another connection into a tlog
+///
+/// Frames are received with [`MavConnection::recv_raw`] and recorded exactly as received. Sent messages are
+/// numbered, signed and serialized by the wrapper, which records the frame and passes it on with
+/// [`MavConnection::send_raw`], so the record matches the frame sent by the wrapped connection.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::tlog::{Rotation, RotatingTlogWriter};
+/// use mavlink::RecordingConnection;
+///
+/// let connection = mavlink::connect::<mavlink::common::MavMessage>("udpin:0.0.0.0:14550")?;
+/// let log = RotatingTlogWriter::new(
+///     "logs/flight.tlog",
+///     Rotation {
+///         max_size: Some(100_000_000),
+///         ..Rotation::default()
+///     },
+/// )?;
+/// let connection = RecordingConnection::new(connection, log);
+/// ```
+pub struct RecordingConnection<M: Message, S> {
+    connection: Box<dyn MavConnection<M> + Sync + Send>,
+    encoder: Mutex<FrameEncoder>,
+    sink: Arc<Mutex<S>>,
+}
+
+impl<M: Message, S: TlogSink> RecordingConnection<M, S> {
+    /// Wrap `connection`, recording its frames into `sink`
+    pub fn new(connection: Box<dyn MavConnection<M> + Sync + Send>, sink: S) -> Self {
+        Self {
+            connection,
+            encoder: Mutex::new(FrameEncoder::new()),
+            sink: Arc::new(Mutex::new(sink)),
+        }
+    }
+
+    /// The wrapped connection
+    pub fn get_ref(&self) -> &(dyn MavConnection<M> + Sync + Send) {
+        self.connection.as_ref()
+    }
+
+    /// Flush the records written so far
+    pub fn flush_log(&self) -> io::Result<()> {
+        self.sink.lock().unwrap().flush()
+    }
+}
+
+impl<M: Message + 'static, S: TlogSink + 'static> MavConnection<M> for RecordingConnection<M, S> {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let raw = self.recv_raw()?;
+        Ok(parse_raw(&raw)?)
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let raw = self.connection.recv_raw()?;
+        record_raw(&self.sink, &raw)?;
+        Ok(raw)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let raw = self.try_recv_raw()?;
+        Ok(parse_raw(&raw)?)
+    }
+
+    fn try_recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let raw = self.connection.try_recv_raw()?;
+        record_raw(&self.sink, &raw)?;
+        Ok(raw)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self.encoder.lock().unwrap().encode(
+            self.connection.protocol_version(),
+            header,
+            data,
+        )?;
+        self.send_raw(&frame)
+    }
+
+    fn send_raw(&self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let len = self.connection.send_raw(frame)?;
+        record_raw(&self.sink, frame)?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.connection.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.connection.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.connection.allow_recv_any_version()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.encoder
+            .get_mut()
+            .unwrap()
+            .setup_signing(signing_data.clone());
+        self.connection.setup_signing(signing_data);
+    }
+
+    fn split(
+        self: Box<Self>,
+    ) -> (
+        Box<dyn MavReceiveHalf<M> + Send>,
+        Box<dyn MavSendHalf<M> + Send>,
+    ) {
+        let (receive_half, send_half) = self.connection.split();
+        let receive_half = RecordingReceiveHalf {
+            half: receive_half,
+            sink: self.sink.clone(),
+        };
+        let send_half = RecordingSendHalf {
+            half: send_half,
+            encoder: self.encoder.into_inner().unwrap(),
+            sink: self.sink,
+        };
+        (Box::new(receive_half), Box::new(send_half))
+    }
+}
+
+/// The receiving half of a [`RecordingConnection`], sharing the log with the sending half
+pub struct RecordingReceiveHalf<M: Message, S> {
+    half: Box<dyn MavReceiveHalf<M> + Send>,
+    sink: Arc<Mutex<S>>,
+}
+
+impl<M: Message, S: TlogSink> MavReceiveHalf<M> for RecordingReceiveHalf<M, S> {
+    fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        let raw = self.recv_raw()?;
+        Ok(parse_raw(&raw)?)
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let raw = self.half.recv_raw()?;
+        record_raw(&self.sink, &raw)?;
+        Ok(raw)
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        let raw = self.try_recv_raw()?;
+        Ok(parse_raw(&raw)?)
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let raw = self.half.try_recv_raw()?;
+        record_raw(&self.sink, &raw)?;
+        Ok(raw)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.half.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.half.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.half.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.half.allow_recv_any_version()
+    }
+}
+
+/// The sending half of a [`RecordingConnection`], sharing the log with the receiving half
+pub struct RecordingSendHalf<M: Message, S> {
+    half: Box<dyn MavSendHalf<M> + Send>,
+    encoder: FrameEncoder,
+    sink: Arc<Mutex<S>>,
+}
+
+impl<M: Message, S: TlogSink> MavSendHalf<M> for RecordingSendHalf<M, S> {
+    fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self
+            .encoder
+            .encode(self.half.protocol_version(), header, data)?;
+        self.send_raw(&frame)
+    }
+
+    fn send_raw(&mut self, frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let len = self.half.send_raw(frame)?;
+        record_raw(&self.sink, frame)?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.half.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.half.protocol_version()
+    }
+}
@@ -0,0 +1,389 @@
+//! Replay of a tlog with its recorded timing
+
+use std::fs::File;
+use std::io;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::tlog::{parse_raw, TlogReader};
+use crate::{Connectable, MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(feature = "signing")]
+use crate::SigningData;
+
+pub mod config;
+
+use config::{valid_speed, ReplayConfig};
+
+/// Next step of a replay
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Next {
+    /// The frame is due
+    Frame(MAVLinkMessageRaw),
+    /// No frame is due for the given time, or until the replay is resumed or its speed is changed
+    Wait(Option<Duration>),
+}
+
+/// Position and timing of a replay
+pub(crate) struct ReplayState {
+    reader: TlogReader<File>,
+    next: Option<(u64, MAVLinkMessageRaw)>,
+    // log time that is replayed at the instant
+    anchor: Option<(Instant, u64)>,
+    speed: f64,
+    paused: bool,
+    looping: bool,
+    seek: Option<u64>,
+    position: Option<u64>,
+}
+
+impl ReplayState {
+    /// Log time replayed at `now`
+    fn log_time(&self, now: Instant) -> Option<u64> {
+        let (instant, timestamp) = self.anchor?;
+        let elapsed = now.saturating_duration_since(instant).as_secs_f64() * self.speed;
+        Some(timestamp.saturating_add((elapsed * 1e6) as u64))
+    }
+
+    /// Restart the replay timing at the current log time
+    fn reanchor(&mut self, now: Instant) {
+        self.anchor = self.log_time(now).map(|timestamp| (now, timestamp));
+    }
+
+    /// Return the next frame if it is due at `now`
+    pub(crate) fn poll<M: Message>(&mut self, now: Instant) -> Result<Next, MessageReadError> {
+        if self.paused {
+            return Ok(Next::Wait(None));
+        }
+        if let Some(timestamp) = self.seek.take() {
+            self.reader.seek_time::<M>(timestamp)?;
+            self.next = None;
+            self.anchor = None;
+        }
+        if self.next.is_none() {
+            self.next = match self.reader.read_raw::<M>()? {
+                None if self.looping => {
+                    self.reader.seek_position(0)?;
+                    self.anchor = None;
+                    self.reader.read_raw::<M>()?
+                }
+                next => next,
+            };
+        }
+        let Some((timestamp, _)) = self.next else {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "End of replayed log").into());
+        };
+
+        let (instant, anchor) = *self.anchor.get_or_insert((now, timestamp));
+        let delay = timestamp.saturating_sub(anchor) as f64 / 1e6 / self.speed;
+        // at a very low speed the frame may not be due within the time an `Instant` can represent
+        let due = Duration::try_from_secs_f64(delay)
+            .ok()
+            .and_then(|delay| instant.checked_add(delay));
+        match due {
+            Some(due) if due > now => return Ok(Next::Wait(Some(due - now))),
+            Some(_) => {}
+            None => return Ok(Next::Wait(None)),
+        }
+        self.position = Some(timestamp);
+        Ok(Next::Frame(self.next.take().unwrap().1))
+    }
+}
+
+/// Replay state shared between a connection, its halves and its controls
+pub(crate) struct Shared {
+    pub(crate) state: Mutex<ReplayState>,
+    pub(crate) changed: Condvar,
+}
+
+impl Shared {
+    pub(crate) fn open(config: &ReplayConfig) -> io::Result<Arc<Self>> {
+        let file = File::open(&config.path)?;
+        Ok(Arc::new(Self {
+            state: Mutex::new(ReplayState {
+                reader: TlogReader::new(file),
+                next: None,
+                anchor: None,
+                speed: config.speed,
+                paused: false,
+                looping: config.looping,
+                seek: None,
+                position: None,
+            }),
+            changed: Condvar::new(),
+        }))
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ReplayState)) {
+        f(&mut self.state.lock().unwrap());
+        self.changed.notify_all();
+    }
+
+    /// Block until the next frame of an accepted version is due
+    fn recv_raw<M: Message>(
+        &self,
+        version: ReadVersion,
+    ) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match state.poll::<M>(Instant::now())? {
+                Next::Frame(raw) if version.accepts_stx(raw.raw_bytes()[0]) => return Ok(raw),
+                Next::Frame(_) => {}
+                Next::Wait(Some(delay)) => {
+                    state = self.changed.wait_timeout(state, delay).unwrap().0;
+                }
+                Next::Wait(None) => state = self.changed.wait(state).unwrap(),
+            }
+        }
+    }
+
+    /// Return the next frame of an accepted version if it is due
+    fn try_recv_raw<M: Message>(
+        &self,
+        version: ReadVersion,
+    ) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match state.poll::<M>(Instant::now())? {
+                Next::Frame(raw) if version.accepts_stx(raw.raw_bytes()[0]) => return Ok(raw),
+                Next::Frame(_) => {}
+                Next::Wait(_) => {
+                    return Err(io::Error::new(io::ErrorKind::WouldBlock, "No frame due").into())
+                }
+            }
+        }
+    }
+
+    fn recv<M: Message>(&self, version: ReadVersion) -> Result<(MavHeader, M), MessageReadError> {
+        loop {
+            // skip frames of unknown messages
+            if let Ok(message) = parse_raw(&self.recv_raw::<M>(version)?) {
+                return Ok(message);
+            }
+        }
+    }
+
+    fn try_recv<M: Message>(
+        &self,
+        version: ReadVersion,
+    ) -> Result<(MavHeader, M), MessageReadError> {
+        loop {
+            if let Ok(message) = parse_raw(&self.try_recv_raw::<M>(version)?) {
+                return Ok(message);
+            }
+        }
+    }
+}
+
+/// Connection replaying the frames of a tlog at the time they were recorded, or at a scaled speed
+///
+/// The first frame is replayed right away, every following frame once the time between the frames' timestamps,
+/// divided by the speed, has passed. The replay is controlled while running through a [`ReplayControl`].
+/// Writing to the connection does nothing. The frames' signatures are not checked, as their signing timestamps are
+/// in the past.
+///
+/// # Example
+///
+/// ```ignore
+/// let connection = mavlink::connect::<MavMessage>("replay:flight.tlog?speed=10")?;
+/// ```
+pub type ReplayConnection = HalvesConnection<ReplayReceiveHalf, ReplaySendHalf>;
+
+impl ReplayConnection {
+    /// Open the tlog of a replay address
+    pub fn open(config: &ReplayConfig) -> io::Result<Self> {
+        let receive_half = ReplayReceiveHalf {
+            shared: Shared::open(config)?,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+        };
+        let send_half = ReplaySendHalf {
+            protocol_version: MavlinkVersion::V2,
+        };
+        Ok(Self::from_halves(receive_half, send_half))
+    }
+
+    /// Control over the replay, which stays usable after the connection is split
+    pub fn control(&mut self) -> ReplayControl {
+        ReplayControl::new(self.receive_half.get_mut().unwrap().shared.clone())
+    }
+}
+
+/// The receiving half of a [`ReplayConnection`]
+pub struct ReplayReceiveHalf {
+    shared: Arc<Shared>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+}
+
+impl<M: Message> MavReceiveHalf<M> for ReplayReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        self.shared.recv(ReadVersion::from_half_cfg(
+            self.protocol_version,
+            self.recv_any_version,
+        ))
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.shared.recv_raw::<M>(ReadVersion::from_half_cfg(
+            self.protocol_version,
+            self.recv_any_version,
+        ))
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        self.shared.try_recv(ReadVersion::from_half_cfg(
+            self.protocol_version,
+            self.recv_any_version,
+        ))
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.shared.try_recv_raw::<M>(ReadVersion::from_half_cfg(
+            self.protocol_version,
+            self.recv_any_version,
+        ))
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for ReplayReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, _signing_data: Option<Arc<SigningData>>) {}
+}
+
+/// The sending half of a [`ReplayConnection`], writing to it does nothing
+pub struct ReplaySendHalf {
+    protocol_version: MavlinkVersion,
+}
+
+impl<M: Message> MavSendHalf<M> for ReplaySendHalf {
+    fn send(&mut self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
+        Ok(0)
+    }
+
+    fn send_raw(&mut self, _frame: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        Ok(0)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for ReplaySendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, _signing_data: Option<Arc<SigningData>>) {}
+}
+
+/// Pauses, seeks and changes the speed of a running replay
+///
+/// Changes take effect for the next received frame, also when a receive is already waiting for it.
+#[derive(Clone)]
+pub struct ReplayControl {
+    shared: Arc<Shared>,
+}
+
+impl ReplayControl {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    /// Stop replaying frames until the replay is resumed
+    pub fn pause(&self) {
+        self.shared.update(|state| {
+            if !state.paused {
+                state.reanchor(Instant::now());
+                state.paused = true;
+            }
+        });
+    }
+
+    /// Continue replaying where the replay was paused
+    pub fn resume(&self) {
+        self.shared.update(|state| {
+            if state.paused {
+                state.paused = false;
+                if let Some((instant, _)) = &mut state.anchor {
+                    *instant = Instant::now();
+                }
+            }
+        });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.state.lock().unwrap().paused
+    }
+
+    /// Set the replay speed relative to the recorded timing
+    ///
+    /// # Panics
+    ///
+    /// Panics if the speed is not positive and finite.
+    pub fn set_speed(&self, speed: f64) {
+        assert!(
+            valid_speed(speed),
+            "Replay speed must be positive and finite"
+        );
+        self.shared.update(|state| {
+            if !state.paused {
+                state.reanchor(Instant::now());
+            }
+            state.speed = speed;
+        });
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.shared.state.lock().unwrap().speed
+    }
+
+    /// Continue the replay at the first frame logged at or after `timestamp`, in microseconds since the Unix epoch
+    ///
+    /// The frame is replayed right away, the replay timing starts over from it.
+    pub fn seek(&self, timestamp: u64) {
+        self.shared.update(|state| state.seek = Some(timestamp));
+    }
+
+    /// Set whether the replay starts over at the end of the log
+    pub fn set_looping(&self, looping: bool) {
+        self.shared.update(|state| state.looping = looping);
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.shared.state.lock().unwrap().looping
+    }
+
+    /// Timestamp of the last replayed frame, in microseconds since the Unix epoch
+    pub fn position(&self) -> Option<u64> {
+        self.shared.state.lock().unwrap().position
+    }
+}
+
+impl Connectable for ReplayConfig {
+    fn connect<M: Message>(&self) -> io::Result<Box<dyn MavConnection<M> + Sync + Send>> {
+        Ok(Box::new(ReplayConnection::open(self)?))
+    }
+}
@@ -0,0 +1,63 @@
+use core::fmt::Display;
+use std::path::PathBuf;
+
+/// MAVLink connection address for replaying a tlog
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connectable, ReplayConfig};
+/// use std::path::PathBuf;
+///
+/// // replay at twice the recorded speed, starting over at the end of the log
+/// let config = ReplayConfig::new(PathBuf::from("flight.tlog"), 2.0, true);
+/// config
+///   .connect::<mavlink::ardupilotmega::MavMessage>()
+///   .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub(crate) path: PathBuf,
+    pub(crate) speed: f64,
+    pub(crate) looping: bool,
+}
+
+impl ReplayConfig {
+    /// Creates a replay address from a tlog path, the replay speed relative to the recorded timing and whether the
+    /// replay starts over at the end of the log.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the speed is not positive and finite.
+    pub fn new(path: PathBuf, speed: f64, looping: bool) -> Self {
+        assert!(
+            valid_speed(speed),
+            "Replay speed must be positive and finite"
+        );
+        Self {
+            path,
+            speed,
+            looping,
+        }
+    }
+}
+
+/// Whether a replay can run at `speed`
+pub(crate) fn valid_speed(speed: f64) -> bool {
+    speed.is_finite() && speed > 0.0
+}
+
+impl Display for ReplayConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "replay:{}", self.path.display())?;
+        let mut separator = '?';
+        if self.speed != 1.0 {
+            write!(f, "{separator}speed={}", self.speed)?;
+            separator = '&';
+        }
+        if self.looping {
+            write!(f, "{separator}loop=true")?;
+        }
+        Ok(())
+    }
+}
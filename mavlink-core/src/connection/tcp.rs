@@ -1,7 +1,9 @@
 //! TCP MAVLink connection
 
 use crate::connection::get_socket_addr;
-use crate::connection::MavConnection;
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
 use crate::peek_reader::PeekReader;
 #[cfg(not(feature = "signing"))]
 use crate::read_raw_versioned_msg;
@@ -10,18 +12,18 @@ use crate::read_raw_versioned_msg_signed;
 use crate::Connectable;
 use crate::MAVLinkMessageRaw;
 use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
-use core::ops::DerefMut;
-use std::io;
+use std::io::{self, Write};
 use std::net::ToSocketAddrs;
 use std::net::{TcpListener, TcpStream};
-use std::sync::Mutex;
+#[cfg(feature = "signing")]
+use std::sync::Arc;
 use std::time::Duration;
 
 #[cfg(not(feature = "signing"))]
 use crate::{read_versioned_msg, write_versioned_msg};
 
 #[cfg(feature = "signing")]
-use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningConfig, SigningData};
+use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningData};
 
 pub mod config;
 
@@ -33,17 +35,7 @@ pub fn tcpout<T: ToSocketAddrs>(address: T) -> io::Result<TcpConnection> {
     let socket = TcpStream::connect(addr)?;
     socket.set_read_timeout(Some(Duration::from_millis(100)))?;
 
-    Ok(TcpConnection {
-        reader: Mutex::new(PeekReader::new(socket.try_clone()?)),
-        writer: Mutex::new(TcpWrite {
-            socket,
-            sequence: 0,
-        }),
-        protocol_version: MavlinkVersion::V2,
-        recv_any_version: false,
-        #[cfg(feature = "signing")]
-        signing_data: None,
-    })
+    connection(socket)
 }
 
 pub fn tcpin<T: ToSocketAddrs>(address: T) -> io::Result<TcpConnection> {
@@ -53,19 +45,7 @@ pub fn tcpin<T: ToSocketAddrs>(address: T) -> io::Result<TcpConnection> {
     //For now we only accept one incoming stream: this blocks until we get one
     for incoming in listener.incoming() {
         match incoming {
-            Ok(socket) => {
-                return Ok(TcpConnection {
-                    reader: Mutex::new(PeekReader::new(socket.try_clone()?)),
-                    writer: Mutex::new(TcpWrite {
-                        socket,
-                        sequence: 0,
-                    }),
-                    protocol_version: MavlinkVersion::V2,
-                    recv_any_version: false,
-                    #[cfg(feature = "signing")]
-                    signing_data: None,
-                })
-            }
+            Ok(socket) => return connection(socket),
             Err(e) => {
                 //TODO don't println in lib
                 println!("listener err: {e}");
@@ -78,87 +58,148 @@ pub fn tcpin<T: ToSocketAddrs>(address: T) -> io::Result<TcpConnection> {
     ))
 }
 
-pub struct TcpConnection {
-    reader: Mutex<PeekReader<TcpStream>>,
-    writer: Mutex<TcpWrite>,
-    protocol_version: MavlinkVersion,
-    recv_any_version: bool,
-    #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+fn connection(socket: TcpStream) -> io::Result<TcpConnection> {
+    let receive_half = TcpReceiveHalf {
+        reader: PeekReader::new(socket.try_clone()?),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = TcpSendHalf {
+        writer: TcpWrite {
+            socket,
+            sequence: 0,
+        },
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(HalvesConnection::from_halves(receive_half, send_half))
 }
 
+/// TCP MAVLink connection
+pub type TcpConnection = HalvesConnection<TcpReceiveHalf, TcpSendHalf>;
+
 struct TcpWrite {
     socket: TcpStream,
     sequence: u8,
 }
 
-impl<M: Message> MavConnection<M> for TcpConnection {
-    fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+/// The receiving half of a [`TcpConnection`]
+pub struct TcpReceiveHalf {
+    reader: PeekReader<TcpStream>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavReceiveHalf<M> for TcpReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
         #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
+        let result = read_versioned_msg(&mut self.reader, version);
         #[cfg(feature = "signing")]
         let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
+            read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
         result
     }
 
-    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
         #[cfg(not(feature = "signing"))]
-        let result = read_raw_versioned_msg::<M, _>(reader.deref_mut(), version);
+        let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
         #[cfg(feature = "signing")]
         let result = read_raw_versioned_msg_signed::<M, _>(
-            reader.deref_mut(),
+            &mut self.reader,
             version,
-            self.signing_data.as_ref(),
+            self.signing_data.as_deref(),
         );
         result
     }
 
-    fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        reader.reader_mut().set_nonblocking(true)?;
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.reader.reader_mut().set_nonblocking(true)?;
+        let result = MavReceiveHalf::<M>::recv(self);
+        self.reader.reader_mut().set_nonblocking(false)?;
+        result
+    }
 
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.reader.reader_mut().set_nonblocking(true)?;
+        let result = MavReceiveHalf::<M>::recv_raw(self);
+        self.reader.reader_mut().set_nonblocking(false)?;
+        result
+    }
 
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
 
-        #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
 
-        reader.reader_mut().set_nonblocking(false)?;
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
 
-        result
+impl ConnectionHalf for TcpReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
+}
 
-    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, crate::error::MessageWriteError> {
-        let mut lock = self.writer.lock().unwrap();
+/// The sending half of a [`TcpConnection`]
+pub struct TcpSendHalf {
+    writer: TcpWrite,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
 
+impl<M: Message> MavSendHalf<M> for TcpSendHalf {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
         let header = MavHeader {
-            sequence: lock.sequence,
+            sequence: self.writer.sequence,
             system_id: header.system_id,
             component_id: header.component_id,
         };
 
-        lock.sequence = lock.sequence.wrapping_add(1);
+        self.writer.sequence = self.writer.sequence.wrapping_add(1);
         #[cfg(not(feature = "signing"))]
-        let result = write_versioned_msg(&mut lock.socket, self.protocol_version, header, data);
+        let result =
+            write_versioned_msg(&mut self.writer.socket, self.protocol_version, header, data);
         #[cfg(feature = "signing")]
         let result = write_versioned_msg_signed(
-            &mut lock.socket,
+            &mut self.writer.socket,
             self.protocol_version,
             header,
             data,
-            self.signing_data.as_ref(),
+            self.signing_data.as_deref(),
         );
         result
     }
 
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.writer.socket.write_all(frame.raw_bytes())?;
+        Ok(frame.raw_bytes().len())
+    }
+
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
         self.protocol_version = version;
     }
@@ -166,18 +207,12 @@ impl<M: Message> MavConnection<M> for TcpConnection {
     fn protocol_version(&self) -> MavlinkVersion {
         self.protocol_version
     }
+}
 
-    fn set_allow_recv_any_version(&mut self, allow: bool) {
-        self.recv_any_version = allow;
-    }
-
-    fn allow_recv_any_version(&self) -> bool {
-        self.recv_any_version
-    }
-
+impl ConnectionHalf for TcpSendHalf {
     #[cfg(feature = "signing")]
-    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
 }
 
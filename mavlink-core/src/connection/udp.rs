@@ -1,7 +1,9 @@
 //! UDP MAVLink connection
 
 use crate::connection::get_socket_addr;
-use crate::connection::MavConnection;
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
 use crate::peek_reader::PeekReader;
 #[cfg(not(feature = "signing"))]
 use crate::read_raw_versioned_msg;
@@ -10,17 +12,16 @@ use crate::read_raw_versioned_msg_signed;
 use crate::Connectable;
 use crate::MAVLinkMessageRaw;
 use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
-use core::ops::DerefMut;
 use std::collections::VecDeque;
 use std::io::{self, Read};
 use std::net::{SocketAddr, UdpSocket};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 #[cfg(not(feature = "signing"))]
 use crate::{read_versioned_msg, write_versioned_msg};
 
 #[cfg(feature = "signing")]
-use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningConfig, SigningData};
+use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningData};
 
 pub mod config;
 
@@ -49,126 +50,180 @@ impl Read for UdpRead {
     }
 }
 
-struct UdpWrite {
+/// UDP MAVLink connection
+pub type UdpConnection = HalvesConnection<UdpReceiveHalf, UdpSendHalf>;
+
+fn connection(
     socket: UdpSocket,
+    server: bool,
     dest: Option<SocketAddr>,
-    sequence: u8,
+) -> io::Result<UdpConnection> {
+    // a server replies to the last sender, so the receive half has to update the destination of the send half
+    let dest = Arc::new(Mutex::new(dest));
+    let receive_half = UdpReceiveHalf {
+        reader: PeekReader::new(UdpRead {
+            socket: socket.try_clone()?,
+            buffer: VecDeque::new(),
+            last_recv_address: None,
+        }),
+        server,
+        dest: dest.clone(),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = UdpSendHalf {
+        socket,
+        dest,
+        sequence: 0,
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(HalvesConnection::from_halves(receive_half, send_half))
 }
 
-pub struct UdpConnection {
-    reader: Mutex<PeekReader<UdpRead>>,
-    writer: Mutex<UdpWrite>,
+/// The receiving half of a [`UdpConnection`]
+pub struct UdpReceiveHalf {
+    reader: PeekReader<UdpRead>,
+    server: bool,
+    dest: Arc<Mutex<Option<SocketAddr>>>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
-    server: bool,
     #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+    signing_data: Option<Arc<SigningData>>,
 }
 
-impl UdpConnection {
-    fn new(socket: UdpSocket, server: bool, dest: Option<SocketAddr>) -> io::Result<Self> {
-        Ok(Self {
-            server,
-            reader: Mutex::new(PeekReader::new(UdpRead {
-                socket: socket.try_clone()?,
-                buffer: VecDeque::new(),
-                last_recv_address: None,
-            })),
-            writer: Mutex::new(UdpWrite {
-                socket,
-                dest,
-                sequence: 0,
-            }),
-            protocol_version: MavlinkVersion::V2,
-            recv_any_version: false,
-            #[cfg(feature = "signing")]
-            signing_data: None,
-        })
+impl UdpReceiveHalf {
+    fn update_dest(&self) {
+        if self.server {
+            if let addr @ Some(_) = self.reader.reader_ref().last_recv_address {
+                *self.dest.lock().unwrap() = addr;
+            }
+        }
     }
 }
 
-impl<M: Message> MavConnection<M> for UdpConnection {
-    fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-
+impl<M: Message> MavReceiveHalf<M> for UdpReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
         loop {
-            let version = ReadVersion::from_conn_cfg::<_, M>(self);
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
             #[cfg(not(feature = "signing"))]
-            let result = read_versioned_msg(reader.deref_mut(), version);
+            let result = read_versioned_msg(&mut self.reader, version);
             #[cfg(feature = "signing")]
             let result =
-                read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
-            if self.server {
-                if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                    self.writer.lock().unwrap().dest = addr;
-                }
-            }
+                read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+            self.update_dest();
             if let ok @ Ok(..) = result {
                 return ok;
             }
         }
     }
 
-    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
         loop {
-            let version = ReadVersion::from_conn_cfg::<_, M>(self);
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
             #[cfg(not(feature = "signing"))]
-            let result = read_raw_versioned_msg::<M, _>(reader.deref_mut(), version);
+            let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
             #[cfg(feature = "signing")]
             let result = read_raw_versioned_msg_signed::<M, _>(
-                reader.deref_mut(),
+                &mut self.reader,
                 version,
-                self.signing_data.as_ref(),
+                self.signing_data.as_deref(),
             );
-            if self.server {
-                if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                    self.writer.lock().unwrap().dest = addr;
-                }
-            }
+            self.update_dest();
             if let ok @ Ok(..) = result {
                 return ok;
             }
         }
     }
 
-    fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        reader.reader_mut().socket.set_nonblocking(true)?;
-
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.reader.reader_mut().socket.set_nonblocking(true)?;
 
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
         #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
+        let result = read_versioned_msg(&mut self.reader, version);
         #[cfg(feature = "signing")]
         let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
+            read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+        self.update_dest();
 
-        if self.server {
-            if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                self.writer.lock().unwrap().dest = addr;
-            }
-        }
+        self.reader.reader_mut().socket.set_nonblocking(false)?;
 
-        reader.reader_mut().socket.set_nonblocking(false)?;
+        result
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.reader.reader_mut().socket.set_nonblocking(true)?;
+
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_signed::<M, _>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        );
+        self.update_dest();
+
+        self.reader.reader_mut().socket.set_nonblocking(false)?;
 
         result
     }
 
-    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, crate::error::MessageWriteError> {
-        let mut guard = self.writer.lock().unwrap();
-        let state = &mut *guard;
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for UdpReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
 
+/// The sending half of a [`UdpConnection`]
+pub struct UdpSendHalf {
+    socket: UdpSocket,
+    dest: Arc<Mutex<Option<SocketAddr>>>,
+    sequence: u8,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for UdpSendHalf {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
         let header = MavHeader {
-            sequence: state.sequence,
+            sequence: self.sequence,
             system_id: header.system_id,
             component_id: header.component_id,
         };
 
-        state.sequence = state.sequence.wrapping_add(1);
+        self.sequence = self.sequence.wrapping_add(1);
 
-        let len = if let Some(addr) = state.dest {
+        let dest = *self.dest.lock().unwrap();
+        let len = if let Some(addr) = dest {
             let mut buf = Vec::new();
             #[cfg(not(feature = "signing"))]
             write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
@@ -178,9 +233,23 @@ impl<M: Message> MavConnection<M> for UdpConnection {
                 self.protocol_version,
                 header,
                 data,
-                self.signing_data.as_ref(),
+                self.signing_data.as_deref(),
             )?;
-            state.socket.send_to(&buf, addr)?
+            self.socket.send_to(&buf, addr)?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let dest = *self.dest.lock().unwrap();
+        let len = if let Some(addr) = dest {
+            self.socket.send_to(frame.raw_bytes(), addr)?
         } else {
             0
         };
@@ -195,18 +264,12 @@ impl<M: Message> MavConnection<M> for UdpConnection {
     fn protocol_version(&self) -> MavlinkVersion {
         self.protocol_version
     }
+}
 
-    fn set_allow_recv_any_version(&mut self, allow: bool) {
-        self.recv_any_version = allow;
-    }
-
-    fn allow_recv_any_version(&self) -> bool {
-        self.recv_any_version
-    }
-
+impl ConnectionHalf for UdpSendHalf {
     #[cfg(feature = "signing")]
-    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
     }
 }
 
@@ -220,7 +283,7 @@ impl Connectable for UdpConfig {
         if matches!(self.mode, UdpMode::Udpcast) {
             socket.set_broadcast(true)?;
         }
-        Ok(Box::new(UdpConnection::new(socket, server, dest)?))
+        Ok(Box::new(connection(socket, server, dest)?))
     }
 }
 
@@ -0,0 +1,245 @@
+//! Unix domain socket MAVLink connection
+
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
+use crate::peek_reader::PeekReader;
+#[cfg(not(feature = "signing"))]
+use crate::read_raw_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::read_raw_versioned_msg_signed;
+use crate::Connectable;
+use crate::MAVLinkMessageRaw;
+use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
+use std::io::{self, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "signing")]
+use std::sync::Arc;
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, write_versioned_msg};
+
+#[cfg(feature = "signing")]
+use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningData};
+
+pub mod config;
+pub mod datagram;
+
+use config::{UnixConfig, UnixMode};
+use datagram::{unixgramin, unixgramout};
+
+/// Path of a socket bound by a connection, the socket file is removed when this is dropped
+#[derive(Debug)]
+pub(crate) struct BoundPath(PathBuf);
+
+impl BoundPath {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self(path)
+    }
+
+    /// Unique path in the temporary directory, used by datagram clients to receive replies
+    pub(crate) fn temporary() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let name = format!("mavlink-{}-{n}.sock", std::process::id());
+        Self(std::env::temp_dir().join(name))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for BoundPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Stream client connecting to the server bound to `path`
+pub fn unixout<P: AsRef<Path>>(path: P) -> io::Result<UnixConnection> {
+    let socket = UnixStream::connect(path)?;
+    connection(socket)
+}
+
+/// Stream server bound to `path`, waiting for a single client to connect
+pub fn unixin<P: AsRef<Path>>(path: P) -> io::Result<UnixConnection> {
+    let listener = UnixListener::bind(&path)?;
+    // the socket file is only needed to accept the connection
+    let _path = BoundPath::new(path.as_ref().to_path_buf());
+
+    //For now we only accept one incoming stream: this blocks until we get one
+    let (socket, _) = listener.accept()?;
+    connection(socket)
+}
+
+/// Unix domain stream socket MAVLink connection
+pub type UnixConnection = HalvesConnection<UnixReceiveHalf, UnixSendHalf>;
+
+struct UnixWrite {
+    socket: UnixStream,
+    sequence: u8,
+}
+
+fn connection(socket: UnixStream) -> io::Result<UnixConnection> {
+    let receive_half = UnixReceiveHalf {
+        reader: PeekReader::new(socket.try_clone()?),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = UnixSendHalf {
+        writer: UnixWrite {
+            socket,
+            sequence: 0,
+        },
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(HalvesConnection::from_halves(receive_half, send_half))
+}
+
+/// The receiving half of a [`UnixConnection`]
+pub struct UnixReceiveHalf {
+    reader: PeekReader<UnixStream>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavReceiveHalf<M> for UnixReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result =
+            read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+        result
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_signed::<M, _>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        );
+        result
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.reader.reader_mut().set_nonblocking(true)?;
+        let result = MavReceiveHalf::<M>::recv(self);
+        self.reader.reader_mut().set_nonblocking(false)?;
+        result
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.reader.reader_mut().set_nonblocking(true)?;
+        let result = MavReceiveHalf::<M>::recv_raw(self);
+        self.reader.reader_mut().set_nonblocking(false)?;
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for UnixReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of a [`UnixConnection`]
+pub struct UnixSendHalf {
+    writer: UnixWrite,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for UnixSendHalf {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.writer.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.writer.sequence = self.writer.sequence.wrapping_add(1);
+        #[cfg(not(feature = "signing"))]
+        let result =
+            write_versioned_msg(&mut self.writer.socket, self.protocol_version, header, data);
+        #[cfg(feature = "signing")]
+        let result = write_versioned_msg_signed(
+            &mut self.writer.socket,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_deref(),
+        );
+        result
+    }
+
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.writer.socket.write_all(frame.raw_bytes())?;
+        Ok(frame.raw_bytes().len())
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for UnixSendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+impl Connectable for UnixConfig {
+    fn connect<M: Message>(&self) -> io::Result<Box<dyn MavConnection<M> + Sync + Send>> {
+        Ok(match self.mode {
+            UnixMode::StreamIn => Box::new(unixin(&self.path)?),
+            UnixMode::StreamOut => Box::new(unixout(&self.path)?),
+            UnixMode::DatagramIn => Box::new(unixgramin(&self.path)?),
+            UnixMode::DatagramOut => Box::new(unixgramout(&self.path)?),
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use core::fmt::Display;
+use std::path::PathBuf;
+
+/// Type of Unix domain socket connection
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connectable, UnixConfig, UnixMode};
+///
+/// let config = UnixConfig::new("/run/mavlink.sock".into(), UnixMode::StreamOut);
+/// config
+///     .connect::<mavlink::ardupilotmega::MavMessage>()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum UnixMode {
+    /// Stream server that binds to the provided path and waits for a client connection
+    StreamIn,
+    /// Stream client connecting to the server at the provided path
+    StreamOut,
+    /// Datagram server that binds to the provided path and replies to the last sender
+    DatagramIn,
+    /// Datagram client sending to the server at the provided path
+    DatagramOut,
+}
+
+/// MAVLink address for a Unix domain socket server or client
+#[derive(Debug, Clone)]
+pub struct UnixConfig {
+    pub(crate) path: PathBuf,
+    pub(crate) mode: UnixMode,
+}
+
+impl UnixConfig {
+    /// Creates a Unix domain socket connection address.
+    ///
+    /// The type of connection depends on the [`UnixMode`]
+    pub fn new(path: PathBuf, mode: UnixMode) -> Self {
+        Self { path, mode }
+    }
+}
+
+impl Display for UnixConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mode = match self.mode {
+            UnixMode::StreamIn => "unixin",
+            UnixMode::StreamOut => "unix",
+            UnixMode::DatagramIn => "unixgramin",
+            UnixMode::DatagramOut => "unixgram",
+        };
+        write!(f, "{mode}:{}", self.path.display())
+    }
+}
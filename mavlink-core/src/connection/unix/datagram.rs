@@ -0,0 +1,289 @@
+//! Unix domain datagram socket MAVLink connection
+
+use super::BoundPath;
+use crate::connection::{ConnectionHalf, HalvesConnection, MavReceiveHalf, MavSendHalf};
+use crate::peek_reader::PeekReader;
+#[cfg(not(feature = "signing"))]
+use crate::read_raw_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::read_raw_versioned_msg_signed;
+use crate::MAVLinkMessageRaw;
+use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, write_versioned_msg};
+
+#[cfg(feature = "signing")]
+use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningData};
+
+/// Datagram server bound to `path`, replying to the last client that sent a message
+pub fn unixgramin<P: AsRef<Path>>(path: P) -> io::Result<UnixDatagramConnection> {
+    let socket = UnixDatagram::bind(&path)?;
+    let path = BoundPath::new(path.as_ref().to_path_buf());
+    connection(socket, path, true, None)
+}
+
+/// Datagram client sending to the server bound to `path`
+///
+/// The client binds to a unique path in the temporary directory to be able to receive replies.
+pub fn unixgramout<P: AsRef<Path>>(path: P) -> io::Result<UnixDatagramConnection> {
+    let local = BoundPath::temporary();
+    let socket = UnixDatagram::bind(local.path())?;
+    connection(socket, local, false, Some(path.as_ref().to_path_buf()))
+}
+
+struct DatagramRead {
+    socket: UnixDatagram,
+    buffer: VecDeque<u8>,
+    last_recv_address: Option<PathBuf>,
+    // keeps the socket file of the bound path around as long as messages can be received
+    _path: BoundPath,
+}
+
+const MTU_SIZE: usize = 1500;
+impl Read for DatagramRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.buffer.is_empty() {
+            self.buffer.read(buf)
+        } else {
+            let mut read_buffer = [0u8; MTU_SIZE];
+            let (n_buffer, address) = self.socket.recv_from(&mut read_buffer)?;
+            let n = (&read_buffer[0..n_buffer]).read(buf)?;
+            self.buffer.extend(&read_buffer[n..n_buffer]);
+
+            // replies are only possible to senders bound to a path
+            self.last_recv_address = address.as_pathname().map(Path::to_path_buf);
+            Ok(n)
+        }
+    }
+}
+
+/// Unix domain datagram socket MAVLink connection
+pub type UnixDatagramConnection = HalvesConnection<UnixDatagramReceiveHalf, UnixDatagramSendHalf>;
+
+fn connection(
+    socket: UnixDatagram,
+    path: BoundPath,
+    server: bool,
+    dest: Option<PathBuf>,
+) -> io::Result<UnixDatagramConnection> {
+    // a server replies to the last sender, so the receive half has to update the destination of the send half
+    let dest = Arc::new(Mutex::new(dest));
+    let receive_half = UnixDatagramReceiveHalf {
+        reader: PeekReader::new(DatagramRead {
+            socket: socket.try_clone()?,
+            buffer: VecDeque::new(),
+            last_recv_address: None,
+            _path: path,
+        }),
+        server,
+        dest: dest.clone(),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = UnixDatagramSendHalf {
+        socket,
+        dest,
+        sequence: 0,
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(HalvesConnection::from_halves(receive_half, send_half))
+}
+
+/// The receiving half of a [`UnixDatagramConnection`]
+pub struct UnixDatagramReceiveHalf {
+    reader: PeekReader<DatagramRead>,
+    server: bool,
+    dest: Arc<Mutex<Option<PathBuf>>>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl UnixDatagramReceiveHalf {
+    fn update_dest(&self) {
+        if self.server {
+            if let addr @ Some(_) = &self.reader.reader_ref().last_recv_address {
+                *self.dest.lock().unwrap() = addr.clone();
+            }
+        }
+    }
+}
+
+impl<M: Message> MavReceiveHalf<M> for UnixDatagramReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        loop {
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_msg(&mut self.reader, version);
+            #[cfg(feature = "signing")]
+            let result =
+                read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+            self.update_dest();
+            if let ok @ Ok(..) = result {
+                return ok;
+            }
+        }
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        loop {
+            let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+            #[cfg(not(feature = "signing"))]
+            let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
+            #[cfg(feature = "signing")]
+            let result = read_raw_versioned_msg_signed::<M, _>(
+                &mut self.reader,
+                version,
+                self.signing_data.as_deref(),
+            );
+            self.update_dest();
+            if let ok @ Ok(..) = result {
+                return ok;
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.reader.reader_mut().socket.set_nonblocking(true)?;
+
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result =
+            read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+        self.update_dest();
+
+        self.reader.reader_mut().socket.set_nonblocking(false)?;
+
+        result
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.reader.reader_mut().socket.set_nonblocking(true)?;
+
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_signed::<M, _>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        );
+        self.update_dest();
+
+        self.reader.reader_mut().socket.set_nonblocking(false)?;
+
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for UnixDatagramReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of a [`UnixDatagramConnection`]
+pub struct UnixDatagramSendHalf {
+    socket: UnixDatagram,
+    dest: Arc<Mutex<Option<PathBuf>>>,
+    sequence: u8,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for UnixDatagramSendHalf {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.sequence = self.sequence.wrapping_add(1);
+
+        let dest = self.dest.lock().unwrap().clone();
+        let len = if let Some(addr) = dest {
+            let mut buf = Vec::new();
+            #[cfg(not(feature = "signing"))]
+            write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+            #[cfg(feature = "signing")]
+            write_versioned_msg_signed(
+                &mut buf,
+                self.protocol_version,
+                header,
+                data,
+                self.signing_data.as_deref(),
+            )?;
+            self.socket.send_to(&buf, addr)?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let dest = self.dest.lock().unwrap().clone();
+        let len = if let Some(addr) = dest {
+            self.socket.send_to(frame.raw_bytes(), addr)?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for UnixDatagramSendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
@@ -0,0 +1,349 @@
+//! WebSocket MAVLink connection
+
+use crate::connection::get_socket_addr;
+use crate::connection::{
+    ConnectionHalf, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
+use crate::peek_reader::PeekReader;
+#[cfg(not(feature = "signing"))]
+use crate::read_raw_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::read_raw_versioned_msg_signed;
+use crate::Connectable;
+use crate::MAVLinkMessageRaw;
+use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message as WsMessage, WebSocket};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, write_versioned_msg};
+
+#[cfg(feature = "signing")]
+use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningData};
+
+pub mod config;
+
+use config::{WebSocketConfig, WebSocketMode};
+
+/// Read timeout of the socket, after which a waiting receiver gives a sender the chance to use the socket
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub(crate) fn to_io_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::ConnectionAborted, "WebSocket closed")
+        }
+        error => io::Error::other(error),
+    }
+}
+
+/// Connect to the WebSocket server at the `ws://` or `wss://` URL
+pub fn wsout(url: &str) -> io::Result<WebSocketConnection> {
+    let (socket, _response) = tungstenite::connect(url).map_err(to_io_error)?;
+    let control = match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.try_clone()?,
+        #[cfg(feature = "websocket-tls")]
+        MaybeTlsStream::Rustls(stream) => stream.sock.try_clone()?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unsupported WebSocket stream",
+            ))
+        }
+    };
+    connection(socket, control)
+}
+
+/// WebSocket server bound to `address`, waiting for a single client to connect
+pub fn wsin<T: ToSocketAddrs>(address: T) -> io::Result<WebSocketConnection> {
+    let addr = get_socket_addr(&address)?;
+    let listener = TcpListener::bind(addr)?;
+
+    //For now we only accept one incoming stream: this blocks until we get one
+    let (stream, _) = listener.accept()?;
+    let control = stream.try_clone()?;
+    let socket = tungstenite::accept(MaybeTlsStream::Plain(stream))
+        .map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, e.to_string()))?;
+    connection(socket, control)
+}
+
+// The WebSocket is used by both the reader and the writer, it can't be split like a TCP stream
+struct WsShared {
+    socket: Mutex<WebSocket<MaybeTlsStream<TcpStream>>>,
+    // duplicate of the underlying TCP stream to change its blocking behaviour
+    control: TcpStream,
+    send_pending: AtomicBool,
+}
+
+impl WsShared {
+    /// Read the next binary message
+    ///
+    /// While blocking, the socket is released every [`POLL_INTERVAL`] so a sender does not have to
+    /// wait for an incoming message.
+    fn read_binary(&self, blocking: bool) -> io::Result<Vec<u8>> {
+        loop {
+            // let a waiting sender take the socket first
+            while self.send_pending.load(Ordering::Acquire) {
+                thread::yield_now();
+            }
+            let mut socket = self.socket.lock().unwrap();
+            if !blocking {
+                self.control.set_nonblocking(true)?;
+            }
+            let result = socket.read();
+            if !blocking {
+                self.control.set_nonblocking(false)?;
+            }
+            match result {
+                Ok(WsMessage::Binary(data)) if !data.is_empty() => return Ok(data),
+                Ok(WsMessage::Close(_)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "WebSocket closed",
+                    ))
+                }
+                // text messages are ignored, control messages are handled by the WebSocket
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if blocking
+                        && matches!(
+                            e.kind(),
+                            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                        ) => {}
+                Err(e) => return Err(to_io_error(e)),
+            }
+        }
+    }
+
+    fn send_binary(&self, data: Vec<u8>) -> io::Result<()> {
+        self.send_pending.store(true, Ordering::Release);
+        let mut socket = self.socket.lock().unwrap();
+        self.send_pending.store(false, Ordering::Release);
+        socket.send(WsMessage::Binary(data)).map_err(to_io_error)
+    }
+}
+
+struct WsRead {
+    shared: Arc<WsShared>,
+    buffer: VecDeque<u8>,
+    blocking: bool,
+}
+
+impl Read for WsRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.is_empty() {
+            let data = self.shared.read_binary(self.blocking)?;
+            self.buffer.extend(data);
+        }
+        self.buffer.read(buf)
+    }
+}
+
+struct WsWrite {
+    shared: Arc<WsShared>,
+    buffer: Vec<u8>,
+    sequence: u8,
+}
+
+impl Write for WsWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// Send the buffered frame as one binary message
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.shared.send_binary(core::mem::take(&mut self.buffer))
+    }
+}
+
+/// WebSocket MAVLink connection, sending each frame as a binary message
+pub type WebSocketConnection = HalvesConnection<WebSocketReceiveHalf, WebSocketSendHalf>;
+
+fn connection(
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    control: TcpStream,
+) -> io::Result<WebSocketConnection> {
+    control.set_read_timeout(Some(POLL_INTERVAL))?;
+    let shared = Arc::new(WsShared {
+        socket: Mutex::new(socket),
+        control,
+        send_pending: AtomicBool::new(false),
+    });
+    let receive_half = WebSocketReceiveHalf {
+        reader: PeekReader::new(WsRead {
+            shared: shared.clone(),
+            buffer: VecDeque::new(),
+            blocking: true,
+        }),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    let send_half = WebSocketSendHalf {
+        writer: WsWrite {
+            shared,
+            buffer: Vec::new(),
+            sequence: 0,
+        },
+        protocol_version: MavlinkVersion::V2,
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    };
+    Ok(HalvesConnection::from_halves(receive_half, send_half))
+}
+
+/// The receiving half of a [`WebSocketConnection`]
+pub struct WebSocketReceiveHalf {
+    reader: PeekReader<WsRead>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavReceiveHalf<M> for WebSocketReceiveHalf {
+    fn recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result =
+            read_versioned_msg_signed(&mut self.reader, version, self.signing_data.as_deref());
+        result
+    }
+
+    fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let version = ReadVersion::from_half_cfg(self.protocol_version, self.recv_any_version);
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg::<M, _>(&mut self.reader, version);
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_signed::<M, _>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_deref(),
+        );
+        result
+    }
+
+    fn try_recv(&mut self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        self.reader.reader_mut().blocking = false;
+        let result = MavReceiveHalf::<M>::recv(self);
+        self.reader.reader_mut().blocking = true;
+        result
+    }
+
+    fn try_recv_raw(&mut self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        self.reader.reader_mut().blocking = false;
+        let result = MavReceiveHalf::<M>::recv_raw(self);
+        self.reader.reader_mut().blocking = true;
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+}
+
+impl ConnectionHalf for WebSocketReceiveHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+/// The sending half of a [`WebSocketConnection`]
+pub struct WebSocketSendHalf {
+    writer: WsWrite,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<M: Message> MavSendHalf<M> for WebSocketSendHalf {
+    fn send(
+        &mut self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.writer.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.writer.sequence = self.writer.sequence.wrapping_add(1);
+        #[cfg(not(feature = "signing"))]
+        let len = write_versioned_msg(&mut self.writer, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        let len = write_versioned_msg_signed(
+            &mut self.writer,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_deref(),
+        )?;
+        self.writer.flush()?;
+        Ok(len)
+    }
+
+    fn send_raw(
+        &mut self,
+        frame: &MAVLinkMessageRaw,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        self.writer.write_all(frame.raw_bytes())?;
+        self.writer.flush()?;
+        Ok(frame.raw_bytes().len())
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+impl ConnectionHalf for WebSocketSendHalf {
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<Arc<SigningData>>) {
+        self.signing_data = signing_data;
+    }
+}
+
+impl Connectable for WebSocketConfig {
+    fn connect<M: Message>(&self) -> io::Result<Box<dyn MavConnection<M> + Sync + Send>> {
+        let conn = match self.mode {
+            WebSocketMode::WsIn => wsin(&self.address),
+            WebSocketMode::WsOut => wsout(&self.address),
+        };
+
+        Ok(Box::new(conn?))
+    }
+}
@@ -0,0 +1,46 @@
+use core::fmt::Display;
+
+/// Type of WebSocket connection
+#[derive(Debug, Clone, Copy)]
+pub enum WebSocketMode {
+    /// Connection will open a WebSocket server that binds to the provided address
+    WsIn,
+    /// Connection will connect to the provided `ws://` or `wss://` URL
+    WsOut,
+}
+
+/// MAVLink connection address for a WebSocket server or client
+///
+/// Every MAVLink frame is sent as a single binary WebSocket message.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connectable, WebSocketConfig, WebSocketMode};
+///
+/// let config = WebSocketConfig::new("ws://127.0.0.1:8080/mavlink".to_owned(), WebSocketMode::WsOut);
+/// config.connect::<mavlink::ardupilotmega::MavMessage>();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebSocketConfig {
+    pub(crate) address: String,
+    pub(crate) mode: WebSocketMode,
+}
+
+impl WebSocketConfig {
+    /// Creates a WebSocket connection address.
+    ///
+    /// A server takes a socket address like `0.0.0.0:8080`, a client takes a `ws://` or `wss://` URL.
+    pub fn new(address: String, mode: WebSocketMode) -> Self {
+        Self { address, mode }
+    }
+}
+
+impl Display for WebSocketConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.mode {
+            WebSocketMode::WsIn => write!(f, "wsin:{}", self.address),
+            WebSocketMode::WsOut => write!(f, "{}", self.address),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Async MAVLink connection over [`embedded_io_async`] streams
+
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::{MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_raw_versioned_msg_async, write_versioned_msg_async};
+#[cfg(feature = "signing")]
+use crate::{
+    read_raw_versioned_msg_async_signed, write_versioned_msg_async_signed, SigningConfig,
+    SigningData,
+};
+
+/// An async MAVLink connection for `no_std` environments
+///
+/// This mirrors the `AsyncMavConnection` trait available with the `tokio-1` feature, but since there is
+/// no allocator and no async mutex available the methods take `&mut self`.
+#[allow(async_fn_in_trait)]
+pub trait AsyncEmbeddedMavConnection<M: Message> {
+    /// Receive a mavlink message.
+    ///
+    /// Yield until a valid frame is received, ignoring invalid messages.
+    async fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError>;
+
+    /// Receive a raw, unparsed mavlink message.
+    ///
+    /// Yield until a valid frame is received, ignoring invalid messages.
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError>;
+
+    /// Send a mavlink message
+    async fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError>;
+
+    /// Sets the MAVLink version to use for receiving (when `allow_recv_any_version()` is `false`) and sending messages.
+    fn set_protocol_version(&mut self, version: MavlinkVersion);
+    /// Gets the currently used MAVLink version
+    fn protocol_version(&self) -> MavlinkVersion;
+
+    /// Set wether MAVLink messages of either version may be received.
+    ///
+    /// If set to false only messages of the version configured with `set_protocol_version()` are received.
+    fn set_allow_recv_any_version(&mut self, allow: bool);
+    /// Wether messages of any MAVLink version may be received
+    fn allow_recv_any_version(&self) -> bool;
+
+    /// Write whole frame
+    async fn send_frame(&mut self, frame: &MavFrame<M>) -> Result<usize, MessageWriteError> {
+        self.send(&frame.header, &frame.msg).await
+    }
+
+    /// Read whole frame
+    async fn recv_frame(&mut self) -> Result<MavFrame<M>, MessageReadError> {
+        let (header, msg) = self.recv().await?;
+        let protocol_version = self.protocol_version();
+        Ok(MavFrame {
+            header,
+            msg,
+            protocol_version,
+        })
+    }
+
+    /// Send a message with default header
+    async fn send_default(&mut self, data: &M) -> Result<usize, MessageWriteError> {
+        let header = MavHeader::default();
+        self.send(&header, data).await
+    }
+
+    /// Setup secret key used for message signing, or disable message signing
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>);
+}
+
+/// Async MAVLink connection over a pair of [`embedded_io_async::Read`] and [`embedded_io_async::Write`]
+/// halves, for example the RX and TX halves of a UART.
+///
+/// The connection keeps track of the sequence number of sent messages and supports receiving messages of
+/// either MAVLink version as well as message signing. With [`set_version_negotiation`] enabled it starts out
+/// sending MAVLink 1 and [switches to MAVLink 2] once the other side is seen sending MAVLink 2.
+///
+/// [`set_version_negotiation`]: AsyncEmbeddedConnection::set_version_negotiation
+/// [switches to MAVLink 2]: https://mavlink.io/en/guide/mavlink_2.html#negotiation
+///
+/// # Example
+/// ```ignore
+/// use mavlink::{AsyncEmbeddedConnection, AsyncEmbeddedMavConnection};
+///
+/// let (tx, rx) = uart.split();
+/// let mut connection = AsyncEmbeddedConnection::new(rx.into_ring_buffered(buf), tx);
+/// connection.set_allow_recv_any_version(true);
+/// let (header, msg) = AsyncEmbeddedMavConnection::<MavMessage>::recv(&mut connection).await?;
+/// ```
+pub struct AsyncEmbeddedConnection<R, W> {
+    reader: R,
+    writer: W,
+    sequence: u8,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    negotiate_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl<R: embedded_io_async::Read, W: embedded_io_async::Write> AsyncEmbeddedConnection<R, W> {
+    /// Create a MAVLink 2 connection from a reader and a writer
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            sequence: 0,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            negotiate_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    /// Enable or disable MAVLink version negotiation.
+    ///
+    /// While enabled, messages of either version are received and messages are sent using MAVLink 1
+    /// until the first MAVLink 2 message is received, after which MAVLink 2 is used for sending.
+    pub fn set_version_negotiation(&mut self, enable: bool) {
+        self.negotiate_version = enable;
+        if enable {
+            self.protocol_version = MavlinkVersion::V1;
+        }
+    }
+
+    /// Deconstruct the connection into its reader and writer
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+
+    fn read_version(&self) -> ReadVersion {
+        if self.recv_any_version || self.negotiate_version {
+            ReadVersion::Any
+        } else {
+            self.protocol_version.into()
+        }
+    }
+}
+
+impl<M, R, W> AsyncEmbeddedMavConnection<M> for AsyncEmbeddedConnection<R, W>
+where
+    M: Message,
+    R: embedded_io_async::Read,
+    W: embedded_io_async::Write,
+{
+    async fn recv(&mut self) -> Result<(MavHeader, M), MessageReadError> {
+        let frame = AsyncEmbeddedMavConnection::<M>::recv_frame(self).await?;
+        Ok((frame.header, frame.msg))
+    }
+
+    async fn recv_raw(&mut self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let version = self.read_version();
+        #[cfg(not(feature = "signing"))]
+        let result = read_raw_versioned_msg_async::<M>(&mut self.reader, version).await;
+        #[cfg(feature = "signing")]
+        let result = read_raw_versioned_msg_async_signed::<M>(
+            &mut self.reader,
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+
+        if let Ok(MAVLinkMessageRaw::V2(_)) = result {
+            if self.negotiate_version {
+                self.protocol_version = MavlinkVersion::V2;
+            }
+        }
+        result
+    }
+
+    async fn recv_frame(&mut self) -> Result<MavFrame<M>, MessageReadError> {
+        let message = AsyncEmbeddedMavConnection::<M>::recv_raw(self).await?;
+        // report the version the message was actually received with
+        let protocol_version = message.version();
+        Ok(MavFrame {
+            header: MavHeader {
+                sequence: message.sequence(),
+                system_id: message.system_id(),
+                component_id: message.component_id(),
+            },
+            msg: M::parse(protocol_version, message.message_id(), message.payload())?,
+            protocol_version,
+        })
+    }
+
+    async fn send(&mut self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        self.sequence = self.sequence.wrapping_add(1);
+        #[cfg(not(feature = "signing"))]
+        let result =
+            write_versioned_msg_async(&mut self.writer, self.protocol_version, header, data).await;
+        #[cfg(feature = "signing")]
+        let result = write_versioned_msg_async_signed(
+            &mut self.writer,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
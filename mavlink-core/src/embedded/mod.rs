@@ -52,3 +52,8 @@ impl<W: embedded_hal_02::serial::Write<u8>> Write for W {
         Ok(())
     }
 }
+
+#[cfg(feature = "embedded")]
+mod connection;
+#[cfg(feature = "embedded")]
+pub use connection::{AsyncEmbeddedConnection, AsyncEmbeddedMavConnection};
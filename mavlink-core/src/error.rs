@@ -11,6 +11,16 @@ pub enum ParserError {
     InvalidEnum { enum_type: &'static str, value: u32 },
     /// Message ID does not exist in this message set
     UnknownMessage { id: u32 },
+    /// Input is truncated or longer than allowed, `expected` is the required length for truncated input
+    /// and the maximum length for oversize input
+    InvalidLength { expected: usize, actual: usize },
+    /// Frame does not start with the packet start marker of the MAVLink version
+    InvalidStx { expected: u8, actual: u8 },
+    /// Checksum of the frame does not match its contents
+    InvalidChecksum { expected: u16, actual: u16 },
+    /// Signature of the frame could not be verified
+    #[cfg(feature = "signing")]
+    InvalidSignature,
 }
 
 impl Display for ParserError {
@@ -25,6 +35,20 @@ impl Display for ParserError {
                 "Invalid enum value for enum type {enum_type:?}, got {value:?}"
             ),
             Self::UnknownMessage { id } => write!(f, "Unknown message with ID {id:?}"),
+            Self::InvalidLength { expected, actual } => write!(
+                f,
+                "Invalid input length, expected {expected} bytes, got {actual}"
+            ),
+            Self::InvalidStx { expected, actual } => write!(
+                f,
+                "Invalid packet start marker, expected {expected:#04x}, got {actual:#04x}"
+            ),
+            Self::InvalidChecksum { expected, actual } => write!(
+                f,
+                "Invalid checksum, expected {expected:#06x}, got {actual:#06x}"
+            ),
+            #[cfg(feature = "signing")]
+            Self::InvalidSignature => write!(f, "Invalid message signature"),
         }
     }
 }
@@ -93,6 +117,8 @@ pub enum MessageWriteError {
     Io,
     /// Message does not support MAVLink 1
     MAVLink2Only,
+    /// Buffer is shorter than the `expected` length of the frame
+    BufferTooSmall { expected: usize, actual: usize },
 }
 
 impl Display for MessageWriteError {
@@ -103,6 +129,10 @@ impl Display for MessageWriteError {
             #[cfg(any(feature = "embedded", feature = "embedded-hal-02"))]
             Self::Io => write!(f, "Failed to write message"),
             Self::MAVLink2Only => write!(f, "Message is not supported in MAVLink 1"),
+            Self::BufferTooSmall { expected, actual } => write!(
+                f,
+                "Buffer is too small, expected {expected} bytes, got {actual}"
+            ),
         }
     }
 }
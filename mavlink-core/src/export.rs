@@ -0,0 +1,291 @@
+//! Export of decoded messages to tables, with one table per message type and one column per field.
+//!
+//! Every table starts with the `timestamp`, `system_id` and `component_id` columns, followed by the fields of the
+//! message in the order of its [`MessageSchema`]. Array fields are flattened into one column per element, named like
+//! `values[0]`, except for arrays of characters, which are a single string column. Enum fields are written as the
+//! names of their entries or as their values, see [`EnumFormat`].
+//!
+//! A [`CsvExporter`] writes a CSV file per message type. With the `parquet` feature, a
+//! [`ParquetExporter`](parquet::ParquetExporter) writes Parquet files and an
+//! [`ArrowTableBuilder`](parquet::ArrowTableBuilder) collects the rows of a message type into Arrow record batches.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::export::{CsvExporter, EnumFormat};
+//! use mavlink::tlog::TlogReader;
+//!
+//! let mut reader = TlogReader::new(std::fs::File::open("flight.tlog")?);
+//! let mut exporter = CsvExporter::new("flight", EnumFormat::Name)?;
+//! for entry in reader.frames::<MavMessage>() {
+//!     let (timestamp, frame) = entry?;
+//!     exporter.write(Some(timestamp), &frame.header, &frame.msg)?;
+//! }
+//! exporter.flush()?;
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::schema::{EnumSchema, FieldSchema, FieldType, FieldValue, MessageFields, MessageSchema};
+use crate::MavHeader;
+
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+/// How the values of enum fields are exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnumFormat {
+    /// Names of the entries, like `MAV_STATE_ACTIVE`, flags of bitmasks are joined with `|`
+    ///
+    /// Values without an entry are written as numbers.
+    #[default]
+    Name,
+    /// Numeric values of the entries
+    Value,
+}
+
+/// Type of the values of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnType {
+    UInt8,
+    Int8,
+    UInt16,
+    Int16,
+    UInt32,
+    Int32,
+    UInt64,
+    Int64,
+    Float,
+    Double,
+    String,
+}
+
+/// A column of the table of a message type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub column_type: ColumnType,
+    /// Whether the column can hold missing values, which is only the case for `timestamp`
+    pub nullable: bool,
+}
+
+impl Column {
+    fn new(name: String, column_type: ColumnType) -> Self {
+        Self {
+            name,
+            column_type,
+            nullable: false,
+        }
+    }
+}
+
+/// Columns of the table of a message type
+pub fn columns(schema: &MessageSchema, enums: EnumFormat) -> Vec<Column> {
+    let mut columns = vec![
+        Column {
+            nullable: true,
+            ..Column::new("timestamp".to_string(), ColumnType::UInt64)
+        },
+        Column::new("system_id".to_string(), ColumnType::UInt8),
+        Column::new("component_id".to_string(), ColumnType::UInt8),
+    ];
+    for field in schema.fields {
+        let column_type = match (field.enum_schema, field.field_type) {
+            (Some(_), _) if enums == EnumFormat::Name => ColumnType::String,
+            (_, FieldType::Char) => ColumnType::String,
+            (_, field_type) => column_type(field_type),
+        };
+        match field.array_length {
+            Some(_) if field.field_type == FieldType::Char => {
+                columns.push(Column::new(field.name.to_string(), ColumnType::String));
+            }
+            Some(length) => columns.extend(
+                (0..length).map(|i| Column::new(format!("{}[{i}]", field.name), column_type)),
+            ),
+            None => columns.push(Column::new(field.name.to_string(), column_type)),
+        }
+    }
+    columns
+}
+
+fn column_type(field_type: FieldType) -> ColumnType {
+    match field_type {
+        FieldType::UInt8 => ColumnType::UInt8,
+        FieldType::Int8 => ColumnType::Int8,
+        FieldType::UInt16 => ColumnType::UInt16,
+        FieldType::Int16 => ColumnType::Int16,
+        FieldType::UInt32 => ColumnType::UInt32,
+        FieldType::Int32 => ColumnType::Int32,
+        FieldType::UInt64 => ColumnType::UInt64,
+        FieldType::Int64 => ColumnType::Int64,
+        FieldType::Float => ColumnType::Float,
+        FieldType::Double => ColumnType::Double,
+        FieldType::Char => ColumnType::String,
+    }
+}
+
+/// Value of a cell, in the column type's representation
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cell {
+    Null,
+    UInt(u64),
+    Int(i64),
+    Float32(f32),
+    Float(f64),
+    Text(String),
+}
+
+impl Cell {
+    fn from_value(field: &FieldSchema, value: FieldValue<'_>, enums: EnumFormat) -> Self {
+        if let (Some(schema), Some(number), EnumFormat::Name) =
+            (field.enum_schema, value.as_u64(), enums)
+        {
+            return Self::Text(enum_name(schema, number));
+        }
+        match value {
+            FieldValue::Char(value) => Self::Text(char::from(value).to_string()),
+            FieldValue::Float(value) => Self::Float32(value),
+            FieldValue::Double(value) => Self::Float(value),
+            FieldValue::Int8(value) => Self::Int(value.into()),
+            FieldValue::Int16(value) => Self::Int(value.into()),
+            FieldValue::Int32(value) => Self::Int(value.into()),
+            FieldValue::Int64(value) => Self::Int(value),
+            // negative values of signed enum fields are written as numbers
+            value => value.as_u64().map_or(Self::Null, Self::UInt),
+        }
+    }
+}
+
+/// Cells of the row of a message, matching the [`columns`] of its type
+pub(crate) fn cells<M: MessageFields>(
+    timestamp: Option<u64>,
+    header: &MavHeader,
+    message: &M,
+    enums: EnumFormat,
+) -> Vec<Cell> {
+    let mut cells = vec![
+        timestamp.map_or(Cell::Null, Cell::UInt),
+        Cell::UInt(header.system_id.into()),
+        Cell::UInt(header.component_id.into()),
+    ];
+    message.for_each_field(&mut |field, value| match value {
+        FieldValue::CharArray(chars) => {
+            let end = chars.iter().position(|c| *c == 0).unwrap_or(chars.len());
+            cells.push(Cell::Text(
+                String::from_utf8_lossy(&chars[..end]).into_owned(),
+            ));
+        }
+        value => match value.array_len() {
+            Some(length) => cells.extend(
+                (0..length).filter_map(|i| Some(Cell::from_value(field, value.element(i)?, enums))),
+            ),
+            None => cells.push(Cell::from_value(field, value, enums)),
+        },
+    });
+    cells
+}
+
+/// Name of an enum value, the names of the set flags joined with `|` for bitmasks
+pub fn enum_name(schema: &EnumSchema, value: u64) -> String {
+    if let Some(name) = schema.entry_name(value) {
+        return name.to_string();
+    }
+    if !schema.bitmask || value == 0 {
+        return value.to_string();
+    }
+    let mut name = String::new();
+    let mut remaining = value;
+    for entry in schema.entries {
+        if entry.value != 0 && remaining & entry.value == entry.value {
+            remaining &= !entry.value;
+            if !name.is_empty() {
+                name.push('|');
+            }
+            name.push_str(entry.name);
+        }
+    }
+    if remaining != 0 {
+        if !name.is_empty() {
+            name.push('|');
+        }
+        let _ = write!(name, "{remaining}");
+    }
+    name
+}
+
+/// Writes the messages of every type to a CSV file named after the message, like `HEARTBEAT.csv`
+///
+/// Files are created in the directory when the first message of their type is written, replacing existing files.
+pub struct CsvExporter {
+    directory: PathBuf,
+    enums: EnumFormat,
+    tables: HashMap<u32, BufWriter<File>>,
+}
+
+impl CsvExporter {
+    /// Create an exporter writing to `directory`, which is created if it does not exist
+    pub fn new(directory: impl Into<PathBuf>, enums: EnumFormat) -> io::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            enums,
+            tables: HashMap::new(),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Write a row to the table of the message's type
+    pub fn write<M: MessageFields>(
+        &mut self,
+        timestamp: Option<u64>,
+        header: &MavHeader,
+        message: &M,
+    ) -> io::Result<()> {
+        let schema = message.schema();
+        let writer = match self.tables.entry(schema.id) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let path = self.directory.join(format!("{}.csv", schema.name));
+                let mut writer = BufWriter::new(File::create(path)?);
+                let names: Vec<_> = columns(schema, self.enums)
+                    .into_iter()
+                    .map(|column| csv_field(&column.name))
+                    .collect();
+                writeln!(writer, "{}", names.join(","))?;
+                entry.insert(writer)
+            }
+        };
+        let row: Vec<_> = cells(timestamp, header, message, self.enums)
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Null => String::new(),
+                Cell::UInt(value) => value.to_string(),
+                Cell::Int(value) => value.to_string(),
+                Cell::Float32(value) => value.to_string(),
+                Cell::Float(value) => value.to_string(),
+                Cell::Text(value) => csv_field(&value),
+            })
+            .collect();
+        writeln!(writer, "{}", row.join(","))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.tables.values_mut().try_for_each(Write::flush)
+    }
+}
+
+/// Quote a CSV field if it contains separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
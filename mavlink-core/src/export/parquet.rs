@@ -0,0 +1,298 @@
+//! Export to [Arrow](https://arrow.apache.org) record batches and [Parquet](https://parquet.apache.org) files.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    ArrayBuilder, Float32Builder, Float64Builder, Int16Builder, Int32Builder, Int64Builder,
+    Int8Builder, StringBuilder, UInt16Builder, UInt32Builder, UInt64Builder, UInt8Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::errors::Result;
+
+// the versions of the crates in the API of the exporters
+pub use ::parquet;
+pub use {arrow_array, arrow_schema};
+
+use super::{cells, columns, Cell, ColumnType, EnumFormat};
+use crate::schema::{MessageFields, MessageSchema};
+use crate::MavHeader;
+
+/// Number of rows that are collected before they are written to a Parquet file as a record batch
+const BATCH_ROWS: usize = 8192;
+
+/// Collects the rows of a message type into Arrow [`RecordBatch`]es
+pub struct ArrowTableBuilder {
+    message: &'static MessageSchema,
+    enums: EnumFormat,
+    schema: SchemaRef,
+    columns: Vec<ColumnBuilder>,
+}
+
+impl ArrowTableBuilder {
+    pub fn new(message: &'static MessageSchema, enums: EnumFormat) -> Self {
+        let columns = columns(message, enums);
+        let schema = Schema::new(
+            columns
+                .iter()
+                .map(|column| {
+                    Field::new(&column.name, data_type(column.column_type), column.nullable)
+                })
+                .collect::<Vec<_>>(),
+        );
+        Self {
+            message,
+            enums,
+            schema: Arc::new(schema),
+            columns: columns
+                .iter()
+                .map(|column| ColumnBuilder::new(column.column_type))
+                .collect(),
+        }
+    }
+
+    /// Schema of the record batches
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Number of rows appended since the last [`finish`](Self::finish)
+    pub fn len(&self) -> usize {
+        self.columns.first().map_or(0, ColumnBuilder::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append the row of a message, which must be of the builder's message type
+    pub fn append<M: MessageFields>(
+        &mut self,
+        timestamp: Option<u64>,
+        header: &MavHeader,
+        message: &M,
+    ) -> core::result::Result<(), ArrowError> {
+        let schema = message.schema();
+        if schema.id != self.message.id {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "{} message appended to the table of {}",
+                schema.name, self.message.name
+            )));
+        }
+        for (column, cell) in self
+            .columns
+            .iter_mut()
+            .zip(cells(timestamp, header, message, self.enums))
+        {
+            column.append(cell);
+        }
+        Ok(())
+    }
+
+    /// Build a record batch of the appended rows, resetting the builder
+    pub fn finish(&mut self) -> core::result::Result<RecordBatch, ArrowError> {
+        let columns = self.columns.iter_mut().map(ColumnBuilder::finish).collect();
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+}
+
+fn data_type(column_type: ColumnType) -> DataType {
+    match column_type {
+        ColumnType::UInt8 => DataType::UInt8,
+        ColumnType::Int8 => DataType::Int8,
+        ColumnType::UInt16 => DataType::UInt16,
+        ColumnType::Int16 => DataType::Int16,
+        ColumnType::UInt32 => DataType::UInt32,
+        ColumnType::Int32 => DataType::Int32,
+        ColumnType::UInt64 => DataType::UInt64,
+        ColumnType::Int64 => DataType::Int64,
+        ColumnType::Float => DataType::Float32,
+        ColumnType::Double => DataType::Float64,
+        ColumnType::String => DataType::Utf8,
+    }
+}
+
+enum ColumnBuilder {
+    UInt8(UInt8Builder),
+    Int8(Int8Builder),
+    UInt16(UInt16Builder),
+    Int16(Int16Builder),
+    UInt32(UInt32Builder),
+    Int32(Int32Builder),
+    UInt64(UInt64Builder),
+    Int64(Int64Builder),
+    Float(Float32Builder),
+    Double(Float64Builder),
+    String(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::UInt8 => Self::UInt8(UInt8Builder::new()),
+            ColumnType::Int8 => Self::Int8(Int8Builder::new()),
+            ColumnType::UInt16 => Self::UInt16(UInt16Builder::new()),
+            ColumnType::Int16 => Self::Int16(Int16Builder::new()),
+            ColumnType::UInt32 => Self::UInt32(UInt32Builder::new()),
+            ColumnType::Int32 => Self::Int32(Int32Builder::new()),
+            ColumnType::UInt64 => Self::UInt64(UInt64Builder::new()),
+            ColumnType::Int64 => Self::Int64(Int64Builder::new()),
+            ColumnType::Float => Self::Float(Float32Builder::new()),
+            ColumnType::Double => Self::Double(Float64Builder::new()),
+            ColumnType::String => Self::String(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, cell: Cell) {
+        let (unsigned, signed) = match cell {
+            Cell::UInt(value) => (Some(value), i64::try_from(value).ok()),
+            Cell::Int(value) => (u64::try_from(value).ok(), Some(value)),
+            _ => (None, None),
+        };
+        match self {
+            Self::UInt8(builder) => builder.append_option(unsigned.and_then(|v| v.try_into().ok())),
+            Self::Int8(builder) => builder.append_option(signed.and_then(|v| v.try_into().ok())),
+            Self::UInt16(builder) => {
+                builder.append_option(unsigned.and_then(|v| v.try_into().ok()));
+            }
+            Self::Int16(builder) => builder.append_option(signed.and_then(|v| v.try_into().ok())),
+            Self::UInt32(builder) => {
+                builder.append_option(unsigned.and_then(|v| v.try_into().ok()));
+            }
+            Self::Int32(builder) => builder.append_option(signed.and_then(|v| v.try_into().ok())),
+            Self::UInt64(builder) => builder.append_option(unsigned),
+            Self::Int64(builder) => builder.append_option(signed),
+            Self::Float(builder) => builder.append_option(match cell {
+                Cell::Float32(value) => Some(value),
+                _ => None,
+            }),
+            Self::Double(builder) => builder.append_option(match cell {
+                Cell::Float(value) => Some(value),
+                _ => None,
+            }),
+            // enum columns hold numbers for values without an entry
+            Self::String(builder) => match cell {
+                Cell::Null => builder.append_null(),
+                Cell::Text(value) => builder.append_value(value),
+                Cell::UInt(value) => builder.append_value(value.to_string()),
+                Cell::Int(value) => builder.append_value(value.to_string()),
+                Cell::Float32(value) => builder.append_value(value.to_string()),
+                Cell::Float(value) => builder.append_value(value.to_string()),
+            },
+        }
+    }
+
+    fn builder(&mut self) -> &mut dyn ArrayBuilder {
+        match self {
+            Self::UInt8(builder) => builder,
+            Self::Int8(builder) => builder,
+            Self::UInt16(builder) => builder,
+            Self::Int16(builder) => builder,
+            Self::UInt32(builder) => builder,
+            Self::Int32(builder) => builder,
+            Self::UInt64(builder) => builder,
+            Self::Int64(builder) => builder,
+            Self::Float(builder) => builder,
+            Self::Double(builder) => builder,
+            Self::String(builder) => builder,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::UInt8(builder) => builder.len(),
+            Self::Int8(builder) => builder.len(),
+            Self::UInt16(builder) => builder.len(),
+            Self::Int16(builder) => builder.len(),
+            Self::UInt32(builder) => builder.len(),
+            Self::Int32(builder) => builder.len(),
+            Self::UInt64(builder) => builder.len(),
+            Self::Int64(builder) => builder.len(),
+            Self::Float(builder) => builder.len(),
+            Self::Double(builder) => builder.len(),
+            Self::String(builder) => builder.len(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        self.builder().finish()
+    }
+}
+
+struct Table {
+    builder: ArrowTableBuilder,
+    writer: ArrowWriter<File>,
+}
+
+impl Table {
+    fn write_batch(&mut self) -> Result<()> {
+        if !self.builder.is_empty() {
+            self.writer.write(&self.builder.finish()?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the messages of every type to a Parquet file named after the message, like `HEARTBEAT.parquet`
+///
+/// Files are created in the directory when the first message of their type is written, replacing existing files.
+/// The files are only complete after [`finish`](Self::finish).
+pub struct ParquetExporter {
+    directory: PathBuf,
+    enums: EnumFormat,
+    tables: HashMap<u32, Table>,
+}
+
+impl ParquetExporter {
+    /// Create an exporter writing to `directory`, which is created if it does not exist
+    pub fn new(directory: impl Into<PathBuf>, enums: EnumFormat) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            enums,
+            tables: HashMap::new(),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Write a row to the table of the message's type
+    pub fn write<M: MessageFields>(
+        &mut self,
+        timestamp: Option<u64>,
+        header: &MavHeader,
+        message: &M,
+    ) -> Result<()> {
+        let schema = message.schema();
+        let table = match self.tables.entry(schema.id) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let path = self.directory.join(format!("{}.parquet", schema.name));
+                let builder = ArrowTableBuilder::new(schema, self.enums);
+                let writer = ArrowWriter::try_new(File::create(path)?, builder.schema(), None)?;
+                entry.insert(Table { builder, writer })
+            }
+        };
+        table.builder.append(timestamp, header, message)?;
+        if table.builder.len() >= BATCH_ROWS {
+            table.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Write the remaining rows and the footers of the files
+    pub fn finish(self) -> Result<()> {
+        for mut table in self.tables.into_values() {
+            table.write_batch()?;
+            table.writer.close()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! This module implements a push based MAVLink frame parser that does not perform any I/O.
+//!
+//! The readers in the crate root pull bytes from a reader on demand, which does not fit event loops, DMA ring
+//! buffers or packet captures where the data arrives in chunks. A [`FrameParser`] is instead fed with these chunks
+//! and yields the frames found in them as soon as they are complete.
+//!
+//! The frame validation is shared with the blocking, `futures` and embedded readers, so all of them accept and
+//! reject the same frames.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::frame_parser::{FrameParser, ParseEvent};
+//!
+//! let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+//! for chunk in chunks {
+//!     for event in parser.feed(chunk) {
+//!         match event {
+//!             ParseEvent::Frame(frame) => println!("received message {}", frame.message_id()),
+//!             ParseEvent::Discarded(count) => println!("skipped {count} bytes"),
+//!         }
+//!     }
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::{
+    MAVLinkMessageRaw, MAVLinkV1MessageRaw, MAVLinkV2MessageRaw, Message, ReadVersion, SigningData,
+    MAVLINK_IFLAG_SIGNED, MAVLINK_SUPPORTED_IFLAGS, MAV_STX, MAV_STX_V2, MAX_FRAME_SIZE,
+};
+
+/// Outcome of checking bytes that start with a packet start marker
+pub(crate) enum FrameCheck<T> {
+    /// At least this many bytes are required to decide
+    Incomplete(usize),
+    /// The bytes do not form a valid frame, only the packet start marker is skipped
+    Invalid,
+    /// The bytes form a frame of this length with a valid checksum that is not accepted
+    Rejected(usize),
+    /// The bytes start with a valid frame
+    Valid(T),
+}
+
+impl<T> FrameCheck<T> {
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> FrameCheck<U> {
+        match self {
+            Self::Incomplete(len) => FrameCheck::Incomplete(len),
+            Self::Invalid => FrameCheck::Invalid,
+            Self::Rejected(len) => FrameCheck::Rejected(len),
+            Self::Valid(message) => FrameCheck::Valid(f(message)),
+        }
+    }
+}
+
+/// Check whether the bytes start with a valid MAVLink 1 frame
+pub(crate) fn check_v1_frame<M: Message>(bytes: &[u8]) -> FrameCheck<MAVLinkV1MessageRaw> {
+    let header_size = 1 + MAVLinkV1MessageRaw::HEADER_SIZE;
+    if bytes.len() < header_size {
+        return FrameCheck::Incomplete(header_size);
+    }
+    let frame_len = header_size + usize::from(bytes[1]) + 2;
+    if bytes.len() < frame_len {
+        return FrameCheck::Incomplete(frame_len);
+    }
+
+    let mut message = MAVLinkV1MessageRaw::new();
+    message.0[..frame_len].copy_from_slice(&bytes[..frame_len]);
+
+    // an STX byte may appear in the middle of a message, so a failed CRC only invalidates this STX
+    if message.has_valid_crc::<M>() {
+        FrameCheck::Valid(message)
+    } else {
+        FrameCheck::Invalid
+    }
+}
+
+/// Check whether the bytes start with a valid MAVLink 2 frame
+///
+/// If signing data is provided, frames with a valid CRC but an invalid signature are rejected.
+#[allow(unused_variables)]
+pub(crate) fn check_v2_frame<M: Message>(
+    bytes: &[u8],
+    signing_data: Option<&SigningData>,
+) -> FrameCheck<MAVLinkV2MessageRaw> {
+    let header_size = 1 + MAVLinkV2MessageRaw::HEADER_SIZE;
+    if bytes.len() < header_size {
+        return FrameCheck::Incomplete(header_size);
+    }
+
+    let incompat_flags = bytes[2];
+    if incompat_flags & !MAVLINK_SUPPORTED_IFLAGS > 0 {
+        // if there are incompatibility flags set that we do not know discard the message
+        return FrameCheck::Invalid;
+    }
+    let signature_size = if incompat_flags & MAVLINK_IFLAG_SIGNED == 0 {
+        0
+    } else {
+        MAVLinkV2MessageRaw::SIGNATURE_SIZE
+    };
+    let frame_len = header_size + usize::from(bytes[1]) + 2 + signature_size;
+    if bytes.len() < frame_len {
+        return FrameCheck::Incomplete(frame_len);
+    }
+
+    let mut message = MAVLinkV2MessageRaw::new();
+    message.0[..frame_len].copy_from_slice(&bytes[..frame_len]);
+
+    if !message.has_valid_crc::<M>() {
+        return FrameCheck::Invalid;
+    }
+
+    // even if the signature turn out to be invalid the valid crc shows that the received data presents a valid message as opposed to random bytes
+    #[cfg(feature = "signing")]
+    if let Some(signing_data) = signing_data {
+        if !signing_data.verify_signature(&message) {
+            return FrameCheck::Rejected(frame_len);
+        }
+    }
+
+    FrameCheck::Valid(message)
+}
+
+/// Check whether the bytes start with a valid MAVLink 1 or 2 frame
+///
+/// With signing data that does not allow unsigned messages, MAVLink 1 frames are rejected.
+#[allow(unused_variables)]
+pub(crate) fn check_any_frame<M: Message>(
+    bytes: &[u8],
+    signing_data: Option<&SigningData>,
+) -> FrameCheck<MAVLinkMessageRaw> {
+    match bytes.first() {
+        Some(&MAV_STX) => match check_v1_frame::<M>(bytes) {
+            #[cfg(feature = "signing")]
+            FrameCheck::Valid(message)
+                if signing_data.is_some_and(|signing| !signing.config.allow_unsigned) =>
+            {
+                FrameCheck::Rejected(message.raw_bytes().len())
+            }
+            check => check.map(MAVLinkMessageRaw::V1),
+        },
+        Some(&MAV_STX_V2) => check_v2_frame::<M>(bytes, signing_data).map(MAVLinkMessageRaw::V2),
+        Some(_) => FrameCheck::Invalid,
+        None => FrameCheck::Incomplete(1),
+    }
+}
+
+/// Event produced while feeding data to a [`FrameParser`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// the frame is not boxed, as the parser must work without an allocator
+#[allow(clippy::large_enum_variant)]
+pub enum ParseEvent {
+    /// A complete frame with a valid checksum, and a valid signature when signing data is used
+    Frame(MAVLinkMessageRaw),
+    /// Number of bytes that were skipped because they are not part of an accepted frame
+    Discarded(usize),
+}
+
+/// Push based MAVLink frame parser
+///
+/// Data is pushed into the parser in chunks of any size using [`FrameParser::feed`], which returns an iterator over
+/// the frames completed by that chunk. Bytes of a frame that is not yet complete are kept in an internal buffer
+/// of [`MAX_FRAME_SIZE`] bytes until the next chunk arrives, so the parser does not allocate.
+///
+/// Like the peeking readers the parser backtracks when a frame has an invalid CRC, so a start marker that is part of
+/// the payload of a corrupted frame does not cause the following frame to be lost.
+pub struct FrameParser<M> {
+    buffer: [u8; MAX_FRAME_SIZE],
+    // Number of buffered bytes, these start with a packet start marker unless the parser just backtracked.
+    len: usize,
+    version: ReadVersion,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<M: Message> FrameParser<M> {
+    /// Create a parser accepting frames of the given version(s)
+    pub const fn new(version: ReadVersion) -> Self {
+        Self {
+            buffer: [0; MAX_FRAME_SIZE],
+            len: 0,
+            version,
+            _message: PhantomData,
+        }
+    }
+
+    /// Returns the version(s) of the frames accepted by the parser
+    pub fn version(&self) -> ReadVersion {
+        self.version
+    }
+
+    /// Set the version(s) of the frames accepted by the parser
+    pub fn set_version(&mut self, version: ReadVersion) {
+        self.version = version;
+    }
+
+    /// Number of bytes held back waiting for the rest of a frame
+    pub fn buffered(&self) -> usize {
+        self.len
+    }
+
+    /// Discard the bytes held back waiting for the rest of a frame, returning their number
+    ///
+    /// This is useful when the data source is known to have lost data, e.g. when a DMA buffer overflowed.
+    pub fn reset(&mut self) -> usize {
+        core::mem::take(&mut self.len)
+    }
+
+    /// Push a chunk of data into the parser
+    ///
+    /// The returned iterator yields the frames completed by the data and reports bytes that were skipped.
+    /// The data is only processed while iterating, data that was not processed when the iterator is
+    /// dropped is lost.
+    pub fn feed<'a>(&'a mut self, data: &'a [u8]) -> Frames<'a, M> {
+        Frames {
+            parser: self,
+            input: data,
+            discarded: 0,
+            pending: None,
+            signing_data: None,
+        }
+    }
+
+    /// Push a chunk of data into the parser with signing support
+    ///
+    /// Like [`FrameParser::feed`], but frames with an invalid signature are discarded.
+    /// This respects the `allow_unsigned` parameter in [`SigningConfig`](crate::SigningConfig), which also applies
+    /// to MAVLink 1 frames when reading [`ReadVersion::Any`].
+    /// When using [`ReadVersion::Single`]`(`[`MavlinkVersion::V1`](crate::MavlinkVersion::V1)`)` signing is ignored.
+    #[cfg(feature = "signing")]
+    pub fn feed_signed<'a>(
+        &'a mut self,
+        data: &'a [u8],
+        signing_data: Option<&'a SigningData>,
+    ) -> Frames<'a, M> {
+        let signing_data =
+            signing_data.filter(|_| self.version != ReadVersion::Single(crate::MavlinkVersion::V1));
+        Frames {
+            parser: self,
+            input: data,
+            discarded: 0,
+            pending: None,
+            signing_data,
+        }
+    }
+
+    // remove bytes from the start of the buffer
+    fn drop_front(&mut self, count: usize) {
+        self.buffer.copy_within(count..self.len, 0);
+        self.len -= count;
+    }
+}
+
+/// Iterator over the [`ParseEvent`]s produced by a chunk of data, see [`FrameParser::feed`]
+pub struct Frames<'a, M> {
+    parser: &'a mut FrameParser<M>,
+    input: &'a [u8],
+    discarded: usize,
+    // frame found after discarded bytes, returned after reporting these
+    pending: Option<MAVLinkMessageRaw>,
+    signing_data: Option<&'a SigningData>,
+}
+
+impl<M: Message> Frames<'_, M> {
+    fn take_discarded(&mut self) -> Option<ParseEvent> {
+        match core::mem::take(&mut self.discarded) {
+            0 => None,
+            count => Some(ParseEvent::Discarded(count)),
+        }
+    }
+}
+
+impl<M: Message> Iterator for Frames<'_, M> {
+    type Item = ParseEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(message) = self.pending.take() {
+            return Some(ParseEvent::Frame(message));
+        }
+        loop {
+            let parser = &mut *self.parser;
+            if parser.len == 0 {
+                // skip data that can not start a frame without buffering it
+                let skip = self
+                    .input
+                    .iter()
+                    .position(|byte| parser.version.accepts_stx(*byte))
+                    .unwrap_or(self.input.len());
+                self.discarded += skip;
+                self.input = &self.input[skip..];
+                let Some((&stx, rest)) = self.input.split_first() else {
+                    return self.take_discarded();
+                };
+                parser.buffer[0] = stx;
+                parser.len = 1;
+                self.input = rest;
+            } else if !parser.version.accepts_stx(parser.buffer[0]) {
+                // after backtracking the buffer may start with bytes that are not part of a frame
+                let skip = parser.buffer[..parser.len]
+                    .iter()
+                    .position(|byte| parser.version.accepts_stx(*byte))
+                    .unwrap_or(parser.len);
+                parser.drop_front(skip);
+                self.discarded += skip;
+                continue;
+            }
+
+            let parser = &mut *self.parser;
+            // the buffer starts with a start marker of an accepted version
+            let check = check_any_frame::<M>(&parser.buffer[..parser.len], self.signing_data);
+
+            match check {
+                FrameCheck::Incomplete(len) => {
+                    let count = (len - parser.len).min(self.input.len());
+                    if count == 0 {
+                        return self.take_discarded();
+                    }
+                    parser.buffer[parser.len..parser.len + count]
+                        .copy_from_slice(&self.input[..count]);
+                    parser.len += count;
+                    self.input = &self.input[count..];
+                }
+                FrameCheck::Invalid => {
+                    parser.drop_front(1);
+                    self.discarded += 1;
+                }
+                FrameCheck::Rejected(len) => {
+                    parser.drop_front(len);
+                    self.discarded += len;
+                }
+                FrameCheck::Valid(message) => {
+                    parser.drop_front(message.raw_bytes().len());
+                    // report skipped bytes before the frame following them
+                    if let Some(event) = self.take_discarded() {
+                        self.pending = Some(message);
+                        return Some(event);
+                    }
+                    return Some(ParseEvent::Frame(message));
+                }
+            }
+        }
+    }
+}
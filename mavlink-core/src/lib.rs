@@ -39,6 +39,16 @@ use serde::{Deserialize, Serialize};
 pub mod peek_reader;
 use peek_reader::PeekReader;
 
+pub mod schema;
+
+pub mod frame_parser;
+use frame_parser::{check_any_frame, check_v1_frame, check_v2_frame, FrameCheck};
+
+#[cfg(feature = "tokio-1")]
+pub mod codec;
+#[cfg(feature = "tokio-1")]
+pub use codec::{MavlinkCodec, RawMavlinkCodec};
+
 use crate::{
     bytes::Bytes,
     error::{MessageWriteError, ParserError},
@@ -54,22 +64,35 @@ pub mod bytes_mut;
 mod connection;
 pub mod error;
 #[cfg(feature = "std")]
-pub use self::connection::{connect, Connectable, MavConnection};
+pub use self::connection::{
+    connect, Connectable, HalvesConnection, MavConnection, MavReceiveHalf, MavSendHalf,
+};
 
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 mod async_connection;
 #[cfg(feature = "tokio-1")]
-pub use self::async_connection::{connect_async, AsyncConnectable, AsyncMavConnection};
+pub use self::async_connection::{
+    connect_async, AsyncConnectable, AsyncMemoryConnection, AsyncReplayConnection,
+    AsyncReplayReceiveHalf, AsyncReplaySendHalf,
+};
+#[cfg(feature = "futures-io")]
+pub use self::async_connection::{
+    AsyncHalvesConnection, AsyncMavConnection, AsyncMavReceiveHalf, AsyncMavSendHalf,
+    AsyncRecordingConnection, AsyncRecordingReceiveHalf, AsyncRecordingSendHalf,
+    AsyncStreamConnection, MavFrameSink, MavFrameStream,
+};
 
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 pub mod async_peek_reader;
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 use async_peek_reader::AsyncPeekReader;
-#[cfg(feature = "tokio-1")]
-use tokio::io::{AsyncWrite, AsyncWriteExt};
+#[cfg(feature = "futures-io")]
+use futures::{AsyncWrite, AsyncWriteExt};
 
 #[cfg(any(feature = "embedded", feature = "embedded-hal-02"))]
 pub mod embedded;
+#[cfg(feature = "embedded")]
+pub use embedded::{AsyncEmbeddedConnection, AsyncEmbeddedMavConnection};
 #[cfg(any(feature = "embedded", feature = "embedded-hal-02"))]
 use embedded::{Read, Write};
 
@@ -78,17 +101,32 @@ type SigningData = ();
 #[cfg(feature = "signing")]
 mod signing;
 #[cfg(feature = "signing")]
-pub use self::signing::{SigningConfig, SigningData};
+pub use self::signing::{signing_timestamp, SigningConfig, SigningData};
 #[cfg(feature = "signing")]
 use sha2::{Digest, Sha256};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 
-#[cfg(any(feature = "std", feature = "tokio-1"))]
+#[cfg(any(feature = "std", feature = "futures-io"))]
 mod connectable;
 
-#[cfg(any(feature = "std", feature = "tokio-1"))]
+#[cfg(feature = "std")]
+pub mod statustext;
+
+#[cfg(feature = "std")]
+pub mod tlog;
+
+#[cfg(feature = "pcap")]
+pub mod pcap;
+
+#[cfg(feature = "std")]
+pub mod export;
+
+#[cfg(feature = "onboard-log")]
+pub mod onboard_log;
+
+#[cfg(any(feature = "std", feature = "futures-io"))]
 pub use connectable::ConnectionAddress;
 
 #[cfg(feature = "direct-serial")]
@@ -100,14 +138,42 @@ pub use connection::tcp::config::{TcpConfig, TcpMode};
 #[cfg(feature = "udp")]
 pub use connection::udp::config::{UdpConfig, UdpMode};
 
+#[cfg(all(feature = "unix", unix))]
+pub use connection::unix::config::{UnixConfig, UnixMode};
+
+#[cfg(feature = "websocket")]
+pub use connection::websocket::config::{WebSocketConfig, WebSocketMode};
+
+#[cfg(feature = "std")]
+pub use connection::file::config::{FileConfig, FileFormat, FileMode};
+
 #[cfg(feature = "std")]
-pub use connection::file::config::FileConfig;
+pub use connection::impairment::{
+    GilbertElliott, ImpairedConnection, ImpairedReceiveHalf, ImpairedSendHalf, ImpairmentStats,
+    Impairments,
+};
+
+#[cfg(feature = "std")]
+pub use connection::memory::{
+    config::MemoryConfig, MemoryConnection, MemoryReceiveHalf, MemorySendHalf,
+};
+
+#[cfg(feature = "std")]
+pub use connection::recording::{RecordingConnection, RecordingReceiveHalf, RecordingSendHalf};
+
+#[cfg(feature = "std")]
+pub use connection::replay::{
+    config::ReplayConfig, ReplayConnection, ReplayControl, ReplayReceiveHalf, ReplaySendHalf,
+};
 
 /// Maximum size of any MAVLink frame in bytes.
 ///
 /// This is a v2 frame with maximum payload size and a signature: <https://mavlink.io/en/guide/serialization.html>
 pub const MAX_FRAME_SIZE: usize = 280;
 
+/// Maximum size of a MAVLink message payload in bytes.
+pub const MAX_PAYLOAD_LEN: usize = 255;
+
 /// A MAVLink message payload
 ///
 /// Each message sets `MavMessage` enum implements this trait. The [`Message`] trait is used to
@@ -161,6 +227,33 @@ pub trait MessageData: Sized {
     fn deser(version: MavlinkVersion, payload: &[u8]) -> Result<Self, ParserError>;
 }
 
+/// Borrowed view of the payload of a message
+///
+/// With the `message-views` feature of the `mavlink` crate a view is generated for every message, e.g. `HeartbeatView`
+/// for `HEARTBEAT_DATA`. Its accessors decode single fields directly from the payload, so routing and filtering does
+/// not copy the whole message.
+/// Bytes that are missing from the payload, because MAVLink 2 truncates trailing zeros, are read as zero.
+pub trait MessageView<'a>: Sized + Copy {
+    type Data: MessageData;
+
+    /// Create a view of the payload of a message, the payload is not validated
+    fn new(payload: &'a [u8]) -> Self;
+
+    /// Returns the payload the view refers to
+    fn payload_bytes(&self) -> &'a [u8];
+
+    /// Create a view of the payload of a raw message, if the message has the id of the view
+    fn from_raw(message: &'a MAVLinkMessageRaw) -> Option<Self> {
+        (message.message_id() == Self::Data::ID).then(|| Self::new(message.payload()))
+    }
+
+    /// Decode all fields into the owned message data
+    fn to_data(&self) -> Result<Self::Data, ParserError> {
+        // only MAVLink 2 payloads can be truncated, so this also decodes MAVLink 1 payloads
+        Self::Data::deser(MavlinkVersion::V2, self.payload_bytes())
+    }
+}
+
 /// Metadata from a MAVLink packet header
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -221,70 +314,106 @@ pub struct MavFrame<M: Message> {
 
 impl<M: Message> MavFrame<M> {
     /// Serialize MavFrame into a byte slice, so it can be sent over a socket, for example.
-    /// The resulting buffer will start with the sequence field of the MAVLink frame
-    /// and will not include the initial packet marker, length field, and flags.
-    pub fn ser(&self, buf: &mut [u8]) -> usize {
-        let mut buf = bytes_mut::BytesMut::new(buf);
-
-        // serialize message
-        let mut payload_buf = [0u8; 255];
-        let payload_len = self.msg.ser(self.protocol_version, &mut payload_buf);
-
-        // Currently expects a buffer with the sequence field at the start.
-        // If this is updated to include the initial packet marker, length field, and flags,
-        // uncomment.
-        //
-        // match self.protocol_version {
-        //     MavlinkVersion::V2 => {
-        //         buf.put_u8(MAV_STX_V2);
-        //         buf.put_u8(payload_len as u8);
-        //         but.put_u8(0); // incompatibility flags
-        //         buf.put_u8(0); // compatibility flags
-        //     }
-        //     MavlinkVersion::V1 => {
-        //         buf.put_u8(MAV_STX);
-        //         buf.put_u8(payload_len as u8);
-        //     }
-        // }
-
-        // serialize header
-        buf.put_u8(self.header.sequence);
-        buf.put_u8(self.header.system_id);
-        buf.put_u8(self.header.component_id);
-
-        // message id
+    ///
+    /// The resulting buffer contains the complete MAVLink frame, from the packet start marker to the checksum,
+    /// exactly as [`write_versioned_msg`] would write it. Returns the length of the frame, or
+    /// [`MessageWriteError::BufferTooSmall`] if the buffer is shorter than the frame. [`MAX_FRAME_SIZE`] bytes are
+    /// always sufficient.
+    pub fn ser(&self, buf: &mut [u8]) -> Result<usize, MessageWriteError> {
         match self.protocol_version {
             MavlinkVersion::V2 => {
-                let bytes: [u8; 4] = self.msg.message_id().to_le_bytes();
-                buf.put_slice(&bytes[..3]);
+                let mut message_raw = MAVLinkV2MessageRaw::new();
+                message_raw.serialize_message(self.header, &self.msg);
+                Self::copy_frame(message_raw.raw_bytes(), buf)
             }
-            MavlinkVersion::V1 => {
-                buf.put_u8(self.msg.message_id() as u8); //TODO check
+            MavlinkVersion::V1 => self.ser_v1(buf),
+        }
+    }
+
+    /// Serialize MavFrame into a byte slice with signing support.
+    ///
+    /// This produces the same bytes as [`write_versioned_msg_signed`], when using [`MavlinkVersion::V1`] signing is ignored.
+    #[cfg(feature = "signing")]
+    pub fn ser_signed(
+        &self,
+        buf: &mut [u8],
+        signing_data: Option<&SigningData>,
+    ) -> Result<usize, MessageWriteError> {
+        match self.protocol_version {
+            MavlinkVersion::V2 => {
+                let mut message_raw = MAVLinkV2MessageRaw::new();
+                match signing_data {
+                    Some(signing_data) if signing_data.config.sign_outgoing => {
+                        message_raw.serialize_message_for_signing(self.header, &self.msg);
+                        signing_data.sign_message(&mut message_raw);
+                    }
+                    _ => message_raw.serialize_message(self.header, &self.msg),
+                }
+                Self::copy_frame(message_raw.raw_bytes(), buf)
             }
+            MavlinkVersion::V1 => self.ser_v1(buf),
         }
+    }
 
-        buf.put_slice(&payload_buf[..payload_len]);
-        buf.len()
+    fn ser_v1(&self, buf: &mut [u8]) -> Result<usize, MessageWriteError> {
+        if self.msg.message_id() > u8::MAX.into() {
+            return Err(MessageWriteError::MAVLink2Only);
+        }
+        let mut message_raw = MAVLinkV1MessageRaw::new();
+        message_raw.serialize_message(self.header, &self.msg);
+        Self::copy_frame(message_raw.raw_bytes(), buf)
+    }
+
+    fn copy_frame(frame: &[u8], buf: &mut [u8]) -> Result<usize, MessageWriteError> {
+        let Some(buf) = buf.get_mut(..frame.len()) else {
+            return Err(MessageWriteError::BufferTooSmall {
+                expected: frame.len(),
+                actual: buf.len(),
+            });
+        };
+        buf.copy_from_slice(frame);
+        Ok(frame.len())
     }
 
     /// Deserialize MavFrame from a slice that has been received from, for example, a socket.
-    /// The input buffer should start with the sequence field of the MAVLink frame. The
-    /// initial packet marker, length field, and flag fields should be excluded.
+    ///
+    /// The input must contain exactly one complete MAVLink frame of the given version, from the packet start marker
+    /// to the checksum and, if the frame is signed, the signature. The signature is not verified,
+    /// use [`MavFrame::deser_signed`] for that.
     pub fn deser(version: MavlinkVersion, input: &[u8]) -> Result<Self, ParserError> {
+        let (stx, header_size) = match version {
+            MavlinkVersion::V2 => (MAV_STX_V2, 1 + MAVLinkV2MessageRaw::HEADER_SIZE),
+            MavlinkVersion::V1 => (MAV_STX, 1 + MAVLinkV1MessageRaw::HEADER_SIZE),
+        };
         let mut buf = Bytes::new(input);
 
-        // Currently expects a buffer with the sequence field at the start.
-        // If this is updated to include the initial packet marker, length field, and flags,
-        // uncomment.
-        // <https://mavlink.io/en/guide/serialization.html#mavlink2_packet_format>
-        // match version {
-        //     MavlinkVersion::V2 => buf.get_u32_le(),
-        //     MavlinkVersion::V1 => buf.get_u16_le().into(),
-        // };
-
-        let sequence = buf.get_u8();
-        let system_id = buf.get_u8();
-        let component_id = buf.get_u8();
+        let magic = buf.get_u8()?;
+        if magic != stx {
+            return Err(ParserError::InvalidStx {
+                expected: stx,
+                actual: magic,
+            });
+        }
+        let payload_len: usize = buf.get_u8()?.into();
+
+        let mut signature_len = 0;
+        if version == MavlinkVersion::V2 {
+            let incompat_flags = buf.get_u8()?;
+            if incompat_flags & !MAVLINK_SUPPORTED_IFLAGS != 0 {
+                return Err(ParserError::InvalidFlag {
+                    flag_type: "incompat_flags",
+                    value: incompat_flags.into(),
+                });
+            }
+            if incompat_flags & MAVLINK_IFLAG_SIGNED != 0 {
+                signature_len = MAVLinkV2MessageRaw::SIGNATURE_SIZE;
+            }
+            let _compat_flags = buf.get_u8()?;
+        }
+
+        let sequence = buf.get_u8()?;
+        let system_id = buf.get_u8()?;
+        let component_id = buf.get_u8()?;
         let header = MavHeader {
             system_id,
             component_id,
@@ -292,17 +421,59 @@ impl<M: Message> MavFrame<M> {
         };
 
         let msg_id = match version {
-            MavlinkVersion::V2 => buf.get_u24_le(),
-            MavlinkVersion::V1 => buf.get_u8().into(),
+            MavlinkVersion::V2 => buf.get_u24_le()?,
+            MavlinkVersion::V1 => buf.get_u8()?.into(),
         };
 
-        M::parse(version, msg_id, buf.remaining_bytes()).map(|msg| Self {
+        let frame_len = header_size + payload_len + 2 + signature_len;
+        if input.len() != frame_len {
+            return Err(ParserError::InvalidLength {
+                expected: frame_len,
+                actual: input.len(),
+            });
+        }
+
+        let payload = &input[header_size..(header_size + payload_len)];
+        let checksum = u16::from_le_bytes([
+            input[header_size + payload_len],
+            input[header_size + payload_len + 1],
+        ]);
+        let crc = calculate_crc(&input[1..(header_size + payload_len)], M::extra_crc(msg_id));
+        if checksum != crc {
+            return Err(ParserError::InvalidChecksum {
+                expected: crc,
+                actual: checksum,
+            });
+        }
+
+        M::parse(version, msg_id, payload).map(|msg| Self {
             header,
             msg,
             protocol_version: version,
         })
     }
 
+    /// Deserialize MavFrame from a slice with signing support.
+    ///
+    /// Like [`MavFrame::deser`], but verifies the signature of the frame if signing data is provided.
+    /// Like the readers of a single version, signing is ignored for MAVLink 1 frames.
+    #[cfg(feature = "signing")]
+    pub fn deser_signed(
+        version: MavlinkVersion,
+        input: &[u8],
+        signing_data: Option<&SigningData>,
+    ) -> Result<Self, ParserError> {
+        let frame = Self::deser(version, input)?;
+        if let (MavlinkVersion::V2, Some(signing_data)) = (version, signing_data) {
+            let mut message_raw = MAVLinkV2MessageRaw::new();
+            message_raw.0[..input.len()].copy_from_slice(input);
+            if !signing_data.verify_signature(&message_raw) {
+                return Err(ParserError::InvalidSignature);
+            }
+        }
+        Ok(frame)
+    }
+
     /// Return the frame header
     pub fn header(&self) -> MavHeader {
         self.header
@@ -328,20 +499,21 @@ pub enum ReadVersion {
 }
 
 impl ReadVersion {
-    #[cfg(feature = "std")]
-    fn from_conn_cfg<C: MavConnection<M>, M: Message>(conn: &C) -> Self {
-        if conn.allow_recv_any_version() {
-            Self::Any
-        } else {
-            conn.protocol_version().into()
+    /// Whether the byte is the packet start marker of an accepted version
+    pub(crate) fn accepts_stx(self, byte: u8) -> bool {
+        match self {
+            Self::Single(MavlinkVersion::V1) => byte == MAV_STX,
+            Self::Single(MavlinkVersion::V2) => byte == MAV_STX_V2,
+            Self::Any => byte == MAV_STX || byte == MAV_STX_V2,
         }
     }
-    #[cfg(feature = "tokio-1")]
-    fn from_async_conn_cfg<C: AsyncMavConnection<M>, M: Message + Sync + Send>(conn: &C) -> Self {
-        if conn.allow_recv_any_version() {
+
+    #[cfg(feature = "std")]
+    fn from_half_cfg(protocol_version: MavlinkVersion, recv_any_version: bool) -> Self {
+        if recv_any_version {
             Self::Any
         } else {
-            conn.protocol_version().into()
+            protocol_version.into()
         }
     }
 }
@@ -381,8 +553,8 @@ pub fn read_raw_versioned_msg<M: Message, R: Read>(
 }
 
 /// Asynchronously read and parse a MAVLink message of the specified version from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_versioned_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_versioned_msg_async<M: Message, R: futures::AsyncRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
@@ -394,8 +566,8 @@ pub async fn read_versioned_msg_async<M: Message, R: tokio::io::AsyncRead + Unpi
 }
 
 /// Asynchronously read and parse a MAVLinkMessageRaw of the specified version from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_raw_versioned_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_raw_versioned_msg_async<M: Message, R: futures::AsyncRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
 ) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
@@ -452,8 +624,8 @@ pub fn read_versioned_msg_signed<M: Message, R: Read>(
 ///
 /// When using [`ReadVersion::Single`]`(`[`MavlinkVersion::V1`]`)` signing is ignored.
 /// When using [`ReadVersion::Any`] MAVlink 1 messages are treated as unsigned.
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_raw_versioned_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(feature = "futures-io", feature = "signing"))]
+pub async fn read_raw_versioned_msg_async_signed<M: Message, R: futures::AsyncRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
     signing_data: Option<&SigningData>,
@@ -473,8 +645,8 @@ pub async fn read_raw_versioned_msg_async_signed<M: Message, R: tokio::io::Async
 ///
 /// When using [`ReadVersion::Single`]`(`[`MavlinkVersion::V1`]`)` signing is ignored.
 /// When using [`ReadVersion::Any`] MAVlink 1 messages are treated as unsigned.
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_versioned_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(feature = "futures-io", feature = "signing"))]
+pub async fn read_versioned_msg_async_signed<M: Message, R: futures::AsyncRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
     signing_data: Option<&SigningData>,
@@ -486,6 +658,74 @@ pub async fn read_versioned_msg_async_signed<M: Message, R: tokio::io::AsyncRead
     }
 }
 
+/// Asynchronously read and parse a MAVLink message of the specified version from a [`embedded_io_async::Read`]er.
+#[cfg(feature = "embedded")]
+pub async fn read_versioned_msg_async<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+    version: ReadVersion,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    match version {
+        ReadVersion::Single(MavlinkVersion::V2) => read_v2_msg_async(r).await,
+        ReadVersion::Single(MavlinkVersion::V1) => read_v1_msg_async(r).await,
+        ReadVersion::Any => read_any_msg_async(r).await,
+    }
+}
+
+/// Asynchronously read a MAVLinkMessageRaw of the specified version from a [`embedded_io_async::Read`]er.
+#[cfg(feature = "embedded")]
+pub async fn read_raw_versioned_msg_async<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+    version: ReadVersion,
+) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
+    match version {
+        ReadVersion::Single(MavlinkVersion::V2) => Ok(MAVLinkMessageRaw::V2(
+            read_v2_raw_message_async::<M>(r).await?,
+        )),
+        ReadVersion::Single(MavlinkVersion::V1) => Ok(MAVLinkMessageRaw::V1(
+            read_v1_raw_message_async::<M>(r).await?,
+        )),
+        ReadVersion::Any => read_any_raw_message_async::<M>(r).await,
+    }
+}
+
+/// Asynchronously read a MAVLinkMessageRaw of the specified version from a [`embedded_io_async::Read`]er with signing support.
+///
+/// When using [`ReadVersion::Single`]`(`[`MavlinkVersion::V1`]`)` signing is ignored.
+/// When using [`ReadVersion::Any`] MAVlink 1 messages are treated as unsigned.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn read_raw_versioned_msg_async_signed<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+    version: ReadVersion,
+    signing_data: Option<&SigningData>,
+) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
+    match version {
+        ReadVersion::Single(MavlinkVersion::V2) => Ok(MAVLinkMessageRaw::V2(
+            read_v2_raw_message_async_inner::<M>(r, signing_data).await?,
+        )),
+        ReadVersion::Single(MavlinkVersion::V1) => Ok(MAVLinkMessageRaw::V1(
+            read_v1_raw_message_async::<M>(r).await?,
+        )),
+        ReadVersion::Any => read_any_raw_message_async_inner::<M>(r, signing_data).await,
+    }
+}
+
+/// Asynchronously read and parse a MAVLink message of the specified version from a [`embedded_io_async::Read`]er with signing support.
+///
+/// When using [`ReadVersion::Single`]`(`[`MavlinkVersion::V1`]`)` signing is ignored.
+/// When using [`ReadVersion::Any`] MAVlink 1 messages are treated as unsigned.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn read_versioned_msg_async_signed<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+    version: ReadVersion,
+    signing_data: Option<&SigningData>,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    match version {
+        ReadVersion::Single(MavlinkVersion::V2) => read_v2_msg_async_inner(r, signing_data).await,
+        ReadVersion::Single(MavlinkVersion::V1) => read_v1_msg_async(r).await,
+        ReadVersion::Any => read_any_msg_async_inner(r, signing_data).await,
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// Byte buffer containing the raw representation of a MAVLink 1 message beginning with the STX marker.
 ///
@@ -591,12 +831,6 @@ impl MAVLinkV1MessageRaw {
         ])
     }
 
-    #[inline]
-    fn mut_payload_and_checksum(&mut self) -> &mut [u8] {
-        let payload_length: usize = self.payload_length().into();
-        &mut self.0[(1 + Self::HEADER_SIZE)..(1 + Self::HEADER_SIZE + payload_length + 2)]
-    }
-
     /// Checks wether the message’s [CRC-16 checksum](https://mavlink.io/en/guide/serialization.html#checksum) calculation matches its checksum field.
     #[inline]
     pub fn has_valid_crc<M: Message>(&self) -> bool {
@@ -676,53 +910,70 @@ impl MAVLinkV1MessageRaw {
 fn try_decode_v1<M: Message, R: Read>(
     reader: &mut PeekReader<R>,
 ) -> Result<Option<MAVLinkV1MessageRaw>, error::MessageReadError> {
-    let mut message = MAVLinkV1MessageRaw::new();
-    let whole_header_size = MAVLinkV1MessageRaw::HEADER_SIZE + 1;
-
-    message.0[0] = MAV_STX;
-    let header = &reader.peek_exact(whole_header_size)?[1..whole_header_size];
-    message.mut_header().copy_from_slice(header);
-    let packet_length = message.raw_bytes().len();
-    let payload_and_checksum = &reader.peek_exact(packet_length)?[whole_header_size..packet_length];
-    message
-        .mut_payload_and_checksum()
-        .copy_from_slice(payload_and_checksum);
-
-    // retry if CRC failed after previous STX
-    // (an STX byte may appear in the middle of a message)
-    if message.has_valid_crc::<M>() {
-        reader.consume(message.raw_bytes().len());
-        Ok(Some(message))
-    } else {
-        Ok(None)
+    try_decode(reader, check_v1_frame::<M>)
+}
+
+/// Peek increasingly more bytes until `check` can decide on the frame starting at the current position.
+///
+/// A valid or rejected frame is consumed, for an invalid frame only the packet start marker is consumed.
+fn try_decode<T, R: Read>(
+    reader: &mut PeekReader<R>,
+    check: impl Fn(&[u8]) -> FrameCheck<T>,
+) -> Result<Option<T>, error::MessageReadError> {
+    let mut len = 1;
+    loop {
+        match check(reader.peek_exact(len)?) {
+            FrameCheck::Incomplete(required) => len = required,
+            FrameCheck::Valid(message) => {
+                reader.consume(len);
+                return Ok(Some(message));
+            }
+            FrameCheck::Rejected(frame_len) => {
+                reader.consume(frame_len);
+                return Ok(None);
+            }
+            // retry if CRC failed after previous STX
+            // (an STX byte may appear in the middle of a message)
+            FrameCheck::Invalid => {
+                reader.consume(1);
+                return Ok(None);
+            }
+        }
     }
 }
 
-#[cfg(feature = "tokio-1")]
-// other then the blocking version the STX is read not peeked, this changed some sizes
-async fn try_decode_v1_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+async fn try_decode_v1_async<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<Option<MAVLinkV1MessageRaw>, error::MessageReadError> {
-    let mut message = MAVLinkV1MessageRaw::new();
-
-    message.0[0] = MAV_STX;
-    let header = &reader.peek_exact(MAVLinkV1MessageRaw::HEADER_SIZE).await?
-        [..MAVLinkV1MessageRaw::HEADER_SIZE];
-    message.mut_header().copy_from_slice(header);
-    let packet_length = message.raw_bytes().len() - 1;
-    let payload_and_checksum =
-        &reader.peek_exact(packet_length).await?[MAVLinkV1MessageRaw::HEADER_SIZE..packet_length];
-    message
-        .mut_payload_and_checksum()
-        .copy_from_slice(payload_and_checksum);
-
-    // retry if CRC failed after previous STX
-    // (an STX byte may appear in the middle of a message)
-    if message.has_valid_crc::<M>() {
-        reader.consume(message.raw_bytes().len() - 1);
-        Ok(Some(message))
-    } else {
-        Ok(None)
+    try_decode_async(reader, check_v1_frame::<M>).await
+}
+
+/// Asynchronous version of [`try_decode`]
+#[cfg(feature = "futures-io")]
+async fn try_decode_async<T, R: futures::AsyncRead + Unpin>(
+    reader: &mut AsyncPeekReader<R>,
+    check: impl Fn(&[u8]) -> FrameCheck<T>,
+) -> Result<Option<T>, error::MessageReadError> {
+    let mut len = 1;
+    loop {
+        match check(reader.peek_exact(len).await?) {
+            FrameCheck::Incomplete(required) => len = required,
+            FrameCheck::Valid(message) => {
+                reader.consume(len);
+                return Ok(Some(message));
+            }
+            FrameCheck::Rejected(frame_len) => {
+                reader.consume(frame_len);
+                return Ok(None);
+            }
+            // retry if CRC failed after previous STX
+            // (an STX byte may appear in the middle of a message)
+            FrameCheck::Invalid => {
+                reader.consume(1);
+                return Ok(None);
+            }
+        }
     }
 }
 
@@ -739,22 +990,18 @@ pub fn read_v1_raw_message<M: Message, R: Read>(
         if let Some(msg) = try_decode_v1::<M, _>(reader)? {
             return Ok(msg);
         }
-
-        reader.consume(1);
     }
 }
 
 /// Asynchronously read a raw MAVLink 1 message from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_v1_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_v1_raw_message_async<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<MAVLinkV1MessageRaw, error::MessageReadError> {
     loop {
-        loop {
-            // search for the magic framing value indicating start of mavlink message
-            if reader.read_u8().await? == MAV_STX {
-                break;
-            }
+        // search for the magic framing value indicating start of mavlink message
+        while reader.peek_exact(1).await?[0] != MAV_STX {
+            reader.consume(1);
         }
 
         if let Some(message) = try_decode_v1_async::<M, _>(reader).await? {
@@ -774,37 +1021,63 @@ pub async fn read_v1_raw_message_async<M: Message>(
 ) -> Result<MAVLinkV1MessageRaw, error::MessageReadError> {
     loop {
         // search for the magic framing value indicating start of mavlink message
-        let mut byte = [0u8];
-        loop {
-            reader
-                .read_exact(&mut byte)
-                .await
-                .map_err(|_| error::MessageReadError::Io)?;
-            if byte[0] == MAV_STX {
-                break;
-            }
-        }
+        while read_u8_async(reader).await? != MAV_STX {}
 
-        let mut message = MAVLinkV1MessageRaw::new();
-
-        message.0[0] = MAV_STX;
-        reader
-            .read_exact(message.mut_header())
-            .await
-            .map_err(|_| error::MessageReadError::Io)?;
-        reader
-            .read_exact(message.mut_payload_and_checksum())
-            .await
-            .map_err(|_| error::MessageReadError::Io)?;
-
-        // retry if CRC failed after previous STX
-        // (an STX byte may appear in the middle of a message)
-        if message.has_valid_crc::<M>() {
+        if let Some(message) = try_decode_v1_async::<M>(reader).await? {
             return Ok(message);
         }
     }
 }
 
+// read a single byte from a [`embedded_io_async::Read`]er
+#[cfg(feature = "embedded")]
+async fn read_u8_async(
+    reader: &mut impl embedded_io_async::Read,
+) -> Result<u8, error::MessageReadError> {
+    let mut byte = [0u8];
+    reader
+        .read_exact(&mut byte)
+        .await
+        .map_err(|_| error::MessageReadError::Io)?;
+    Ok(byte[0])
+}
+
+// other than the peeking readers, the STX is already consumed and the message bytes can not be
+// pushed back, so the bytes of a message with an invalid CRC are lost
+#[cfg(feature = "embedded")]
+async fn try_decode_v1_async<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+) -> Result<Option<MAVLinkV1MessageRaw>, error::MessageReadError> {
+    try_decode_async(reader, MAV_STX, check_v1_frame::<M>).await
+}
+
+/// Read increasingly more bytes after the STX until `check` can decide on the frame.
+#[cfg(feature = "embedded")]
+async fn try_decode_async<T>(
+    reader: &mut impl embedded_io_async::Read,
+    stx: u8,
+    check: impl Fn(&[u8]) -> FrameCheck<T>,
+) -> Result<Option<T>, error::MessageReadError> {
+    let mut buf = [0u8; MAX_FRAME_SIZE];
+    buf[0] = stx;
+    let mut len = 1;
+    loop {
+        match check(&buf[..len]) {
+            FrameCheck::Incomplete(required) => {
+                reader
+                    .read_exact(&mut buf[len..required])
+                    .await
+                    .map_err(|_| error::MessageReadError::Io)?;
+                len = required;
+            }
+            FrameCheck::Valid(message) => return Ok(Some(message)),
+            // retry if CRC failed after previous STX
+            // (an STX byte may appear in the middle of a message)
+            FrameCheck::Invalid | FrameCheck::Rejected(_) => return Ok(None),
+        }
+    }
+}
+
 /// Read and parse a MAVLink 1 message from a [`PeekReader`].
 pub fn read_v1_msg<M: Message, R: Read>(
     r: &mut PeekReader<R>,
@@ -826,8 +1099,8 @@ pub fn read_v1_msg<M: Message, R: Read>(
 }
 
 /// Asynchronously read and parse a MAVLink 1 message from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_v1_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_v1_msg_async<M: Message, R: futures::AsyncRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
     let message = read_v1_raw_message_async::<M, _>(r).await?;
@@ -1082,20 +1355,6 @@ impl MAVLinkV2MessageRaw {
         &mut self.0[signature_start..(signature_start + 6)]
     }
 
-    fn mut_payload_and_checksum_and_sign(&mut self) -> &mut [u8] {
-        let payload_length: usize = self.payload_length().into();
-
-        // Signature to ensure the link is tamper-proof.
-        let signature_size = if (self.incompatibility_flags() & MAVLINK_IFLAG_SIGNED) == 0 {
-            0
-        } else {
-            Self::SIGNATURE_SIZE
-        };
-
-        &mut self.0
-            [(1 + Self::HEADER_SIZE)..(1 + Self::HEADER_SIZE + payload_length + signature_size + 2)]
-    }
-
     /// Checks wether the message's [CRC-16 checksum](https://mavlink.io/en/guide/serialization.html#checksum) calculation matches its checksum field.
     #[inline]
     pub fn has_valid_crc<M: Message>(&self) -> bool {
@@ -1212,90 +1471,19 @@ impl MAVLinkV2MessageRaw {
     }
 }
 
-#[allow(unused_variables)]
 fn try_decode_v2<M: Message, R: Read>(
     reader: &mut PeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<Option<MAVLinkV2MessageRaw>, error::MessageReadError> {
-    let mut message = MAVLinkV2MessageRaw::new();
-    let whole_header_size = MAVLinkV2MessageRaw::HEADER_SIZE + 1;
-
-    message.0[0] = MAV_STX_V2;
-    let header = &reader.peek_exact(whole_header_size)?[1..whole_header_size];
-    message.mut_header().copy_from_slice(header);
-
-    if message.incompatibility_flags() & !MAVLINK_SUPPORTED_IFLAGS > 0 {
-        // if there are incompatibility flags set that we do not know discard the message
-        reader.consume(1);
-        return Ok(None);
-    }
-
-    let packet_length = message.raw_bytes().len();
-    let payload_and_checksum_and_sign =
-        &reader.peek_exact(packet_length)?[whole_header_size..packet_length];
-    message
-        .mut_payload_and_checksum_and_sign()
-        .copy_from_slice(payload_and_checksum_and_sign);
-
-    if message.has_valid_crc::<M>() {
-        // even if the signature turn out to be invalid the valid crc shows that the received data presents a valid message as opposed to random bytes
-        reader.consume(message.raw_bytes().len());
-    } else {
-        reader.consume(1);
-        return Ok(None);
-    }
-
-    #[cfg(feature = "signing")]
-    if let Some(signing_data) = signing_data {
-        if !signing_data.verify_signature(&message) {
-            return Ok(None);
-        }
-    }
-
-    Ok(Some(message))
+    try_decode(reader, |bytes| check_v2_frame::<M>(bytes, signing_data))
 }
 
-#[cfg(feature = "tokio-1")]
-#[allow(unused_variables)]
-// other then the blocking version the STX is read not peeked, this changed some sizes
-async fn try_decode_v2_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+async fn try_decode_v2_async<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<Option<MAVLinkV2MessageRaw>, error::MessageReadError> {
-    let mut message = MAVLinkV2MessageRaw::new();
-
-    message.0[0] = MAV_STX_V2;
-    let header = &reader.peek_exact(MAVLinkV2MessageRaw::HEADER_SIZE).await?
-        [..MAVLinkV2MessageRaw::HEADER_SIZE];
-    message.mut_header().copy_from_slice(header);
-
-    if message.incompatibility_flags() & !MAVLINK_SUPPORTED_IFLAGS > 0 {
-        // if there are incompatibility flags set that we do not know discard the message
-        return Ok(None);
-    }
-
-    let packet_length = message.raw_bytes().len() - 1;
-    let payload_and_checksum_and_sign =
-        &reader.peek_exact(packet_length).await?[MAVLinkV2MessageRaw::HEADER_SIZE..packet_length];
-    message
-        .mut_payload_and_checksum_and_sign()
-        .copy_from_slice(payload_and_checksum_and_sign);
-
-    if message.has_valid_crc::<M>() {
-        // even if the signature turn out to be invalid the valid crc shows that the received data presents a valid message as opposed to random bytes
-        reader.consume(message.raw_bytes().len() - 1);
-    } else {
-        return Ok(None);
-    }
-
-    #[cfg(feature = "signing")]
-    if let Some(signing_data) = signing_data {
-        if !signing_data.verify_signature(&message) {
-            return Ok(None);
-        }
-    }
-
-    Ok(Some(message))
+    try_decode_async(reader, |bytes| check_v2_frame::<M>(bytes, signing_data)).await
 }
 
 /// Read a raw MAVLink 2 message from a [`PeekReader`].
@@ -1334,25 +1522,22 @@ fn read_v2_raw_message_inner<M: Message, R: Read>(
 }
 
 /// Asynchronously read a raw MAVLink 2 message from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_v2_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_v2_raw_message_async<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<MAVLinkV2MessageRaw, error::MessageReadError> {
     read_v2_raw_message_async_inner::<M, R>(reader, None).await
 }
 
-#[cfg(feature = "tokio-1")]
-#[allow(unused_variables)]
-async fn read_v2_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+async fn read_v2_raw_message_async_inner<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkV2MessageRaw, error::MessageReadError> {
     loop {
-        loop {
-            // search for the magic framing value indicating start of mavlink message
-            if reader.read_u8().await? == MAV_STX_V2 {
-                break;
-            }
+        // search for the magic framing value indicating start of mavlink message
+        while reader.peek_exact(1).await?[0] != MAV_STX_V2 {
+            reader.consume(1);
         }
 
         if let Some(message) = try_decode_v2_async::<M, _>(reader, signing_data).await? {
@@ -1362,15 +1547,15 @@ async fn read_v2_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead + U
 }
 
 /// Asynchronously read a raw MAVLink 2 message with signing support from a [`AsyncPeekReader`].
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_v2_raw_message_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(feature = "futures-io", feature = "signing"))]
+pub async fn read_v2_raw_message_async_signed<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkV2MessageRaw, error::MessageReadError> {
     read_v2_raw_message_async_inner::<M, R>(reader, signing_data).await
 }
 
-/// Asynchronously read a raw MAVLink 2 message with signing support from a [`embedded_io_async::Read`]er.
+/// Asynchronously read a raw MAVLink 2 message from a [`embedded_io_async::Read`]er.
 ///
 /// # Example
 /// See mavlink/examples/embedded-async-read full example for details.
@@ -1378,45 +1563,46 @@ pub async fn read_v2_raw_message_async_signed<M: Message, R: tokio::io::AsyncRea
 pub async fn read_v2_raw_message_async<M: Message>(
     reader: &mut impl embedded_io_async::Read,
 ) -> Result<MAVLinkV2MessageRaw, error::MessageReadError> {
-    loop {
-        // search for the magic framing value indicating start of mavlink message
-        let mut byte = [0u8];
-        loop {
-            reader
-                .read_exact(&mut byte)
-                .await
-                .map_err(|_| error::MessageReadError::Io)?;
-            if byte[0] == MAV_STX_V2 {
-                break;
-            }
-        }
-
-        let mut message = MAVLinkV2MessageRaw::new();
-
-        message.0[0] = MAV_STX_V2;
-        reader
-            .read_exact(message.mut_header())
-            .await
-            .map_err(|_| error::MessageReadError::Io)?;
+    read_v2_raw_message_async_inner::<M>(reader, None).await
+}
 
-        if message.incompatibility_flags() & !MAVLINK_SUPPORTED_IFLAGS > 0 {
-            // if there are incompatibility flags set that we do not know discard the message
-            continue;
-        }
+/// Asynchronously read a raw MAVLink 2 message with signing support from a [`embedded_io_async::Read`]er.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn read_v2_raw_message_async_signed<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<MAVLinkV2MessageRaw, error::MessageReadError> {
+    read_v2_raw_message_async_inner::<M>(reader, signing_data).await
+}
 
-        reader
-            .read_exact(message.mut_payload_and_checksum_and_sign())
-            .await
-            .map_err(|_| error::MessageReadError::Io)?;
+#[cfg(feature = "embedded")]
+async fn read_v2_raw_message_async_inner<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<MAVLinkV2MessageRaw, error::MessageReadError> {
+    loop {
+        // search for the magic framing value indicating start of mavlink message
+        while read_u8_async(reader).await? != MAV_STX_V2 {}
 
-        // retry if CRC failed after previous STX
-        // (an STX byte may appear in the middle of a message)
-        if message.has_valid_crc::<M>() {
+        if let Some(message) = try_decode_v2_async::<M>(reader, signing_data).await? {
             return Ok(message);
         }
     }
 }
 
+// other than the peeking readers, the STX is already consumed and the message bytes can not be
+// pushed back, so the bytes of a message with an invalid CRC are lost
+#[cfg(feature = "embedded")]
+async fn try_decode_v2_async<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<Option<MAVLinkV2MessageRaw>, error::MessageReadError> {
+    try_decode_async(reader, MAV_STX_V2, |bytes| {
+        check_v2_frame::<M>(bytes, signing_data)
+    })
+    .await
+}
+
 /// Read and parse a MAVLink 2 message from a [`PeekReader`].
 #[inline]
 pub fn read_v2_msg<M: Message, R: Read>(
@@ -1452,24 +1638,24 @@ fn read_v2_msg_inner<M: Message, R: Read>(
 }
 
 /// Asynchronously read and parse a MAVLink 2 message from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_v2_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_v2_msg_async<M: Message, R: futures::AsyncRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
     read_v2_msg_async_inner(read, None).await
 }
 
 /// Asynchronously read and parse a MAVLink 2  message from a [`AsyncPeekReader`].
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_v2_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(feature = "futures-io", feature = "signing"))]
+pub async fn read_v2_msg_async_signed<M: Message, R: futures::AsyncRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
     read_v2_msg_async_inner(read, signing_data).await
 }
 
-#[cfg(feature = "tokio-1")]
-async fn read_v2_msg_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+async fn read_v2_msg_async_inner<M: Message, R: futures::AsyncRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
@@ -1493,7 +1679,24 @@ async fn read_v2_msg_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
 pub async fn read_v2_msg_async<M: Message, R: embedded_io_async::Read>(
     r: &mut R,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
-    let message = read_v2_raw_message_async::<M>(r).await?;
+    read_v2_msg_async_inner(r, None).await
+}
+
+/// Asynchronously read and parse a MAVLink 2 message with signing support from a [`embedded_io_async::Read`]er.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn read_v2_msg_async_signed<M: Message, R: embedded_io_async::Read>(
+    r: &mut R,
+    signing_data: Option<&SigningData>,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    read_v2_msg_async_inner(r, signing_data).await
+}
+
+#[cfg(feature = "embedded")]
+async fn read_v2_msg_async_inner<M: Message, R: embedded_io_async::Read>(
+    r: &mut R,
+    signing_data: Option<&SigningData>,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    let message = read_v2_raw_message_async_inner::<M>(r, signing_data).await?;
 
     Ok((
         MavHeader {
@@ -1501,15 +1704,12 @@ pub async fn read_v2_msg_async<M: Message, R: embedded_io_async::Read>(
             system_id: message.system_id(),
             component_id: message.component_id(),
         },
-        M::parse(
-            MavlinkVersion::V2,
-            u32::from(message.message_id()),
-            message.payload(),
-        )?,
+        M::parse(MavlinkVersion::V2, message.message_id(), message.payload())?,
     ))
 }
 
 /// Raw byte representation of a MAVLink message of either version
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum MAVLinkMessageRaw {
     V1(MAVLinkV1MessageRaw),
     V2(MAVLinkV2MessageRaw),
@@ -1552,6 +1752,12 @@ impl MAVLinkMessageRaw {
             Self::V2(_) => MavlinkVersion::V2,
         }
     }
+    pub fn raw_bytes(&self) -> &[u8] {
+        match self {
+            Self::V1(msg) => msg.raw_bytes(),
+            Self::V2(msg) => msg.raw_bytes(),
+        }
+    }
 }
 
 /// Read a raw MAVLink 1 or 2 message from a [`PeekReader`].
@@ -1572,110 +1778,148 @@ pub fn read_any_raw_message_signed<M: Message, R: Read>(
     read_any_raw_message_inner::<M, R>(reader, signing_data)
 }
 
-#[allow(unused_variables)]
 fn read_any_raw_message_inner<M: Message, R: Read>(
     reader: &mut PeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
     loop {
         // search for the magic framing value indicating start of MAVLink message
-        let version = loop {
+        loop {
             let byte = reader.peek_exact(1)?[0];
-            if byte == MAV_STX {
-                break MavlinkVersion::V1;
-            }
-            if byte == MAV_STX_V2 {
-                break MavlinkVersion::V2;
+            if byte == MAV_STX || byte == MAV_STX_V2 {
+                break;
             }
             reader.consume(1);
-        };
-        match version {
-            MavlinkVersion::V1 => {
-                if let Some(message) = try_decode_v1::<M, _>(reader)? {
-                    // With signing enabled and unsigned messages not allowed do not further process V1
-                    #[cfg(feature = "signing")]
-                    if let Some(signing) = signing_data {
-                        if signing.config.allow_unsigned {
-                            return Ok(MAVLinkMessageRaw::V1(message));
-                        }
-                    } else {
-                        return Ok(MAVLinkMessageRaw::V1(message));
-                    }
-                    #[cfg(not(feature = "signing"))]
-                    return Ok(MAVLinkMessageRaw::V1(message));
-                }
+        }
 
-                reader.consume(1);
-            }
-            MavlinkVersion::V2 => {
-                if let Some(message) = try_decode_v2::<M, _>(reader, signing_data)? {
-                    return Ok(MAVLinkMessageRaw::V2(message));
-                }
-            }
+        // With signing enabled and unsigned messages not allowed MAVLink 1 messages are rejected
+        if let Some(message) =
+            try_decode(reader, |bytes| check_any_frame::<M>(bytes, signing_data))?
+        {
+            return Ok(message);
         }
     }
 }
 
 /// Asynchronously read a raw MAVLink 1 or 2 message from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_any_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_any_raw_message_async<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
     read_any_raw_message_async_inner::<M, R>(reader, None).await
 }
 
 /// Asynchronously read a raw MAVLink 1 or 2 message from a [`AsyncPeekReader`] with signing support.
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_any_raw_message_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(feature = "futures-io", feature = "signing"))]
+pub async fn read_any_raw_message_async_signed<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
     read_any_raw_message_async_inner::<M, R>(reader, signing_data).await
 }
 
-#[cfg(feature = "tokio-1")]
-#[allow(unused_variables)]
-async fn read_any_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+async fn read_any_raw_message_async_inner<M: Message, R: futures::AsyncRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
     loop {
         // search for the magic framing value indicating start of MAVLink 1 or 2 message
-        let version = loop {
-            let read = reader.read_u8().await?;
-            if read == MAV_STX {
-                break MavlinkVersion::V1;
+        loop {
+            let byte = reader.peek_exact(1).await?[0];
+            if byte == MAV_STX || byte == MAV_STX_V2 {
+                break;
             }
-            if read == MAV_STX_V2 {
-                break MavlinkVersion::V2;
+            reader.consume(1);
+        }
+
+        // With signing enabled and unsigned messages not allowed MAVLink 1 messages are rejected
+        if let Some(message) =
+            try_decode_async(reader, |bytes| check_any_frame::<M>(bytes, signing_data)).await?
+        {
+            return Ok(message);
+        }
+    }
+}
+
+/// Asynchronously read a raw MAVLink 1 or 2 message from a [`embedded_io_async::Read`]er.
+#[cfg(feature = "embedded")]
+pub async fn read_any_raw_message_async<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
+    read_any_raw_message_async_inner::<M>(reader, None).await
+}
+
+/// Asynchronously read a raw MAVLink 1 or 2 message from a [`embedded_io_async::Read`]er with signing support.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn read_any_raw_message_async_signed<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
+    read_any_raw_message_async_inner::<M>(reader, signing_data).await
+}
+
+#[cfg(feature = "embedded")]
+async fn read_any_raw_message_async_inner<M: Message>(
+    reader: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<MAVLinkMessageRaw, error::MessageReadError> {
+    loop {
+        // search for the magic framing value indicating start of MAVLink 1 or 2 message
+        let stx = loop {
+            let read = read_u8_async(reader).await?;
+            if read == MAV_STX || read == MAV_STX_V2 {
+                break read;
             }
         };
 
-        match version {
-            MavlinkVersion::V1 => {
-                if let Some(message) = try_decode_v1_async::<M, _>(reader).await? {
-                    // With signing enabled and unsigned messages not allowed do not further process them
-                    #[cfg(feature = "signing")]
-                    if let Some(signing) = signing_data {
-                        if signing.config.allow_unsigned {
-                            return Ok(MAVLinkMessageRaw::V1(message));
-                        }
-                    } else {
-                        return Ok(MAVLinkMessageRaw::V1(message));
-                    }
-                    #[cfg(not(feature = "signing"))]
-                    return Ok(MAVLinkMessageRaw::V1(message));
-                }
-            }
-            MavlinkVersion::V2 => {
-                if let Some(message) = try_decode_v2_async::<M, _>(reader, signing_data).await? {
-                    return Ok(MAVLinkMessageRaw::V2(message));
-                }
-            }
+        // With signing enabled and unsigned messages not allowed MAVLink 1 messages are rejected
+        if let Some(message) = try_decode_async(reader, stx, |bytes| {
+            check_any_frame::<M>(bytes, signing_data)
+        })
+        .await?
+        {
+            return Ok(message);
         }
     }
 }
 
+/// Asynchronously read and parse a MAVLink 1 or 2 message from a [`embedded_io_async::Read`]er.
+#[cfg(feature = "embedded")]
+pub async fn read_any_msg_async<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    read_any_msg_async_inner(r, None).await
+}
+
+/// Asynchronously read and parse a MAVLink 1 or 2 message from a [`embedded_io_async::Read`]er with signing support.
+///
+/// MAVLink 1 messages a treated as unsigned.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn read_any_msg_async_signed<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    read_any_msg_async_inner(r, signing_data).await
+}
+
+#[cfg(feature = "embedded")]
+async fn read_any_msg_async_inner<M: Message>(
+    r: &mut impl embedded_io_async::Read,
+    signing_data: Option<&SigningData>,
+) -> Result<(MavHeader, M), error::MessageReadError> {
+    let message = read_any_raw_message_async_inner::<M>(r, signing_data).await?;
+
+    Ok((
+        MavHeader {
+            sequence: message.sequence(),
+            system_id: message.system_id(),
+            component_id: message.component_id(),
+        },
+        M::parse(message.version(), message.message_id(), message.payload())?,
+    ))
+}
+
 /// Read and parse a MAVLink 1 or 2 message from a [`PeekReader`].
 #[inline]
 pub fn read_any_msg<M: Message, R: Read>(
@@ -1712,8 +1956,8 @@ fn read_any_msg_inner<M: Message, R: Read>(
 }
 
 /// Asynchronously read and parse a MAVLink 1 or 2 message from a [`AsyncPeekReader`].
-#[cfg(feature = "tokio-1")]
-pub async fn read_any_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+pub async fn read_any_msg_async<M: Message, R: futures::AsyncRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
     read_any_msg_async_inner(read, None).await
@@ -1722,17 +1966,17 @@ pub async fn read_any_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
 /// Asynchronously read and parse a MAVLink 1 or 2 message from a [`AsyncPeekReader`] with signing support.
 ///
 /// MAVLink 1 messages a treated as unsigned.
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
+#[cfg(all(feature = "futures-io", feature = "signing"))]
 #[inline]
-pub async fn read_any_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+pub async fn read_any_msg_async_signed<M: Message, R: futures::AsyncRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
     read_any_msg_async_inner(read, signing_data).await
 }
 
-#[cfg(feature = "tokio-1")]
-async fn read_any_msg_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(feature = "futures-io")]
+async fn read_any_msg_async_inner<M: Message, R: futures::AsyncRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), error::MessageReadError> {
@@ -1779,7 +2023,7 @@ pub fn write_versioned_msg_signed<M: Message, W: Write>(
 }
 
 /// Asynchronously write a MAVLink message using the given MAVLink version to a [`AsyncWrite`]r.
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 pub async fn write_versioned_msg_async<M: Message, W: AsyncWrite + Unpin>(
     w: &mut W,
     version: MavlinkVersion,
@@ -1795,7 +2039,7 @@ pub async fn write_versioned_msg_async<M: Message, W: AsyncWrite + Unpin>(
 /// Asynchronously write a MAVLink message using the given MAVLink version to a [`AsyncWrite`]r with signing support.
 ///
 /// When using [`MavlinkVersion::V1`] signing is ignored.
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
+#[cfg(all(feature = "futures-io", feature = "signing"))]
 pub async fn write_versioned_msg_async_signed<M: Message, W: AsyncWrite + Unpin>(
     w: &mut W,
     version: MavlinkVersion,
@@ -1826,6 +2070,23 @@ pub async fn write_versioned_msg_async<M: Message>(
     }
 }
 
+/// Asynchronously write a MAVLink message using the given MAVLink version to a [`embedded_io_async::Write`]r with signing support.
+///
+/// When using [`MavlinkVersion::V1`] signing is ignored.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn write_versioned_msg_async_signed<M: Message>(
+    w: &mut impl embedded_io_async::Write,
+    version: MavlinkVersion,
+    header: MavHeader,
+    data: &M,
+    signing_data: Option<&SigningData>,
+) -> Result<usize, error::MessageWriteError> {
+    match version {
+        MavlinkVersion::V2 => write_v2_msg_async_signed(w, header, data, signing_data).await,
+        MavlinkVersion::V1 => write_v1_msg_async(w, header, data).await,
+    }
+}
+
 /// Write a MAVLink 2 message to a [`Write`]r.
 pub fn write_v2_msg<M: Message, W: Write>(
     w: &mut W,
@@ -1876,7 +2137,7 @@ pub fn write_v2_msg_signed<M: Message, W: Write>(
 }
 
 /// Asynchronously write a MAVLink 2 message to a [`AsyncWrite`]r.
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 pub async fn write_v2_msg_async<M: Message, W: AsyncWrite + Unpin>(
     w: &mut W,
     header: MavHeader,
@@ -1895,7 +2156,7 @@ pub async fn write_v2_msg_async<M: Message, W: AsyncWrite + Unpin>(
 
 /// Write a MAVLink 2 message to a [`AsyncWrite`]r with signing support.
 #[cfg(feature = "signing")]
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 pub async fn write_v2_msg_async_signed<M: Message, W: AsyncWrite + Unpin>(
     w: &mut W,
     header: MavHeader,
@@ -1949,6 +2210,40 @@ pub async fn write_v2_msg_async<M: Message>(
     Ok(len)
 }
 
+/// Asynchronously write a MAVLink 2 message to a [`embedded_io_async::Write`]r with signing support.
+#[cfg(all(feature = "embedded", feature = "signing"))]
+pub async fn write_v2_msg_async_signed<M: Message>(
+    w: &mut impl embedded_io_async::Write,
+    header: MavHeader,
+    data: &M,
+    signing_data: Option<&SigningData>,
+) -> Result<usize, error::MessageWriteError> {
+    let mut message_raw = MAVLinkV2MessageRaw::new();
+
+    let signature_len = if let Some(signing_data) = signing_data {
+        if signing_data.config.sign_outgoing {
+            message_raw.serialize_message_for_signing(header, data);
+            signing_data.sign_message(&mut message_raw);
+            MAVLinkV2MessageRaw::SIGNATURE_SIZE
+        } else {
+            message_raw.serialize_message(header, data);
+            0
+        }
+    } else {
+        message_raw.serialize_message(header, data);
+        0
+    };
+
+    let payload_length: usize = message_raw.payload_length().into();
+    let len = 1 + MAVLinkV2MessageRaw::HEADER_SIZE + payload_length + 2 + signature_len;
+
+    w.write_all(&message_raw.0[..len])
+        .await
+        .map_err(|_| error::MessageWriteError::Io)?;
+
+    Ok(len)
+}
+
 /// Write a MAVLink 1 message to a [`Write`]r.
 pub fn write_v1_msg<M: Message, W: Write>(
     w: &mut W,
@@ -1970,7 +2265,7 @@ pub fn write_v1_msg<M: Message, W: Write>(
 }
 
 /// Asynchronously write a MAVLink 1 message to a [`AsyncWrite`]r.
-#[cfg(feature = "tokio-1")]
+#[cfg(feature = "futures-io")]
 pub async fn write_v1_msg_async<M: Message, W: AsyncWrite + Unpin>(
     w: &mut W,
     header: MavHeader,
@@ -0,0 +1,221 @@
+//! Readers for the logs vehicles record onboard, ArduPilot DataFlash logs (`.bin`) and PX4 ULog files (`.ulg`).
+//!
+//! Both formats describe their messages in the log itself, so the records are not decoded into the message types of
+//! a MAVLink dialect but into [`Record`]s of named, typed [`Value`]s. The timestamp of a record is taken from its
+//! time field, counted in microseconds since the vehicle booted, like the `time_boot_ms` and `time_usec` fields of
+//! MAVLink messages. Telemetry of a tlog is lined up with an onboard log through messages with both clocks, like
+//! `SYSTEM_TIME`.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::onboard_log::dataflash::DataFlashReader;
+//!
+//! let mut reader = DataFlashReader::new(std::io::BufReader::new(std::fs::File::open("00000042.BIN")?));
+//! for record in reader.records() {
+//!     let record = record?;
+//!     if record.name() == "ATT" {
+//!         println!("{:?}: roll {:?}", record.timestamp(), record.get("Roll"));
+//!     }
+//! }
+//! ```
+
+use core::fmt::Display;
+use std::io;
+use std::sync::Arc;
+
+pub mod dataflash;
+pub mod ulog;
+
+/// Value of a record field
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Array(Vec<Self>),
+}
+
+impl Value {
+    /// The value of a number or boolean as a float
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Bool(value) => Some(f64::from(u8::from(value))),
+            Self::F32(value) => Some(f64::from(value)),
+            Self::F64(value) => Some(value),
+            // 64 bit integers may lose precision
+            Self::I64(value) => Some(value as f64),
+            Self::U64(value) => Some(value as f64),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+
+    /// The value of an integer that fits into an `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::I8(value) => Some(value.into()),
+            Self::U8(value) => Some(value.into()),
+            Self::I16(value) => Some(value.into()),
+            Self::U16(value) => Some(value.into()),
+            Self::I32(value) => Some(value.into()),
+            Self::U32(value) => Some(value.into()),
+            Self::I64(value) => Some(value),
+            Self::U64(value) => i64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value of a non-negative integer
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::U64(value) => Some(value),
+            _ => self.as_i64().and_then(|value| u64::try_from(value).ok()),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bool(value) => value.fmt(f),
+            Self::I8(value) => value.fmt(f),
+            Self::U8(value) => value.fmt(f),
+            Self::I16(value) => value.fmt(f),
+            Self::U16(value) => value.fmt(f),
+            Self::I32(value) => value.fmt(f),
+            Self::U32(value) => value.fmt(f),
+            Self::I64(value) => value.fmt(f),
+            Self::U64(value) => value.fmt(f),
+            Self::F32(value) => value.fmt(f),
+            Self::F64(value) => value.fmt(f),
+            Self::String(value) => value.fmt(f),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt(f)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Name and field names of a type of record, as defined in the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordFormat {
+    name: String,
+    fields: Vec<String>,
+}
+
+impl RecordFormat {
+    pub(crate) fn new(name: String, fields: Vec<String>) -> Self {
+        Self { name, fields }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Index of a field in the values of a record
+    pub fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|name| name == field)
+    }
+}
+
+/// A record of an onboard log
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    format: Arc<RecordFormat>,
+    timestamp: Option<u64>,
+    values: Vec<Value>,
+}
+
+impl Record {
+    pub(crate) fn new(
+        format: Arc<RecordFormat>,
+        timestamp: Option<u64>,
+        values: Vec<Value>,
+    ) -> Self {
+        Self {
+            format,
+            timestamp,
+            values,
+        }
+    }
+
+    /// Name of the record's type, like `ATT` or `vehicle_attitude`
+    pub fn name(&self) -> &str {
+        &self.format.name
+    }
+
+    pub fn format(&self) -> &Arc<RecordFormat> {
+        &self.format
+    }
+
+    /// Time the record was logged at in microseconds since boot, if the record has a time field
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    /// Values of the fields, in the order of the format's fields
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    /// Value of a field
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.values.get(self.format.field_index(field)?)
+    }
+
+    /// Iterator over the names and values of the fields
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.format
+            .fields
+            .iter()
+            .map(String::as_str)
+            .zip(&self.values)
+    }
+}
+
+/// String of a fixed size field, which is padded with zeros
+fn fixed_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Read exactly `buf.len()` bytes, returning `false` if the log ends before
+///
+/// Logs of a vehicle that lost power often end with a truncated message, so the end of the log is not an error.
+fn read_or_end(reader: &mut impl io::Read, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
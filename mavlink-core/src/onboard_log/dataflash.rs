@@ -0,0 +1,235 @@
+//! Reader for ArduPilot DataFlash logs (`.bin`), as downloaded from the vehicle or its SD card.
+//!
+//! A DataFlash log is a sequence of messages, each starting with the two byte header `0xA3 0x95` and a message type.
+//! The `FMT` messages of the log define the name, size, field types and field names of every other message type.
+//! Bytes between messages, which are left by interrupted writes, are skipped and counted.
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::sync::Arc;
+
+use super::{fixed_string, read_or_end, Record, RecordFormat, Value};
+
+const HEADER: [u8; 2] = [0xa3, 0x95];
+const HEADER_SIZE: usize = 3;
+/// Message type of the `FMT` messages defining the other message types
+const FMT_TYPE: u8 = 128;
+
+/// Layout of a message type
+struct Format {
+    length: usize,
+    types: Vec<u8>,
+    record_format: Arc<RecordFormat>,
+    // index of the time field and the microseconds per unit
+    time: Option<(usize, u64)>,
+}
+
+impl Format {
+    /// The `FMT` format, which every log starts with
+    fn fmt() -> Self {
+        Self::new("FMT", "BBnNZ", "Type,Length,Name,Format,Columns", 89).unwrap()
+    }
+
+    /// Create a format from the fields of its `FMT` message, `None` if they are inconsistent
+    fn new(name: &str, types: &str, labels: &str, length: usize) -> Option<Self> {
+        let types = types.as_bytes().to_vec();
+        let size = types
+            .iter()
+            .try_fold(HEADER_SIZE, |size, t| Some(size + field_size(*t)?))?;
+        let fields: Vec<String> = labels.split(',').map(str::to_string).collect();
+        if size != length || fields.len() != types.len() {
+            return None;
+        }
+        let time = fields
+            .iter()
+            .enumerate()
+            .find_map(|(index, field)| match field.as_str() {
+                "TimeUS" => Some((index, 1)),
+                "TimeMS" => Some((index, 1000)),
+                _ => None,
+            });
+        Some(Self {
+            length,
+            types,
+            record_format: Arc::new(RecordFormat::new(name.to_string(), fields)),
+            time,
+        })
+    }
+
+    fn decode(&self, mut payload: &[u8]) -> Record {
+        let values: Vec<Value> = self
+            .types
+            .iter()
+            .map(|t| {
+                let (field, rest) = payload.split_at(field_size(*t).unwrap());
+                payload = rest;
+                decode_field(*t, field)
+            })
+            .collect();
+        let timestamp = self.time.and_then(|(index, multiplier)| {
+            values[index]
+                .as_u64()
+                .map(|time| time.saturating_mul(multiplier))
+        });
+        Record::new(self.record_format.clone(), timestamp, values)
+    }
+}
+
+/// Size of a field type of a `FMT` message
+fn field_size(t: u8) -> Option<usize> {
+    Some(match t {
+        b'b' | b'B' | b'M' => 1,
+        b'h' | b'H' | b'c' | b'C' => 2,
+        b'i' | b'I' | b'f' | b'e' | b'E' | b'L' | b'n' => 4,
+        b'd' | b'q' | b'Q' => 8,
+        b'N' => 16,
+        b'Z' | b'a' => 64,
+        _ => return None,
+    })
+}
+
+/// Decode a field, scaled fields are converted to their unit
+fn decode_field(t: u8, bytes: &[u8]) -> Value {
+    macro_rules! le {
+        ($ty:ty) => {
+            <$ty>::from_le_bytes(bytes.try_into().unwrap())
+        };
+    }
+    match t {
+        b'b' => Value::I8(bytes[0] as i8),
+        b'B' | b'M' => Value::U8(bytes[0]),
+        b'h' => Value::I16(le!(i16)),
+        b'H' => Value::U16(le!(u16)),
+        b'i' => Value::I32(le!(i32)),
+        b'I' => Value::U32(le!(u32)),
+        b'q' => Value::I64(le!(i64)),
+        b'Q' => Value::U64(le!(u64)),
+        b'f' => Value::F32(le!(f32)),
+        b'd' => Value::F64(le!(f64)),
+        // centi-units
+        b'c' => Value::F64(f64::from(le!(i16)) / 100.0),
+        b'C' => Value::F64(f64::from(le!(u16)) / 100.0),
+        b'e' => Value::F64(f64::from(le!(i32)) / 100.0),
+        b'E' => Value::F64(f64::from(le!(u32)) / 100.0),
+        // latitude or longitude in 1e-7 degrees
+        b'L' => Value::F64(f64::from(le!(i32)) * 1e-7),
+        b'n' | b'N' | b'Z' => Value::String(fixed_string(bytes)),
+        b'a' => Value::Array(
+            bytes
+                .chunks_exact(2)
+                .map(|value| Value::I16(i16::from_le_bytes([value[0], value[1]])))
+                .collect(),
+        ),
+        _ => unreachable!(),
+    }
+}
+
+/// Reads the records of a DataFlash log
+///
+/// The reader reads the log in small pieces, so `reader` should be buffered.
+pub struct DataFlashReader<R> {
+    reader: R,
+    formats: HashMap<u8, Format>,
+    skipped: u64,
+}
+
+impl<R: Read> DataFlashReader<R> {
+    /// Create a reader, reading the log from the current position of `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            formats: HashMap::from([(FMT_TYPE, Format::fmt())]),
+            skipped: 0,
+        }
+    }
+
+    /// Formats of the message types defined so far
+    pub fn formats(&self) -> impl Iterator<Item = &Arc<RecordFormat>> {
+        self.formats.values().map(|format| &format.record_format)
+    }
+
+    /// Number of bytes skipped because they were not part of a message of a known type
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped
+    }
+
+    /// The underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Unwrap the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next record
+    ///
+    /// `FMT` messages are returned as records too. Returns `None` at the end of the log.
+    pub fn read_record(&mut self) -> io::Result<Option<Record>> {
+        let mut header = [0; HEADER_SIZE];
+        let mut filled = 0;
+        loop {
+            if !read_or_end(&mut self.reader, &mut header[filled..])? {
+                return Ok(None);
+            }
+            let format = match self.formats.get(&header[2]) {
+                Some(format) if header[..2] == HEADER => format,
+                _ => {
+                    header.copy_within(1.., 0);
+                    filled = HEADER_SIZE - 1;
+                    self.skipped += 1;
+                    continue;
+                }
+            };
+            let mut payload = vec![0; format.length - HEADER_SIZE];
+            if !read_or_end(&mut self.reader, &mut payload)? {
+                return Ok(None);
+            }
+            let record = format.decode(&payload);
+            if header[2] == FMT_TYPE {
+                self.define(&record);
+            }
+            return Ok(Some(record));
+        }
+    }
+
+    /// Iterator over the remaining records, see [`DataFlashReader::read_record`]
+    pub fn records(&mut self) -> Records<'_, R> {
+        Records { reader: self }
+    }
+
+    /// Add the message type defined by a `FMT` record
+    fn define(&mut self, fmt: &Record) {
+        let values = fmt.values();
+        let (Some(Value::U8(t)), Some(Value::U8(length)), Some(name), Some(types), Some(labels)) = (
+            values.first(),
+            values.get(1),
+            values.get(2).and_then(Value::as_str),
+            values.get(3).and_then(Value::as_str),
+            values.get(4).and_then(Value::as_str),
+        ) else {
+            return;
+        };
+        // the format of FMT itself is fixed
+        if *t == FMT_TYPE {
+            return;
+        }
+        if let Some(format) = Format::new(name, types, labels, usize::from(*length)) {
+            self.formats.insert(*t, format);
+        }
+    }
+}
+
+/// Iterator over the records of a [`DataFlashReader`]
+pub struct Records<'a, R> {
+    reader: &'a mut DataFlashReader<R>,
+}
+
+impl<R: Read> Iterator for Records<'_, R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_record().transpose()
+    }
+}
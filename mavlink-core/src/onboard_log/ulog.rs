@@ -0,0 +1,532 @@
+//! Reader for PX4 ULog files (`.ulg`).
+//!
+//! A ULog file starts with a definitions section, holding the formats of the logged messages, information about the
+//! vehicle and the initial parameter values. The data section that follows holds the logged messages, log output and
+//! parameter changes. Message types the reader does not know are skipped, as required by the format.
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{fixed_string, invalid_data, read_or_end, Record, RecordFormat, Value};
+
+const MAGIC: [u8; 7] = *b"ULog\x01\x12\x35";
+const HEADER_SIZE: usize = 16;
+const MESSAGE_HEADER_SIZE: usize = 3;
+
+const FLAG_BITS: u8 = b'B';
+const FORMAT: u8 = b'F';
+const INFO: u8 = b'I';
+const MULTI_INFO: u8 = b'M';
+const PARAMETER: u8 = b'P';
+const PARAMETER_DEFAULT: u8 = b'Q';
+const ADD_LOGGED: u8 = b'A';
+const REMOVE_LOGGED: u8 = b'R';
+const DATA: u8 = b'D';
+const LOGGING: u8 = b'L';
+const TAGGED_LOGGING: u8 = b'C';
+const DROPOUT: u8 = b'O';
+
+/// Incompatible flag of logs with appended data, which is read like the rest of the log
+const INCOMPAT_DATA_APPENDED: u8 = 0x01;
+/// Deepest nesting of message formats that is resolved
+const MAX_NESTING: usize = 16;
+/// Largest payload of a message, no message format or array can be larger
+const MAX_MESSAGE_SIZE: usize = u16::MAX as usize;
+
+/// An entry of the data section of a ULog file
+#[derive(Debug, Clone, PartialEq)]
+pub enum UlogEntry {
+    /// A logged message, `multi_id` tells multiple instances of the same message type apart
+    Data { multi_id: u8, record: Record },
+    /// Log output of the vehicle, `level` is the syslog severity from 0 (emergency) to 7 (debug), like the severity
+    /// of a `STATUSTEXT`
+    Logging {
+        level: u8,
+        tag: Option<u16>,
+        timestamp: u64,
+        message: String,
+    },
+    /// A parameter changed while logging
+    Parameter { name: String, value: Value },
+    /// Messages were lost because the logger could not keep up
+    Dropout { duration: Duration },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Primitive {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+    Bool,
+    Char,
+}
+
+impl Primitive {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "int8_t" => Self::I8,
+            "uint8_t" => Self::U8,
+            "int16_t" => Self::I16,
+            "uint16_t" => Self::U16,
+            "int32_t" => Self::I32,
+            "uint32_t" => Self::U32,
+            "int64_t" => Self::I64,
+            "uint64_t" => Self::U64,
+            "float" => Self::F32,
+            "double" => Self::F64,
+            "bool" => Self::Bool,
+            "char" => Self::Char,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 | Self::Bool | Self::Char => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Value {
+        macro_rules! le {
+            ($ty:ty) => {
+                <$ty>::from_le_bytes(bytes[..self.size()].try_into().unwrap())
+            };
+        }
+        match self {
+            Self::I8 => Value::I8(bytes[0] as i8),
+            Self::U8 => Value::U8(bytes[0]),
+            Self::I16 => Value::I16(le!(i16)),
+            Self::U16 => Value::U16(le!(u16)),
+            Self::I32 => Value::I32(le!(i32)),
+            Self::U32 => Value::U32(le!(u32)),
+            Self::I64 => Value::I64(le!(i64)),
+            Self::U64 => Value::U64(le!(u64)),
+            Self::F32 => Value::F32(le!(f32)),
+            Self::F64 => Value::F64(le!(f64)),
+            Self::Bool => Value::Bool(bytes[0] != 0),
+            Self::Char => Value::String(fixed_string(&bytes[..1])),
+        }
+    }
+
+    /// Decode a value or an array of `count` values, arrays of chars are strings
+    fn decode_value(self, count: Option<usize>, bytes: &[u8]) -> Value {
+        match (self, count) {
+            (_, None) => self.decode(bytes),
+            (Self::Char, Some(count)) => Value::String(fixed_string(&bytes[..count])),
+            (_, Some(count)) => Value::Array(
+                bytes
+                    .chunks_exact(self.size())
+                    .take(count)
+                    .map(|value| self.decode(value))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A field of a message format or the key of an information message, like `float[3] velocity`
+struct Field {
+    name: String,
+    type_name: String,
+    count: Option<usize>,
+}
+
+impl Field {
+    fn parse(field: &str) -> Option<Self> {
+        let (type_name, name) = field.trim().split_once(' ')?;
+        let (type_name, count) = match type_name.split_once('[') {
+            Some((type_name, count)) => {
+                let count: usize = count.strip_suffix(']')?.parse().ok()?;
+                // an array larger than a message cannot be logged
+                if count > MAX_MESSAGE_SIZE {
+                    return None;
+                }
+                (type_name, Some(count))
+            }
+            None => (type_name, None),
+        };
+        Some(Self {
+            name: name.trim().to_string(),
+            type_name: type_name.to_string(),
+            count,
+        })
+    }
+}
+
+/// Flattened layout of a message format
+struct Layout {
+    record_format: Arc<RecordFormat>,
+    // offset, type and array length of the fields
+    fields: Vec<(usize, Primitive, Option<usize>)>,
+    timestamp: Option<usize>,
+}
+
+impl Layout {
+    /// Resolve a message format, naming the fields of nested formats like `accel[0].x`
+    fn new(name: &str, formats: &HashMap<String, Vec<Field>>) -> Option<Self> {
+        let mut names = Vec::new();
+        let mut fields = Vec::new();
+        Self::flatten(name, "", 0, formats, &mut names, &mut fields, 0)?;
+        let timestamp = names.iter().position(|name| name == "timestamp");
+        Some(Self {
+            record_format: Arc::new(RecordFormat::new(name.to_string(), names)),
+            fields,
+            timestamp,
+        })
+    }
+
+    /// Add the fields of a format at `offset`, returning its size
+    fn flatten(
+        format: &str,
+        prefix: &str,
+        mut offset: usize,
+        formats: &HashMap<String, Vec<Field>>,
+        names: &mut Vec<String>,
+        fields: &mut Vec<(usize, Primitive, Option<usize>)>,
+        depth: usize,
+    ) -> Option<usize> {
+        if depth > MAX_NESTING {
+            return None;
+        }
+        let start = offset;
+        for field in formats.get(format)? {
+            let name = format!("{prefix}{}", field.name);
+            if let Some(primitive) = Primitive::parse(&field.type_name) {
+                if !field.name.starts_with("_padding") {
+                    names.push(name);
+                    fields.push((offset, primitive, field.count));
+                }
+                offset =
+                    offset.checked_add(primitive.size().checked_mul(field.count.unwrap_or(1))?)?;
+            } else if let Some(count) = field.count {
+                for i in 0..count {
+                    let prefix = format!("{name}[{i}].");
+                    let size = Self::flatten(
+                        &field.type_name,
+                        &prefix,
+                        offset,
+                        formats,
+                        names,
+                        fields,
+                        depth + 1,
+                    )?;
+                    // arrays of empty formats are not resolved, naming their elements could take very long
+                    if size == 0 {
+                        return None;
+                    }
+                    offset = offset.checked_add(size)?;
+                    if offset > MAX_MESSAGE_SIZE {
+                        return None;
+                    }
+                }
+            } else {
+                let prefix = format!("{name}.");
+                offset = offset.checked_add(Self::flatten(
+                    &field.type_name,
+                    &prefix,
+                    offset,
+                    formats,
+                    names,
+                    fields,
+                    depth + 1,
+                )?)?;
+            }
+            if offset > MAX_MESSAGE_SIZE {
+                return None;
+            }
+        }
+        Some(offset - start)
+    }
+
+    /// Decode the data of a message, `None` if it is too short
+    ///
+    /// Padding at the end of a message is not logged, so only the fields have to fit.
+    fn decode(&self, data: &[u8]) -> Option<Record> {
+        let values = self
+            .fields
+            .iter()
+            .map(|(offset, primitive, count)| {
+                let size = primitive.size().checked_mul(count.unwrap_or(1))?;
+                let bytes = data.get(*offset..offset.checked_add(size)?)?;
+                Some(primitive.decode_value(*count, bytes))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let timestamp = self.timestamp.and_then(|index| values[index].as_u64());
+        Some(Record::new(self.record_format.clone(), timestamp, values))
+    }
+}
+
+/// Reads the entries of a ULog file
+///
+/// The definitions section is read when the reader is created. The reader reads the log in small pieces, so
+/// `reader` should be buffered.
+pub struct UlogReader<R> {
+    reader: R,
+    version: u8,
+    start_timestamp: u64,
+    formats: HashMap<String, Vec<Field>>,
+    layouts: HashMap<String, Arc<Layout>>,
+    subscriptions: HashMap<u16, (u8, Arc<Layout>)>,
+    info: HashMap<String, Value>,
+    parameters: HashMap<String, Value>,
+    // first message of the data section, read with the definitions
+    pending: Option<(u8, Vec<u8>)>,
+}
+
+impl<R: Read> UlogReader<R> {
+    /// Create a reader, reading the header and definitions section of the log
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        if header[..MAGIC.len()] != MAGIC {
+            return Err(invalid_data("Not a ULog file"));
+        }
+        let mut ulog = Self {
+            reader,
+            version: header[7],
+            start_timestamp: u64::from_le_bytes(header[8..].try_into().unwrap()),
+            formats: HashMap::new(),
+            layouts: HashMap::new(),
+            subscriptions: HashMap::new(),
+            info: HashMap::new(),
+            parameters: HashMap::new(),
+            pending: None,
+        };
+        while let Some((message_type, payload)) = ulog.read_message()? {
+            match message_type {
+                FLAG_BITS => {
+                    let incompatible = payload.get(8..16).unwrap_or(&[]);
+                    let supported = [INCOMPAT_DATA_APPENDED, 0, 0, 0, 0, 0, 0, 0];
+                    if incompatible
+                        .iter()
+                        .zip(supported)
+                        .any(|(flags, supported)| flags & !supported != 0)
+                    {
+                        return Err(invalid_data(
+                            "ULog file uses unsupported incompatible flags",
+                        ));
+                    }
+                }
+                FORMAT => {
+                    let definition = String::from_utf8_lossy(&payload);
+                    if let Some((name, fields)) = definition.split_once(':') {
+                        // the offsets of the fields are unknown after a field that cannot be parsed
+                        let fields = fields
+                            .split(';')
+                            .filter(|field| !field.trim().is_empty())
+                            .map(Field::parse)
+                            .collect::<Option<_>>();
+                        if let Some(fields) = fields {
+                            ulog.formats.insert(name.to_string(), fields);
+                        }
+                    }
+                }
+                INFO | MULTI_INFO | PARAMETER | PARAMETER_DEFAULT => {
+                    ulog.define(message_type, &payload);
+                }
+                _ => {
+                    ulog.pending = Some((message_type, payload));
+                    break;
+                }
+            }
+        }
+        Ok(ulog)
+    }
+
+    /// Version of the file format
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Time logging started at, in microseconds since boot
+    pub fn start_timestamp(&self) -> u64 {
+        self.start_timestamp
+    }
+
+    /// Information about the vehicle and the log, like `sys_name` or `ver_sw`
+    pub fn info(&self) -> &HashMap<String, Value> {
+        &self.info
+    }
+
+    /// Current values of the parameters, updated by the parameter changes read so far
+    pub fn parameters(&self) -> &HashMap<String, Value> {
+        &self.parameters
+    }
+
+    /// The underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Unwrap the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next entry of the data section
+    ///
+    /// Returns `None` at the end of the log.
+    pub fn read_entry(&mut self) -> io::Result<Option<UlogEntry>> {
+        loop {
+            let message = match self.pending.take() {
+                Some(message) => Some(message),
+                None => self.read_message()?,
+            };
+            let Some((message_type, payload)) = message else {
+                return Ok(None);
+            };
+            match message_type {
+                ADD_LOGGED if payload.len() >= 3 => {
+                    let id = u16::from_le_bytes([payload[1], payload[2]]);
+                    let name = String::from_utf8_lossy(&payload[3..]).into_owned();
+                    if let Some(layout) = self.layout(&name) {
+                        self.subscriptions.insert(id, (payload[0], layout));
+                    }
+                }
+                REMOVE_LOGGED if payload.len() >= 2 => {
+                    self.subscriptions
+                        .remove(&u16::from_le_bytes([payload[0], payload[1]]));
+                }
+                DATA if payload.len() >= 2 => {
+                    let id = u16::from_le_bytes([payload[0], payload[1]]);
+                    let Some((multi_id, layout)) = self.subscriptions.get(&id) else {
+                        continue;
+                    };
+                    if let Some(record) = layout.decode(&payload[2..]) {
+                        return Ok(Some(UlogEntry::Data {
+                            multi_id: *multi_id,
+                            record,
+                        }));
+                    }
+                }
+                LOGGING if payload.len() >= 9 => {
+                    return Ok(Some(UlogEntry::Logging {
+                        level: log_level(payload[0]),
+                        tag: None,
+                        timestamp: u64::from_le_bytes(payload[1..9].try_into().unwrap()),
+                        message: String::from_utf8_lossy(&payload[9..]).into_owned(),
+                    }));
+                }
+                TAGGED_LOGGING if payload.len() >= 11 => {
+                    return Ok(Some(UlogEntry::Logging {
+                        level: log_level(payload[0]),
+                        tag: Some(u16::from_le_bytes([payload[1], payload[2]])),
+                        timestamp: u64::from_le_bytes(payload[3..11].try_into().unwrap()),
+                        message: String::from_utf8_lossy(&payload[11..]).into_owned(),
+                    }));
+                }
+                PARAMETER => {
+                    if let Some((name, value)) = self.define(message_type, &payload) {
+                        return Ok(Some(UlogEntry::Parameter { name, value }));
+                    }
+                }
+                INFO | MULTI_INFO => {
+                    self.define(message_type, &payload);
+                }
+                DROPOUT if payload.len() >= 2 => {
+                    let duration = u16::from_le_bytes([payload[0], payload[1]]);
+                    return Ok(Some(UlogEntry::Dropout {
+                        duration: Duration::from_millis(duration.into()),
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Iterator over the remaining entries, see [`UlogReader::read_entry`]
+    pub fn entries(&mut self) -> Entries<'_, R> {
+        Entries { reader: self }
+    }
+
+    /// Read the next message, returning its type and payload
+    fn read_message(&mut self) -> io::Result<Option<(u8, Vec<u8>)>> {
+        let mut header = [0; MESSAGE_HEADER_SIZE];
+        if !read_or_end(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+        let mut payload = vec![0; usize::from(u16::from_le_bytes([header[0], header[1]]))];
+        if !read_or_end(&mut self.reader, &mut payload)? {
+            return Ok(None);
+        }
+        Ok(Some((header[2], payload)))
+    }
+
+    /// Store the value of an information or parameter message, returning the name and value
+    fn define(&mut self, message_type: u8, payload: &[u8]) -> Option<(String, Value)> {
+        // multi information messages start with a flag whether they continue the previous value
+        let (continued, payload) = match message_type {
+            MULTI_INFO => (*payload.first()? != 0, &payload[1..]),
+            // default values are not tracked
+            PARAMETER_DEFAULT => return None,
+            _ => (false, payload),
+        };
+        let key_length = usize::from(*payload.first()?);
+        let key = Field::parse(&String::from_utf8_lossy(payload.get(1..1 + key_length)?))?;
+        let bytes = &payload[1 + key_length..];
+        let primitive = Primitive::parse(&key.type_name)?;
+        let value = match (primitive, key.count) {
+            // the length of a string is the length of the message
+            (Primitive::Char, Some(_)) => Value::String(fixed_string(bytes)),
+            (_, count) => {
+                if bytes.len() < primitive.size().checked_mul(count.unwrap_or(1))? {
+                    return None;
+                }
+                primitive.decode_value(count, bytes)
+            }
+        };
+        let values = match message_type {
+            PARAMETER => &mut self.parameters,
+            _ => &mut self.info,
+        };
+        match (values.get_mut(&key.name), &value) {
+            (Some(Value::String(previous)), Value::String(value)) if continued => {
+                previous.push_str(value);
+            }
+            _ => {
+                values.insert(key.name.clone(), value.clone());
+            }
+        }
+        Some((key.name, value))
+    }
+
+    /// Layout of a message format, resolved on first use
+    fn layout(&mut self, name: &str) -> Option<Arc<Layout>> {
+        if let Some(layout) = self.layouts.get(name) {
+            return Some(layout.clone());
+        }
+        let layout = Arc::new(Layout::new(name, &self.formats)?);
+        self.layouts.insert(name.to_string(), layout.clone());
+        Some(layout)
+    }
+}
+
+/// Severity of a log message, which is logged as an ASCII digit
+fn log_level(level: u8) -> u8 {
+    level.checked_sub(b'0').unwrap_or(level)
+}
+
+/// Iterator over the entries of a [`UlogReader`]
+pub struct Entries<'a, R> {
+    reader: &'a mut UlogReader<R>,
+}
+
+impl<R: Read> Iterator for Entries<'_, R> {
+    type Item = io::Result<UlogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_entry().transpose()
+    }
+}
@@ -0,0 +1,833 @@
+//! Reader and writer for packet captures (`.pcap` and `.pcapng`) of MAVLink traffic, as recorded by Wireshark or
+//! tcpdump.
+//!
+//! A [`PcapReader`] extracts the MAVLink frames from the UDP and TCP payloads of a capture and attaches the capture
+//! time and the endpoints of the packet to every frame. TCP streams are reassembled per connection direction, so
+//! frames split over several segments, or segments captured out of order, are read as sent. Fragmented IP packets
+//! are not reassembled and skipped.
+//!
+//! A [`PcapWriter`] exports frames as synthetic UDP packets of a raw IP capture, which can be inspected with
+//! Wireshark's MAVLink dissector or read again with a [`PcapReader`].
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::pcap::PcapReader;
+//!
+//! let mut reader = PcapReader::new(std::fs::File::open("capture.pcapng")?)?;
+//! for entry in reader.frames::<MavMessage>() {
+//!     let captured = entry?;
+//!     println!(
+//!         "{} {} -> {}: {:?}",
+//!         captured.timestamp, captured.source, captured.destination, captured.frame.msg
+//!     );
+//! }
+//! ```
+
+use core::marker::PhantomData;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::error::{MessageReadError, MessageWriteError, ParserError};
+use crate::frame_parser::FrameCheck;
+use crate::tlog::{check_record, parse_raw};
+use crate::{MAVLinkMessageRaw, MavFrame, Message, MAX_FRAME_SIZE};
+
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_PACKET: u32 = 2;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+
+const IP_PROTOCOL_TCP: u8 = 6;
+const IP_PROTOCOL_UDP: u8 = 17;
+
+const TCP_FIN: u8 = 0x01;
+const TCP_SYN: u8 = 0x02;
+const TCP_RST: u8 = 0x04;
+
+/// Largest block or packet that is read, larger ones are treated as corrupt
+const MAX_BLOCK_SIZE: usize = 1 << 24;
+/// Out of order segments kept per TCP stream before a missing segment is given up
+const MAX_PENDING_SEGMENTS: usize = 64;
+
+/// Transport protocol a frame was captured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transport {
+    Udp,
+    Tcp,
+}
+
+/// A frame extracted from a capture, with the packet it was captured in
+#[derive(Debug, Clone)]
+pub struct Captured<T> {
+    /// Capture time of the packet completing the frame, in microseconds since the Unix epoch
+    pub timestamp: u64,
+    pub transport: Transport,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub frame: T,
+}
+
+impl<T> Captured<T> {
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Captured<U> {
+        Captured {
+            timestamp: self.timestamp,
+            transport: self.transport,
+            source: self.source,
+            destination: self.destination,
+            frame: f(self.frame),
+        }
+    }
+}
+
+enum Format {
+    Pcap {
+        big_endian: bool,
+        nanos: bool,
+        link_type: u32,
+    },
+    Pcapng {
+        big_endian: bool,
+        interfaces: Vec<Interface>,
+    },
+}
+
+struct Interface {
+    link_type: u32,
+    // timestamp units per second
+    resolution: u64,
+}
+
+/// Captured link layer packet
+struct Packet {
+    timestamp: u64,
+    link_type: u32,
+    data: Vec<u8>,
+}
+
+/// Reassembly state of one direction of a TCP connection
+#[derive(Default)]
+struct TcpStream {
+    next_sequence: Option<u32>,
+    pending: Vec<(u32, Vec<u8>)>,
+    buffer: Vec<u8>,
+    synced: bool,
+}
+
+impl TcpStream {
+    /// Add a segment, moving the data that is in order into the buffer
+    fn push(&mut self, sequence: u32, syn: bool, payload: &[u8]) {
+        if syn {
+            *self = Self {
+                next_sequence: Some(sequence.wrapping_add(1)),
+                ..Self::default()
+            };
+            return;
+        }
+        if payload.is_empty() {
+            return;
+        }
+        // the capture may start in the middle of a connection
+        self.next_sequence.get_or_insert(sequence);
+        self.pending.push((sequence, payload.to_vec()));
+
+        while let Some(next) = self.next_sequence {
+            let Some(index) = self
+                .pending
+                .iter()
+                .position(|(sequence, _)| (next.wrapping_sub(*sequence) as i32) >= 0)
+            else {
+                if self.pending.len() <= MAX_PENDING_SEGMENTS {
+                    break;
+                }
+                // the missing segment was not captured, continue after the gap
+                let skip_to = self
+                    .pending
+                    .iter()
+                    .map(|(sequence, _)| *sequence)
+                    .min_by_key(|sequence| sequence.wrapping_sub(next))
+                    .unwrap();
+                self.next_sequence = Some(skip_to);
+                self.synced = false;
+                continue;
+            };
+            let (sequence, data) = self.pending.swap_remove(index);
+            // retransmitted data is skipped
+            let overlap = next.wrapping_sub(sequence) as usize;
+            if overlap < data.len() {
+                self.buffer.extend_from_slice(&data[overlap..]);
+                self.next_sequence = Some(sequence.wrapping_add(data.len() as u32));
+            }
+        }
+    }
+}
+
+/// Reads the MAVLink frames of the UDP and TCP traffic in a pcap or pcapng capture
+///
+/// Like a [`TlogReader`](crate::tlog::TlogReader), the reader's methods are generic over the [`Message`] type of the
+/// dialect to check the CRC of the frames.
+pub struct PcapReader<R> {
+    reader: R,
+    format: Format,
+    streams: HashMap<(SocketAddr, SocketAddr), TcpStream>,
+    frames: VecDeque<Captured<MAVLinkMessageRaw>>,
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Create a reader, reading the header of the capture
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let format = if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER {
+            // the section header block is read with the first packet
+            Format::Pcapng {
+                big_endian: false,
+                interfaces: Vec::new(),
+            }
+        } else {
+            let (big_endian, nanos) = match magic {
+                _ if u32::from_le_bytes(magic) == PCAP_MAGIC => (false, false),
+                _ if u32::from_be_bytes(magic) == PCAP_MAGIC => (true, false),
+                _ if u32::from_le_bytes(magic) == PCAP_MAGIC_NANOS => (false, true),
+                _ if u32::from_be_bytes(magic) == PCAP_MAGIC_NANOS => (true, true),
+                _ => return Err(invalid_data("Not a pcap or pcapng capture")),
+            };
+            let mut header = [0; 20];
+            reader.read_exact(&mut header)?;
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type: read_u32(&header[16..], big_endian) & 0xffff,
+            }
+        };
+        let mut reader = Self {
+            reader,
+            format,
+            streams: HashMap::new(),
+            frames: VecDeque::new(),
+        };
+        if matches!(reader.format, Format::Pcapng { .. }) {
+            reader.read_section_header()?;
+        }
+        Ok(reader)
+    }
+
+    /// The underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Unwrap the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next frame
+    ///
+    /// Returns `None` at the end of the capture.
+    pub fn read_raw<M: Message>(&mut self) -> io::Result<Option<Captured<MAVLinkMessageRaw>>> {
+        loop {
+            if let Some(frame) = self.frames.pop_front() {
+                return Ok(Some(frame));
+            }
+            let Some(packet) = self.read_packet()? else {
+                return Ok(None);
+            };
+            self.process::<M>(&packet);
+        }
+    }
+
+    /// Read and parse the next frame of a message known to the dialect
+    ///
+    /// Frames of unknown messages are skipped. Returns `None` at the end of the capture.
+    pub fn read_frame<M: Message>(
+        &mut self,
+    ) -> Result<Option<Captured<MavFrame<M>>>, MessageReadError> {
+        loop {
+            let Some(captured) = self.read_raw::<M>()? else {
+                return Ok(None);
+            };
+            match parse_raw(&captured.frame) {
+                Ok((header, msg)) => {
+                    let protocol_version = captured.frame.version();
+                    return Ok(Some(captured.map(|_| MavFrame {
+                        header,
+                        msg,
+                        protocol_version,
+                    })));
+                }
+                Err(ParserError::UnknownMessage { .. }) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Iterator over the remaining raw frames, see [`PcapReader::read_raw`]
+    pub fn raw_frames<M: Message>(&mut self) -> RawFrames<'_, R, M> {
+        RawFrames {
+            reader: self,
+            _message: PhantomData,
+        }
+    }
+
+    /// Iterator over the remaining frames, see [`PcapReader::read_frame`]
+    pub fn frames<M: Message>(&mut self) -> Frames<'_, R, M> {
+        Frames {
+            reader: self,
+            _message: PhantomData,
+        }
+    }
+
+    /// Extract the frames of a packet
+    fn process<M: Message>(&mut self, packet: &Packet) {
+        let Some(ip) = link_payload(packet.link_type, &packet.data) else {
+            return;
+        };
+        let Some((source, destination, protocol, payload)) = ip_payload(ip) else {
+            return;
+        };
+        match protocol {
+            IP_PROTOCOL_UDP if payload.len() >= 8 => {
+                let length = usize::from(read_u16(&payload[4..], true)).clamp(8, payload.len());
+                let source = SocketAddr::new(source, read_u16(payload, true));
+                let destination = SocketAddr::new(destination, read_u16(&payload[2..], true));
+                // every datagram starts with a frame
+                let mut synced = true;
+                let (frames, _) = extract_frames::<M>(&payload[8..length], &mut synced, true);
+                self.frames.extend(frames.into_iter().map(|frame| Captured {
+                    timestamp: packet.timestamp,
+                    transport: Transport::Udp,
+                    source,
+                    destination,
+                    frame,
+                }));
+            }
+            IP_PROTOCOL_TCP if payload.len() >= 20 => {
+                let data_offset = usize::from(payload[12] >> 4) * 4;
+                let Some(data) = payload.get(data_offset..) else {
+                    return;
+                };
+                let flags = payload[13];
+                let source = SocketAddr::new(source, read_u16(payload, true));
+                let destination = SocketAddr::new(destination, read_u16(&payload[2..], true));
+                let key = (source, destination);
+                let stream = self.streams.entry(key).or_default();
+                stream.push(read_u32(&payload[4..], true), flags & TCP_SYN != 0, data);
+
+                let (frames, consumed) =
+                    extract_frames::<M>(&stream.buffer, &mut stream.synced, false);
+                stream.buffer.drain(..consumed);
+                self.frames.extend(frames.into_iter().map(|frame| Captured {
+                    timestamp: packet.timestamp,
+                    transport: Transport::Tcp,
+                    source,
+                    destination,
+                    frame,
+                }));
+                if flags & (TCP_FIN | TCP_RST) != 0 {
+                    self.streams.remove(&key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Read the next packet of the capture, skipping other pcapng blocks
+    fn read_packet(&mut self) -> io::Result<Option<Packet>> {
+        let (big_endian, nanos, link_type) = match &self.format {
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type,
+            } => (*big_endian, *nanos, *link_type),
+            Format::Pcapng { .. } => return self.read_pcapng_packet(),
+        };
+        let mut header = [0; 16];
+        if !read_or_end(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+        let seconds = u64::from(read_u32(&header, big_endian));
+        let fraction = u64::from(read_u32(&header[4..], big_endian));
+        let length = read_u32(&header[8..], big_endian) as usize;
+        let Some(data) = self.read_body(length)? else {
+            return Ok(None);
+        };
+        let fraction = if nanos { fraction / 1000 } else { fraction };
+        Ok(Some(Packet {
+            timestamp: seconds * 1_000_000 + fraction,
+            link_type,
+            data,
+        }))
+    }
+
+    fn read_pcapng_packet(&mut self) -> io::Result<Option<Packet>> {
+        loop {
+            let Some((block_type, body)) = self.read_pcapng_block()? else {
+                return Ok(None);
+            };
+            let Format::Pcapng {
+                big_endian,
+                interfaces,
+            } = &mut self.format
+            else {
+                unreachable!()
+            };
+            let big_endian = *big_endian;
+            let (interface, timestamp, data) = match block_type {
+                PCAPNG_INTERFACE_DESCRIPTION if body.len() >= 8 => {
+                    interfaces.push(Interface {
+                        link_type: u32::from(read_u16(&body, big_endian)),
+                        resolution: interface_resolution(&body[8..], big_endian),
+                    });
+                    continue;
+                }
+                PCAPNG_ENHANCED_PACKET if body.len() >= 20 => {
+                    let interface = read_u32(&body, big_endian) as usize;
+                    let timestamp = (u64::from(read_u32(&body[4..], big_endian)) << 32)
+                        | u64::from(read_u32(&body[8..], big_endian));
+                    let length = read_u32(&body[12..], big_endian) as usize;
+                    let data = body[20..].get(..length).unwrap_or(&body[20..]);
+                    (interface, timestamp, data)
+                }
+                PCAPNG_PACKET if body.len() >= 20 => {
+                    let interface = usize::from(read_u16(&body, big_endian));
+                    let timestamp = (u64::from(read_u32(&body[4..], big_endian)) << 32)
+                        | u64::from(read_u32(&body[8..], big_endian));
+                    let length = read_u32(&body[12..], big_endian) as usize;
+                    let data = body[20..].get(..length).unwrap_or(&body[20..]);
+                    (interface, timestamp, data)
+                }
+                // simple packets have no timestamp
+                PCAPNG_SIMPLE_PACKET if body.len() >= 4 => {
+                    let length = read_u32(&body, big_endian) as usize;
+                    let data = body[4..].get(..length).unwrap_or(&body[4..]);
+                    (0, 0, data)
+                }
+                PCAPNG_SECTION_HEADER => unreachable!(),
+                _ => continue,
+            };
+            let Some(interface) = interfaces.get(interface) else {
+                return Err(invalid_data("Packet of an undescribed interface"));
+            };
+            let timestamp =
+                (u128::from(timestamp) * 1_000_000 / u128::from(interface.resolution)) as u64;
+            return Ok(Some(Packet {
+                timestamp,
+                link_type: interface.link_type,
+                data: data.to_vec(),
+            }));
+        }
+    }
+
+    /// Read the next pcapng block, reading section headers
+    fn read_pcapng_block(&mut self) -> io::Result<Option<(u32, Vec<u8>)>> {
+        loop {
+            let mut block_type = [0; 4];
+            if !read_or_end(&mut self.reader, &mut block_type)? {
+                return Ok(None);
+            }
+            if u32::from_le_bytes(block_type) == PCAPNG_SECTION_HEADER {
+                self.read_section_header()?;
+                continue;
+            }
+            let Format::Pcapng { big_endian, .. } = self.format else {
+                unreachable!()
+            };
+            let mut length = [0; 4];
+            if !read_or_end(&mut self.reader, &mut length)? {
+                return Ok(None);
+            }
+            let length = read_u32(&length, big_endian) as usize;
+            if length < 12 || length % 4 != 0 {
+                return Err(invalid_data("Invalid pcapng block length"));
+            }
+            let Some(mut body) = self.read_body(length - 8)? else {
+                return Ok(None);
+            };
+            // trailing copy of the block length
+            body.truncate(length - 12);
+            return Ok(Some((read_u32(&block_type, big_endian), body)));
+        }
+    }
+
+    /// Read a section header block after its block type, which starts a new list of interfaces
+    fn read_section_header(&mut self) -> io::Result<()> {
+        let mut header = [0; 8];
+        self.reader.read_exact(&mut header)?;
+        let big_endian = match &header[4..] {
+            magic if read_u32(magic, false) == PCAPNG_BYTE_ORDER_MAGIC => false,
+            magic if read_u32(magic, true) == PCAPNG_BYTE_ORDER_MAGIC => true,
+            _ => return Err(invalid_data("Invalid pcapng byte order")),
+        };
+        let length = read_u32(&header, big_endian) as usize;
+        if length < 16 || length % 4 != 0 {
+            return Err(invalid_data("Invalid pcapng block length"));
+        }
+        if self.read_body(length - 12)?.is_none() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.format = Format::Pcapng {
+            big_endian,
+            interfaces: Vec::new(),
+        };
+        Ok(())
+    }
+
+    /// Read a record body, returning `None` if the capture ends before it
+    fn read_body(&mut self, length: usize) -> io::Result<Option<Vec<u8>>> {
+        if length > MAX_BLOCK_SIZE {
+            return Err(invalid_data("Record of the capture is too large"));
+        }
+        let mut body = vec![0; length];
+        Ok(read_or_end(&mut self.reader, &mut body)?.then_some(body))
+    }
+}
+
+/// Read exactly `buf.len()` bytes, returning `false` if the capture ends before
+fn read_or_end(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        // captures of a killed capture process often end with a truncated record
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u16(bytes: &[u8], big_endian: bool) -> u16 {
+    let bytes = [bytes[0], bytes[1]];
+    if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    }
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+/// Timestamp units per second of an interface description's options
+fn interface_resolution(mut options: &[u8], big_endian: bool) -> u64 {
+    while options.len() >= 4 {
+        let code = read_u16(options, big_endian);
+        let length = usize::from(read_u16(&options[2..], big_endian));
+        let Some(value) = options[4..].get(..length) else {
+            break;
+        };
+        if code == PCAPNG_OPTION_TSRESOL && length == 1 {
+            let exponent = u32::from(value[0] & 0x7f);
+            return if value[0] & 0x80 == 0 {
+                10u64.checked_pow(exponent)
+            } else {
+                2u64.checked_pow(exponent)
+            }
+            .unwrap_or(1_000_000);
+        }
+        options = options.get(4 + ((length + 3) & !3)..).unwrap_or(&[]);
+    }
+    1_000_000
+}
+
+/// IP packet of a link layer packet
+fn link_payload(link_type: u32, data: &[u8]) -> Option<&[u8]> {
+    let (ethertype, offset) = match link_type {
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => return Some(data),
+        // the address family is in the byte order of the capturing host, the IP version is checked instead
+        LINKTYPE_NULL => return data.get(4..),
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ethertype = read_u16(data.get(offset..offset + 2)?, true);
+            while ethertype == ETHERTYPE_VLAN || ethertype == ETHERTYPE_QINQ {
+                offset += 4;
+                ethertype = read_u16(data.get(offset..offset + 2)?, true);
+            }
+            (ethertype, offset + 2)
+        }
+        LINKTYPE_LINUX_SLL => (read_u16(data.get(14..16)?, true), 16),
+        LINKTYPE_LINUX_SLL2 => (read_u16(data.get(0..2)?, true), 20),
+        _ => return None,
+    };
+    match ethertype {
+        ETHERTYPE_IPV4 | ETHERTYPE_IPV6 => data.get(offset..),
+        _ => None,
+    }
+}
+
+/// Addresses, protocol and payload of an IP packet
+fn ip_payload(ip: &[u8]) -> Option<(IpAddr, IpAddr, u8, &[u8])> {
+    match ip.first()? >> 4 {
+        4 => {
+            let header_length = usize::from(ip[0] & 0x0f) * 4;
+            if ip.len() < 20 || header_length < 20 {
+                return None;
+            }
+            // fragments are not reassembled
+            if read_u16(&ip[6..], true) & 0x3fff != 0 {
+                return None;
+            }
+            let total_length = usize::from(read_u16(&ip[2..], true)).min(ip.len());
+            let source = Ipv4Addr::from(<[u8; 4]>::try_from(&ip[12..16]).unwrap());
+            let destination = Ipv4Addr::from(<[u8; 4]>::try_from(&ip[16..20]).unwrap());
+            Some((
+                source.into(),
+                destination.into(),
+                ip[9],
+                ip.get(header_length..total_length)?,
+            ))
+        }
+        6 => {
+            if ip.len() < 40 {
+                return None;
+            }
+            let source = Ipv6Addr::from(<[u8; 16]>::try_from(&ip[8..24]).unwrap());
+            let destination = Ipv6Addr::from(<[u8; 16]>::try_from(&ip[24..40]).unwrap());
+            let end = (40 + usize::from(read_u16(&ip[4..], true))).min(ip.len());
+            let mut next_header = ip[6];
+            let mut payload = &ip[40..end];
+            // hop-by-hop, routing and destination options extension headers
+            while matches!(next_header, 0 | 43 | 60) {
+                let length = (usize::from(*payload.get(1)?) + 1) * 8;
+                next_header = payload[0];
+                payload = payload.get(length..)?;
+            }
+            Some((source.into(), destination.into(), next_header, payload))
+        }
+        _ => None,
+    }
+}
+
+/// Extract the frames at the start of `bytes`, returning them and the number of bytes consumed
+///
+/// Unless `complete`, the bytes of a frame that is not complete yet are not consumed.
+fn extract_frames<M: Message>(
+    bytes: &[u8],
+    synced: &mut bool,
+    complete: bool,
+) -> (Vec<MAVLinkMessageRaw>, usize) {
+    let mut frames = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        match check_record::<M>(&bytes[start..], *synced, None) {
+            FrameCheck::Incomplete(_) if !complete => break,
+            FrameCheck::Valid((frame, checked)) => {
+                start += frame.raw_bytes().len();
+                *synced |= checked;
+                frames.push(frame);
+            }
+            _ => {
+                start += 1;
+                *synced = false;
+            }
+        }
+    }
+    (frames, start)
+}
+
+/// Iterator over the raw frames of a [`PcapReader`]
+pub struct RawFrames<'a, R, M> {
+    reader: &'a mut PcapReader<R>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<R: Read, M: Message> Iterator for RawFrames<'_, R, M> {
+    type Item = io::Result<Captured<MAVLinkMessageRaw>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_raw::<M>().transpose()
+    }
+}
+
+/// Iterator over the frames of a [`PcapReader`]
+pub struct Frames<'a, R, M> {
+    reader: &'a mut PcapReader<R>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<R: Read, M: Message> Iterator for Frames<'_, R, M> {
+    type Item = Result<Captured<MavFrame<M>>, MessageReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_frame().transpose()
+    }
+}
+
+/// Writes frames as UDP packets of a pcap capture with raw IP packets
+pub struct PcapWriter<W> {
+    writer: W,
+    identification: u16,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Create a writer, writing the header of the capture
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(24);
+        header.extend_from_slice(&PCAP_MAGIC.to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&4u16.to_le_bytes());
+        // time zone and timestamp accuracy
+        header.extend_from_slice(&[0; 8]);
+        header.extend_from_slice(&65_535u32.to_le_bytes());
+        header.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer,
+            identification: 0,
+        })
+    }
+
+    /// Write a raw frame as a UDP datagram
+    pub fn write_raw(
+        &mut self,
+        timestamp: u64,
+        source: SocketAddr,
+        destination: SocketAddr,
+        frame: &MAVLinkMessageRaw,
+    ) -> io::Result<()> {
+        self.write_datagram(timestamp, source, destination, frame.raw_bytes())
+    }
+
+    /// Write the frame of a message as a UDP datagram, returning the size of the frame
+    pub fn write_message<M: Message>(
+        &mut self,
+        timestamp: u64,
+        source: SocketAddr,
+        destination: SocketAddr,
+        frame: &MavFrame<M>,
+    ) -> Result<usize, MessageWriteError> {
+        let mut buf = [0; MAX_FRAME_SIZE];
+        let len = frame.ser(&mut buf)?;
+        self.write_datagram(timestamp, source, destination, &buf[..len])?;
+        Ok(len)
+    }
+
+    /// Write a UDP datagram, captured at `timestamp` in microseconds since the Unix epoch
+    ///
+    /// The source and destination must be of the same IP version.
+    pub fn write_datagram(
+        &mut self,
+        timestamp: u64,
+        source: SocketAddr,
+        destination: SocketAddr,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        let udp_length = u16::try_from(8 + payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Datagram too large"))?;
+        let mut udp = Vec::with_capacity(usize::from(udp_length));
+        udp.extend_from_slice(&source.port().to_be_bytes());
+        udp.extend_from_slice(&destination.port().to_be_bytes());
+        udp.extend_from_slice(&udp_length.to_be_bytes());
+        udp.extend_from_slice(&[0, 0]);
+        udp.extend_from_slice(payload);
+
+        let mut packet = Vec::with_capacity(40 + udp.len());
+        let mut pseudo_header = Vec::with_capacity(40);
+        match (source.ip(), destination.ip()) {
+            (IpAddr::V4(source), IpAddr::V4(destination)) => {
+                let total_length = 20 + udp_length;
+                packet.extend_from_slice(&[0x45, 0]);
+                packet.extend_from_slice(&total_length.to_be_bytes());
+                packet.extend_from_slice(&self.identification.to_be_bytes());
+                // don't fragment, time to live, protocol and header checksum
+                packet.extend_from_slice(&[0x40, 0, 64, IP_PROTOCOL_UDP, 0, 0]);
+                packet.extend_from_slice(&source.octets());
+                packet.extend_from_slice(&destination.octets());
+                let checksum = internet_checksum(&[&packet]);
+                packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+                self.identification = self.identification.wrapping_add(1);
+
+                pseudo_header.extend_from_slice(&source.octets());
+                pseudo_header.extend_from_slice(&destination.octets());
+                pseudo_header.extend_from_slice(&[0, IP_PROTOCOL_UDP]);
+                pseudo_header.extend_from_slice(&udp_length.to_be_bytes());
+            }
+            (IpAddr::V6(source), IpAddr::V6(destination)) => {
+                packet.extend_from_slice(&[0x60, 0, 0, 0]);
+                packet.extend_from_slice(&udp_length.to_be_bytes());
+                packet.extend_from_slice(&[IP_PROTOCOL_UDP, 64]);
+                packet.extend_from_slice(&source.octets());
+                packet.extend_from_slice(&destination.octets());
+
+                pseudo_header.extend_from_slice(&source.octets());
+                pseudo_header.extend_from_slice(&destination.octets());
+                pseudo_header.extend_from_slice(&u32::from(udp_length).to_be_bytes());
+                pseudo_header.extend_from_slice(&[0, 0, 0, IP_PROTOCOL_UDP]);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Source and destination are of different IP versions",
+                ))
+            }
+        }
+        let checksum = match internet_checksum(&[&pseudo_header, &udp]) {
+            0 => 0xffff,
+            checksum => checksum,
+        };
+        udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+        packet.extend_from_slice(&udp);
+
+        let mut header = Vec::with_capacity(16);
+        header.extend_from_slice(&((timestamp / 1_000_000) as u32).to_le_bytes());
+        header.extend_from_slice(&((timestamp % 1_000_000) as u32).to_le_bytes());
+        header.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&packet)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// The underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Unwrap the underlying writer, without flushing it
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Ones' complement checksum of IP, UDP and TCP headers
+fn internet_checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum = 0u32;
+    for part in parts {
+        for word in part.chunks(2) {
+            let word = u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+            sum += u32::from(word);
+        }
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
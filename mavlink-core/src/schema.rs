@@ -0,0 +1,212 @@
+//! Metadata of the messages and enums of a message set, generated from the message definitions.
+//!
+//! With the `message-schemas` feature of the `mavlink` crate, every message struct has a [`MessageSchema`] in
+//! [`MessageDataSchema::SCHEMA`], listing its fields in the order they are serialized, with their types and the enum
+//! they take values of. Every `MavMessage` implements [`MessageFields`] to look up schemas and to visit the fields of
+//! a message without knowing its type, which is what generic tooling like the table exporters in
+//! [`export`](crate::export) is built on.
+
+use crate::{Message, MessageData};
+
+/// Primitive type of a field or of the elements of an array field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    UInt8,
+    Int8,
+    UInt16,
+    Int16,
+    UInt32,
+    Int32,
+    UInt64,
+    Int64,
+    Float,
+    Double,
+    /// A character, arrays of characters are strings
+    Char,
+}
+
+impl FieldType {
+    /// Size of the type in bytes
+    pub const fn size(self) -> usize {
+        match self {
+            Self::UInt8 | Self::Int8 | Self::Char => 1,
+            Self::UInt16 | Self::Int16 => 2,
+            Self::UInt32 | Self::Int32 | Self::Float => 4,
+            Self::UInt64 | Self::Int64 | Self::Double => 8,
+        }
+    }
+}
+
+/// An entry of an enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnumEntry {
+    pub name: &'static str,
+    pub value: u64,
+}
+
+/// Name and entries of an enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnumSchema {
+    /// Name of the generated type, like `MavState`
+    pub name: &'static str,
+    /// Whether the entries are flags that can be combined
+    pub bitmask: bool,
+    pub entries: &'static [EnumEntry],
+}
+
+impl EnumSchema {
+    /// Name of the entry with the given value
+    pub fn entry_name(&self, value: u64) -> Option<&'static str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.value == value)
+            .map(|entry| entry.name)
+    }
+}
+
+/// A field of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSchema {
+    /// Name of the field of the generated struct, fields named `type` are called `mavtype`
+    pub name: &'static str,
+    /// Type of the field, or of its elements if it is an array
+    pub field_type: FieldType,
+    pub array_length: Option<usize>,
+    /// Enum the values of the field are entries of
+    pub enum_schema: Option<&'static EnumSchema>,
+    /// Whether the field is a MAVLink 2 extension field
+    pub extension: bool,
+}
+
+/// Name, id and fields of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSchema {
+    pub id: u32,
+    pub name: &'static str,
+    /// Fields in the order they are serialized
+    pub fields: &'static [FieldSchema],
+}
+
+impl MessageSchema {
+    pub fn field(&self, name: &str) -> Option<&'static FieldSchema> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// Value of a field, enum fields have the value of their entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue<'a> {
+    UInt8(u8),
+    Int8(i8),
+    UInt16(u16),
+    Int16(i16),
+    UInt32(u32),
+    Int32(i32),
+    UInt64(u64),
+    Int64(i64),
+    Float(f32),
+    Double(f64),
+    Char(u8),
+    UInt8Array(&'a [u8]),
+    Int8Array(&'a [i8]),
+    UInt16Array(&'a [u16]),
+    Int16Array(&'a [i16]),
+    UInt32Array(&'a [u32]),
+    Int32Array(&'a [i32]),
+    UInt64Array(&'a [u64]),
+    Int64Array(&'a [i64]),
+    FloatArray(&'a [f32]),
+    DoubleArray(&'a [f64]),
+    /// A string, padded with zeros if it is shorter than the field
+    CharArray(&'a [u8]),
+}
+
+impl FieldValue<'_> {
+    /// Number of elements of an array, `None` for single values
+    pub fn array_len(&self) -> Option<usize> {
+        Some(match self {
+            Self::UInt8Array(values) | Self::CharArray(values) => values.len(),
+            Self::Int8Array(values) => values.len(),
+            Self::UInt16Array(values) => values.len(),
+            Self::Int16Array(values) => values.len(),
+            Self::UInt32Array(values) => values.len(),
+            Self::Int32Array(values) => values.len(),
+            Self::UInt64Array(values) => values.len(),
+            Self::Int64Array(values) => values.len(),
+            Self::FloatArray(values) => values.len(),
+            Self::DoubleArray(values) => values.len(),
+            _ => return None,
+        })
+    }
+
+    /// Element of an array, `None` for single values or if `index` is out of bounds
+    pub fn element(&self, index: usize) -> Option<FieldValue<'static>> {
+        Some(match self {
+            Self::UInt8Array(values) => FieldValue::UInt8(*values.get(index)?),
+            Self::Int8Array(values) => FieldValue::Int8(*values.get(index)?),
+            Self::UInt16Array(values) => FieldValue::UInt16(*values.get(index)?),
+            Self::Int16Array(values) => FieldValue::Int16(*values.get(index)?),
+            Self::UInt32Array(values) => FieldValue::UInt32(*values.get(index)?),
+            Self::Int32Array(values) => FieldValue::Int32(*values.get(index)?),
+            Self::UInt64Array(values) => FieldValue::UInt64(*values.get(index)?),
+            Self::Int64Array(values) => FieldValue::Int64(*values.get(index)?),
+            Self::FloatArray(values) => FieldValue::Float(*values.get(index)?),
+            Self::DoubleArray(values) => FieldValue::Double(*values.get(index)?),
+            Self::CharArray(values) => FieldValue::Char(*values.get(index)?),
+            _ => return None,
+        })
+    }
+
+    /// The value of an integer, `None` for floats and arrays
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::UInt8(value) | Self::Char(value) => Some(value.into()),
+            Self::UInt16(value) => Some(value.into()),
+            Self::UInt32(value) => Some(value.into()),
+            Self::UInt64(value) => Some(value),
+            Self::Int8(value) => u64::try_from(value).ok(),
+            Self::Int16(value) => u64::try_from(value).ok(),
+            Self::Int32(value) => u64::try_from(value).ok(),
+            Self::Int64(value) => u64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value of a number as a float, `None` for arrays
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Float(value) => Some(value.into()),
+            Self::Double(value) => Some(value),
+            Self::Int8(value) => Some(value.into()),
+            Self::Int16(value) => Some(value.into()),
+            Self::Int32(value) => Some(value.into()),
+            Self::Int64(value) => Some(value as f64),
+            _ => self.as_u64().map(|value| value as f64),
+        }
+    }
+}
+
+/// Schema of a generated message struct
+pub trait MessageDataSchema: MessageData {
+    /// Fields of the message
+    const SCHEMA: MessageSchema;
+}
+
+/// Schemas and dynamic field access of the messages of a message set
+///
+/// Implemented by the generated `MavMessage` enums.
+pub trait MessageFields: Message {
+    /// Schemas of all messages of the message set
+    fn schemas() -> &'static [MessageSchema];
+
+    /// Schema of a message id
+    fn schema_from_id(id: u32) -> Option<&'static MessageSchema> {
+        Self::schemas().iter().find(|schema| schema.id == id)
+    }
+
+    /// Schema of the message
+    fn schema(&self) -> &'static MessageSchema;
+
+    /// Call `f` with every field of the message, in the order of the schema's fields
+    fn for_each_field(&self, f: &mut dyn FnMut(&'static FieldSchema, FieldValue<'_>));
+}
@@ -1,7 +1,14 @@
 use crate::MAVLinkV2MessageRaw;
 
+#[cfg(not(feature = "std"))]
+use core::cell::{RefCell, RefMut};
+#[cfg(feature = "std")]
 use std::time::SystemTime;
-use std::{collections::HashMap, sync::Mutex};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
 
 use crate::MAVLINK_IFLAG_SIGNED;
 
@@ -28,15 +35,84 @@ pub struct SigningConfig {
 // mutable state of signing per connection
 pub(crate) struct SigningState {
     timestamp: u64,
-    stream_timestamps: HashMap<(u8, u8, u8), u64>,
+    stream_timestamps: StreamTimestamps,
 }
 
 /// MAVLink 2 message signing data
 ///
 /// Contains a [`SigningConfig`] as well as a mutable state that is reused for all messages in a connection.  
+///
+/// Without the `std` feature there is no system clock available, the signing timestamp is then
+/// only advanced by sent messages, the timestamps of received messages and [`SigningData::set_timestamp`].
+/// Peers reject new signing streams whose timestamps lag more than a minute behind, so `no_std` senders
+/// should set the timestamp from a clock source, like a GPS time or an RTC, before signing messages.
 pub struct SigningData {
     pub(crate) config: SigningConfig,
+    #[cfg(feature = "std")]
     pub(crate) state: Mutex<SigningState>,
+    #[cfg(not(feature = "std"))]
+    pub(crate) state: RefCell<SigningState>,
+}
+
+// signing streams are identified by (link id, system id, component id)
+type StreamKey = (u8, u8, u8);
+
+// last accepted timestamp of each signing stream
+#[cfg(feature = "std")]
+struct StreamTimestamps(HashMap<StreamKey, u64>);
+
+// without an allocator only a fixed number of streams is tracked,
+// when full the stream with the oldest timestamp is replaced
+#[cfg(not(feature = "std"))]
+struct StreamTimestamps([Option<(StreamKey, u64)>; MAX_SIGNING_STREAMS]);
+
+#[cfg(not(feature = "std"))]
+const MAX_SIGNING_STREAMS: usize = 16;
+
+impl StreamTimestamps {
+    #[cfg(feature = "std")]
+    fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn new() -> Self {
+        Self([None; MAX_SIGNING_STREAMS])
+    }
+
+    #[cfg(feature = "std")]
+    fn get(&self, key: &StreamKey) -> Option<&u64> {
+        self.0.get(key)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn get(&self, key: &StreamKey) -> Option<&u64> {
+        self.0
+            .iter()
+            .flatten()
+            .find(|(stream, _)| stream == key)
+            .map(|(_, timestamp)| timestamp)
+    }
+
+    #[cfg(feature = "std")]
+    fn insert(&mut self, key: StreamKey, timestamp: u64) {
+        self.0.insert(key, timestamp);
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn insert(&mut self, key: StreamKey, timestamp: u64) {
+        let slot = self
+            .0
+            .iter()
+            .position(|entry| matches!(entry, Some((stream, _)) if *stream == key))
+            .or_else(|| self.0.iter().position(Option::is_none))
+            .unwrap_or_else(|| {
+                (0..MAX_SIGNING_STREAMS)
+                    .min_by_key(|i| self.0[*i].map_or(0, |(_, timestamp)| timestamp))
+                    .unwrap_or(0)
+            });
+        self.0[slot] = Some((key, timestamp));
+    }
 }
 
 impl SigningConfig {
@@ -64,24 +140,50 @@ impl SigningData {
     pub fn from_config(config: SigningConfig) -> Self {
         Self {
             config,
+            #[cfg(feature = "std")]
             state: Mutex::new(SigningState {
                 timestamp: 0,
-                stream_timestamps: HashMap::new(),
+                stream_timestamps: StreamTimestamps::new(),
+            }),
+            #[cfg(not(feature = "std"))]
+            state: RefCell::new(SigningState {
+                timestamp: 0,
+                stream_timestamps: StreamTimestamps::new(),
             }),
         }
     }
 
-    /// Verify the signature of a MAVLink 2 message.
-    ///
-    /// This respects the `allow_unsigned` parameter in [`SigningConfig`].
-    pub fn verify_signature(&self, message: &MAVLinkV2MessageRaw) -> bool {
+    #[cfg(feature = "std")]
+    fn lock_state(&self) -> MutexGuard<'_, SigningState> {
         // The code that holds the mutex lock is not expected to panic, therefore the expect is justified.
         // The only issue that might cause a panic, presuming the opertions on the message buffer are sound,
         // is the `SystemTime::now()` call in `get_current_timestamp()`.
-        let mut state = self
-            .state
+        self.state
             .lock()
-            .expect("Code holding MutexGuard should not panic.");
+            .expect("Code holding MutexGuard should not panic.")
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn lock_state(&self) -> RefMut<'_, SigningState> {
+        // the state is only borrowed within `verify_signature` and `sign_message`, which do not call each other
+        self.state.borrow_mut()
+    }
+
+    /// Advance the signing timestamp to the current time.
+    ///
+    /// The timestamp is in units of 10 microseconds since 1st January 2015 GMT, see [`signing_timestamp`].
+    /// It never goes backwards, earlier timestamps are ignored. With the `std` feature the system clock is
+    /// used in addition.
+    pub fn set_timestamp(&self, timestamp: u64) {
+        let mut state = self.lock_state();
+        state.timestamp = u64::max(state.timestamp, timestamp);
+    }
+
+    /// Verify the signature of a MAVLink 2 message.
+    ///
+    /// This respects the `allow_unsigned` parameter in [`SigningConfig`].
+    pub fn verify_signature(&self, message: &MAVLinkV2MessageRaw) -> bool {
+        let mut state = self.lock_state();
         if message.incompatibility_flags() & MAVLINK_IFLAG_SIGNED > 0 {
             state.timestamp = u64::max(state.timestamp, Self::get_current_timestamp());
             let timestamp = message.signature_timestamp();
@@ -120,13 +222,7 @@ impl SigningData {
     /// Sign a MAVLink 2 message if its incompatibility flag is set accordingly.
     pub fn sign_message(&self, message: &mut MAVLinkV2MessageRaw) {
         if message.incompatibility_flags() & MAVLINK_IFLAG_SIGNED > 0 {
-            // The code that holds the mutex lock is not expected to panic, therefore the expect is justified.
-            // The only issue that might cause a panic, presuming the opertions on the message buffer are sound,
-            // is the `SystemTime::now()` call in `get_current_timestamp()`.
-            let mut state = self
-                .state
-                .lock()
-                .expect("Code holding MutexGuard should not panic.");
+            let mut state = self.lock_state();
             state.timestamp = u64::max(state.timestamp, Self::get_current_timestamp());
             let ts_bytes = u64::to_le_bytes(state.timestamp);
             message
@@ -144,16 +240,27 @@ impl SigningData {
         }
     }
 
+    #[cfg(feature = "std")]
     fn get_current_timestamp() -> u64 {
         // fallback to 0 if the system time appears to be before epoch
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .map(|n| n.as_micros())
             .unwrap_or(0);
-        // use 1st January 2015 GMT as offset, fallback to 0 if before that date, the used 48 bit of this will overflow in 2104
-        ((now
-            .checked_sub(1420070400u128 * 1000000u128)
-            .unwrap_or_default())
-            / 10u128) as u64
+        signing_timestamp(now as u64)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn get_current_timestamp() -> u64 {
+        // no clock available, rely on the timestamps of sent and received messages
+        0
     }
 }
+
+/// Signing timestamp of a time given in microseconds since the Unix epoch
+///
+/// Signing timestamps count units of 10 microseconds since 1st January 2015 GMT, times before that date result in 0.
+/// The 48 bits of the timestamp that are sent will overflow in 2104.
+pub fn signing_timestamp(unix_micros: u64) -> u64 {
+    unix_micros.saturating_sub(1_420_070_400 * 1_000_000) / 10
+}
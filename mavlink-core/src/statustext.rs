@@ -0,0 +1,510 @@
+//! Reassembly of chunked `STATUSTEXT` messages.
+//!
+//! Texts longer than 50 characters are split by the sender into several `STATUSTEXT` messages
+//! that share a non-zero `id` and are ordered by `chunk_seq`, both of which are
+//! [MAVLink 2 message extensions]. The last chunk of a text is terminated by a NUL byte,
+//! see <https://mavlink.io/en/messages/common.html#STATUSTEXT>.
+//!
+//! The [`StatusTextAssembler`] collects these chunks per sending system and component and yields
+//! a decoded [`StatusText`] once all chunks of a text have been received.
+//! Since this module works on the raw message payload, it does not depend on a specific message set
+//! and works regardless of the `emit-extensions` feature.
+//!
+//! With the `log` or `tracing` feature enabled, reassembled texts can be forwarded to the
+//! respective ecosystem at the level matching their `MAV_SEVERITY`.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::statustext::StatusTextAssembler;
+//!
+//! let mut assembler = StatusTextAssembler::new();
+//! loop {
+//!     let raw = connection.recv_raw()?;
+//!     if let Some(text) = assembler.push_raw(&raw) {
+//!         text.forward_to_log();
+//!     }
+//!     for text in assembler.take_expired() {
+//!         text.forward_to_log();
+//!     }
+//! }
+//! ```
+//!
+//! [MAVLink 2 message extensions]: https://mavlink.io/en/guide/define_xml_element.html#message_extensions
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
+
+/// Message ID of the `STATUSTEXT` message
+pub const STATUSTEXT_ID: u32 = 253;
+
+/// Length of the `text` field of a single `STATUSTEXT` message
+pub const STATUSTEXT_CHUNK_LEN: usize = 50;
+
+// payload layout: severity (u8), text (char[50]), id (u16, extension), chunk_seq (u8, extension)
+const STATUSTEXT_PAYLOAD_LEN: usize = 54;
+
+/// Default time after which an incomplete text is considered expired
+pub const DEFAULT_CHUNK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default maximum number of incomplete texts kept by a [`StatusTextAssembler`]
+pub const DEFAULT_MAX_PENDING: usize = 64;
+
+/// Severity of a status text, mirrors the `MAV_SEVERITY` enum of the common message set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// System is unusable. This is a "panic" condition.
+    Emergency = 0,
+    /// Action should be taken immediately.
+    Alert = 1,
+    /// Action must be taken immediately.
+    Critical = 2,
+    /// Indicates an error in secondary/redundant systems.
+    Error = 3,
+    /// Indicates about a possible future error if this is not resolved within a given timeframe.
+    Warning = 4,
+    /// An unusual event has occurred, though not an error condition.
+    Notice = 5,
+    /// Normal operational messages.
+    Info = 6,
+    /// Useful non-operational messages that can assist in debugging.
+    Debug = 7,
+}
+
+impl Severity {
+    /// Converts a raw `MAV_SEVERITY` value, values outside of the defined range are treated as [`Severity::Debug`].
+    pub fn from_raw(value: u8) -> Self {
+        match value {
+            0 => Self::Emergency,
+            1 => Self::Alert,
+            2 => Self::Critical,
+            3 => Self::Error,
+            4 => Self::Warning,
+            5 => Self::Notice,
+            6 => Self::Info,
+            _ => Self::Debug,
+        }
+    }
+
+    /// The [`log::Level`] corresponding to this severity
+    #[cfg(feature = "log")]
+    pub fn log_level(self) -> log::Level {
+        match self {
+            Self::Emergency | Self::Alert | Self::Critical | Self::Error => log::Level::Error,
+            Self::Warning => log::Level::Warn,
+            Self::Notice | Self::Info => log::Level::Info,
+            Self::Debug => log::Level::Debug,
+        }
+    }
+
+    /// The [`tracing::Level`] corresponding to this severity
+    #[cfg(feature = "tracing")]
+    pub fn tracing_level(self) -> tracing::Level {
+        match self {
+            Self::Emergency | Self::Alert | Self::Critical | Self::Error => tracing::Level::ERROR,
+            Self::Warning => tracing::Level::WARN,
+            Self::Notice | Self::Info => tracing::Level::INFO,
+            Self::Debug => tracing::Level::DEBUG,
+        }
+    }
+}
+
+/// A single `STATUSTEXT` message as received from a system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusTextChunk {
+    /// Sender system ID
+    pub system_id: u8,
+    /// Sender component ID
+    pub component_id: u8,
+    /// Raw `MAV_SEVERITY` value
+    pub severity: u8,
+    /// Text of this chunk, NUL terminated if shorter than 50 bytes
+    pub text: [u8; STATUSTEXT_CHUNK_LEN],
+    /// Unique (non-zero) ID of a chunked text, zero if the text is not chunked
+    pub id: u16,
+    /// Index of this chunk within the text
+    pub chunk_seq: u8,
+}
+
+impl StatusTextChunk {
+    /// Create a chunk from the `STATUSTEXT` payload of a message sent by the system identified by `header`.
+    ///
+    /// Missing trailing bytes, for example due to MAVLink 2 payload truncation, are treated as zero.
+    pub fn from_payload(header: &MavHeader, payload: &[u8]) -> Self {
+        let mut buf = [0u8; STATUSTEXT_PAYLOAD_LEN];
+        let len = payload.len().min(STATUSTEXT_PAYLOAD_LEN);
+        buf[..len].copy_from_slice(&payload[..len]);
+
+        let mut text = [0u8; STATUSTEXT_CHUNK_LEN];
+        text.copy_from_slice(&buf[1..=STATUSTEXT_CHUNK_LEN]);
+
+        Self {
+            system_id: header.system_id,
+            component_id: header.component_id,
+            severity: buf[0],
+            text,
+            id: u16::from_le_bytes([buf[51], buf[52]]),
+            chunk_seq: buf[53],
+        }
+    }
+
+    /// Create a chunk from a raw message, returns `None` if it is not a `STATUSTEXT` message.
+    pub fn from_raw(raw: &MAVLinkMessageRaw) -> Option<Self> {
+        if raw.message_id() != STATUSTEXT_ID {
+            return None;
+        }
+        let header = MavHeader {
+            system_id: raw.system_id(),
+            component_id: raw.component_id(),
+            sequence: raw.sequence(),
+        };
+        Some(Self::from_payload(&header, raw.payload()))
+    }
+
+    /// Create a chunk from a parsed message, returns `None` if it is not a `STATUSTEXT` message.
+    ///
+    /// The `id` and `chunk_seq` fields are only available if the message set was generated with extensions.
+    pub fn from_message<M: Message>(header: &MavHeader, msg: &M) -> Option<Self> {
+        if msg.message_id() != STATUSTEXT_ID {
+            return None;
+        }
+        let mut payload = [0u8; 255];
+        let len = msg.ser(MavlinkVersion::V2, &mut payload);
+        Some(Self::from_payload(header, &payload[..len]))
+    }
+
+    /// Bytes of this chunks text up to the terminating NUL byte
+    pub fn text_bytes(&self) -> &[u8] {
+        let len = self
+            .text
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(STATUSTEXT_CHUNK_LEN);
+        &self.text[..len]
+    }
+
+    /// Whether this is the last chunk of a text
+    pub fn is_last(&self) -> bool {
+        self.text.contains(&0)
+    }
+}
+
+/// A complete status text, reassembled from one or more `STATUSTEXT` messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusText {
+    /// Sender system ID
+    pub system_id: u8,
+    /// Sender component ID
+    pub component_id: u8,
+    /// Severity of the text
+    pub severity: Severity,
+    /// ID of the chunked text, zero if the text was not chunked
+    pub id: u16,
+    /// The decoded text, invalid UTF-8 sequences are replaced with `U+FFFD`
+    pub text: String,
+    /// `false` if some chunks did not arrive before the text expired
+    pub complete: bool,
+}
+
+impl StatusText {
+    /// Forward this text to the [`log`] crate at the level matching its severity.
+    ///
+    /// Texts are logged with the target `mavlink::statustext`.
+    #[cfg(feature = "log")]
+    pub fn forward_to_log(&self) {
+        log::log!(target: "mavlink::statustext", self.severity.log_level(), "{self}");
+    }
+
+    /// Emit this text as a [`tracing`] event at the level matching its severity.
+    ///
+    /// Events are emitted with the target `mavlink::statustext` and carry the sender IDs as fields.
+    #[cfg(feature = "tracing")]
+    pub fn forward_to_tracing(&self) {
+        macro_rules! event {
+            ($level:expr) => {
+                tracing::event!(
+                    target: "mavlink::statustext",
+                    $level,
+                    system_id = self.system_id,
+                    component_id = self.component_id,
+                    complete = self.complete,
+                    "{}",
+                    self.text
+                )
+            };
+        }
+
+        match self.severity.tracing_level() {
+            tracing::Level::ERROR => event!(tracing::Level::ERROR),
+            tracing::Level::WARN => event!(tracing::Level::WARN),
+            tracing::Level::INFO => event!(tracing::Level::INFO),
+            tracing::Level::DEBUG => event!(tracing::Level::DEBUG),
+            tracing::Level::TRACE => event!(tracing::Level::TRACE),
+        }
+    }
+}
+
+impl Display for StatusText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}:{}] {}",
+            self.system_id, self.component_id, self.text
+        )?;
+        if !self.complete {
+            write!(f, " (incomplete)")?;
+        }
+        Ok(())
+    }
+}
+
+// chunks of a text that has not been completed yet
+struct PendingText {
+    severity: u8,
+    chunks: BTreeMap<u8, Vec<u8>>,
+    last_seq: Option<u8>,
+    last_update: Instant,
+    // number of the chunk that last updated this text, orders texts for eviction
+    last_chunk: u64,
+}
+
+impl PendingText {
+    fn is_complete(&self) -> bool {
+        self.last_seq
+            .is_some_and(|last| self.chunks.len() == usize::from(last) + 1)
+    }
+}
+
+/// Reassembles chunked `STATUSTEXT` messages per sending system and component.
+///
+/// Texts that are not chunked (`id` of zero) are returned immediately. Chunked texts are returned once
+/// all chunks up to the NUL terminated last chunk have been received. Texts that do not complete within
+/// the configured timeout can be collected using [`StatusTextAssembler::take_expired`].
+///
+/// At most [`DEFAULT_MAX_PENDING`] incomplete texts are kept, when a chunk of another text arrives the text
+/// that has not received a chunk for the longest time is dropped.
+pub struct StatusTextAssembler {
+    timeout: Duration,
+    max_pending: usize,
+    chunks: u64,
+    pending: HashMap<(u8, u8, u16), PendingText>,
+}
+
+impl Default for StatusTextAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatusTextAssembler {
+    /// Create an assembler using [`DEFAULT_CHUNK_TIMEOUT`]
+    pub fn new() -> Self {
+        Self::with_timeout(DEFAULT_CHUNK_TIMEOUT)
+    }
+
+    /// Create an assembler that expires incomplete texts after `timeout` without a new chunk
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            max_pending: DEFAULT_MAX_PENDING,
+            chunks: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Set the maximum number of incomplete texts that are kept, at least one text is always kept
+    pub fn set_max_pending(&mut self, max_pending: usize) {
+        self.max_pending = max_pending.max(1);
+        while self.pending.len() > self.max_pending {
+            self.evict_oldest();
+        }
+    }
+
+    /// Add a chunk, returns the complete text if this chunk completed it.
+    pub fn push(&mut self, chunk: StatusTextChunk) -> Option<StatusText> {
+        if chunk.id == 0 {
+            return Some(StatusText {
+                system_id: chunk.system_id,
+                component_id: chunk.component_id,
+                severity: Severity::from_raw(chunk.severity),
+                id: 0,
+                text: String::from_utf8_lossy(chunk.text_bytes()).into_owned(),
+                complete: true,
+            });
+        }
+
+        let key = (chunk.system_id, chunk.component_id, chunk.id);
+        if !self.pending.contains_key(&key) && self.pending.len() >= self.max_pending {
+            self.evict_oldest();
+        }
+        let pending = self.pending.entry(key).or_insert_with(|| PendingText {
+            severity: chunk.severity,
+            chunks: BTreeMap::new(),
+            last_seq: None,
+            last_update: Instant::now(),
+            last_chunk: 0,
+        });
+        self.chunks += 1;
+        pending.last_update = Instant::now();
+        pending.last_chunk = self.chunks;
+        if chunk.is_last() {
+            pending.last_seq = Some(chunk.chunk_seq);
+        }
+        pending
+            .chunks
+            .insert(chunk.chunk_seq, chunk.text_bytes().to_vec());
+
+        if pending.is_complete() {
+            let pending = self.pending.remove(&key)?;
+            Some(Self::assemble(key, pending, true))
+        } else {
+            None
+        }
+    }
+
+    /// Add a raw message, non `STATUSTEXT` messages are ignored.
+    pub fn push_raw(&mut self, raw: &MAVLinkMessageRaw) -> Option<StatusText> {
+        StatusTextChunk::from_raw(raw).and_then(|chunk| self.push(chunk))
+    }
+
+    /// Add a parsed message, non `STATUSTEXT` messages are ignored.
+    pub fn push_message<M: Message>(&mut self, header: &MavHeader, msg: &M) -> Option<StatusText> {
+        StatusTextChunk::from_message(header, msg).and_then(|chunk| self.push(chunk))
+    }
+
+    /// Remove and return all texts that did not receive a chunk within the timeout.
+    ///
+    /// The returned texts contain all chunks received so far and are marked as incomplete.
+    pub fn take_expired(&mut self) -> Vec<StatusText> {
+        let now = Instant::now();
+        let expired: Vec<_> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.last_update) >= self.timeout)
+            .map(|(key, _)| *key)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|key| {
+                let pending = self.pending.remove(&key)?;
+                Some(Self::assemble(key, pending, false))
+            })
+            .collect()
+    }
+
+    /// Number of texts that are still waiting for chunks
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    // drop the text that has not received a chunk for the longest time
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .pending
+            .iter()
+            .min_by_key(|(_, pending)| pending.last_chunk)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.pending.remove(&key);
+        }
+    }
+
+    fn assemble(key: (u8, u8, u16), pending: PendingText, complete: bool) -> StatusText {
+        // concatenate before decoding, multi-byte characters may span chunk boundaries
+        let bytes: Vec<u8> = pending.chunks.into_values().flatten().collect();
+        StatusText {
+            system_id: key.0,
+            component_id: key.1,
+            severity: Severity::from_raw(pending.severity),
+            id: key.2,
+            text: String::from_utf8_lossy(&bytes).into_owned(),
+            complete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: u16, chunk_seq: u8, text: &[u8]) -> StatusTextChunk {
+        let mut buf = [0u8; STATUSTEXT_CHUNK_LEN];
+        buf[..text.len()].copy_from_slice(text);
+        StatusTextChunk {
+            system_id: 1,
+            component_id: 1,
+            severity: 4,
+            text: buf,
+            id,
+            chunk_seq,
+        }
+    }
+
+    #[test]
+    fn test_single_text() {
+        let mut assembler = StatusTextAssembler::new();
+        let text = assembler
+            .push(chunk(0, 0, b"PreArm: Gyros not healthy"))
+            .unwrap();
+        assert_eq!(text.text, "PreArm: Gyros not healthy");
+        assert_eq!(text.severity, Severity::Warning);
+        assert!(text.complete);
+    }
+
+    #[test]
+    fn test_chunked_text_out_of_order() {
+        let mut assembler = StatusTextAssembler::new();
+        let first = [b'a'; STATUSTEXT_CHUNK_LEN];
+        // "é" split across the chunk boundary
+        let mut second = [b'b'; STATUSTEXT_CHUNK_LEN];
+        second[49] = 0xC3;
+
+        assert!(assembler.push(chunk(7, 2, &[0xA9, b'c'])).is_none());
+        assert!(assembler.push(chunk(7, 0, &first)).is_none());
+        let text = assembler.push(chunk(7, 1, &second)).unwrap();
+
+        assert_eq!(text.id, 7);
+        assert_eq!(text.text.len(), 2 * STATUSTEXT_CHUNK_LEN + 2);
+        assert!(text.text.ends_with("bbéc"));
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_expired_text() {
+        let mut assembler = StatusTextAssembler::with_timeout(Duration::ZERO);
+        assert!(assembler
+            .push(chunk(3, 0, &[b'x'; STATUSTEXT_CHUNK_LEN]))
+            .is_none());
+        let expired = assembler.take_expired();
+        assert_eq!(expired.len(), 1);
+        assert!(!expired[0].complete);
+        assert_eq!(expired[0].text, "x".repeat(STATUSTEXT_CHUNK_LEN));
+    }
+
+    #[test]
+    fn test_max_pending() {
+        let mut assembler = StatusTextAssembler::new();
+        assembler.set_max_pending(2);
+        for id in 1..=3 {
+            assert!(assembler
+                .push(chunk(id, 0, &[b'x'; STATUSTEXT_CHUNK_LEN]))
+                .is_none());
+        }
+        assert_eq!(assembler.pending(), 2);
+        // the oldest text was dropped, the others can still complete
+        assert!(assembler.push(chunk(1, 1, b"y")).is_none());
+        assert!(assembler.push(chunk(3, 1, b"y")).is_some());
+    }
+
+    #[test]
+    fn test_truncated_payload() {
+        let header = MavHeader::default();
+        let chunk = StatusTextChunk::from_payload(&header, &[6, b'h', b'i']);
+        assert_eq!(chunk.text_bytes(), b"hi");
+        assert_eq!(chunk.id, 0);
+        assert!(chunk.is_last());
+    }
+}
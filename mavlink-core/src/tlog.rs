@@ -0,0 +1,568 @@
+//! Reader and writer for telemetry logs (`.tlog`) as written by QGroundControl and MAVProxy.
+//!
+//! A tlog is a sequence of records, each made of the time the frame was logged at as a big-endian 64 bit count of
+//! microseconds since the Unix epoch, followed by the MAVLink frame exactly as it was received.
+//!
+//! Unlike a `file:` connection, which scans for packet start markers and skips the timestamps, a [`TlogReader`]
+//! keeps the original timing of every frame. Bytes that do not form a valid record are skipped until the next valid
+//! record and reported as corrupt regions.
+//!
+//! An [`index::TlogIndex`] of the records of a large log, kept in a sidecar file next to it, lets a reader seek
+//! straight to the frames of a message type, a system or a time range.
+//!
+//! Logs are merged, filtered and split with the functions of [`edit`], which copy the frames without encoding them
+//! again.
+//!
+//! Logs are written with a [`TlogWriter`], or a [`RotatingTlogWriter`] that starts a new file once a size or
+//! duration is reached. The recording connection wrappers log every frame passing through a connection to one of
+//! them.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::tlog::TlogReader;
+//!
+//! let mut reader = TlogReader::new(std::fs::File::open("flight.tlog")?);
+//! for entry in reader.frames::<MavMessage>() {
+//!     let (timestamp, frame) = entry?;
+//!     println!("{timestamp}: {:?}", frame.msg);
+//! }
+//! for region in reader.corrupt_regions() {
+//!     println!("corrupt bytes {region:?}");
+//! }
+//! ```
+
+use core::marker::PhantomData;
+use core::ops::Range;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::{MessageReadError, MessageWriteError, ParserError};
+use crate::frame_parser::{check_any_frame, FrameCheck};
+use crate::{
+    MAVLinkMessageRaw, MAVLinkV1MessageRaw, MAVLinkV2MessageRaw, MavFrame, MavHeader, Message,
+    SigningData, MAVLINK_SUPPORTED_IFLAGS, MAV_STX, MAV_STX_V2, MAX_FRAME_SIZE,
+};
+
+pub mod edit;
+pub mod index;
+
+/// Size of the timestamp preceding every frame
+pub const TIMESTAMP_SIZE: usize = 8;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Reads the timestamped frames of a tlog
+///
+/// The reader's methods are generic over the [`Message`] type of the dialect, which is required to check the CRC of
+/// the frames. Frames of messages the dialect does not know are returned as raw frames as long as the reader is in
+/// sync with the records, after a corrupt region only frames with a valid CRC are accepted to resynchronize.
+pub struct TlogReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    // start of the unread data in the buffer
+    start: usize,
+    // offset of the unread data in the log
+    offset: u64,
+    synced: bool,
+    corrupt_start: Option<u64>,
+    corrupt_regions: Vec<Range<u64>>,
+}
+
+impl<R: Read> TlogReader<R> {
+    /// Create a reader, reading the log from the current position of `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            start: 0,
+            offset: 0,
+            synced: true,
+            corrupt_start: None,
+            corrupt_regions: Vec::new(),
+        }
+    }
+
+    /// Byte offset of the next record, relative to the position the reader started at
+    pub fn position(&self) -> u64 {
+        self.offset
+    }
+
+    /// Byte ranges of the log that were skipped because they do not form a valid record
+    ///
+    /// A region is reported once the next valid record or the end of the log is reached.
+    pub fn corrupt_regions(&self) -> &[Range<u64>] {
+        &self.corrupt_regions
+    }
+
+    /// The underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Unwrap the underlying reader, its position is after the last buffered byte
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next frame and its timestamp in microseconds since the Unix epoch
+    ///
+    /// Returns `None` at the end of the log.
+    pub fn read_raw<M: Message>(&mut self) -> io::Result<Option<(u64, MAVLinkMessageRaw)>> {
+        loop {
+            if self.fill(1)?.is_empty() {
+                self.end_corrupt_region();
+                return Ok(None);
+            }
+
+            let synced = self.synced;
+            let mut needed = TIMESTAMP_SIZE + 1;
+            let frame = loop {
+                let bytes = self.fill(needed)?;
+                if bytes.len() < needed {
+                    // truncated record at the end of the log
+                    break None;
+                }
+                match check_record::<M>(&bytes[TIMESTAMP_SIZE..], synced, None) {
+                    FrameCheck::Incomplete(len) => needed = TIMESTAMP_SIZE + len,
+                    FrameCheck::Valid(frame) => break Some(frame),
+                    FrameCheck::Invalid | FrameCheck::Rejected(_) => break None,
+                }
+            };
+
+            match frame {
+                Some((frame, checked)) => {
+                    let bytes = &self.buffer[self.start..];
+                    let timestamp = u64::from_be_bytes(bytes[..TIMESTAMP_SIZE].try_into().unwrap());
+                    self.end_corrupt_region();
+                    self.consume(TIMESTAMP_SIZE + frame.raw_bytes().len());
+                    self.synced |= checked;
+                    return Ok(Some((timestamp, frame)));
+                }
+                None => {
+                    self.corrupt_start.get_or_insert(self.offset);
+                    self.synced = false;
+                    self.consume(1);
+                }
+            }
+        }
+    }
+
+    /// Read and parse the next frame of a message known to the dialect, and its timestamp in microseconds since
+    /// the Unix epoch
+    ///
+    /// Frames of unknown messages are skipped. Returns `None` at the end of the log.
+    pub fn read_frame<M: Message>(
+        &mut self,
+    ) -> Result<Option<(u64, MavFrame<M>)>, MessageReadError> {
+        loop {
+            let Some((timestamp, raw)) = self.read_raw::<M>()? else {
+                return Ok(None);
+            };
+            match parse_raw(&raw) {
+                Ok((header, msg)) => {
+                    let frame = MavFrame {
+                        header,
+                        msg,
+                        protocol_version: raw.version(),
+                    };
+                    return Ok(Some((timestamp, frame)));
+                }
+                Err(ParserError::UnknownMessage { .. }) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Iterator over the remaining raw frames, see [`TlogReader::read_raw`]
+    pub fn raw_frames<M: Message>(&mut self) -> RawFrames<'_, R, M> {
+        RawFrames {
+            reader: self,
+            _message: PhantomData,
+        }
+    }
+
+    /// Iterator over the remaining frames, see [`TlogReader::read_frame`]
+    pub fn frames<M: Message>(&mut self) -> Frames<'_, R, M> {
+        Frames {
+            reader: self,
+            _message: PhantomData,
+        }
+    }
+
+    /// Make at least `amount` bytes available, unless the end of the log is reached
+    fn fill(&mut self, amount: usize) -> io::Result<&[u8]> {
+        while self.buffer.len() - self.start < amount {
+            if self.start >= CHUNK_SIZE {
+                self.buffer.drain(..self.start);
+                self.start = 0;
+            }
+            let len = self.buffer.len();
+            self.buffer.resize(len + CHUNK_SIZE, 0);
+            let read = loop {
+                match self.reader.read(&mut self.buffer[len..]) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    result => break result,
+                }
+            };
+            match read {
+                Ok(read) => {
+                    self.buffer.truncate(len + read);
+                    if read == 0 {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    self.buffer.truncate(len);
+                    return Err(e);
+                }
+            }
+        }
+        let end = self.buffer.len().min(self.start + amount);
+        Ok(&self.buffer[self.start..end])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.start += amount;
+        self.offset += amount as u64;
+    }
+
+    fn end_corrupt_region(&mut self) {
+        if let Some(start) = self.corrupt_start.take() {
+            // regions are found again when seeking back
+            if !self
+                .corrupt_regions
+                .iter()
+                .any(|region| region.contains(&start))
+            {
+                self.corrupt_regions.push(start..self.offset);
+                self.corrupt_regions.sort_by_key(|region| region.start);
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> TlogReader<R> {
+    /// Continue reading at the given byte offset, which should be the start of a record
+    ///
+    /// Like [`position`](Self::position) the offset is relative to the position the reader started at.
+    pub fn seek_position(&mut self, offset: u64) -> io::Result<()> {
+        self.end_corrupt_region();
+        // offset of the first buffered byte, seeking within the buffer does not read it again
+        let buffered = self.offset - self.start as u64;
+        let read = buffered + self.buffer.len() as u64;
+        if (buffered..=read).contains(&offset) {
+            self.start = (offset - buffered) as usize;
+        } else {
+            // the underlying reader is at offset `read` of the log, which may not start at its beginning
+            self.reader
+                .seek(SeekFrom::Current(relative_offset(read, offset)?))?;
+            self.buffer.clear();
+            self.start = 0;
+        }
+        self.offset = offset;
+        self.synced = true;
+        Ok(())
+    }
+
+    /// Continue reading at the first record logged at or after `timestamp`, in microseconds since the Unix epoch
+    ///
+    /// The log is scanned from the start, so the reader is at the end of the log if there is no such record.
+    pub fn seek_time<M: Message>(&mut self, timestamp: u64) -> io::Result<()> {
+        self.seek_position(0)?;
+        loop {
+            let offset = self.offset;
+            match self.read_raw::<M>()? {
+                Some((time, _)) if time >= timestamp => return self.seek_position(offset),
+                Some(_) => {}
+                None => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Parse the message of a raw frame
+pub(crate) fn parse_raw<M: Message>(
+    raw: &MAVLinkMessageRaw,
+) -> Result<(MavHeader, M), ParserError> {
+    let header = MavHeader {
+        sequence: raw.sequence(),
+        system_id: raw.system_id(),
+        component_id: raw.component_id(),
+    };
+    Ok((
+        header,
+        M::parse(raw.version(), raw.message_id(), raw.payload())?,
+    ))
+}
+
+/// Distance from the offset `from` to the offset `to`, for a relative seek
+pub(crate) fn relative_offset(from: u64, to: u64) -> io::Result<i64> {
+    let too_far = || io::Error::new(io::ErrorKind::InvalidInput, "seek offset out of range");
+    if to >= from {
+        i64::try_from(to - from).map_err(|_| too_far())
+    } else {
+        i64::try_from(from - to)
+            .map(|distance| -distance)
+            .map_err(|_| too_far())
+    }
+}
+
+/// Check whether the bytes start with a frame, returning whether the frame's CRC was checked
+///
+/// Frames of unknown messages are accepted unchecked while `synced`.
+pub(crate) fn check_record<M: Message>(
+    bytes: &[u8],
+    synced: bool,
+    signing_data: Option<&SigningData>,
+) -> FrameCheck<(MAVLinkMessageRaw, bool)> {
+    match check_any_frame::<M>(bytes, signing_data) {
+        FrameCheck::Invalid if synced => unchecked_frame::<M>(bytes).map(|frame| (frame, false)),
+        check => check.map(|frame| (frame, true)),
+    }
+}
+
+/// Frame of a message unknown to the dialect, whose CRC can not be checked
+fn unchecked_frame<M: Message>(bytes: &[u8]) -> FrameCheck<MAVLinkMessageRaw> {
+    match bytes[0] {
+        MAV_STX => {
+            let mut message = MAVLinkV1MessageRaw::new();
+            let len = 1 + MAVLinkV1MessageRaw::HEADER_SIZE + usize::from(bytes[1]) + 2;
+            message.0[..len].copy_from_slice(&bytes[..len]);
+            if M::default_message_from_id(message.message_id().into()).is_some() {
+                return FrameCheck::Invalid;
+            }
+            FrameCheck::Valid(MAVLinkMessageRaw::V1(message))
+        }
+        MAV_STX_V2 if bytes[2] & !MAVLINK_SUPPORTED_IFLAGS == 0 => {
+            let mut message = MAVLinkV2MessageRaw::new();
+            let signature_size = if bytes[2] & crate::MAVLINK_IFLAG_SIGNED == 0 {
+                0
+            } else {
+                MAVLinkV2MessageRaw::SIGNATURE_SIZE
+            };
+            let len =
+                1 + MAVLinkV2MessageRaw::HEADER_SIZE + usize::from(bytes[1]) + 2 + signature_size;
+            message.0[..len].copy_from_slice(&bytes[..len]);
+            if M::default_message_from_id(message.message_id()).is_some() {
+                return FrameCheck::Invalid;
+            }
+            FrameCheck::Valid(MAVLinkMessageRaw::V2(message))
+        }
+        _ => FrameCheck::Invalid,
+    }
+}
+
+/// Iterator over the raw frames of a [`TlogReader`]
+pub struct RawFrames<'a, R, M> {
+    reader: &'a mut TlogReader<R>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<R: Read, M: Message> Iterator for RawFrames<'_, R, M> {
+    type Item = io::Result<(u64, MAVLinkMessageRaw)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_raw::<M>().transpose()
+    }
+}
+
+/// Iterator over the frames of a [`TlogReader`]
+pub struct Frames<'a, R, M> {
+    reader: &'a mut TlogReader<R>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<R: Read, M: Message> Iterator for Frames<'_, R, M> {
+    type Item = Result<(u64, MavFrame<M>), MessageReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_frame().transpose()
+    }
+}
+
+/// Current time in microseconds since the Unix epoch, as used for the timestamps of a tlog
+pub fn timestamp_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_micros() as u64)
+}
+
+/// Destination of the frames recorded by a recording connection
+pub trait TlogSink: Send {
+    /// Write a record of a complete frame and the time it was sent or received at
+    fn record(&mut self, timestamp: u64, frame: &[u8]) -> io::Result<()>;
+
+    /// Flush the written records
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Writes timestamped frames in the tlog format
+pub struct TlogWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> TlogWriter<W> {
+    /// Create a writer, appending records at the current position of `writer`
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write a record of a raw frame
+    pub fn write_raw(&mut self, timestamp: u64, frame: &MAVLinkMessageRaw) -> io::Result<()> {
+        self.write_frame(timestamp, frame.raw_bytes())
+    }
+
+    /// Write a record of the frame of a message, returning the size of the record
+    pub fn write_message<M: Message>(
+        &mut self,
+        timestamp: u64,
+        frame: &MavFrame<M>,
+    ) -> Result<usize, MessageWriteError> {
+        let mut buf = [0; MAX_FRAME_SIZE];
+        let len = frame.ser(&mut buf)?;
+        self.write_frame(timestamp, &buf[..len])?;
+        Ok(TIMESTAMP_SIZE + len)
+    }
+
+    /// Write a record of the bytes of a complete frame
+    pub fn write_frame(&mut self, timestamp: u64, frame: &[u8]) -> io::Result<()> {
+        self.writer.write_all(&timestamp.to_be_bytes())?;
+        self.writer.write_all(frame)
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// The underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Unwrap the underlying writer, without flushing it
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + Send> TlogSink for TlogWriter<W> {
+    fn record(&mut self, timestamp: u64, frame: &[u8]) -> io::Result<()> {
+        self.write_frame(timestamp, frame)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Self::flush(self)
+    }
+}
+
+/// When a [`RotatingTlogWriter`] starts a new file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rotation {
+    /// Maximum size of a file in bytes, a record is never split across files
+    pub max_size: Option<u64>,
+    /// Maximum time between the first and the last record of a file
+    pub max_duration: Option<Duration>,
+}
+
+/// Writes a tlog into a sequence of files, starting a new file according to a [`Rotation`]
+///
+/// Files are named after the path the writer is created with and numbered, `flight.tlog` results in
+/// `flight-000.tlog`, `flight-001.tlog` and so on. Numbers of existing files are skipped, so logs of earlier runs
+/// are never overwritten.
+pub struct RotatingTlogWriter {
+    base: PathBuf,
+    rotation: Rotation,
+    index: u32,
+    path: PathBuf,
+    writer: TlogWriter<BufWriter<File>>,
+    size: u64,
+    started: Option<Instant>,
+}
+
+impl RotatingTlogWriter {
+    /// Create the first file of the log
+    pub fn new(path: impl Into<PathBuf>, rotation: Rotation) -> io::Result<Self> {
+        let base = path.into();
+        let (index, path, file) = Self::create(&base, 0)?;
+        Ok(Self {
+            base,
+            rotation,
+            index,
+            path,
+            writer: TlogWriter::new(BufWriter::new(file)),
+            size: 0,
+            started: None,
+        })
+    }
+
+    /// Path of the file currently written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Create the first file numbered `index` or higher that does not exist yet
+    fn create(base: &Path, mut index: u32) -> io::Result<(u32, PathBuf, File)> {
+        let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = base
+            .extension()
+            .unwrap_or("tlog".as_ref())
+            .to_string_lossy();
+        loop {
+            let path = base.with_file_name(format!("{stem}-{index:03}.{extension}"));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((index, path, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => index += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        let (index, path, file) = Self::create(&self.base, self.index + 1)?;
+        self.index = index;
+        self.path = path;
+        self.writer = TlogWriter::new(BufWriter::new(file));
+        self.size = 0;
+        self.started = None;
+        Ok(())
+    }
+}
+
+impl TlogSink for RotatingTlogWriter {
+    fn record(&mut self, timestamp: u64, frame: &[u8]) -> io::Result<()> {
+        let len = (TIMESTAMP_SIZE + frame.len()) as u64;
+        let full = self
+            .rotation
+            .max_size
+            .is_some_and(|max_size| self.size + len > max_size);
+        let expired = self
+            .rotation
+            .max_duration
+            .zip(self.started)
+            .is_some_and(|(max_duration, started)| started.elapsed() >= max_duration);
+        if self.size > 0 && (full || expired) {
+            self.rotate()?;
+        }
+
+        self.writer.write_frame(timestamp, frame)?;
+        self.size += len;
+        self.started.get_or_insert_with(Instant::now);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Record a raw frame sent or received now
+pub(crate) fn record_raw<S: TlogSink>(sink: &Mutex<S>, raw: &MAVLinkMessageRaw) -> io::Result<()> {
+    sink.lock()
+        .unwrap()
+        .record(timestamp_now(), raw.raw_bytes())
+}
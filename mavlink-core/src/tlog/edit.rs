@@ -0,0 +1,284 @@
+//! Merging, filtering and splitting of tlogs.
+//!
+//! The frames are copied as raw frames, exactly as they were logged, so they are never encoded again and the
+//! signatures of signed frames stay valid. Frames of messages unknown to the dialect are copied as well.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::tlog::edit::{copy, message_ids, Filter, Merge};
+//! use mavlink::tlog::{TlogReader, TlogWriter};
+//!
+//! // merge the logs of two ground stations, without the high rate messages and without system 42
+//! let readers = vec![
+//!     TlogReader::new(BufReader::new(File::open("gcs1.tlog")?)),
+//!     TlogReader::new(BufReader::new(File::open("gcs2.tlog")?)),
+//! ];
+//! let merge = Merge::<_, MavMessage>::new(readers)?.skip_duplicates(Duration::from_secs(1));
+//! let filter = Filter {
+//!     excluded_messages: message_ids::<MavMessage>(&["ATTITUDE", "RAW_IMU"])?,
+//!     excluded_systems: vec![42],
+//!     ..Default::default()
+//! };
+//! let mut writer = TlogWriter::new(BufWriter::new(File::create("merged.tlog")?));
+//! copy(merge, &filter, &mut writer)?;
+//! writer.flush()?;
+//! ```
+
+use core::marker::PhantomData;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use super::{TlogReader, TlogWriter, TIMESTAMP_SIZE};
+use crate::{MAVLinkMessageRaw, Message};
+
+/// Ids of messages of a dialect by their names, like `ATTITUDE`
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] on the first name the dialect does not know.
+pub fn message_ids<M: Message>(names: &[&str]) -> io::Result<Vec<u32>> {
+    names
+        .iter()
+        .map(|name| {
+            M::message_id_from_name(name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown message {name}"),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Selects the frames that are copied
+///
+/// The default filter selects every frame. Lists of ids to keep select every id when they are empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Ids of the messages to keep, see [`message_ids`]
+    pub messages: Vec<u32>,
+    /// Ids of the messages to drop
+    pub excluded_messages: Vec<u32>,
+    pub systems: Vec<u8>,
+    pub excluded_systems: Vec<u8>,
+    pub components: Vec<u8>,
+    pub excluded_components: Vec<u8>,
+    /// First timestamp to keep, in microseconds since the Unix epoch
+    pub start: Option<u64>,
+    /// Timestamp the kept frames end before, in microseconds since the Unix epoch
+    pub end: Option<u64>,
+}
+
+impl Filter {
+    /// Whether the filter selects a frame logged at `timestamp`
+    pub fn matches(&self, timestamp: u64, frame: &MAVLinkMessageRaw) -> bool {
+        fn selects<T: PartialEq>(kept: &[T], excluded: &[T], value: T) -> bool {
+            (kept.is_empty() || kept.contains(&value)) && !excluded.contains(&value)
+        }
+
+        selects(&self.messages, &self.excluded_messages, frame.message_id())
+            && selects(&self.systems, &self.excluded_systems, frame.system_id())
+            && selects(
+                &self.components,
+                &self.excluded_components,
+                frame.component_id(),
+            )
+            && self.start.map_or(true, |start| timestamp >= start)
+            && self.end.map_or(true, |end| timestamp < end)
+    }
+}
+
+/// Write the frames selected by a filter, returning the number of written frames
+///
+/// `frames` are the raw frames of a [`TlogReader`] or a [`Merge`].
+pub fn copy<W: Write>(
+    frames: impl IntoIterator<Item = io::Result<(u64, MAVLinkMessageRaw)>>,
+    filter: &Filter,
+    writer: &mut TlogWriter<W>,
+) -> io::Result<u64> {
+    let mut count = 0;
+    for frame in frames {
+        let (timestamp, frame) = frame?;
+        if filter.matches(timestamp, &frame) {
+            writer.write_raw(timestamp, &frame)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// When [`split`] starts a new log
+///
+/// Durations are measured with the timestamps of the frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Split {
+    /// Maximum size of a log in bytes, a record is never split across logs
+    pub max_size: Option<u64>,
+    /// Maximum time between the first and the last frame of a log
+    pub max_duration: Option<Duration>,
+    /// Maximum time between two frames, longer gaps like those between flights start a new log
+    pub max_gap: Option<Duration>,
+}
+
+/// Write the frames selected by a filter into a sequence of logs, returning the number of logs
+///
+/// `create` is called with the number of each log, starting at 0, to create the writer of the log. Logs are only
+/// created for frames selected by the filter, so there are no empty logs.
+pub fn split<W: Write>(
+    frames: impl IntoIterator<Item = io::Result<(u64, MAVLinkMessageRaw)>>,
+    filter: &Filter,
+    split: &Split,
+    mut create: impl FnMut(usize) -> io::Result<W>,
+) -> io::Result<usize> {
+    let max_duration = split.max_duration.map(|duration| duration.as_micros());
+    let max_gap = split.max_gap.map(|gap| gap.as_micros());
+    let mut count = 0;
+    let mut current: Option<Part<W>> = None;
+    for frame in frames {
+        let (timestamp, frame) = frame?;
+        if !filter.matches(timestamp, &frame) {
+            continue;
+        }
+        let len = (TIMESTAMP_SIZE + frame.raw_bytes().len()) as u64;
+        let full = |part: &Part<W>| {
+            split
+                .max_size
+                .is_some_and(|max_size| part.size + len > max_size)
+                || max_duration.is_some_and(|max_duration| {
+                    u128::from(timestamp.saturating_sub(part.first)) > max_duration
+                })
+                || max_gap.is_some_and(|max_gap| {
+                    u128::from(timestamp.saturating_sub(part.last)) > max_gap
+                })
+        };
+        let part = match current {
+            Some(ref mut part) if !full(part) => part,
+            _ => {
+                if let Some(mut part) = current.take() {
+                    part.writer.flush()?;
+                }
+                count += 1;
+                current.insert(Part {
+                    writer: TlogWriter::new(create(count - 1)?),
+                    size: 0,
+                    first: timestamp,
+                    last: timestamp,
+                })
+            }
+        };
+        part.writer.write_raw(timestamp, &frame)?;
+        part.size += len;
+        part.last = timestamp;
+    }
+    if let Some(mut part) = current {
+        part.writer.flush()?;
+    }
+    Ok(count)
+}
+
+/// A log written by [`split`]
+struct Part<W> {
+    writer: TlogWriter<W>,
+    size: u64,
+    // timestamps of the first and the last frame
+    first: u64,
+    last: u64,
+}
+
+/// Merges the frames of several logs in the order of their timestamps
+///
+/// Frames with the same timestamp are taken from the logs in the order the readers are given. The logs should be
+/// ordered by time themselves, which tlogs usually are.
+pub struct Merge<R, M> {
+    readers: Vec<TlogReader<R>>,
+    // next frame of every reader, `None` at the end of its log
+    heads: Vec<Option<(u64, MAVLinkMessageRaw)>>,
+    duplicate_window: Option<u64>,
+    recent: VecDeque<(u64, MAVLinkMessageRaw)>,
+    duplicates: u64,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<R: Read, M: Message> Merge<R, M> {
+    /// Create a merge of the logs, reading the first frame of each of them
+    pub fn new(mut readers: Vec<TlogReader<R>>) -> io::Result<Self> {
+        let heads = readers
+            .iter_mut()
+            .map(TlogReader::read_raw::<M>)
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            readers,
+            heads,
+            duplicate_window: None,
+            recent: VecDeque::new(),
+            duplicates: 0,
+            _message: PhantomData,
+        })
+    }
+
+    /// Drop frames that are identical to a frame merged at most `window` earlier
+    ///
+    /// Logs of several ground stations connected to the same vehicle contain the same frames, which are only merged
+    /// once. Frames include their sequence number, so identical frames are copies of the same frame.
+    pub fn skip_duplicates(mut self, window: Duration) -> Self {
+        self.duplicate_window = Some(window.as_micros() as u64);
+        self
+    }
+
+    /// Number of frames dropped as duplicates
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// The readers of the logs
+    pub fn readers(&self) -> &[TlogReader<R>] {
+        &self.readers
+    }
+
+    fn is_duplicate(&mut self, timestamp: u64, frame: &MAVLinkMessageRaw) -> bool {
+        let Some(window) = self.duplicate_window else {
+            return false;
+        };
+        while self
+            .recent
+            .front()
+            .is_some_and(|(time, _)| time.saturating_add(window) < timestamp)
+        {
+            self.recent.pop_front();
+        }
+        if self
+            .recent
+            .iter()
+            .any(|(_, recent)| recent.raw_bytes() == frame.raw_bytes())
+        {
+            return true;
+        }
+        self.recent.push_back((timestamp, *frame));
+        false
+    }
+}
+
+impl<R: Read, M: Message> Iterator for Merge<R, M> {
+    type Item = io::Result<(u64, MAVLinkMessageRaw)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, next) = self
+                .heads
+                .iter()
+                .enumerate()
+                .filter_map(|(i, head)| Some((head.as_ref()?.0, i)))
+                .min()?;
+            // the frame stays the head of its log if reading the following frame fails
+            let following = match self.readers[next].read_raw::<M>() {
+                Ok(following) => following,
+                Err(e) => return Some(Err(e)),
+            };
+            let (timestamp, frame) = core::mem::replace(&mut self.heads[next], following)?;
+            if self.is_duplicate(timestamp, &frame) {
+                self.duplicates += 1;
+                continue;
+            }
+            return Some(Ok((timestamp, frame)));
+        }
+    }
+}
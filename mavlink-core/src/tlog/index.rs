@@ -0,0 +1,398 @@
+//! Index of the records of a tlog, for random access to the frames of large logs.
+//!
+//! A [`TlogIndex`] lists the offset, timestamp, message id and sender of every record. It is built by reading the
+//! log once and is stored in a sidecar file next to the log, `flight.tlog` is indexed in `flight.tlog.idx`. A
+//! [`Query`] selects entries of the index, whose frames a [`TlogReader`] then reads without scanning the log.
+//!
+//! # Example
+//! ```ignore
+//! use mavlink::tlog::index::{Query, TlogIndex};
+//! use mavlink::tlog::TlogReader;
+//!
+//! // loads flight.tlog.idx, or builds it if it does not exist yet
+//! let index = TlogIndex::open::<MavMessage>("flight.tlog")?;
+//! let query = Query {
+//!     message_id: Some(30),
+//!     system_id: Some(1),
+//!     start: Some(t0),
+//!     end: Some(t1),
+//!     ..Default::default()
+//! };
+//! let mut reader = TlogReader::new(std::fs::File::open("flight.tlog")?);
+//! for entry in index.frames::<MavMessage, _>(&query, &mut reader) {
+//!     let (timestamp, frame) = entry?;
+//!     println!("{timestamp}: {:?}", frame.msg);
+//! }
+//! ```
+
+use core::marker::PhantomData;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::{parse_raw, relative_offset, TlogReader, TIMESTAMP_SIZE};
+use crate::error::{MessageReadError, ParserError};
+use crate::{MAVLinkMessageRaw, MavFrame, Message};
+
+/// Identifies the format of an index file
+const MAGIC: &[u8; 8] = b"TLOGIDX1";
+
+/// Size of an entry in an index file
+const ENTRY_SIZE: usize = 24;
+
+/// Path of the sidecar index file of a log, the path of the log with `.idx` appended
+pub fn sidecar_path(log: &Path) -> PathBuf {
+    let mut path = log.as_os_str().to_owned();
+    path.push(".idx");
+    path.into()
+}
+
+/// A record of an indexed log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Byte offset of the record in the log
+    pub offset: u64,
+    /// Time the frame was logged at in microseconds since the Unix epoch
+    pub timestamp: u64,
+    pub message_id: u32,
+    pub system_id: u8,
+    pub component_id: u8,
+}
+
+impl IndexEntry {
+    fn to_bytes(self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+        bytes[..8].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.timestamp.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.message_id.to_le_bytes());
+        bytes[20] = self.system_id;
+        bytes[21] = self.component_id;
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; ENTRY_SIZE]) -> Self {
+        Self {
+            offset: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            timestamp: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            message_id: u32::from_le_bytes(bytes[16..20].try_into().unwrap()),
+            system_id: bytes[20],
+            component_id: bytes[21],
+        }
+    }
+}
+
+/// Selects entries of a [`TlogIndex`], fields that are `None` match every entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Query {
+    pub message_id: Option<u32>,
+    pub system_id: Option<u8>,
+    pub component_id: Option<u8>,
+    /// First timestamp of the range, in microseconds since the Unix epoch
+    pub start: Option<u64>,
+    /// Timestamp the range ends before, in microseconds since the Unix epoch
+    pub end: Option<u64>,
+}
+
+impl Query {
+    /// Whether the query selects an entry
+    pub fn matches(&self, entry: &IndexEntry) -> bool {
+        self.message_id.map_or(true, |id| entry.message_id == id)
+            && self.system_id.map_or(true, |id| entry.system_id == id)
+            && self
+                .component_id
+                .map_or(true, |id| entry.component_id == id)
+            && self.start.map_or(true, |start| entry.timestamp >= start)
+            && self.end.map_or(true, |end| entry.timestamp < end)
+    }
+}
+
+/// Index of the records of a tlog
+///
+/// Records are indexed in the order of the log. Time ranges of logs whose timestamps never decrease are found with a
+/// binary search, other logs are filtered entry by entry, which is still much faster than reading the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlogIndex {
+    entries: Vec<IndexEntry>,
+    indexed_len: u64,
+    sorted: bool,
+}
+
+impl Default for TlogIndex {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            indexed_len: 0,
+            sorted: true,
+        }
+    }
+}
+
+impl TlogIndex {
+    /// Index a log, read from the current position of `reader`
+    ///
+    /// Corrupt regions are skipped, like a [`TlogReader`] does.
+    pub fn build<M: Message>(reader: impl Read) -> io::Result<Self> {
+        let mut index = Self::default();
+        index.index::<M>(reader)?;
+        Ok(index)
+    }
+
+    /// Index the records appended to the log since it was indexed, returning the number of new entries
+    ///
+    /// Like for [`build`](Self::build) the log is read from the current position of `reader`, which has to be the
+    /// position the log was indexed from.
+    pub fn update<M: Message>(&mut self, mut reader: impl Read + Seek) -> io::Result<usize> {
+        reader.seek(SeekFrom::Current(relative_offset(0, self.indexed_len)?))?;
+        self.index::<M>(reader)
+    }
+
+    /// Load the sidecar index of a log, building it or indexing the records appended since it was saved
+    ///
+    /// The sidecar file is written if it does not exist, does not match the log or the log has grown.
+    pub fn open<M: Message>(log: impl AsRef<Path>) -> io::Result<Self> {
+        let log = log.as_ref();
+        let mut file = File::open(log)?;
+        let path = sidecar_path(log);
+        let (mut index, mut changed) = match Self::load(&path) {
+            Ok(index) if index.matches_log(&mut file)? => (index, false),
+            Ok(_) => (Self::default(), true),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::InvalidData
+                ) =>
+            {
+                (Self::default(), true)
+            }
+            Err(e) => return Err(e),
+        };
+        // the log starts at the beginning of the file
+        file.rewind()?;
+        changed |= index.update::<M>(&mut file)? > 0;
+        if changed {
+            index.save(&path)?;
+        }
+        Ok(index)
+    }
+
+    /// Load an index written by [`TlogIndex::save`]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Write the index to a file, replacing it if it exists
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Read an index written by [`TlogIndex::write_to`]
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut header = [0; 24];
+        reader.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => invalid_index(),
+            _ => e,
+        })?;
+        if &header[..8] != MAGIC {
+            return Err(invalid_index());
+        }
+        let indexed_len = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let count = u64::from_le_bytes(header[16..24].try_into().unwrap());
+
+        let mut index = Self {
+            indexed_len,
+            ..Self::default()
+        };
+        let mut bytes = [0; ENTRY_SIZE];
+        for _ in 0..count {
+            reader.read_exact(&mut bytes).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => invalid_index(),
+                _ => e,
+            })?;
+            index.push(IndexEntry::from_bytes(&bytes));
+        }
+        Ok(index)
+    }
+
+    /// Write the index in the format of the sidecar files
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.indexed_len.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for entry in &self.entries {
+            writer.write_all(&entry.to_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Entries of all records, in the order of the log
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of bytes of the log that are indexed, up to the end of the last indexed record
+    pub fn indexed_len(&self) -> u64 {
+        self.indexed_len
+    }
+
+    /// Iterator over the entries selected by a query, in the order of the log
+    pub fn query<'a>(&'a self, query: &'a Query) -> Matches<'a> {
+        let mut entries = &self.entries[..];
+        if self.sorted {
+            if let Some(end) = query.end {
+                entries = &entries[..entries.partition_point(|entry| entry.timestamp < end)];
+            }
+            if let Some(start) = query.start {
+                entries = &entries[entries.partition_point(|entry| entry.timestamp < start)..];
+            }
+        }
+        Matches {
+            entries: entries.iter(),
+            query,
+        }
+    }
+
+    /// Iterator over the frames selected by a query, read from the indexed log
+    ///
+    /// Frames of messages unknown to the dialect are skipped.
+    pub fn frames<'a, M: Message, R: Read + Seek>(
+        &'a self,
+        query: &'a Query,
+        reader: &'a mut TlogReader<R>,
+    ) -> IndexedFrames<'a, R, M> {
+        IndexedFrames {
+            matches: self.query(query),
+            reader,
+            _message: PhantomData,
+        }
+    }
+
+    /// Index the records read from `reader`, which is at the end of the indexed part of the log
+    fn index<M: Message>(&mut self, reader: impl Read) -> io::Result<usize> {
+        let start = self.indexed_len;
+        let count = self.entries.len();
+        let mut reader = TlogReader::new(reader);
+        while let Some((timestamp, frame)) = reader.read_raw::<M>()? {
+            // the record starts after the corrupt bytes skipped before it
+            let end = start + reader.position();
+            self.push(IndexEntry {
+                offset: end - (TIMESTAMP_SIZE + frame.raw_bytes().len()) as u64,
+                timestamp,
+                message_id: frame.message_id(),
+                system_id: frame.system_id(),
+                component_id: frame.component_id(),
+            });
+            self.indexed_len = end;
+        }
+        Ok(self.entries.len() - count)
+    }
+
+    fn push(&mut self, entry: IndexEntry) {
+        self.sorted &= self
+            .entries
+            .last()
+            .map_or(true, |last| last.timestamp <= entry.timestamp);
+        self.entries.push(entry);
+    }
+
+    /// Check whether the log is the one that was indexed, by comparing the timestamp of the last indexed record
+    fn matches_log(&self, log: &mut File) -> io::Result<bool> {
+        if log.metadata()?.len() < self.indexed_len {
+            return Ok(false);
+        }
+        let Some(last) = self.entries.last() else {
+            return Ok(self.indexed_len == 0);
+        };
+        let mut timestamp = [0; TIMESTAMP_SIZE];
+        log.seek(SeekFrom::Start(last.offset))?;
+        log.read_exact(&mut timestamp)?;
+        Ok(u64::from_be_bytes(timestamp) == last.timestamp)
+    }
+}
+
+fn invalid_index() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid tlog index")
+}
+
+impl<R: Read + Seek> TlogReader<R> {
+    /// Read the frame of an index entry and its timestamp in microseconds since the Unix epoch
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the log does not have the entry's record at its offset.
+    pub fn read_indexed<M: Message>(
+        &mut self,
+        entry: &IndexEntry,
+    ) -> io::Result<(u64, MAVLinkMessageRaw)> {
+        self.seek_position(entry.offset)?;
+        match self.read_raw::<M>()? {
+            Some((timestamp, frame))
+                if self.offset
+                    == entry.offset + (TIMESTAMP_SIZE + frame.raw_bytes().len()) as u64
+                    && timestamp == entry.timestamp
+                    && frame.message_id() == entry.message_id =>
+            {
+                Ok((timestamp, frame))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the index does not match the log",
+            )),
+        }
+    }
+}
+
+/// Iterator over the entries of a [`TlogIndex`] selected by a [`Query`]
+pub struct Matches<'a> {
+    entries: core::slice::Iter<'a, IndexEntry>,
+    query: &'a Query,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = &'a IndexEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let query = self.query;
+        self.entries.find(|entry| query.matches(entry))
+    }
+}
+
+/// Iterator over the frames selected by a [`Query`], see [`TlogIndex::frames`]
+pub struct IndexedFrames<'a, R, M> {
+    matches: Matches<'a>,
+    reader: &'a mut TlogReader<R>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<R: Read + Seek, M: Message> Iterator for IndexedFrames<'_, R, M> {
+    type Item = Result<(u64, MavFrame<M>), MessageReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.matches.next()?;
+            let (timestamp, raw) = match self.reader.read_indexed::<M>(entry) {
+                Ok(record) => record,
+                Err(e) => return Some(Err(e.into())),
+            };
+            match parse_raw(&raw) {
+                Ok((header, msg)) => {
+                    let frame = MavFrame {
+                        header,
+                        msg,
+                        protocol_version: raw.version(),
+                    };
+                    return Some(Ok((timestamp, frame)));
+                }
+                Err(ParserError::UnknownMessage { .. }) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
@@ -16,6 +16,19 @@ pub fn remove_trailing_zeroes(data: &[u8]) -> usize {
     len
 }
 
+/// Read `N` bytes starting at `offset` of a payload
+///
+/// Bytes beyond the end of the payload are read as zero, as MAVLink 2 truncates trailing zeros.
+#[inline]
+pub fn get_padded<const N: usize>(payload: &[u8], offset: usize) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    if let Some(available) = payload.get(offset..) {
+        let len = available.len().min(N);
+        bytes[..len].copy_from_slice(&available[..len]);
+    }
+    bytes
+}
+
 /// A trait very similar to [`Default`] but is only implemented for the equivalent Rust types to
 /// `MavType`s.
 ///
@@ -65,4 +78,12 @@ mod tests {
     fn test_remove_trailing_zeroes_empty_slice() {
         remove_trailing_zeroes(&[]);
     }
+
+    #[test]
+    fn test_get_padded() {
+        let payload = [1, 2, 3];
+        assert_eq!(get_padded::<2>(&payload, 0), [1, 2]);
+        assert_eq!(get_padded::<2>(&payload, 2), [3, 0]);
+        assert_eq!(get_padded::<2>(&payload, 5), [0, 0]);
+    }
 }
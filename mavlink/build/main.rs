@@ -77,7 +77,30 @@ fn main() -> ExitCode {
         XmlDefinitions::Files(definitions_to_bind)
     };
 
-    let result = match mavlink_bindgen::generate(xml_definitions, out_dir) {
+    #[allow(unused_mut)]
+    let mut options = mavlink_bindgen::GeneratorOptions::default();
+    #[cfg(feature = "box-large-messages")]
+    {
+        println!("cargo:rerun-if-env-changed=MAVLINK_BOX_THRESHOLD");
+        options.box_threshold = Some(
+            env::var("MAVLINK_BOX_THRESHOLD")
+                .ok()
+                .and_then(|threshold| threshold.parse().ok())
+                .unwrap_or(64),
+        );
+    }
+
+    #[cfg(feature = "message-views")]
+    {
+        options.emit_views = true;
+    }
+
+    #[cfg(feature = "message-schemas")]
+    {
+        options.emit_schemas = true;
+    }
+
+    let result = match mavlink_bindgen::generate_with_options(xml_definitions, out_dir, &options) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("{e}");
@@ -90,5 +113,13 @@ fn main() -> ExitCode {
 
     mavlink_bindgen::emit_cargo_build_messages(&result);
 
+    // keep the payload lengths of the generated messages next to the generated code
+    for binding in &result.bindings {
+        let report_path = binding.rust_module.with_extension("payloads.txt");
+        if let Err(error) = std::fs::write(&report_path, binding.payload_report.to_string()) {
+            eprintln!("Failed to write {}: {error}", report_path.display());
+        }
+    }
+
     ExitCode::SUCCESS
 }
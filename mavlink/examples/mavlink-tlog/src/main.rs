@@ -0,0 +1,153 @@
+use mavlink::ardupilotmega::MavMessage;
+use mavlink::tlog::edit::{copy, message_ids, split, Filter, Merge, Split};
+use mavlink::tlog::{TlogReader, TlogWriter};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+use std::time::Duration;
+use std::{env, process};
+
+const USAGE: &str = "Usage:
+    mavlink-tlog filter INPUT OUTPUT [FILTER...]
+    mavlink-tlog merge OUTPUT INPUT... [FILTER...] [--dedup SECONDS]
+    mavlink-tlog split INPUT OUTPUT [FILTER...] [--max-size BYTES] [--max-duration SECONDS] [--max-gap SECONDS]
+
+Filters:
+    --messages NAME,...             keep only these messages
+    --exclude-messages NAME,...     drop these messages
+    --systems ID,...                keep only these system ids
+    --exclude-systems ID,...        drop these system ids
+    --components ID,...             keep only these component ids
+    --exclude-components ID,...     drop these component ids
+    --start MICROSECONDS            drop frames logged before, in microseconds since the Unix epoch
+    --end MICROSECONDS              drop frames logged at or after
+
+Split logs are named after OUTPUT, `part.tlog` results in `part-000.tlog`, `part-001.tlog` and so on.";
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("mavlink-tlog: {e}");
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> io::Result<()> {
+    let (command, args) = args
+        .split_first()
+        .ok_or_else(|| invalid("missing command"))?;
+    let mut paths = Vec::new();
+    let mut filter = Filter::default();
+    let mut split_options = Split::default();
+    let mut dedup = None;
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        if !option.starts_with("--") {
+            paths.push(option);
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| invalid(&format!("missing value of {option}")))?;
+        match option.as_str() {
+            "--messages" => filter.messages = message_ids::<MavMessage>(&list(value))?,
+            "--exclude-messages" => {
+                filter.excluded_messages = message_ids::<MavMessage>(&list(value))?;
+            }
+            "--systems" => filter.systems = numbers(value)?,
+            "--exclude-systems" => filter.excluded_systems = numbers(value)?,
+            "--components" => filter.components = numbers(value)?,
+            "--exclude-components" => filter.excluded_components = numbers(value)?,
+            "--start" => filter.start = Some(number(value)?),
+            "--end" => filter.end = Some(number(value)?),
+            "--dedup" if command == "merge" => dedup = Some(seconds(value)?),
+            "--max-size" if command == "split" => split_options.max_size = Some(number(value)?),
+            "--max-duration" if command == "split" => {
+                split_options.max_duration = Some(seconds(value)?);
+            }
+            "--max-gap" if command == "split" => split_options.max_gap = Some(seconds(value)?),
+            _ => return Err(invalid(&format!("unknown option {option}"))),
+        }
+    }
+
+    match (command.as_str(), &paths[..]) {
+        ("filter", [input, output]) => {
+            let mut reader = open(input)?;
+            let mut writer = create(output)?;
+            let count = copy(reader.raw_frames::<MavMessage>(), &filter, &mut writer)?;
+            writer.flush()?;
+            println!("wrote {count} frames to {output}");
+        }
+        ("merge", [output, inputs @ ..]) if !inputs.is_empty() => {
+            let readers = inputs
+                .iter()
+                .map(|input| open(input))
+                .collect::<io::Result<_>>()?;
+            let mut merge = Merge::<_, MavMessage>::new(readers)?;
+            if let Some(window) = dedup {
+                merge = merge.skip_duplicates(window);
+            }
+            let mut writer = create(output)?;
+            let count = copy(merge.by_ref(), &filter, &mut writer)?;
+            writer.flush()?;
+            println!(
+                "wrote {count} frames to {output}, dropped {} duplicates",
+                merge.duplicates()
+            );
+        }
+        ("split", [input, output]) => {
+            let mut reader = open(input)?;
+            let path = Path::new(output);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let extension = path
+                .extension()
+                .unwrap_or("tlog".as_ref())
+                .to_string_lossy();
+            let count = split(
+                reader.raw_frames::<MavMessage>(),
+                &filter,
+                &split_options,
+                |index| {
+                    let part = path.with_file_name(format!("{stem}-{index:03}.{extension}"));
+                    println!("writing {}", part.display());
+                    File::create(part).map(BufWriter::new)
+                },
+            )?;
+            println!("wrote {count} logs");
+        }
+        _ => return Err(invalid("invalid arguments")),
+    }
+    Ok(())
+}
+
+fn open(path: &str) -> io::Result<TlogReader<BufReader<File>>> {
+    Ok(TlogReader::new(BufReader::new(File::open(path)?)))
+}
+
+fn create(path: &str) -> io::Result<TlogWriter<BufWriter<File>>> {
+    Ok(TlogWriter::new(BufWriter::new(File::create(path)?)))
+}
+
+fn list(value: &str) -> Vec<&str> {
+    value.split(',').map(str::trim).collect()
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(&format!("invalid number {value}")))
+}
+
+fn numbers<T: std::str::FromStr>(value: &str) -> io::Result<Vec<T>> {
+    list(value).into_iter().map(number).collect()
+}
+
+fn seconds(value: &str) -> io::Result<Duration> {
+    Duration::try_from_secs_f64(number(value)?)
+        .map_err(|_| invalid(&format!("invalid duration {value}")))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
@@ -12,14 +12,31 @@
 //! - `direct-serial`: Enable serial MAVLink connections, enabled by default.
 //! - `udp`: Enables UDP based MAVLink connections, enabled by default.
 //! - `tcp`: Enables TCP based MAVLink connections, enabled by default.
+//! - `unix`: Enables Unix domain stream and datagram socket MAVLink connections on unix platforms.
+//! - `websocket`: Enables WebSocket client and server MAVLink connections.
+//! - `websocket-tls`: Enables `wss://` WebSocket clients, implies `websocket`.
 //! - `signing`: Enable support for [MAVLink 2 message signing]
-//! - `embedded`: Enables embedded support using the [embedded-io] crate, incompatible with `embedded-hal-02` and `tokio-1`.
+//! - `embedded`: Enables embedded support using the [embedded-io] crate, incompatible with `embedded-hal-02`, `futures-io` and `tokio-1`.
+//!   This includes the `no_std` [`AsyncEmbeddedMavConnection`] over [embedded-io-async] streams.
 //! - `embedded-hal-02`: Enables embedded support using version 0.2 of the [embedded-hal] crate, incompatible with `embedded`.
-//! - `tokio-1`: Enable support for asynchronous I/O using [tokio], incompatible with `embedded`.
+//! - `futures-io`: Enable support for runtime-agnostic asynchronous I/O over the [futures] I/O traits, incompatible with `embedded`.
+//! - `tokio-1`: Enable support for asynchronous I/O using [tokio], implies `futures-io`, incompatible with `embedded`.
 //! - `serde`: Enables [serde] support in generated message sets, enabled by default.
 //! - `format-generated-code`: Generated MAVLink message set code will be formatted, requires `rustfmt` to be installed.
 //! - `emit-extensions`: Generated MAVLink message set code will include [MAVLink 2 message extensions].
+//! - `box-large-messages`: Messages with a payload longer than 64 bytes are boxed in the generated `MavMessage` enums,
+//!   which requires an allocator. The threshold can be changed with the `MAVLINK_BOX_THRESHOLD` environment variable
+//!   at build time. The payload length of all messages is listed in `<dialect>.payloads.txt` in the build output directory.
+//! - `message-views`: Generated message set code will include a borrowed `<Message>View` per message, reading
+//!   its fields directly from the payload of a [`MAVLinkMessageRaw`](crate::MAVLinkMessageRaw).
+//! - `message-schemas`: Generated message set code will include the [`schema`] of every message and enum, which the
+//!   table exporters in [`export`] require.
 //! - `arbitrary`: Enable support for the [arbitrary] crate.
+//! - `log`: Enables forwarding of reassembled `STATUSTEXT` messages to the [log] crate.
+//! - `onboard-log`: Enables readers for ArduPilot DataFlash logs and PX4 ULog files, see [`onboard_log`].
+//! - `parquet`: Enables exporting messages to Arrow record batches and Parquet files, see [`export`].
+//! - `pcap`: Enables reading and writing MAVLink traffic in pcap and pcapng packet captures, see [`pcap`].
+//! - `tracing`: Enables forwarding of reassembled `STATUSTEXT` messages to the [tracing] crate.
 //!
 //! Either `std`, `embedded` or `embedded-hal-02` must be enabled.
 //!
@@ -51,10 +68,17 @@
 //! [MAVLink 2 message signing]: https://mavlink.io/en/guide/message_signing.html
 //! [MAVLink 2 message extensions]: https://mavlink.io/en/guide/define_xml_element.html#message_extensions
 //! [embedded-io]: https://crates.io/crates/embedded-io
+//! [embedded-io-async]: https://crates.io/crates/embedded-io-async
 //! [embedded-hal]: https://crates.io/crates/embedded-hal
+//! [futures]: https://crates.io/crates/futures
 //! [tokio]: https://crates.io/crates/tokio
 //! [serde]: https://crates.io/crates/serde
 //! [arbitrary]: https://crates.io/crates/arbitrary
+//! [log]: https://crates.io/crates/log
+//! [tracing]: https://crates.io/crates/tracing
+
+#[cfg(feature = "box-large-messages")]
+extern crate alloc;
 
 // include generate definitions
 include!(concat!(env!("OUT_DIR"), "/mod.rs"));
@@ -0,0 +1,47 @@
+#[cfg(all(feature = "box-large-messages", feature = "common"))]
+mod boxed_messages {
+    use mavlink::common::{MavMessage, HEARTBEAT_DATA, HIL_ACTUATOR_CONTROLS_DATA};
+    use mavlink::{MavlinkVersion, Message, MessageData};
+
+    #[test]
+    pub fn test_enum_size() {
+        // the largest inline message limits the size of the enum
+        assert!(
+            core::mem::size_of::<MavMessage>() < core::mem::size_of::<HIL_ACTUATOR_CONTROLS_DATA>()
+        );
+    }
+
+    #[test]
+    pub fn test_boxed_roundtrip() {
+        let mut controls = [0.0; 16];
+        controls[2] = 0.5;
+        let msg = MavMessage::from(HIL_ACTUATOR_CONTROLS_DATA {
+            time_usec: 1234,
+            controls,
+            ..HIL_ACTUATOR_CONTROLS_DATA::DEFAULT
+        });
+        assert!(matches!(msg, MavMessage::HIL_ACTUATOR_CONTROLS(_)));
+
+        let mut payload = [0u8; 255];
+        let len = msg.ser(MavlinkVersion::V2, &mut payload);
+        let parsed = MavMessage::parse(
+            MavlinkVersion::V2,
+            HIL_ACTUATOR_CONTROLS_DATA::ID,
+            &payload[..len],
+        )
+        .expect("Failed to parse boxed message");
+        assert_eq!(parsed, msg);
+
+        let MavMessage::HIL_ACTUATOR_CONTROLS(data) = parsed else {
+            panic!("Decoded wrong message type");
+        };
+        assert_eq!(data.controls, controls);
+
+        // small messages are not boxed
+        let heartbeat = MavMessage::default_message_from_id(HEARTBEAT_DATA::ID);
+        assert!(matches!(
+            heartbeat,
+            Some(MavMessage::HEARTBEAT(HEARTBEAT_DATA { .. }))
+        ));
+    }
+}
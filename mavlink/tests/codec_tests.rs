@@ -0,0 +1,190 @@
+mod test_shared;
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod codec {
+    use futures::{SinkExt, StreamExt};
+    use mavlink::common::MavMessage;
+    use mavlink::{MavFrame, MavlinkCodec, MavlinkVersion, RawMavlinkCodec, ReadVersion};
+    use tokio_util::bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder, Framed};
+
+    use crate::test_shared::{HEARTBEAT_V1, HEARTBEAT_V2};
+
+    fn heartbeat_frame(protocol_version: MavlinkVersion) -> MavFrame<MavMessage> {
+        MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg()),
+            protocol_version,
+        }
+    }
+
+    fn assert_frame_eq(frame: &MavFrame<MavMessage>, expected: &MavFrame<MavMessage>) {
+        assert_eq!(frame.header, expected.header);
+        assert_eq!(frame.msg, expected.msg);
+        assert_eq!(frame.protocol_version, expected.protocol_version);
+    }
+
+    #[test]
+    pub fn test_decode_chunks() {
+        let mut input = vec![0x01, 0x02];
+        input.extend_from_slice(HEARTBEAT_V1);
+        input.extend_from_slice(HEARTBEAT_V2);
+
+        let mut codec = MavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        let mut src = BytesMut::new();
+        let mut frames = Vec::new();
+        for chunk in input.chunks(5) {
+            src.extend_from_slice(chunk);
+            while let Some(frame) = codec.decode(&mut src).expect("Failed to decode") {
+                frames.push(frame);
+            }
+        }
+        assert!(src.is_empty());
+        assert_eq!(codec.discarded(), 2);
+        assert_eq!(frames.len(), 2);
+        assert_frame_eq(&frames[0], &heartbeat_frame(MavlinkVersion::V1));
+        assert_frame_eq(&frames[1], &heartbeat_frame(MavlinkVersion::V2));
+    }
+
+    #[test]
+    pub fn test_encode() {
+        let mut codec = MavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        let mut dst = BytesMut::new();
+        codec
+            .encode(heartbeat_frame(MavlinkVersion::V1), &mut dst)
+            .expect("Failed to encode");
+        codec
+            .encode(&heartbeat_frame(MavlinkVersion::V2), &mut dst)
+            .expect("Failed to encode");
+
+        let mut expected = HEARTBEAT_V1.to_vec();
+        expected.extend_from_slice(HEARTBEAT_V2);
+        assert_eq!(dst, expected);
+    }
+
+    #[test]
+    pub fn test_version_filter() {
+        let mut codec = RawMavlinkCodec::<MavMessage>::new(MavlinkVersion::V2.into());
+        let mut src = BytesMut::from(HEARTBEAT_V1);
+        src.extend_from_slice(HEARTBEAT_V2);
+
+        let frame = codec
+            .decode(&mut src)
+            .expect("Failed to decode")
+            .expect("Missing frame");
+        assert_eq!(frame.raw_bytes(), HEARTBEAT_V2);
+        assert_eq!(codec.discarded(), HEARTBEAT_V1.len());
+        assert!(codec.decode(&mut src).expect("Failed to decode").is_none());
+    }
+
+    #[test]
+    pub fn test_incomplete_frame() {
+        let mut codec = RawMavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        let mut src = BytesMut::from(&HEARTBEAT_V2[..5]);
+        assert!(codec.decode(&mut src).expect("Failed to decode").is_none());
+        // the partial frame is kept until the rest arrives
+        assert_eq!(src.len(), 5);
+
+        src.extend_from_slice(&HEARTBEAT_V2[5..]);
+        let frame = codec
+            .decode(&mut src)
+            .expect("Failed to decode")
+            .expect("Missing frame");
+        assert_eq!(frame.raw_bytes(), HEARTBEAT_V2);
+        assert_eq!(codec.discarded(), 0);
+    }
+
+    #[test]
+    pub fn test_unknown_message() {
+        // a valid MAVLink 2 frame of a message id that is not part of the dialect
+        let mut unknown = vec![0xfd, 2, 0, 0, 5, 1, 1, 0xff, 0xff, 0xff, 0x12, 0x34];
+        let crc = mavlink::calculate_crc(&unknown[1..], 0);
+        unknown.extend_from_slice(&crc.to_le_bytes());
+
+        let mut codec = MavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        let mut src = BytesMut::from(&unknown[..]);
+        src.extend_from_slice(HEARTBEAT_V2);
+
+        let frame = codec
+            .decode(&mut src)
+            .expect("Failed to decode")
+            .expect("Missing frame");
+        assert_frame_eq(&frame, &heartbeat_frame(MavlinkVersion::V2));
+        assert!(src.is_empty());
+        assert_eq!(codec.discarded(), 0);
+    }
+
+    #[tokio::test]
+    pub async fn test_framed_roundtrip() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = Framed::new(client, MavlinkCodec::<MavMessage>::new(ReadVersion::Any));
+        let mut server = Framed::new(server, RawMavlinkCodec::<MavMessage>::new(ReadVersion::Any));
+
+        client
+            .send(heartbeat_frame(MavlinkVersion::V2))
+            .await
+            .expect("Failed to send");
+        let raw = server
+            .next()
+            .await
+            .expect("Stream ended")
+            .expect("Failed to receive");
+        assert_eq!(raw.raw_bytes(), HEARTBEAT_V2);
+
+        // forward the raw frame back unchanged
+        server.send(raw).await.expect("Failed to send");
+        let frame = client
+            .next()
+            .await
+            .expect("Stream ended")
+            .expect("Failed to receive");
+        assert_frame_eq(&frame, &heartbeat_frame(MavlinkVersion::V2));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    pub fn test_signing() {
+        use mavlink::SigningConfig;
+
+        use crate::test_shared::SECRET_KEY;
+
+        let mut codec = MavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        codec.setup_signing(Some(SigningConfig::new(SECRET_KEY, 0, true, false)));
+        let mut src = BytesMut::new();
+        codec
+            .encode(heartbeat_frame(MavlinkVersion::V2), &mut src)
+            .expect("Failed to encode");
+        assert_eq!(src.len(), HEARTBEAT_V2.len() + 13);
+
+        // a codec using another key skips the frame
+        let mut other = MavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        other.setup_signing(Some(SigningConfig::new([0u8; 32], 0, true, false)));
+        let mut copy = src.clone();
+        assert!(other.decode(&mut copy).expect("Failed to decode").is_none());
+        assert_eq!(other.discarded(), src.len());
+
+        let mut receiver = MavlinkCodec::<MavMessage>::new(ReadVersion::Any);
+        receiver.setup_signing(Some(SigningConfig::new(SECRET_KEY, 0, true, false)));
+        // unsigned MAVLink 1 frames are rejected
+        src.extend_from_slice(HEARTBEAT_V1);
+        let frame = receiver
+            .decode(&mut src)
+            .expect("Failed to decode")
+            .expect("Missing frame");
+        assert_eq!(frame.msg, heartbeat_frame(MavlinkVersion::V2).msg);
+        assert!(receiver
+            .decode(&mut src)
+            .expect("Failed to decode")
+            .is_none());
+        assert_eq!(receiver.discarded(), HEARTBEAT_V1.len());
+
+        // unless only MAVLink 1 frames are decoded
+        receiver.set_version(MavlinkVersion::V1.into());
+        src.extend_from_slice(HEARTBEAT_V1);
+        let frame = receiver
+            .decode(&mut src)
+            .expect("Failed to decode")
+            .expect("Missing frame");
+        assert_frame_eq(&frame, &heartbeat_frame(MavlinkVersion::V1));
+    }
+}
@@ -0,0 +1,70 @@
+mod test_shared;
+
+#[cfg(all(feature = "embedded", feature = "common"))]
+mod embedded_async {
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncEmbeddedConnection, AsyncEmbeddedMavConnection, MavlinkVersion};
+
+    #[tokio::test]
+    pub async fn test_recv_any_version() {
+        let mut input = crate::test_shared::HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(crate::test_shared::HEARTBEAT_V2);
+        let mut output = [0u8; 64];
+
+        let mut conn = AsyncEmbeddedConnection::new(input.as_slice(), output.as_mut_slice());
+        AsyncEmbeddedMavConnection::<MavMessage>::set_allow_recv_any_version(&mut conn, true);
+
+        let frame = AsyncEmbeddedMavConnection::<MavMessage>::recv_frame(&mut conn)
+            .await
+            .expect("Failed to receive V1 heartbeat");
+        assert_eq!(frame.protocol_version, MavlinkVersion::V1);
+        assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
+
+        let frame = AsyncEmbeddedMavConnection::<MavMessage>::recv_frame(&mut conn)
+            .await
+            .expect("Failed to receive V2 heartbeat");
+        assert_eq!(frame.protocol_version, MavlinkVersion::V2);
+        if let MavMessage::HEARTBEAT(msg) = frame.msg {
+            assert_eq!(msg, crate::test_shared::get_heartbeat_msg());
+        } else {
+            panic!("Decoded wrong message type");
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_send_sequence() {
+        let mut output = [0u8; 64];
+        let mut conn = AsyncEmbeddedConnection::new([].as_slice(), output.as_mut_slice());
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        let mut header = crate::test_shared::COMMON_MSG_HEADER;
+        let first = conn.send(&header, &msg).await.expect("Failed to send");
+        header.sequence = 0;
+        conn.send(&header, &msg).await.expect("Failed to send");
+
+        // the connection assigns its own sequence numbers
+        assert_eq!(output[4], 0);
+        assert_eq!(output[first + 4], 1);
+        assert_eq!(output[first + 5], header.system_id);
+    }
+
+    #[tokio::test]
+    pub async fn test_version_negotiation() {
+        let mut output = [0u8; 64];
+        let mut conn =
+            AsyncEmbeddedConnection::new(crate::test_shared::HEARTBEAT_V2, output.as_mut_slice());
+        conn.set_version_negotiation(true);
+        assert_eq!(
+            AsyncEmbeddedMavConnection::<MavMessage>::protocol_version(&conn),
+            MavlinkVersion::V1
+        );
+
+        AsyncEmbeddedMavConnection::<MavMessage>::recv(&mut conn)
+            .await
+            .expect("Failed to receive V2 heartbeat");
+        assert_eq!(
+            AsyncEmbeddedMavConnection::<MavMessage>::protocol_version(&conn),
+            MavlinkVersion::V2
+        );
+    }
+}
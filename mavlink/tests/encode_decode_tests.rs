@@ -57,7 +57,7 @@ mod test_encode_decode {
         mavlink::write_v2_msg(
             &mut v,
             crate::test_shared::COMMON_MSG_HEADER,
-            &mavlink::common::MavMessage::HIL_ACTUATOR_CONTROLS(send_msg),
+            &mavlink::common::MavMessage::from(send_msg),
         )
         .expect("Failed to write message");
 
@@ -0,0 +1,271 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "message-schemas", feature = "common"))]
+mod test_export {
+    use mavlink::common::{MavMessage, MavSeverity, HEARTBEAT_DATA, STATUSTEXT_DATA};
+    use mavlink::export::{columns, enum_name, ColumnType, CsvExporter, EnumFormat};
+    use mavlink::schema::{FieldType, FieldValue, MessageDataSchema, MessageFields};
+
+    fn export_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mavlink-export-test-{}-{name}", std::process::id()))
+    }
+
+    fn statustext(text: &str) -> MavMessage {
+        let mut data = STATUSTEXT_DATA {
+            severity: MavSeverity::MAV_SEVERITY_WARNING,
+            ..Default::default()
+        };
+        data.text[..text.len()].copy_from_slice(text.as_bytes());
+        MavMessage::STATUSTEXT(data)
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = &HEARTBEAT_DATA::SCHEMA;
+        assert_eq!(schema.id, 0);
+        assert_eq!(schema.name, "HEARTBEAT");
+        let names: Vec<_> = schema.fields.iter().map(|field| field.name).collect();
+        assert_eq!(
+            names,
+            [
+                "custom_mode",
+                "mavtype",
+                "autopilot",
+                "base_mode",
+                "system_status",
+                "mavlink_version"
+            ]
+        );
+        let base_mode = schema.field("base_mode").unwrap();
+        assert_eq!(base_mode.field_type, FieldType::UInt8);
+        assert!(base_mode.enum_schema.unwrap().bitmask);
+        assert_eq!(
+            MavMessage::schema_from_id(253).map(|schema| schema.name),
+            Some("STATUSTEXT")
+        );
+        assert!(MavMessage::schemas()
+            .iter()
+            .any(|schema| schema.name == "HIL_ACTUATOR_CONTROLS"));
+
+        let text = STATUSTEXT_DATA::SCHEMA.field("text").unwrap();
+        assert_eq!(text.field_type, FieldType::Char);
+        assert_eq!(text.array_length, Some(50));
+    }
+
+    #[test]
+    fn test_for_each_field() {
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        assert_eq!(heartbeat.schema().name, "HEARTBEAT");
+        let mut values = Vec::new();
+        heartbeat.for_each_field(&mut |field, value| values.push((field.name, value.as_u64())));
+        assert_eq!(values[0], ("custom_mode", Some(5)));
+        assert_eq!(values[3], ("base_mode", Some(89)));
+        assert_eq!(values[4], ("system_status", Some(3)));
+
+        let controls = MavMessage::from(crate::test_shared::get_hil_actuator_controls_msg());
+        controls.for_each_field(&mut |field, value| {
+            if field.name == "controls" {
+                assert_eq!(value.array_len(), Some(16));
+                assert_eq!(value.element(2), Some(FieldValue::Float(2.0)));
+            }
+        });
+    }
+
+    #[test]
+    fn test_columns() {
+        let schema = MavMessage::schema_from_id(93).unwrap();
+        let named = columns(schema, EnumFormat::Name);
+        let names: Vec<_> = named.iter().map(|column| column.name.as_str()).collect();
+        assert_eq!(&names[..3], ["timestamp", "system_id", "component_id"]);
+        let controls = names
+            .iter()
+            .position(|name| *name == "controls[0]")
+            .unwrap();
+        assert_eq!(names[controls + 15], "controls[15]");
+        assert_eq!(named[controls].column_type, ColumnType::Float);
+        assert!(named[0].nullable);
+        let mode = named.iter().find(|column| column.name == "mode").unwrap();
+        assert_eq!(mode.column_type, ColumnType::String);
+
+        let values = columns(schema, EnumFormat::Value);
+        let mode = values.iter().find(|column| column.name == "mode").unwrap();
+        assert_eq!(mode.column_type, ColumnType::UInt8);
+
+        let text = columns(&STATUSTEXT_DATA::SCHEMA, EnumFormat::Value);
+        assert_eq!(text.len(), 5);
+        assert_eq!(text[4].column_type, ColumnType::String);
+    }
+
+    #[test]
+    fn test_enum_name() {
+        let flags = HEARTBEAT_DATA::SCHEMA
+            .field("base_mode")
+            .unwrap()
+            .enum_schema
+            .unwrap();
+        assert_eq!(
+            enum_name(flags, 0x81),
+            "MAV_MODE_FLAG_SAFETY_ARMED|MAV_MODE_FLAG_CUSTOM_MODE_ENABLED"
+        );
+        assert_eq!(enum_name(flags, 0), "0");
+        let state = HEARTBEAT_DATA::SCHEMA
+            .field("system_status")
+            .unwrap()
+            .enum_schema
+            .unwrap();
+        assert_eq!(enum_name(state, 4), "MAV_STATE_ACTIVE");
+        assert_eq!(enum_name(state, 200), "200");
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let header = crate::test_shared::COMMON_MSG_HEADER;
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        for (enums, expected) in [
+            (
+                EnumFormat::Name,
+                "1000,1,2,5,MAV_TYPE_QUADROTOR,MAV_AUTOPILOT_ARDUPILOTMEGA,\
+                 MAV_MODE_FLAG_MANUAL_INPUT_ENABLED|MAV_MODE_FLAG_STABILIZE_ENABLED|\
+                 MAV_MODE_FLAG_GUIDED_ENABLED|MAV_MODE_FLAG_CUSTOM_MODE_ENABLED,MAV_STATE_STANDBY,3",
+            ),
+            (EnumFormat::Value, "1000,1,2,5,2,3,89,3,3"),
+        ] {
+            let dir = export_dir(&format!("{enums:?}"));
+            let mut exporter = CsvExporter::new(&dir, enums).unwrap();
+            exporter.write(Some(1000), &header, &heartbeat).unwrap();
+            exporter.write(None, &header, &heartbeat).unwrap();
+            exporter
+                .write(Some(2000), &header, &statustext("Low battery, 10%"))
+                .unwrap();
+            exporter
+                .write(Some(3000), &header, &statustext("Say \"hi\""))
+                .unwrap();
+            exporter.flush().unwrap();
+
+            let heartbeats = std::fs::read_to_string(dir.join("HEARTBEAT.csv")).unwrap();
+            let lines: Vec<_> = heartbeats.lines().collect();
+            assert_eq!(
+                lines[0],
+                "timestamp,system_id,component_id,custom_mode,mavtype,autopilot,base_mode,system_status,mavlink_version"
+            );
+            assert_eq!(lines[1], expected);
+            assert!(lines[2].starts_with(",1,2,5,"));
+            assert_eq!(lines.len(), 3);
+
+            let texts = std::fs::read_to_string(dir.join("STATUSTEXT.csv")).unwrap();
+            let severity = match enums {
+                EnumFormat::Name => "MAV_SEVERITY_WARNING",
+                EnumFormat::Value => "4",
+            };
+            assert_eq!(
+                texts,
+                format!(
+                    "timestamp,system_id,component_id,severity,text\n\
+                     2000,1,2,{severity},\"Low battery, 10%\"\n\
+                     3000,1,2,{severity},\"Say \"\"hi\"\"\"\n"
+                )
+            );
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_csv_export_arrays() {
+        let dir = export_dir("arrays");
+        let mut exporter = CsvExporter::new(&dir, EnumFormat::Value).unwrap();
+        let controls = MavMessage::from(crate::test_shared::get_hil_actuator_controls_msg());
+        exporter
+            .write(Some(1), &crate::test_shared::COMMON_MSG_HEADER, &controls)
+            .unwrap();
+        exporter.flush().unwrap();
+
+        let table = std::fs::read_to_string(dir.join("HIL_ACTUATOR_CONTROLS.csv")).unwrap();
+        let mut lines = table.lines();
+        let header: Vec<_> = lines.next().unwrap().split(',').collect();
+        let row: Vec<_> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header.len(), row.len());
+        let control = |i: usize| {
+            let column = header
+                .iter()
+                .position(|name| *name == format!("controls[{i}]"))
+                .unwrap();
+            row[column]
+        };
+        assert_eq!(control(0), "0");
+        assert_eq!(control(15), "15");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[cfg(all(feature = "parquet", feature = "common"))]
+mod test_parquet {
+    use mavlink::common::{MavMessage, STATUSTEXT_DATA};
+    use mavlink::export::parquet::arrow_array::cast::AsArray;
+    use mavlink::export::parquet::arrow_array::types::{UInt64Type, UInt8Type};
+    use mavlink::export::parquet::arrow_array::Array;
+    use mavlink::export::parquet::arrow_schema::DataType;
+    use mavlink::export::parquet::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use mavlink::export::parquet::{ArrowTableBuilder, ParquetExporter};
+    use mavlink::export::EnumFormat;
+    use mavlink::schema::MessageDataSchema;
+
+    #[test]
+    fn test_arrow_table() {
+        let header = crate::test_shared::COMMON_MSG_HEADER;
+        let mut builder = ArrowTableBuilder::new(&STATUSTEXT_DATA::SCHEMA, EnumFormat::Value);
+        let mut data = STATUSTEXT_DATA::default();
+        data.text[..5].copy_from_slice(b"Hello");
+        builder
+            .append(Some(10), &header, &MavMessage::STATUSTEXT(data.clone()))
+            .unwrap();
+        builder
+            .append(None, &header, &MavMessage::STATUSTEXT(data.clone()))
+            .unwrap();
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        assert!(builder.append(None, &header, &heartbeat).is_err());
+        assert_eq!(builder.len(), 2);
+
+        let batch = builder.finish().unwrap();
+        assert!(builder.is_empty());
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().field(3).data_type(), &DataType::UInt8);
+        let timestamps = batch.column(0).as_primitive::<UInt64Type>();
+        assert_eq!(timestamps.value(0), 10);
+        assert!(timestamps.is_null(1));
+        assert_eq!(batch.column(1).as_primitive::<UInt8Type>().value(0), 1);
+        assert_eq!(batch.column(4).as_string::<i32>().value(1), "Hello");
+    }
+
+    #[test]
+    fn test_parquet_export() {
+        let dir = std::env::temp_dir().join(format!(
+            "mavlink-export-test-{}-parquet",
+            std::process::id()
+        ));
+        let header = crate::test_shared::COMMON_MSG_HEADER;
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        let mut exporter = ParquetExporter::new(&dir, EnumFormat::Name).unwrap();
+        for timestamp in 0..10_000 {
+            exporter
+                .write(Some(timestamp), &header, &heartbeat)
+                .unwrap();
+        }
+        exporter.finish().unwrap();
+
+        let file = std::fs::File::open(dir.join("HEARTBEAT.parquet")).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+        let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        assert_eq!(rows, 10_000);
+        let batch = &batches[0];
+        assert_eq!(batch.schema().field(4).name(), "mavtype");
+        assert_eq!(
+            batch.column(4).as_string::<i32>().value(0),
+            "MAV_TYPE_QUADROTOR"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
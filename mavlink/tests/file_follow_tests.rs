@@ -0,0 +1,166 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_file_follow_address {
+    use mavlink::common::MavMessage;
+    use mavlink::ConnectionAddress;
+
+    #[test]
+    fn test_parse_address() {
+        let address = "file:flight.tlog?format=tlog&follow=true&throttle=true";
+        let parsed = ConnectionAddress::parse_address(address).expect("Failed to parse");
+        assert_eq!(parsed.to_string(), address);
+
+        for address in [
+            "file:flight.bin?throttle=true",
+            "file:flight.tlog?format=tlog&follow=yes",
+        ] {
+            assert!(
+                ConnectionAddress::parse_address(address).is_err(),
+                "{address}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_blocking_follow_unsupported() {
+        let path = std::env::temp_dir().join("mavlink-file-follow-blocking.bin");
+        std::fs::write(&path, []).unwrap();
+        let result =
+            mavlink::connect::<MavMessage>(&format!("file:{}?follow=true", path.display()));
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(std::io::ErrorKind::Unsupported)
+        );
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_file_follow {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    use mavlink::common::MavMessage;
+    use mavlink::tlog::TlogWriter;
+    use mavlink::{MavFrame, MavlinkVersion};
+
+    fn heartbeat(id: u32) -> MavMessage {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavMessage::HEARTBEAT(heartbeat)
+    }
+
+    fn id(msg: &MavMessage) -> u32 {
+        match msg {
+            MavMessage::HEARTBEAT(heartbeat) => heartbeat.custom_mode,
+            _ => panic!("Unexpected message {msg:?}"),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "mavlink-file-follow-test-{}-{name}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Serialized tlog record of a heartbeat
+    fn record(timestamp: u64, id: u32) -> Vec<u8> {
+        let mut writer = TlogWriter::new(Vec::new());
+        let frame = MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: heartbeat(id),
+            protocol_version: MavlinkVersion::V2,
+        };
+        writer.write_message(timestamp, &frame).unwrap();
+        writer.into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_follow_raw() {
+        let path = temp_path("raw.bin");
+        let writer =
+            mavlink::connect::<MavMessage>(&format!("file:{}?mode=write", path.display())).unwrap();
+        writer.send_default(&heartbeat(0)).unwrap();
+
+        let follower =
+            mavlink::connect_async::<MavMessage>(&format!("file:{}?follow=true", path.display()))
+                .await
+                .unwrap();
+        assert_eq!(id(&follower.recv().await.unwrap().1), 0);
+
+        // the end of the file is not the end of the connection
+        let pending = tokio::time::timeout(Duration::from_millis(100), follower.recv()).await;
+        assert!(pending.is_err());
+
+        let append = tokio::task::spawn_blocking(move || {
+            for i in 1..4 {
+                std::thread::sleep(Duration::from_millis(20));
+                writer.send_default(&heartbeat(i)).unwrap();
+            }
+        });
+        for i in 1..4 {
+            assert_eq!(id(&follower.recv().await.unwrap().1), i);
+        }
+        append.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_follow_tlog() {
+        let path = temp_path("follow.tlog");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(&record(1, 0)).unwrap();
+
+        let follower = mavlink::connect_async::<MavMessage>(&format!(
+            "file:{}?format=tlog&follow=true",
+            path.display()
+        ))
+        .await
+        .unwrap();
+        let mut receive_half = follower.split().0;
+        assert_eq!(id(&receive_half.recv().await.unwrap().1), 0);
+
+        // a record that is only partially written is completed by the recorder
+        let append = tokio::task::spawn_blocking(move || {
+            let partial = record(2, 1);
+            let (head, tail) = partial.split_at(12);
+            file.write_all(head).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+            file.write_all(tail).unwrap();
+            file.write_all(&record(3, 2)).unwrap();
+        });
+        assert_eq!(id(&receive_half.recv().await.unwrap().1), 1);
+        assert_eq!(id(&receive_half.recv().await.unwrap().1), 2);
+        append.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_throttle() {
+        let path = temp_path("throttle.tlog");
+        let mut log = Vec::new();
+        for i in 0..3 {
+            log.extend(record(1_000_000 + u64::from(i) * 30_000, i));
+        }
+        // corrupt bytes before the records are skipped
+        log.splice(0..0, [0xfd, 0x01, 0x02]);
+        std::fs::write(&path, log).unwrap();
+
+        for (throttle, minimum) in [(false, 0), (true, 60)] {
+            let connection = mavlink::connect_async::<MavMessage>(&format!(
+                "file:{}?format=tlog&throttle={throttle}",
+                path.display()
+            ))
+            .await
+            .unwrap();
+            let start = Instant::now();
+            for i in 0..3 {
+                assert_eq!(id(&connection.recv().await.unwrap().1), i);
+            }
+            assert!(start.elapsed() >= Duration::from_millis(minimum));
+            assert!(connection.recv().await.is_err());
+        }
+    }
+}
@@ -0,0 +1,227 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_file_output {
+    use std::fs::File;
+    use std::path::{Path, PathBuf};
+
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::tlog::TlogReader;
+    use mavlink::{ConnectionAddress, FileConfig, FileFormat, FileMode};
+
+    fn heartbeat(id: u32) -> MavMessage {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavMessage::HEARTBEAT(heartbeat)
+    }
+
+    fn id(msg: &MavMessage) -> u32 {
+        match msg {
+            MavMessage::HEARTBEAT(heartbeat) => heartbeat.custom_mode,
+            _ => panic!("Unexpected message {msg:?}"),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("mavlink-file-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Read all messages of a raw file
+    fn read_ids(path: &Path) -> Vec<(u8, u32)> {
+        let connection =
+            mavlink::connect::<MavMessage>(&format!("file:{}", path.display())).unwrap();
+        let mut ids = Vec::new();
+        loop {
+            match connection.recv() {
+                Ok((header, msg)) => ids.push((header.sequence, id(&msg))),
+                Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return ids
+                }
+                Err(e) => panic!("Failed to read file: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_address() {
+        for address in [
+            "file:in.bin",
+            "file:out.bin?mode=write",
+            "file:out.tlog?mode=write&format=tlog",
+            "file:in.bin?mode=readwrite&output=out.bin",
+            // parameters that are not known are part of the path
+            "file:in.bin?speed=2",
+            "file:what?.bin?mode=write",
+        ] {
+            let parsed = ConnectionAddress::parse_address(address).expect("Failed to parse");
+            assert!(matches!(parsed, ConnectionAddress::File(_)));
+            assert_eq!(parsed.to_string(), address);
+        }
+        assert_eq!(
+            ConnectionAddress::parse_address("file:in.bin?output=out.tlog&format=tlog")
+                .unwrap()
+                .to_string(),
+            "file:in.bin?mode=readwrite&output=out.tlog&format=tlog"
+        );
+        let config = FileConfig::with_mode(
+            PathBuf::from("in.bin"),
+            FileMode::ReadWrite(PathBuf::from("out.bin")),
+            FileFormat::Raw,
+        );
+        assert_eq!(
+            ConnectionAddress::from(config).to_string(),
+            "file:in.bin?mode=readwrite&output=out.bin"
+        );
+
+        for address in [
+            "file:in.bin?mode=append",
+            "file:in.bin?mode=readwrite",
+            "file:in.bin?mode=write&output=out.bin",
+            "file:in.bin?format=pcap",
+        ] {
+            assert!(
+                ConnectionAddress::parse_address(address).is_err(),
+                "{address}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_raw() {
+        let path = temp_path("raw.bin");
+        let connection =
+            mavlink::connect::<MavMessage>(&format!("file:{}?mode=write", path.display())).unwrap();
+        for i in 0..3 {
+            assert!(connection.send_default(&heartbeat(i)).unwrap() > 0);
+        }
+        assert!(matches!(
+            connection.recv(),
+            Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported
+        ));
+        drop(connection);
+
+        // frames are appended to existing files
+        let connection =
+            mavlink::connect::<MavMessage>(&format!("file:{}?mode=write", path.display())).unwrap();
+        connection.send_default(&heartbeat(3)).unwrap();
+        assert_eq!(read_ids(&path), vec![(0, 0), (1, 1), (2, 2), (0, 3)]);
+    }
+
+    #[test]
+    fn test_write_tlog() {
+        let path = temp_path("out.tlog");
+        let connection = mavlink::connect::<MavMessage>(&format!(
+            "file:{}?mode=write&format=tlog",
+            path.display()
+        ))
+        .unwrap();
+        let start = mavlink::tlog::timestamp_now();
+        for i in 0..3 {
+            connection.send_default(&heartbeat(i)).unwrap();
+        }
+        let end = mavlink::tlog::timestamp_now();
+
+        let mut reader = TlogReader::new(File::open(&path).unwrap());
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap())
+            .collect();
+        assert_eq!(frames.len(), 3);
+        for (i, (timestamp, frame)) in frames.iter().enumerate() {
+            assert!(*timestamp >= start && *timestamp <= end);
+            assert_eq!(id(&frame.msg), i as u32);
+        }
+        assert!(reader.corrupt_regions().is_empty());
+    }
+
+    /// Test a file connection standing in for a link, answering every input message
+    #[test]
+    fn test_read_write() {
+        let input = temp_path("input.bin");
+        let output = temp_path("output.bin");
+        let writer =
+            mavlink::connect::<MavMessage>(&format!("file:{}?mode=write", input.display()))
+                .unwrap();
+        for i in 0..3 {
+            writer.send_default(&heartbeat(i)).unwrap();
+        }
+
+        let connection = mavlink::connect::<MavMessage>(&format!(
+            "file:{}?mode=readwrite&output={}",
+            input.display(),
+            output.display()
+        ))
+        .unwrap();
+        while let Ok((_header, msg)) = connection.recv() {
+            connection.send_default(&heartbeat(10 + id(&msg))).unwrap();
+        }
+        assert_eq!(read_ids(&output), vec![(0, 10), (1, 11), (2, 12)]);
+
+        // the halves of a split connection keep the files
+        let connection = mavlink::connect::<MavMessage>(&format!(
+            "file:{}?mode=readwrite&output={}",
+            input.display(),
+            output.display()
+        ))
+        .unwrap();
+        let (mut receive_half, mut send_half) = connection.split();
+        let (_header, msg) = receive_half.recv().unwrap();
+        send_half.send_default(&msg).unwrap();
+        assert_eq!(read_ids(&output).last(), Some(&(0, 0)));
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_file_output_async {
+    use std::fs::File;
+
+    use mavlink::common::MavMessage;
+    use mavlink::tlog::TlogReader;
+
+    #[tokio::test]
+    async fn test_async_read_write() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!(
+            "mavlink-file-test-{}-async-in.tlog",
+            std::process::id()
+        ));
+        let output = dir.join(format!(
+            "mavlink-file-test-{}-async-out.tlog",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let writer = mavlink::connect_async::<MavMessage>(&format!(
+            "file:{}?mode=write&format=tlog",
+            input.display()
+        ))
+        .await
+        .unwrap();
+        writer.send_default(&msg).await.unwrap();
+        writer.send_default(&msg).await.unwrap();
+
+        let connection = mavlink::connect_async::<MavMessage>(&format!(
+            "file:{}?output={}&format=tlog",
+            input.display(),
+            output.display()
+        ))
+        .await
+        .unwrap();
+        while let Ok((_header, received)) = connection.recv().await {
+            connection.send_default(&received).await.unwrap();
+        }
+
+        let mut reader = TlogReader::new(File::open(&output).unwrap());
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap().1.msg)
+            .collect();
+        assert_eq!(frames, vec![msg; 2]);
+    }
+}
@@ -0,0 +1,176 @@
+mod test_shared;
+
+#[cfg(feature = "common")]
+mod frame_parser {
+    use mavlink::common::MavMessage;
+    use mavlink::frame_parser::{FrameParser, ParseEvent};
+    use mavlink::peek_reader::PeekReader;
+    use mavlink::{MavlinkVersion, ReadVersion};
+
+    use crate::test_shared::{HEARTBEAT_V1, HEARTBEAT_V2};
+
+    fn frames(events: &[ParseEvent]) -> Vec<&[u8]> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ParseEvent::Frame(frame) => Some(frame.raw_bytes()),
+                ParseEvent::Discarded(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn test_feed_byte_by_byte() {
+        let mut input = HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(HEARTBEAT_V2);
+
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        let mut events = Vec::new();
+        for byte in &input {
+            events.extend(parser.feed(core::slice::from_ref(byte)));
+        }
+        assert_eq!(frames(&events), [HEARTBEAT_V1, HEARTBEAT_V2]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(parser.buffered(), 0);
+
+        let ParseEvent::Frame(frame) = events[1] else {
+            panic!("expected a frame");
+        };
+        assert_eq!(frame.version(), MavlinkVersion::V2);
+        assert_eq!(
+            frame.sequence(),
+            crate::test_shared::COMMON_MSG_HEADER.sequence
+        );
+    }
+
+    #[test]
+    pub fn test_feed_incomplete_frame() {
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        assert_eq!(parser.feed(&HEARTBEAT_V2[..10]).count(), 0);
+        assert_eq!(parser.buffered(), 10);
+
+        let events: Vec<_> = parser.feed(&HEARTBEAT_V2[10..]).collect();
+        assert_eq!(frames(&events), [HEARTBEAT_V2]);
+
+        assert_eq!(parser.feed(&HEARTBEAT_V2[..3]).count(), 0);
+        assert_eq!(parser.reset(), 3);
+        assert_eq!(parser.buffered(), 0);
+    }
+
+    #[test]
+    pub fn test_discarded_bytes() {
+        let mut input = vec![0x01, 0x02, 0x03];
+        input.extend_from_slice(HEARTBEAT_V2);
+        input.extend_from_slice(&[0x04, 0x05]);
+        // a frame with a corrupted checksum
+        let mut corrupted = HEARTBEAT_V2.to_vec();
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        input.extend_from_slice(&corrupted);
+        input.extend_from_slice(HEARTBEAT_V1);
+        input.push(0x06);
+
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        let events: Vec<_> = parser.feed(&input).collect();
+        assert_eq!(
+            events,
+            [
+                ParseEvent::Discarded(3),
+                events[1],
+                ParseEvent::Discarded(2 + corrupted.len()),
+                events[3],
+                ParseEvent::Discarded(1),
+            ]
+        );
+        assert_eq!(frames(&events), [HEARTBEAT_V2, HEARTBEAT_V1]);
+    }
+
+    #[test]
+    pub fn test_backtracking() {
+        // the start of a frame that was cut off, followed by a complete frame
+        let mut input = HEARTBEAT_V2[..5].to_vec();
+        input.extend_from_slice(HEARTBEAT_V2);
+
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        let events: Vec<_> = parser.feed(&input).collect();
+        assert_eq!(events[0], ParseEvent::Discarded(5));
+        assert_eq!(frames(&events), [HEARTBEAT_V2]);
+    }
+
+    #[test]
+    pub fn test_version_filter() {
+        let mut input = HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(HEARTBEAT_V2);
+
+        let mut parser = FrameParser::<MavMessage>::new(MavlinkVersion::V2.into());
+        let events: Vec<_> = parser.feed(&input).collect();
+        assert_eq!(events[0], ParseEvent::Discarded(HEARTBEAT_V1.len()));
+        assert_eq!(frames(&events), [HEARTBEAT_V2]);
+
+        parser.set_version(MavlinkVersion::V1.into());
+        let events: Vec<_> = parser.feed(&input).collect();
+        assert_eq!(frames(&events), [HEARTBEAT_V1]);
+        assert_eq!(events[1], ParseEvent::Discarded(HEARTBEAT_V2.len()));
+    }
+
+    #[test]
+    pub fn test_random_chunks_match_reader() {
+        // xorshift, so failures are reproducible without extra dependencies
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut input = Vec::new();
+        for _ in 0..200 {
+            match next() % 4 {
+                0 => input.extend_from_slice(HEARTBEAT_V1),
+                1 => input.extend_from_slice(HEARTBEAT_V2),
+                2 => {
+                    let frame = if next() % 2 == 0 {
+                        HEARTBEAT_V1
+                    } else {
+                        HEARTBEAT_V2
+                    };
+                    let cut = next() as usize % frame.len();
+                    input.extend_from_slice(&frame[..cut]);
+                }
+                _ => {
+                    for _ in 0..next() % 16 {
+                        input.push(next() as u8);
+                    }
+                }
+            }
+        }
+
+        let mut expected = Vec::new();
+        let mut reader = PeekReader::new(input.as_slice());
+        while let Ok(frame) = mavlink::read_any_raw_message::<MavMessage, _>(&mut reader) {
+            expected.push(frame.raw_bytes().to_vec());
+        }
+
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        let mut received = Vec::new();
+        let mut consumed = 0;
+        let mut remaining = input.as_slice();
+        while !remaining.is_empty() {
+            let (chunk, rest) = remaining.split_at((next() as usize % 64).min(remaining.len()));
+            remaining = rest;
+            for event in parser.feed(chunk) {
+                match event {
+                    ParseEvent::Frame(frame) => {
+                        consumed += frame.raw_bytes().len();
+                        received.push(frame.raw_bytes().to_vec());
+                    }
+                    ParseEvent::Discarded(count) => consumed += count,
+                }
+            }
+        }
+
+        assert_eq!(received, expected);
+        // every byte is either part of a frame, discarded or still buffered
+        assert_eq!(consumed + parser.buffered(), input.len());
+    }
+}
@@ -0,0 +1,175 @@
+mod test_shared;
+
+#[cfg(all(feature = "futures-io", feature = "common"))]
+mod futures_io {
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::stream::FusedStream;
+    use futures::{SinkExt, StreamExt};
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{
+        AsyncMavConnection, AsyncStreamConnection, MavFrame, MavFrameSink, MavFrameStream,
+        MavlinkVersion,
+    };
+
+    #[test]
+    pub fn test_recv_without_tokio() {
+        let mut input = crate::test_shared::HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(crate::test_shared::HEARTBEAT_V2);
+
+        let mut conn = AsyncStreamConnection::new(input.as_slice(), futures::io::sink());
+        AsyncMavConnection::<MavMessage>::set_allow_recv_any_version(&mut conn, true);
+
+        block_on(async {
+            for _ in 0..2 {
+                let (header, msg) = AsyncMavConnection::<MavMessage>::recv(&conn)
+                    .await
+                    .expect("Failed to receive heartbeat");
+                assert_eq!(header, crate::test_shared::COMMON_MSG_HEADER);
+                if let MavMessage::HEARTBEAT(msg) = msg {
+                    assert_eq!(msg, crate::test_shared::get_heartbeat_msg());
+                } else {
+                    panic!("Decoded wrong message type");
+                }
+            }
+        });
+    }
+
+    #[test]
+    pub fn test_send_roundtrip() {
+        let mut conn = AsyncStreamConnection::new([].as_slice(), Vec::new());
+        AsyncMavConnection::<MavMessage>::set_protocol_version(&mut conn, MavlinkVersion::V1);
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        block_on(async {
+            for _ in 0..2 {
+                conn.send(&crate::test_shared::COMMON_MSG_HEADER, &msg)
+                    .await
+                    .expect("Failed to send heartbeat");
+            }
+        });
+        let (_, output) = conn.into_inner();
+
+        let mut reader = AsyncStreamConnection::new(output.as_slice(), futures::io::sink());
+        AsyncMavConnection::<MavMessage>::set_protocol_version(&mut reader, MavlinkVersion::V1);
+        block_on(async {
+            for sequence in 0..2 {
+                let (header, received) = AsyncMavConnection::<MavMessage>::recv(&reader)
+                    .await
+                    .expect("Failed to receive heartbeat");
+                // the connection assigns its own sequence numbers
+                assert_eq!(header.sequence, sequence);
+                assert_eq!(received, msg);
+            }
+        });
+    }
+
+    #[test]
+    pub fn test_frame_stream() {
+        let mut input = crate::test_shared::HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(crate::test_shared::HEARTBEAT_V2);
+
+        let mut conn = AsyncStreamConnection::new(Cursor::new(input), futures::io::sink());
+        AsyncMavConnection::<MavMessage>::set_allow_recv_any_version(&mut conn, true);
+
+        // the stream yields the end of file error and then ends
+        let frames: Vec<_> =
+            block_on(MavFrameStream::<MavMessage, _>::new(Arc::new(conn)).collect());
+        assert_eq!(frames.len(), 3);
+        for frame in &frames[..2] {
+            let frame = frame.as_ref().expect("Failed to receive heartbeat");
+            assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
+        }
+        assert!(frames[2].is_err());
+    }
+
+    #[test]
+    pub fn test_frame_stream_reset() {
+        struct ResetReader;
+
+        impl futures::AsyncRead for ResetReader {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+                _buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Err(io::ErrorKind::ConnectionReset.into()))
+            }
+        }
+
+        // the stream yields the persistent error once and then ends
+        let conn = AsyncStreamConnection::new(ResetReader, futures::io::sink());
+        let mut stream = MavFrameStream::<MavMessage, _>::new(Arc::new(conn));
+        let frames: Vec<_> = block_on((&mut stream).collect());
+        assert_eq!(frames.len(), 1);
+        assert!(matches!(
+            &frames[0],
+            Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::ConnectionReset
+        ));
+        assert!(stream.is_terminated());
+    }
+
+    #[test]
+    pub fn test_frame_sink() {
+        let conn = Arc::new(AsyncStreamConnection::new([].as_slice(), Vec::new()));
+        let mut sink = MavFrameSink::new(conn.clone());
+
+        let frame = MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg()),
+            protocol_version: MavlinkVersion::V2,
+        };
+        let frames = [frame.clone(), frame.clone(), frame.clone()];
+        block_on(sink.send_all(&mut futures::stream::iter(frames.map(Ok))))
+            .expect("Failed to send frames");
+        drop(sink);
+
+        let (_, output) = Arc::try_unwrap(conn)
+            .ok()
+            .expect("Connection still shared")
+            .into_inner();
+        let reader = AsyncStreamConnection::new(Cursor::new(output), futures::io::sink());
+        let received: Vec<_> = block_on(
+            MavFrameStream::<MavMessage, _>::new(Arc::new(reader))
+                .filter_map(|frame| async move { frame.ok() })
+                .collect(),
+        );
+        assert_eq!(received.len(), 3);
+        for (sequence, received) in received.into_iter().enumerate() {
+            assert_eq!(received.header.sequence, sequence as u8);
+            assert_eq!(received.msg, frame.msg);
+        }
+    }
+
+    #[test]
+    pub fn test_split() {
+        let mut input = crate::test_shared::HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(crate::test_shared::HEARTBEAT_V2);
+
+        let mut conn = AsyncStreamConnection::new(Cursor::new(input), futures::io::sink());
+        AsyncMavConnection::<MavMessage>::set_allow_recv_any_version(&mut conn, true);
+        let conn: Box<dyn AsyncMavConnection<MavMessage> + Sync + Send> = Box::new(conn);
+        let (mut receiver, mut sender) = conn.split();
+
+        // the halves keep the configuration of the connection
+        assert!(receiver.allow_recv_any_version());
+        assert_eq!(sender.protocol_version(), MavlinkVersion::V2);
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        block_on(async {
+            for _ in 0..2 {
+                let (header, received) = receiver.recv().await.expect("Failed to receive");
+                assert_eq!(header, crate::test_shared::COMMON_MSG_HEADER);
+                assert_eq!(received, msg);
+            }
+            assert!(receiver.recv().await.is_err());
+            sender.send_default(&msg).await.expect("Failed to send");
+        });
+    }
+}
@@ -0,0 +1,189 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_impaired_connections {
+    use std::time::{Duration, Instant};
+
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{
+        GilbertElliott, ImpairedConnection, Impairments, MavConnection, MavHeader, MavlinkVersion,
+        MemoryConnection,
+    };
+
+    fn heartbeat(id: u32) -> MavMessage {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavMessage::HEARTBEAT(heartbeat)
+    }
+
+    fn id(msg: &MavMessage) -> u32 {
+        match msg {
+            MavMessage::HEARTBEAT(heartbeat) => heartbeat.custom_mode,
+            _ => panic!("Unexpected message {msg:?}"),
+        }
+    }
+
+    /// Receive until no more messages are available
+    fn drain(connection: &dyn MavConnection<MavMessage>) -> Vec<u32> {
+        let mut received = Vec::new();
+        loop {
+            match connection.try_recv() {
+                Ok((_header, msg)) => received.push(id(&msg)),
+                Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    return received
+                }
+                Err(e) => panic!("Failed to receive message: {e}"),
+            }
+        }
+    }
+
+    fn impaired_pair(
+        outgoing: Impairments,
+        incoming: Impairments,
+    ) -> (ImpairedConnection<MavMessage>, MemoryConnection) {
+        let (a, b) = MemoryConnection::pair();
+        (ImpairedConnection::new(Box::new(a), outgoing, incoming), b)
+    }
+
+    #[test]
+    fn test_transparent() {
+        let (a, b) = impaired_pair(Impairments::default(), Impairments::default());
+        for i in 0..10 {
+            a.send_default(&heartbeat(i)).unwrap();
+            b.send_default(&heartbeat(i)).unwrap();
+        }
+        assert_eq!(drain(&b), (0..10).collect::<Vec<_>>());
+        assert_eq!(drain(&a), (0..10).collect::<Vec<_>>());
+        assert_eq!(a.outgoing_stats().delivered, 10);
+        assert_eq!(a.incoming_stats().delivered, 10);
+    }
+
+    #[test]
+    fn test_outgoing_impairments() {
+        let impairments = Impairments {
+            loss: 0.1,
+            duplication: 0.1,
+            reorder: 0.1,
+            corruption: 0.1,
+            seed: 5,
+            ..Impairments::default()
+        };
+        let transfer = || {
+            let (a, b) = impaired_pair(impairments.clone(), Impairments::default());
+            for i in 0..100 {
+                a.send_default(&heartbeat(i)).unwrap();
+            }
+            (drain(&b), a.outgoing_stats())
+        };
+        let (received, stats) = transfer();
+        assert_eq!(transfer(), (received.clone(), stats));
+
+        assert_eq!(stats.frames, 100);
+        assert!(stats.lost > 0 && stats.duplicated > 0 && stats.corrupted > 0);
+        // corrupted frames are discarded
+        assert!(received.len() as u64 <= stats.delivered);
+        assert!(received.len() as u64 >= stats.delivered - stats.corrupted);
+        assert!(received.windows(2).any(|w| w[0] == w[1]));
+        assert!(received.windows(2).any(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn test_incoming_burst_loss() {
+        let (a, b) = impaired_pair(
+            Impairments::default(),
+            Impairments {
+                burst_loss: Some(GilbertElliott::new(0.05, 0.2)),
+                seed: 9,
+                ..Impairments::default()
+            },
+        );
+        for i in 0..200 {
+            b.send_default(&heartbeat(i)).unwrap();
+        }
+        let received = drain(&a);
+        let stats = a.incoming_stats();
+        assert_eq!(received.len() as u64, stats.delivered);
+        assert_eq!(stats.frames - stats.lost, stats.delivered);
+        assert!(received.windows(2).any(|w| w[1] - w[0] > 3));
+    }
+
+    #[test]
+    fn test_latency() {
+        let latency = Duration::from_millis(50);
+        let delayed = Impairments {
+            latency,
+            ..Impairments::default()
+        };
+        let (a, b) = impaired_pair(delayed.clone(), delayed);
+
+        let start = Instant::now();
+        b.send_default(&heartbeat(1)).unwrap();
+        assert_eq!(id(&a.recv().unwrap().1), 1);
+        assert!(start.elapsed() >= latency);
+
+        // delayed sent frames are sent while receiving
+        let start = Instant::now();
+        a.send_default(&heartbeat(2)).unwrap();
+        assert!(drain(&b).is_empty());
+        b.send_default(&heartbeat(3)).unwrap();
+        assert_eq!(id(&a.recv().unwrap().1), 3);
+        assert!(start.elapsed() >= latency);
+        assert_eq!(drain(&b), vec![2]);
+    }
+
+    #[test]
+    fn test_split() {
+        let (a, b) = impaired_pair(Impairments::default(), Impairments::default());
+        let (mut receive_half, mut send_half) =
+            (Box::new(a) as Box<dyn MavConnection<_> + Sync + Send>).split();
+        send_half.send_default(&heartbeat(1)).unwrap();
+        b.send_default(&heartbeat(2)).unwrap();
+        assert_eq!(id(&b.recv().unwrap().1), 1);
+        assert_eq!(id(&receive_half.recv().unwrap().1), 2);
+    }
+
+    #[test]
+    fn test_sequence_gaps() {
+        let (a, b) = impaired_pair(
+            Impairments {
+                loss: 0.3,
+                seed: 3,
+                ..Impairments::default()
+            },
+            Impairments::default(),
+        );
+        for i in 0..50 {
+            a.send_default(&heartbeat(i)).unwrap();
+        }
+        let mut sequences = Vec::new();
+        while let Ok((header, msg)) = MavConnection::<MavMessage>::try_recv(&b) {
+            // frames are numbered before they are impaired
+            assert_eq!(u32::from(header.sequence), id(&msg));
+            sequences.push(header.sequence);
+        }
+        assert_eq!(sequences.len() as u64, a.outgoing_stats().delivered);
+        assert!(sequences.windows(2).any(|w| w[1] - w[0] > 1));
+    }
+
+    #[test]
+    fn test_raw_frames_unchanged() {
+        let (mut a, mut b) = impaired_pair(Impairments::default(), Impairments::default());
+        a.set_allow_recv_any_version(true);
+        let header = MavHeader {
+            system_id: 3,
+            component_id: 4,
+            sequence: 200,
+        };
+        MavConnection::<MavMessage>::set_protocol_version(&mut b, MavlinkVersion::V1);
+        b.send(&header, &heartbeat(1)).unwrap();
+        let frame = a.recv_raw().unwrap();
+        assert_eq!(frame.version(), MavlinkVersion::V1);
+        assert_eq!(frame.system_id(), 3);
+
+        // forwarded frames keep their version and sequence number
+        a.send_raw(&frame).unwrap();
+        let forwarded = MavConnection::<MavMessage>::recv_raw(&b).unwrap();
+        assert_eq!(forwarded.raw_bytes(), frame.raw_bytes());
+    }
+}
@@ -0,0 +1,88 @@
+mod test_shared;
+
+#[cfg(feature = "common")]
+mod malformed_input {
+    use mavlink::common::MavMessage;
+    use mavlink::error::ParserError;
+    use mavlink::{MavFrame, MavlinkVersion, Message, MAV_STX, MAV_STX_V2};
+
+    #[test]
+    pub fn test_truncated_frame() {
+        let frame = crate::test_shared::HEARTBEAT_V2;
+        for len in 0..frame.len() {
+            let result = MavFrame::<MavMessage>::deser(MavlinkVersion::V2, &frame[..len]);
+            assert!(
+                matches!(result, Err(ParserError::InvalidLength { actual, .. }) if actual == len),
+                "unexpected result for {len} bytes: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_truncated_payload() {
+        // MAVLink 2 truncates trailing zero bytes, so a short payload is padded with zeros
+        let mut frame = vec![MAV_STX_V2, 1, 0, 0, 0, 1, 1, 0, 0, 0, 5];
+        let crc = mavlink::calculate_crc(&frame[1..], MavMessage::extra_crc(0));
+        frame.extend_from_slice(&crc.to_le_bytes());
+
+        let frame = MavFrame::<MavMessage>::deser(MavlinkVersion::V2, &frame)
+            .expect("failed to parse truncated payload");
+        let MavMessage::HEARTBEAT(msg) = frame.msg else {
+            panic!("Decoded wrong message type");
+        };
+        assert_eq!(msg.custom_mode, 5);
+    }
+
+    #[test]
+    pub fn test_oversize_frame() {
+        let mut input = crate::test_shared::HEARTBEAT_V2.to_vec();
+        input.push(0);
+        let result = MavFrame::<MavMessage>::deser(MavlinkVersion::V2, &input);
+        assert!(matches!(
+            result,
+            Err(ParserError::InvalidLength { expected, actual })
+                if expected == crate::test_shared::HEARTBEAT_V2.len() && actual == input.len()
+        ));
+    }
+
+    #[test]
+    pub fn test_random_input_does_not_panic() {
+        // xorshift, so failures are reproducible without extra dependencies
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut input = [0u8; mavlink::MAX_FRAME_SIZE + 16];
+        for _ in 0..20_000 {
+            let len = next() as usize % input.len();
+            for byte in &mut input[..len] {
+                *byte = next() as u8;
+            }
+            let input = &input[..len];
+
+            for (version, stx) in [
+                (MavlinkVersion::V1, MAV_STX),
+                (MavlinkVersion::V2, MAV_STX_V2),
+            ] {
+                let _ = MavFrame::<MavMessage>::deser(version, input);
+                // also get past the packet start marker check
+                if let Some((_, rest)) = input.split_first() {
+                    let mut frame = vec![stx];
+                    frame.extend_from_slice(rest);
+                    let _ = MavFrame::<MavMessage>::deser(version, &frame);
+                }
+                // pick known message ids most of the time to exercise the generated decoders
+                let id = if next() % 4 == 0 {
+                    next() as u32 & 0xff_ffff
+                } else {
+                    next() as u32 % 400
+                };
+                let _ = MavMessage::parse(version, id, input);
+            }
+        }
+    }
+}
@@ -3,45 +3,20 @@ pub mod test_shared;
 mod mav_frame_tests {
     use mavlink::MavFrame;
 
-    // NOTE: No STX, length, or flag fields in the header
-    pub const HEARTBEAT_V2: &[u8] = &[
-        // Currently [`MavFrame::deser`] and [`MavFrame::ser`] does not account for the first four fields.
-        // 0xfd, // STX V2
-        // 0x09, // len
-        // 0x00, // incompat_flags
-        // 0x00, // compat_flags
-        crate::test_shared::COMMON_MSG_HEADER.sequence,
-        crate::test_shared::COMMON_MSG_HEADER.system_id,
-        crate::test_shared::COMMON_MSG_HEADER.component_id,
-        0x00, // msg ID
-        0x00,
-        0x00,
-        0x05, // payload
-        0x00,
-        0x00,
-        0x00,
-        0x02,
-        0x03,
-        0x59,
-        0x03,
-        0x03,
-        0x10, // checksum
-        0xf0,
-    ];
-
     #[cfg(feature = "common")]
     #[test]
     pub fn test_deser_ser() {
         use mavlink::{common::MavMessage, MavlinkVersion};
-        let frame = MavFrame::<MavMessage>::deser(MavlinkVersion::V2, HEARTBEAT_V2)
-            .expect("failed to parse message");
+        let frame =
+            MavFrame::<MavMessage>::deser(MavlinkVersion::V2, crate::test_shared::HEARTBEAT_V2)
+                .expect("failed to parse message");
 
         assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
         let heartbeat_msg = crate::test_shared::get_heartbeat_msg();
 
-        let mut buffer = [0u8; HEARTBEAT_V2.len()];
-        frame.ser(&mut buffer);
-        assert_eq!(buffer[..buffer.len() - 2], HEARTBEAT_V2[..buffer.len() - 2]);
+        let mut buffer = [0u8; mavlink::MAX_FRAME_SIZE];
+        let len = frame.ser(&mut buffer).expect("failed to serialize message");
+        assert_eq!(&buffer[..len], crate::test_shared::HEARTBEAT_V2);
 
         let msg = match frame.msg {
             MavMessage::HEARTBEAT(msg) => msg,
@@ -55,19 +30,118 @@ mod mav_frame_tests {
         assert_eq!(msg.mavlink_version, heartbeat_msg.mavlink_version);
     }
 
+    #[cfg(feature = "common")]
+    #[test]
+    pub fn test_deser_ser_v1() {
+        use mavlink::{common::MavMessage, MavlinkVersion};
+        let frame =
+            MavFrame::<MavMessage>::deser(MavlinkVersion::V1, crate::test_shared::HEARTBEAT_V1)
+                .expect("failed to parse message");
+        assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
+        assert_eq!(
+            frame.msg,
+            MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg())
+        );
+
+        let mut buffer = [0u8; mavlink::MAX_FRAME_SIZE];
+        let len = frame.ser(&mut buffer).expect("failed to serialize message");
+        assert_eq!(&buffer[..len], crate::test_shared::HEARTBEAT_V1);
+    }
+
+    #[cfg(all(feature = "std", feature = "common"))]
+    #[test]
+    pub fn test_ser_matches_write_versioned_msg() {
+        use mavlink::{common::MavMessage, MavlinkVersion};
+        let msg = MavMessage::COMMAND_INT(crate::test_shared::get_cmd_nav_takeoff_msg());
+        for version in [MavlinkVersion::V1, MavlinkVersion::V2] {
+            let frame = MavFrame {
+                header: crate::test_shared::COMMON_MSG_HEADER,
+                msg: msg.clone(),
+                protocol_version: version,
+            };
+            let mut buffer = [0u8; mavlink::MAX_FRAME_SIZE];
+            let len = frame.ser(&mut buffer).expect("failed to serialize message");
+
+            let mut written = Vec::new();
+            mavlink::write_versioned_msg(&mut written, version, frame.header, &frame.msg)
+                .expect("failed to write message");
+            assert_eq!(&buffer[..len], written.as_slice());
+
+            let parsed = MavFrame::<MavMessage>::deser(version, &buffer[..len])
+                .expect("failed to parse message");
+            assert_eq!(parsed.msg, msg);
+        }
+    }
+
+    #[cfg(feature = "common")]
+    #[test]
+    pub fn test_ser_v1_rejects_v2_only_message() {
+        use mavlink::{common::MavMessage, error::MessageWriteError, MavlinkVersion};
+        let frame = MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::SETUP_SIGNING(Default::default()),
+            protocol_version: MavlinkVersion::V1,
+        };
+        let mut buffer = [0u8; mavlink::MAX_FRAME_SIZE];
+        assert!(matches!(
+            frame.ser(&mut buffer),
+            Err(MessageWriteError::MAVLink2Only)
+        ));
+    }
+
+    #[cfg(feature = "common")]
+    #[test]
+    pub fn test_ser_buffer_too_small() {
+        use mavlink::{common::MavMessage, error::MessageWriteError, MavlinkVersion};
+        let frame = MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg()),
+            protocol_version: MavlinkVersion::V2,
+        };
+        let mut buffer = [0u8; 8];
+        assert!(matches!(
+            frame.ser(&mut buffer),
+            Err(MessageWriteError::BufferTooSmall {
+                expected: 21,
+                actual: 8
+            })
+        ));
+    }
+
+    #[cfg(feature = "common")]
+    #[test]
+    pub fn test_deser_invalid_frame() {
+        use mavlink::{common::MavMessage, error::ParserError, MavlinkVersion};
+        let mut frame = crate::test_shared::HEARTBEAT_V2.to_vec();
+
+        assert!(matches!(
+            MavFrame::<MavMessage>::deser(MavlinkVersion::V1, &frame),
+            Err(ParserError::InvalidStx { .. })
+        ));
+
+        let last = frame.len() - 1;
+        frame[last] ^= 0xff;
+        assert!(matches!(
+            MavFrame::<MavMessage>::deser(MavlinkVersion::V2, &frame),
+            Err(ParserError::InvalidChecksum { .. })
+        ));
+    }
+
     #[cfg(feature = "ardupilotmega")]
     #[test]
     pub fn test_deser_ser_message() {
-        let buf: &mut [u8; 255] = &mut [0; 255];
+        let buf: &mut [u8; 280] = &mut [0; 280];
 
         let mavlink_message = mavlink_message();
         let mavlink_frame = new(mavlink_message);
 
-        let _len = mavlink_frame.ser(buf);
+        let len = mavlink_frame.ser(buf).unwrap();
 
-        let parsed_mavlink_frame =
-            MavFrame::<mavlink::ardupilotmega::MavMessage>::deser(mavlink::MavlinkVersion::V2, buf)
-                .unwrap();
+        let parsed_mavlink_frame = MavFrame::<mavlink::ardupilotmega::MavMessage>::deser(
+            mavlink::MavlinkVersion::V2,
+            &buf[..len],
+        )
+        .unwrap();
 
         assert_eq!(
             format!("{mavlink_frame:?}"),
@@ -0,0 +1,73 @@
+mod test_shared;
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_memory_connections {
+    use std::time::Duration;
+
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncMavConnection, AsyncMemoryConnection, Impairments};
+
+    /// Test whether messages can be exchanged in both directions over an async connection pair
+    #[tokio::test]
+    async fn test_pair_loopback() {
+        let (a, b) = AsyncMemoryConnection::pair();
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server = tokio::spawn({
+            let msg = msg.clone();
+            async move {
+                for _ in 0..5 {
+                    let (_header, received): (_, MavMessage) =
+                        b.recv().await.expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    b.send_default(&received)
+                        .await
+                        .expect("Failed to send message");
+                }
+            }
+        });
+
+        for _ in 0..5 {
+            a.send_default(&msg).await.expect("Failed to send message");
+            let (_header, received): (_, MavMessage) =
+                a.recv().await.expect("Failed to receive message");
+            assert_eq!(received, msg);
+        }
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_named_connection() {
+        let a = mavlink::connect_async::<MavMessage>("memory:test_async_named_connection")
+            .await
+            .unwrap();
+        let b = mavlink::connect_async::<MavMessage>("memory:test_async_named_connection")
+            .await
+            .unwrap();
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        a.send_default(&msg).await.unwrap();
+        assert_eq!(b.recv().await.unwrap().1, msg);
+    }
+
+    #[tokio::test]
+    async fn test_latency() {
+        let latency = Duration::from_millis(50);
+        let (a, b) = AsyncMemoryConnection::pair_with(
+            Impairments {
+                latency,
+                ..Impairments::default()
+            },
+            Impairments::default(),
+        );
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let start = tokio::time::Instant::now();
+        a.send_default(&msg).await.unwrap();
+        let (_header, received): (_, MavMessage) = b.recv().await.unwrap();
+        assert_eq!(received, msg);
+        assert!(start.elapsed() >= latency);
+
+        drop(a);
+        assert!(AsyncMavConnection::<MavMessage>::recv(&b).await.is_err());
+    }
+}
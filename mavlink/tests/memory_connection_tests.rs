@@ -0,0 +1,206 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_memory_connections {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{
+        ConnectionAddress, Impairments, MAVLinkMessageRaw, MavConnection, MavHeader,
+        MavlinkVersion, MemoryConfig, MemoryConnection,
+    };
+
+    fn would_block(result: &Result<(MavHeader, MavMessage), MessageReadError>) -> bool {
+        matches!(result, Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock)
+    }
+
+    /// Send `count` heartbeats and return the sequence numbers of the ones received
+    fn transfer(impairments: Impairments, count: usize) -> Vec<u8> {
+        let (a, b) = MemoryConnection::pair_with(impairments, Impairments::default());
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        for _ in 0..count {
+            a.send_default(&msg).expect("Failed to send message");
+        }
+        let mut received = Vec::new();
+        loop {
+            let result = MavConnection::<MavMessage>::try_recv(&b);
+            if would_block(&result) {
+                return received;
+            }
+            if let Ok((header, _)) = result {
+                received.push(header.sequence);
+            }
+        }
+    }
+
+    /// Connection that forwards to a memory connection and keeps the default `split`
+    struct Wrapper(MemoryConnection);
+
+    impl MavConnection<MavMessage> for Wrapper {
+        fn recv(&self) -> Result<(MavHeader, MavMessage), MessageReadError> {
+            self.0.recv()
+        }
+
+        fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+            MavConnection::<MavMessage>::recv_raw(&self.0)
+        }
+
+        fn try_recv(&self) -> Result<(MavHeader, MavMessage), MessageReadError> {
+            self.0.try_recv()
+        }
+
+        fn send(
+            &self,
+            header: &MavHeader,
+            data: &MavMessage,
+        ) -> Result<usize, mavlink::error::MessageWriteError> {
+            self.0.send(header, data)
+        }
+
+        fn set_protocol_version(&mut self, version: MavlinkVersion) {
+            MavConnection::<MavMessage>::set_protocol_version(&mut self.0, version);
+        }
+
+        fn protocol_version(&self) -> MavlinkVersion {
+            MavConnection::<MavMessage>::protocol_version(&self.0)
+        }
+
+        fn set_allow_recv_any_version(&mut self, allow: bool) {
+            MavConnection::<MavMessage>::set_allow_recv_any_version(&mut self.0, allow);
+        }
+
+        fn allow_recv_any_version(&self) -> bool {
+            MavConnection::<MavMessage>::allow_recv_any_version(&self.0)
+        }
+
+        #[cfg(feature = "signing")]
+        fn setup_signing(&mut self, signing_data: Option<mavlink::SigningConfig>) {
+            MavConnection::<MavMessage>::setup_signing(&mut self.0, signing_data);
+        }
+    }
+
+    #[test]
+    fn test_parse_address() {
+        let parsed = ConnectionAddress::parse_address("memory:link").expect("Failed to parse");
+        assert!(matches!(parsed, ConnectionAddress::Memory(_)));
+        assert_eq!(parsed.to_string(), "memory:link");
+
+        let config = MemoryConfig::new("other".to_string());
+        assert_eq!(ConnectionAddress::from(config).to_string(), "memory:other");
+    }
+
+    /// Test whether messages can be exchanged in both directions over a connection pair
+    #[test]
+    fn test_pair_loopback() {
+        let (a, b) = MemoryConnection::pair();
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server_thread = thread::spawn({
+            let msg = msg.clone();
+            move || {
+                for _ in 0..5 {
+                    let (_header, received): (_, MavMessage) =
+                        b.recv().expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    b.send_default(&received).expect("Failed to send message");
+                }
+            }
+        });
+
+        for _ in 0..5 {
+            a.send_default(&msg).expect("Failed to send message");
+            let (_header, received): (_, MavMessage) = a.recv().expect("Failed to receive message");
+            assert_eq!(received, msg);
+        }
+        server_thread.join().unwrap();
+    }
+
+    /// Test whether two connections to the same name are linked
+    #[test]
+    fn test_named_connection() {
+        let a = mavlink::connect::<MavMessage>("memory:test_named_connection").unwrap();
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        // sent before the other end is connected
+        a.send_default(&msg).unwrap();
+
+        let b = mavlink::connect::<MavMessage>("memory:test_named_connection").unwrap();
+        assert_eq!(b.recv().unwrap().1, msg);
+        b.send_default(&msg).unwrap();
+        assert_eq!(a.recv().unwrap().1, msg);
+    }
+
+    #[test]
+    fn test_try_recv_and_close() {
+        let (a, b) = MemoryConnection::pair();
+        assert!(would_block(&MavConnection::<MavMessage>::try_recv(&b)));
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        a.send_default(&msg).unwrap();
+        drop(a);
+
+        // frames sent before closing are still received
+        assert_eq!(MavConnection::<MavMessage>::try_recv(&b).unwrap().1, msg);
+        assert!(matches!(
+            MavConnection::<MavMessage>::recv(&b),
+            Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::ConnectionAborted
+        ));
+    }
+
+    #[test]
+    fn test_impairments_deterministic() {
+        let impairments = Impairments {
+            loss: 0.1,
+            reorder: 0.1,
+            corruption: 0.1,
+            seed: 3,
+            ..Impairments::default()
+        };
+        let received = transfer(impairments.clone(), 100);
+        assert_eq!(received, transfer(impairments, 100));
+        assert!(received.len() > 50 && received.len() < 100);
+        assert!(received.windows(2).any(|w| w[0] > w[1]));
+
+        assert_eq!(
+            transfer(Impairments::default(), 100),
+            (0..100).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn test_latency() {
+        let latency = Duration::from_millis(50);
+        let (a, b) = MemoryConnection::pair_with(
+            Impairments {
+                latency,
+                ..Impairments::default()
+            },
+            Impairments::default(),
+        );
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let start = Instant::now();
+        a.send_default(&msg).unwrap();
+        assert!(would_block(&MavConnection::<MavMessage>::try_recv(&b)));
+        assert_eq!(MavConnection::<MavMessage>::recv(&b).unwrap().1, msg);
+        assert!(start.elapsed() >= latency);
+    }
+
+    /// Test whether the halves of a connection without its own `split` share the connection
+    #[test]
+    fn test_default_split() {
+        let (a, b) = MemoryConnection::pair();
+        let connection: Box<dyn MavConnection<MavMessage> + Sync + Send> = Box::new(Wrapper(a));
+        let (mut receive_half, mut send_half) = connection.split();
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        send_half.send_default(&msg).unwrap();
+        assert_eq!(MavConnection::<MavMessage>::recv(&b).unwrap().1, msg);
+        b.send_default(&msg).unwrap();
+        assert_eq!(receive_half.recv().unwrap().1, msg);
+
+        send_half.set_protocol_version(MavlinkVersion::V1);
+        assert_eq!(receive_half.protocol_version(), MavlinkVersion::V1);
+    }
+}
@@ -0,0 +1,95 @@
+mod test_shared;
+
+#[cfg(all(feature = "message-views", feature = "common"))]
+mod message_view {
+    use mavlink::common::{
+        HeartbeatView, HilActuatorControlsView, MavMessage, StatustextView, HEARTBEAT_DATA,
+        HIL_ACTUATOR_CONTROLS_DATA, STATUSTEXT_DATA,
+    };
+    use mavlink::error::ParserError;
+    use mavlink::peek_reader::PeekReader;
+    use mavlink::{MavlinkVersion, MessageData, MessageView};
+
+    #[test]
+    pub fn test_heartbeat_view() {
+        let mut reader = PeekReader::new(crate::test_shared::HEARTBEAT_V2);
+        let raw = mavlink::read_any_raw_message::<MavMessage, _>(&mut reader)
+            .expect("Failed to read heartbeat");
+
+        let view = HeartbeatView::from_raw(&raw).expect("Wrong message id");
+        let expected = crate::test_shared::get_heartbeat_msg();
+        assert_eq!(view.custom_mode(), expected.custom_mode);
+        assert_eq!(view.mavtype().unwrap(), expected.mavtype);
+        assert_eq!(view.autopilot().unwrap(), expected.autopilot);
+        assert_eq!(view.base_mode().unwrap(), expected.base_mode);
+        assert_eq!(view.system_status().unwrap(), expected.system_status);
+        assert_eq!(view.mavlink_version(), expected.mavlink_version);
+        assert_eq!(view.to_data().unwrap(), expected);
+
+        assert!(StatustextView::from_raw(&raw).is_none());
+    }
+
+    #[test]
+    pub fn test_truncated_payload() {
+        // MAVLink 2 truncates the trailing zeros of the payload
+        let msg = HEARTBEAT_DATA {
+            custom_mode: 0x0102,
+            mavlink_version: 0,
+            ..HEARTBEAT_DATA::DEFAULT
+        };
+        let mut payload = [0u8; HEARTBEAT_DATA::ENCODED_LEN];
+        let len = msg.ser(MavlinkVersion::V2, &mut payload);
+        assert!(len < HEARTBEAT_DATA::ENCODED_LEN);
+
+        let view = HeartbeatView::new(&payload[..len]);
+        assert_eq!(view.custom_mode(), 0x0102);
+        assert_eq!(view.mavtype().unwrap(), msg.mavtype);
+        assert_eq!(view.mavlink_version(), 0);
+        assert_eq!(view.to_data().unwrap(), msg);
+
+        // an empty payload decodes to zeros
+        assert_eq!(HeartbeatView::new(&[]).custom_mode(), 0);
+    }
+
+    #[test]
+    pub fn test_invalid_enum() {
+        let mut payload = [0u8; HEARTBEAT_DATA::ENCODED_LEN];
+        crate::test_shared::get_heartbeat_msg().ser(MavlinkVersion::V2, &mut payload);
+        payload[4] = 0xff;
+
+        let view = HeartbeatView::new(&payload);
+        assert!(matches!(
+            view.mavtype(),
+            Err(ParserError::InvalidEnum { value: 0xff, .. })
+        ));
+        // the other fields are still accessible
+        assert_eq!(view.custom_mode(), 5);
+    }
+
+    #[test]
+    pub fn test_array_fields() {
+        let mut text = [0u8; 50];
+        text[..5].copy_from_slice(b"hello");
+        let msg = STATUSTEXT_DATA {
+            text,
+            ..STATUSTEXT_DATA::DEFAULT
+        };
+        let mut payload = [0u8; 255];
+        let len = msg.ser(MavlinkVersion::V2, &mut payload);
+
+        let view = StatustextView::new(&payload[..len]);
+        assert_eq!(view.text(), text);
+        assert_eq!(view.text_bytes(), b"hello");
+
+        let mut controls = [0.0f32; 16];
+        controls[3] = 1.5;
+        let msg = HIL_ACTUATOR_CONTROLS_DATA {
+            controls,
+            ..HIL_ACTUATOR_CONTROLS_DATA::DEFAULT
+        };
+        let len = msg.ser(MavlinkVersion::V2, &mut payload);
+        let view = HilActuatorControlsView::new(&payload[..len]);
+        assert_eq!(view.controls(), controls);
+        assert_eq!(view.to_data().unwrap(), msg);
+    }
+}
@@ -0,0 +1,272 @@
+mod test_shared;
+
+#[cfg(feature = "onboard-log")]
+mod test_dataflash {
+    use mavlink::onboard_log::dataflash::DataFlashReader;
+    use mavlink::onboard_log::Value;
+
+    fn message(message_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut message = vec![0xa3, 0x95, message_type];
+        message.extend_from_slice(payload);
+        message
+    }
+
+    fn fixed(text: &str, size: usize) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize(size, 0);
+        bytes
+    }
+
+    fn fmt(message_type: u8, length: u8, name: &str, format: &str, columns: &str) -> Vec<u8> {
+        let mut payload = vec![message_type, length];
+        payload.extend_from_slice(&fixed(name, 4));
+        payload.extend_from_slice(&fixed(format, 16));
+        payload.extend_from_slice(&fixed(columns, 64));
+        message(128, &payload)
+    }
+
+    fn att(time: u64, roll: i16, yaw: f32, mode: &str) -> Vec<u8> {
+        let mut payload = time.to_le_bytes().to_vec();
+        payload.extend_from_slice(&roll.to_le_bytes());
+        payload.extend_from_slice(&yaw.to_le_bytes());
+        payload.extend_from_slice(&fixed(mode, 4));
+        message(129, &payload)
+    }
+
+    #[test]
+    fn test_read_records() {
+        let mut log = fmt(128, 89, "FMT", "BBnNZ", "Type,Length,Name,Format,Columns");
+        log.extend(fmt(129, 21, "ATT", "Qcfn", "TimeUS,Roll,Yaw,Mode"));
+        log.extend(fmt(130, 11, "GPS", "IL", "TimeMS,Lat"));
+        log.extend(att(1_000_000, -1250, 90.5, "AUTO"));
+        // an interrupted write
+        log.extend_from_slice(&[0xa3, 0x95, 0xa3, 0x01]);
+        let mut gps = 2000u32.to_le_bytes().to_vec();
+        gps.extend_from_slice(&473_977_420i32.to_le_bytes());
+        log.extend(message(130, &gps));
+        // a message of an undefined type
+        log.extend(message(200, &[1, 2]));
+        log.extend(att(1_100_000, 0, 0.0, ""));
+        // a truncated last message
+        log.extend_from_slice(&att(1_200_000, 0, 0.0, "")[..10]);
+
+        let mut reader = DataFlashReader::new(log.as_slice());
+        let records: Vec<_> = reader.records().map(|record| record.unwrap()).collect();
+        let names: Vec<_> = records.iter().map(|record| record.name()).collect();
+        assert_eq!(names, ["FMT", "FMT", "FMT", "ATT", "GPS", "ATT"]);
+        assert_eq!(reader.skipped_bytes(), 4 + 5);
+        assert_eq!(reader.formats().count(), 3);
+
+        let att = &records[3];
+        assert_eq!(att.timestamp(), Some(1_000_000));
+        assert_eq!(att.get("Roll"), Some(&Value::F64(-12.5)));
+        assert_eq!(att.get("Yaw").and_then(Value::as_f64), Some(90.5));
+        assert_eq!(att.get("Mode").and_then(Value::as_str), Some("AUTO"));
+        let fields: Vec<_> = att.fields().map(|(name, _)| name).collect();
+        assert_eq!(fields, ["TimeUS", "Roll", "Yaw", "Mode"]);
+
+        let gps = &records[4];
+        assert_eq!(gps.timestamp(), Some(2_000_000));
+        let lat = gps.get("Lat").and_then(Value::as_f64).unwrap();
+        assert!((lat - 47.397742).abs() < 1e-9);
+        assert_eq!(records[5].timestamp(), Some(1_100_000));
+    }
+}
+
+#[cfg(feature = "onboard-log")]
+mod test_ulog {
+    use std::time::Duration;
+
+    use mavlink::onboard_log::ulog::{UlogEntry, UlogReader};
+    use mavlink::onboard_log::Value;
+
+    fn message(message_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut message = (payload.len() as u16).to_le_bytes().to_vec();
+        message.push(message_type);
+        message.extend_from_slice(payload);
+        message
+    }
+
+    fn key_value(key: &str, value: &[u8]) -> Vec<u8> {
+        let mut payload = vec![key.len() as u8];
+        payload.extend_from_slice(key.as_bytes());
+        payload.extend_from_slice(value);
+        payload
+    }
+
+    fn sensor_data(timestamp: u64, accel: [[f32; 3]; 2], name: &str) -> Vec<u8> {
+        let mut payload = 7u16.to_le_bytes().to_vec();
+        payload.extend_from_slice(&timestamp.to_le_bytes());
+        for value in accel.iter().flatten() {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        payload.extend_from_slice(name.as_bytes());
+        message(b'D', &payload)
+    }
+
+    fn log(incompatible_flags: u8) -> Vec<u8> {
+        let mut log = b"ULog\x01\x12\x35\x01".to_vec();
+        log.extend_from_slice(&5_000u64.to_le_bytes());
+        let mut flags = vec![0; 40];
+        flags[8] = incompatible_flags;
+        log.extend(message(b'B', &flags));
+        log.extend(message(b'F', b"vec3:float x;float y;float z;"));
+        log.extend(message(
+            b'F',
+            b"sensor:uint64_t timestamp;vec3[2] accel;char[4] name;uint8_t[4] _padding0;",
+        ));
+        log.extend(message(b'I', &key_value("char[3] sys_name", b"PX4")));
+        log.extend(message(
+            b'M',
+            &[&[0][..], &key_value("char[5] perf", b"top: ")].concat(),
+        ));
+        log.extend(message(
+            b'M',
+            &[&[1][..], &key_value("char[4] perf", b"idle")].concat(),
+        ));
+        log.extend(message(
+            b'P',
+            &key_value("int32_t SYS_AUTOSTART", &4001i32.to_le_bytes()),
+        ));
+        log
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let mut log = log(0);
+        let mut add = vec![1];
+        add.extend_from_slice(&7u16.to_le_bytes());
+        add.extend_from_slice(b"sensor");
+        log.extend(message(b'A', &add));
+        log.extend(sensor_data(
+            10_000,
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            "imu0",
+        ));
+        // a message type the reader does not know
+        log.extend(message(b'X', &[1, 2, 3]));
+        let mut logging = vec![b'3'];
+        logging.extend_from_slice(&11_000u64.to_le_bytes());
+        logging.extend_from_slice(b"Low battery");
+        log.extend(message(b'L', &logging));
+        log.extend(message(
+            b'P',
+            &key_value("float MPC_XY_VEL_MAX", &12.5f32.to_le_bytes()),
+        ));
+        log.extend(message(b'O', &150u16.to_le_bytes()));
+        log.extend(message(b'R', &7u16.to_le_bytes()));
+        // data of a removed subscription is skipped
+        log.extend(sensor_data(12_000, [[0.0; 3]; 2], "imu0"));
+        // a truncated last message
+        log.extend_from_slice(&[20, 0, b'D', 7]);
+
+        let mut reader = UlogReader::new(log.as_slice()).unwrap();
+        assert_eq!(reader.version(), 1);
+        assert_eq!(reader.start_timestamp(), 5_000);
+        assert_eq!(
+            reader.info().get("sys_name"),
+            Some(&Value::String("PX4".to_string()))
+        );
+        assert_eq!(
+            reader.info().get("perf"),
+            Some(&Value::String("top: idle".to_string()))
+        );
+        assert_eq!(
+            reader.parameters().get("SYS_AUTOSTART"),
+            Some(&Value::I32(4001))
+        );
+
+        let entries: Vec<_> = reader.entries().map(|entry| entry.unwrap()).collect();
+        assert_eq!(entries.len(), 4);
+        let UlogEntry::Data { multi_id, record } = &entries[0] else {
+            panic!("Unexpected entry {:?}", entries[0]);
+        };
+        assert_eq!(*multi_id, 1);
+        assert_eq!(record.name(), "sensor");
+        assert_eq!(record.timestamp(), Some(10_000));
+        let fields: Vec<_> = record.fields().map(|(name, _)| name).collect();
+        assert_eq!(
+            fields,
+            [
+                "timestamp",
+                "accel[0].x",
+                "accel[0].y",
+                "accel[0].z",
+                "accel[1].x",
+                "accel[1].y",
+                "accel[1].z",
+                "name"
+            ]
+        );
+        assert_eq!(record.get("accel[1].y"), Some(&Value::F32(5.0)));
+        assert_eq!(record.get("name").and_then(Value::as_str), Some("imu0"));
+
+        assert_eq!(
+            entries[1],
+            UlogEntry::Logging {
+                level: 3,
+                tag: None,
+                timestamp: 11_000,
+                message: "Low battery".to_string(),
+            }
+        );
+        assert_eq!(
+            entries[2],
+            UlogEntry::Parameter {
+                name: "MPC_XY_VEL_MAX".to_string(),
+                value: Value::F32(12.5),
+            }
+        );
+        assert_eq!(
+            entries[3],
+            UlogEntry::Dropout {
+                duration: Duration::from_millis(150)
+            }
+        );
+        assert_eq!(
+            reader.parameters().get("MPC_XY_VEL_MAX"),
+            Some(&Value::F32(12.5))
+        );
+    }
+
+    /// Test whether formats and values too large for a message are skipped
+    #[test]
+    fn test_oversized_formats() {
+        let mut log = log(0);
+        log.extend(message(b'F', b"huge:uint64_t[2305843009213693952] values;"));
+        log.extend(message(b'F', b"wide:uint64_t timestamp;vec3[6000] accel;"));
+        log.extend(message(b'F', b"empty:"));
+        log.extend(message(b'F', b"hollow:empty[65535] a;"));
+        log.extend(message(b'F', b"deep:hollow[65535] b;"));
+        log.extend(message(
+            b'I',
+            &key_value("uint64_t[2305843009213693952] big", &[0; 8]),
+        ));
+        for (id, name) in ["huge", "wide", "hollow", "deep", "sensor"]
+            .iter()
+            .enumerate()
+        {
+            let mut add = vec![0];
+            add.extend_from_slice(&(id as u16).to_le_bytes());
+            add.extend_from_slice(name.as_bytes());
+            log.extend(message(b'A', &add));
+            let mut data = (id as u16).to_le_bytes().to_vec();
+            data.extend_from_slice(&[0; 64]);
+            log.extend(message(b'D', &data));
+        }
+
+        let mut reader = UlogReader::new(log.as_slice()).unwrap();
+        assert_eq!(reader.info().get("big"), None);
+        let entries: Vec<_> = reader.entries().map(|entry| entry.unwrap()).collect();
+        assert_eq!(entries.len(), 1, "{entries:?}");
+        assert!(matches!(&entries[0], UlogEntry::Data { record, .. } if record.name() == "sensor"));
+    }
+
+    #[test]
+    fn test_invalid_logs() {
+        assert!(UlogReader::new(&b"ULog\x01\x12\x36\x01\0\0\0\0\0\0\0\0"[..]).is_err());
+        // appended data is supported, other incompatible flags are not
+        assert!(UlogReader::new(log(0x01).as_slice()).is_ok());
+        assert!(UlogReader::new(log(0x02).as_slice()).is_err());
+    }
+}
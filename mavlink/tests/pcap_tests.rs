@@ -0,0 +1,220 @@
+mod test_shared;
+
+#[cfg(all(feature = "pcap", feature = "common"))]
+mod test_pcap {
+    use std::net::SocketAddr;
+
+    use mavlink::common::MavMessage;
+    use mavlink::pcap::{PcapReader, PcapWriter, Transport};
+    use mavlink::{MavFrame, MavlinkVersion};
+
+    const START: u64 = 1_700_000_000_000_000;
+
+    fn frame(id: u32) -> MavFrame<MavMessage> {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(heartbeat),
+            protocol_version: MavlinkVersion::V2,
+        }
+    }
+
+    fn id(msg: &MavMessage) -> u32 {
+        match msg {
+            MavMessage::HEARTBEAT(heartbeat) => heartbeat.custom_mode,
+            _ => panic!("Unexpected message {msg:?}"),
+        }
+    }
+
+    fn frame_bytes(id: u32) -> Vec<u8> {
+        let mut buf = [0; mavlink::MAX_FRAME_SIZE];
+        let len = frame(id).ser(&mut buf).unwrap();
+        buf[..len].to_vec()
+    }
+
+    fn roundtrip(source: SocketAddr, destination: SocketAddr) {
+        let mut writer = PcapWriter::new(Vec::new()).unwrap();
+        for i in 0..3 {
+            writer
+                .write_message(START + u64::from(i) * 1000, source, destination, &frame(i))
+                .unwrap();
+        }
+        let capture = writer.into_inner();
+        let mut reader = PcapReader::new(capture.as_slice()).unwrap();
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap())
+            .collect();
+        assert_eq!(frames.len(), 3);
+        for (i, captured) in frames.iter().enumerate() {
+            assert_eq!(captured.timestamp, START + i as u64 * 1000);
+            assert_eq!(captured.transport, Transport::Udp);
+            assert_eq!(captured.source, source);
+            assert_eq!(captured.destination, destination);
+            assert_eq!(captured.frame.header, crate::test_shared::COMMON_MSG_HEADER);
+            assert_eq!(id(&captured.frame.msg), i as u32);
+        }
+    }
+
+    #[test]
+    fn test_write_read_ipv4() {
+        roundtrip(
+            "192.168.1.10:14550".parse().unwrap(),
+            "192.168.1.1:14555".parse().unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_write_read_ipv6() {
+        roundtrip(
+            "[fe80::1]:14550".parse().unwrap(),
+            "[fe80::2]:14555".parse().unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_mixed_address_families() {
+        let mut writer = PcapWriter::new(Vec::new()).unwrap();
+        assert!(writer
+            .write_message(
+                START,
+                "127.0.0.1:14550".parse().unwrap(),
+                "[::1]:14550".parse().unwrap(),
+                &frame(0),
+            )
+            .is_err());
+    }
+
+    /// Ethernet frame of an IPv4 TCP segment from 10.0.0.2:5760 to 10.0.0.1:40000
+    fn tcp_segment(sequence: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0; 12];
+        packet.extend_from_slice(&0x0800u16.to_be_bytes());
+        let total_length = (40 + payload.len()) as u16;
+        packet.extend_from_slice(&[0x45, 0]);
+        packet.extend_from_slice(&total_length.to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0, 10, 0, 0, 2, 10, 0, 0, 1]);
+        packet.extend_from_slice(&5760u16.to_be_bytes());
+        packet.extend_from_slice(&40000u16.to_be_bytes());
+        packet.extend_from_slice(&sequence.to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let padded = (body.len() + 3) & !3;
+        let length = (12 + padded) as u32;
+        let mut block = block_type.to_le_bytes().to_vec();
+        block.extend_from_slice(&length.to_le_bytes());
+        block.extend_from_slice(body);
+        block.resize(8 + padded, 0);
+        block.extend_from_slice(&length.to_le_bytes());
+        block
+    }
+
+    fn enhanced_packet(timestamp_nanos: u64, data: &[u8]) -> Vec<u8> {
+        let mut body = 0u32.to_le_bytes().to_vec();
+        body.extend_from_slice(&((timestamp_nanos >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(timestamp_nanos as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(data);
+        pcapng_block(6, &body)
+    }
+
+    #[test]
+    fn test_pcapng_tcp_reassembly() {
+        let mut capture = Vec::new();
+        let mut header = 0x1a2b_3c4du32.to_le_bytes().to_vec();
+        header.extend_from_slice(&[1, 0, 0, 0]);
+        header.extend_from_slice(&(-1i64).to_le_bytes());
+        capture.extend_from_slice(&pcapng_block(0x0a0d_0d0a, &header));
+        // Ethernet interface with nanosecond timestamps
+        let mut interface = vec![1, 0, 0, 0, 0, 0, 4, 0];
+        interface.extend_from_slice(&[9, 0, 1, 0, 9, 0, 0, 0, 0, 0, 0, 0]);
+        capture.extend_from_slice(&pcapng_block(1, &interface));
+
+        let mut stream = Vec::new();
+        for i in 0..3 {
+            stream.extend_from_slice(&frame_bytes(i));
+        }
+        let first = frame_bytes(0).len();
+        let sequence = 1000;
+        let nanos = START * 1000;
+        let segments = [
+            tcp_segment(sequence, 0x02, &[]),
+            // the first frame and the start of the second one
+            tcp_segment(sequence + 1, 0x18, &stream[..first + 5]),
+            // the third frame arrives before the rest of the second one
+            tcp_segment(sequence + 1 + 2 * first as u32, 0x18, &stream[2 * first..]),
+            tcp_segment(
+                sequence + 1 + first as u32 + 5,
+                0x18,
+                &stream[first + 5..2 * first],
+            ),
+            // retransmission
+            tcp_segment(sequence + 1, 0x18, &stream[..first]),
+        ];
+        for (i, segment) in segments.iter().enumerate() {
+            capture.extend_from_slice(&enhanced_packet(nanos + i as u64 * 1000, segment));
+        }
+        // statistics blocks are skipped
+        capture.extend_from_slice(&pcapng_block(5, &[0; 12]));
+
+        let mut reader = PcapReader::new(capture.as_slice()).unwrap();
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap())
+            .collect();
+        let received: Vec<_> = frames
+            .iter()
+            .map(|captured| (captured.timestamp, id(&captured.frame.msg)))
+            .collect();
+        assert_eq!(
+            received,
+            vec![(START + 1, 0), (START + 3, 1), (START + 3, 2)]
+        );
+        assert_eq!(frames[0].transport, Transport::Tcp);
+        assert_eq!(frames[0].source, "10.0.0.2:5760".parse().unwrap());
+        assert_eq!(frames[0].destination, "10.0.0.1:40000".parse().unwrap());
+    }
+
+    #[test]
+    fn test_nanosecond_pcap_with_noise() {
+        let source: SocketAddr = "127.0.0.1:14550".parse().unwrap();
+        let destination: SocketAddr = "127.0.0.1:14551".parse().unwrap();
+        let mut writer = PcapWriter::new(Vec::new()).unwrap();
+        // garbage before the frames of a datagram is skipped
+        let mut payload = vec![0x55, 0xaa, 1, 2];
+        payload.extend_from_slice(&frame_bytes(1));
+        payload.extend_from_slice(&frame_bytes(2));
+        writer
+            .write_datagram(START, source, destination, &payload)
+            .unwrap();
+        let mut capture = writer.into_inner();
+
+        // convert to a big endian capture with nanosecond timestamps
+        capture[..4].copy_from_slice(&0xa1b2_3c4du32.to_be_bytes());
+        for field in [4..6, 6..8] {
+            capture[field].reverse();
+        }
+        for field in [8..12, 12..16, 16..20, 20..24, 24..28, 32..36, 36..40] {
+            capture[field].reverse();
+        }
+        let nanos = (START % 1_000_000) as u32 * 1000 + 999;
+        capture[28..32].copy_from_slice(&nanos.to_be_bytes());
+        // a truncated last record ends the capture
+        capture.extend_from_slice(&[0; 10]);
+
+        let mut reader = PcapReader::new(capture.as_slice()).unwrap();
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap())
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].timestamp, START);
+        assert_eq!(id(&frames[0].frame.msg), 1);
+        assert_eq!(id(&frames[1].frame.msg), 2);
+    }
+}
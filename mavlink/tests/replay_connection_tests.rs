@@ -0,0 +1,243 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_replay_connection {
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::tlog::TlogWriter;
+    use mavlink::{
+        ConnectionAddress, MavConnection, MavFrame, MavlinkVersion, ReplayConfig, ReplayConnection,
+    };
+
+    /// Time between the records of the test logs
+    const INTERVAL: u64 = 100_000;
+    const START: u64 = 1_700_000_000_000_000;
+
+    fn heartbeat(id: u32) -> MavMessage {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavMessage::HEARTBEAT(heartbeat)
+    }
+
+    fn id(msg: &MavMessage) -> u32 {
+        match msg {
+            MavMessage::HEARTBEAT(heartbeat) => heartbeat.custom_mode,
+            _ => panic!("Unexpected message {msg:?}"),
+        }
+    }
+
+    /// Write a log of `count` heartbeats, recorded `INTERVAL` apart
+    fn write_log(name: &str, count: u32) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "mavlink-replay-test-{}-{name}.tlog",
+            std::process::id()
+        ));
+        let mut writer = TlogWriter::new(std::fs::File::create(&path).unwrap());
+        for i in 0..count {
+            let frame = MavFrame {
+                header: crate::test_shared::COMMON_MSG_HEADER,
+                msg: heartbeat(i),
+                protocol_version: MavlinkVersion::V2,
+            };
+            writer
+                .write_message(START + u64::from(i) * INTERVAL, &frame)
+                .unwrap();
+        }
+        writer.flush().unwrap();
+        path
+    }
+
+    fn recv_id(connection: &ReplayConnection) -> u32 {
+        let (_header, msg): (_, MavMessage) = connection.recv().unwrap();
+        id(&msg)
+    }
+
+    #[test]
+    fn test_parse_address() {
+        let parsed =
+            ConnectionAddress::parse_address("replay:logs/flight.tlog?speed=2.5&loop=true")
+                .expect("Failed to parse");
+        assert!(matches!(parsed, ConnectionAddress::Replay(_)));
+        assert_eq!(
+            parsed.to_string(),
+            "replay:logs/flight.tlog?speed=2.5&loop=true"
+        );
+
+        for address in [
+            "replay:flight.tlog",
+            // parameters that are not known are part of the path
+            "replay:flight.tlog?rate=2",
+            "replay:what?.tlog?speed=2",
+        ] {
+            let parsed = ConnectionAddress::parse_address(address).expect("Failed to parse");
+            assert!(matches!(parsed, ConnectionAddress::Replay(_)));
+            assert_eq!(parsed.to_string(), address);
+        }
+        let config = ReplayConfig::new(PathBuf::from("flight.tlog"), 1.0, true);
+        assert_eq!(
+            ConnectionAddress::from(config).to_string(),
+            "replay:flight.tlog?loop=true"
+        );
+
+        for address in [
+            "replay:flight.tlog?speed=0",
+            "replay:flight.tlog?speed=fast",
+            "replay:flight.tlog?speed=inf",
+            "replay:flight.tlog?speed=NaN",
+            "replay:flight.tlog?loop=1",
+        ] {
+            assert!(
+                ConnectionAddress::parse_address(address).is_err(),
+                "{address}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_scaled_timing() {
+        let path = write_log("timing", 5);
+        let mut connection = ReplayConnection::open(&ReplayConfig::new(path, 10.0, false)).unwrap();
+
+        let start = Instant::now();
+        assert_eq!(recv_id(&connection), 0);
+        assert!(matches!(
+            MavConnection::<MavMessage>::try_recv(&connection),
+            Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock
+        ));
+        for i in 1..5 {
+            assert_eq!(recv_id(&connection), i);
+        }
+        // four intervals of 100ms at ten times the speed
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert_eq!(connection.control().position(), Some(START + 4 * INTERVAL));
+
+        assert!(matches!(
+            MavConnection::<MavMessage>::recv(&connection),
+            Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn test_pause_and_speed() {
+        let path = write_log("pause", 3);
+        // a lower speed makes the time between receiving and changing the speed count less
+        let mut connection = ReplayConnection::open(&ReplayConfig::new(path, 10.0, false)).unwrap();
+        let control = connection.control();
+
+        assert_eq!(recv_id(&connection), 0);
+        control.pause();
+        assert!(control.is_paused());
+        let resume = std::thread::spawn({
+            let control = control.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(50));
+                control.resume();
+            }
+        });
+        let start = Instant::now();
+        assert_eq!(recv_id(&connection), 1);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        resume.join().unwrap();
+
+        control.set_speed(2.0);
+        assert_eq!(control.speed(), 2.0);
+        let start = Instant::now();
+        assert_eq!(recv_id(&connection), 2);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    /// Test whether a replay at a speed too low to ever replay the next frame waits for a speed change
+    #[test]
+    fn test_tiny_speed() {
+        let path = write_log("tiny", 2);
+        let mut connection =
+            ReplayConnection::open(&ReplayConfig::new(path, 1e-300, false)).unwrap();
+        let control = connection.control();
+
+        assert_eq!(recv_id(&connection), 0);
+        assert!(matches!(
+            MavConnection::<MavMessage>::try_recv(&connection),
+            Err(MessageReadError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock
+        ));
+        let speed_up = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            control.set_speed(100.0);
+        });
+        assert_eq!(recv_id(&connection), 1);
+        speed_up.join().unwrap();
+    }
+
+    #[test]
+    fn test_seek_and_loop() {
+        let path = write_log("seek", 5);
+        let mut connection = ReplayConnection::open(&ReplayConfig::new(path, 100.0, true)).unwrap();
+        let control = connection.control();
+
+        assert_eq!(recv_id(&connection), 0);
+        control.seek(START + 3 * INTERVAL);
+        // the frame sought to is replayed right away
+        let start = Instant::now();
+        assert_eq!(recv_id(&connection), 3);
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert_eq!(recv_id(&connection), 4);
+        assert_eq!(recv_id(&connection), 0);
+        assert_eq!(recv_id(&connection), 1);
+
+        control.set_looping(false);
+        control.seek(START + 4 * INTERVAL);
+        assert_eq!(recv_id(&connection), 4);
+        assert!(MavConnection::<MavMessage>::recv(&connection).is_err());
+    }
+
+    #[test]
+    fn test_connect_and_split() {
+        let path = write_log("split", 3);
+        let connection =
+            mavlink::connect::<MavMessage>(&format!("replay:{}?speed=100", path.display()))
+                .unwrap();
+        let (mut receive_half, mut send_half) = connection.split();
+        assert_eq!(send_half.send_default(&heartbeat(9)).unwrap(), 0);
+        for i in 0..3 {
+            assert_eq!(id(&receive_half.recv().unwrap().1), i);
+        }
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_replay_async_connection {
+    use mavlink::common::MavMessage;
+    use mavlink::tlog::TlogWriter;
+    use mavlink::{MavFrame, MavlinkVersion};
+
+    #[tokio::test]
+    async fn test_async_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "mavlink-replay-test-{}-async.tlog",
+            std::process::id()
+        ));
+        let mut writer = TlogWriter::new(std::fs::File::create(&path).unwrap());
+        let frame = MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg()),
+            protocol_version: MavlinkVersion::V2,
+        };
+        for i in 0..3 {
+            writer.write_message(i * 10_000, &frame).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let connection =
+            mavlink::connect_async::<MavMessage>(&format!("replay:{}", path.display()))
+                .await
+                .unwrap();
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            assert_eq!(connection.recv().await.unwrap().1, frame.msg);
+        }
+        assert!(start.elapsed() >= std::time::Duration::from_millis(20));
+        assert!(connection.recv().await.is_err());
+    }
+}
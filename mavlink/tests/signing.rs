@@ -3,8 +3,9 @@ mod test_shared;
 #[cfg(feature = "signing")]
 mod signing {
     use mavlink::{
-        common::HEARTBEAT_DATA, peek_reader::PeekReader, read_v2_raw_message, MAVLinkV2MessageRaw,
-        MavHeader, SigningConfig, SigningData, MAV_STX_V2,
+        common::HEARTBEAT_DATA, error::ParserError, peek_reader::PeekReader, read_v2_raw_message,
+        signing_timestamp, MAVLinkV2MessageRaw, MavFrame, MavHeader, MavlinkVersion, SigningConfig,
+        SigningData, MAV_STX_V2,
     };
 
     use crate::test_shared::SECRET_KEY;
@@ -97,4 +98,157 @@ mod signing {
             "Invalid message verified"
         );
     }
+
+    #[test]
+    pub fn test_set_timestamp() {
+        use mavlink::common::MavMessage;
+        let heartbeat_message = MavMessage::HEARTBEAT(HEARTBEAT_DATA::default());
+        let mut message = MAVLinkV2MessageRaw::new();
+        message.serialize_message_for_signing(
+            crate::test_shared::COMMON_MSG_HEADER,
+            &heartbeat_message,
+        );
+
+        let signing_cfg = SigningConfig::new(SECRET_KEY, 0, true, false);
+        let signing_data = SigningData::from_config(signing_cfg);
+        // a clock ahead of the system clock, in 2090
+        let timestamp = signing_timestamp(3_800_000_000_000_000);
+        signing_data.set_timestamp(timestamp);
+        // earlier timestamps are ignored
+        signing_data.set_timestamp(0);
+        signing_data.sign_message(&mut message);
+        assert_eq!(message.signature_timestamp(), timestamp);
+        assert_eq!(signing_timestamp(1_420_070_400_000_010), 1);
+        assert_eq!(signing_timestamp(0), 0);
+    }
+
+    #[test]
+    pub fn test_frame_deser_signed() {
+        use mavlink::common::MavMessage;
+        let signing_data = SigningData::from_config(SigningConfig::new(SECRET_KEY, 0, true, false));
+        let frame = MavFrame::<MavMessage>::deser_signed(
+            MavlinkVersion::V2,
+            HEARTBEAT_SIGNED,
+            Some(&signing_data),
+        )
+        .expect("Failed to deserialize signed frame");
+        assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
+
+        let mut other_key = SECRET_KEY;
+        other_key[0] ^= 0xff;
+        let signing_data = SigningData::from_config(SigningConfig::new(other_key, 0, true, false));
+        assert!(matches!(
+            MavFrame::<MavMessage>::deser_signed(
+                MavlinkVersion::V2,
+                HEARTBEAT_SIGNED,
+                Some(&signing_data)
+            ),
+            Err(ParserError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    pub fn test_frame_ser_signed() {
+        use mavlink::common::MavMessage;
+        let frame = MavFrame {
+            header: crate::test_shared::COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg()),
+            protocol_version: MavlinkVersion::V2,
+        };
+        let signing_cfg = SigningConfig::new(SECRET_KEY, 0, true, false);
+
+        let mut buf = [0u8; mavlink::MAX_FRAME_SIZE];
+        let len = frame
+            .ser_signed(
+                &mut buf,
+                Some(&SigningData::from_config(signing_cfg.clone())),
+            )
+            .expect("Failed to serialize signed frame");
+        assert_eq!(len, HEARTBEAT_SIGNED.len());
+        // everything up to the signature timestamp matches
+        assert_eq!(buf[..22], HEARTBEAT_SIGNED[..22]);
+
+        let parsed = MavFrame::<MavMessage>::deser_signed(
+            MavlinkVersion::V2,
+            &buf[..len],
+            Some(&SigningData::from_config(signing_cfg)),
+        )
+        .expect("Failed to verify signed frame");
+        assert_eq!(parsed.msg, frame.msg);
+
+        // the signature is not verified without signing data
+        buf[len - 1] ^= 0xff;
+        assert!(MavFrame::<MavMessage>::deser(MavlinkVersion::V2, &buf[..len]).is_ok());
+    }
+
+    #[test]
+    pub fn test_frame_parser_signed() {
+        use mavlink::common::MavMessage;
+        use mavlink::frame_parser::{FrameParser, ParseEvent};
+        use mavlink::ReadVersion;
+
+        let mut input = crate::test_shared::HEARTBEAT_V1.to_vec();
+        input.extend_from_slice(HEARTBEAT_SIGNED);
+
+        // unsigned MAVLink 1 messages are not allowed
+        let signing_data = SigningData::from_config(SigningConfig::new(SECRET_KEY, 0, true, false));
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        let events: Vec<_> = parser.feed_signed(&input, Some(&signing_data)).collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            ParseEvent::Discarded(crate::test_shared::HEARTBEAT_V1.len())
+        );
+        assert!(
+            matches!(events[1], ParseEvent::Frame(frame) if frame.raw_bytes() == HEARTBEAT_SIGNED)
+        );
+
+        // the signature was already used, so the frame is rejected as a replay
+        let events: Vec<_> = parser
+            .feed_signed(HEARTBEAT_SIGNED, Some(&signing_data))
+            .collect();
+        assert_eq!(events, [ParseEvent::Discarded(HEARTBEAT_SIGNED.len())]);
+    }
+
+    #[test]
+    pub fn test_v1_signing_ignored() {
+        use mavlink::common::MavMessage;
+        use mavlink::frame_parser::{FrameParser, ParseEvent};
+        use mavlink::ReadVersion;
+
+        use crate::test_shared::HEARTBEAT_V1;
+
+        // like the readers, MAVLink 1 only parsing ignores signing
+        let signing_data = SigningData::from_config(SigningConfig::new(SECRET_KEY, 0, true, false));
+        let mut parser = FrameParser::<MavMessage>::new(MavlinkVersion::V1.into());
+        let events: Vec<_> = parser
+            .feed_signed(HEARTBEAT_V1, Some(&signing_data))
+            .collect();
+        assert!(
+            matches!(events[..], [ParseEvent::Frame(frame)] if frame.raw_bytes() == HEARTBEAT_V1)
+        );
+
+        let frame = MavFrame::<MavMessage>::deser_signed(
+            MavlinkVersion::V1,
+            HEARTBEAT_V1,
+            Some(&signing_data),
+        )
+        .expect("Failed to deserialize MAVLink 1 frame");
+        assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
+
+        let mut reader = PeekReader::new(HEARTBEAT_V1);
+        mavlink::read_versioned_msg_signed::<MavMessage, _>(
+            &mut reader,
+            MavlinkVersion::V1.into(),
+            Some(&signing_data),
+        )
+        .expect("Failed to read MAVLink 1 frame");
+
+        // reading any version still rejects unsigned MAVLink 1 frames
+        let mut parser = FrameParser::<MavMessage>::new(ReadVersion::Any);
+        let events: Vec<_> = parser
+            .feed_signed(HEARTBEAT_V1, Some(&signing_data))
+            .collect();
+        assert_eq!(events, [ParseEvent::Discarded(HEARTBEAT_V1.len())]);
+    }
 }
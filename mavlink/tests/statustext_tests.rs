@@ -0,0 +1,58 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod statustext {
+    use mavlink::common::{MavMessage, MavSeverity, STATUSTEXT_DATA};
+    use mavlink::statustext::{Severity, StatusTextAssembler, StatusTextChunk};
+
+    // the extension fields are only present with `emit-extensions`
+    #[allow(clippy::needless_update)]
+    fn statustext(text: &str) -> MavMessage {
+        let mut buf = [0u8; 50];
+        buf[..text.len()].copy_from_slice(text.as_bytes());
+        MavMessage::STATUSTEXT(STATUSTEXT_DATA {
+            severity: MavSeverity::MAV_SEVERITY_CRITICAL,
+            text: buf,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    pub fn test_statustext_from_message() {
+        let msg = statustext("EKF variance");
+        let mut assembler = StatusTextAssembler::new();
+        let text = assembler
+            .push_message(&crate::test_shared::COMMON_MSG_HEADER, &msg)
+            .expect("unchunked text should complete immediately");
+
+        assert_eq!(text.text, "EKF variance");
+        assert_eq!(text.severity, Severity::Critical);
+        assert_eq!(
+            text.system_id,
+            crate::test_shared::COMMON_MSG_HEADER.system_id
+        );
+        assert_eq!(
+            text.component_id,
+            crate::test_shared::COMMON_MSG_HEADER.component_id
+        );
+    }
+
+    #[test]
+    pub fn test_statustext_from_raw() {
+        let msg = statustext("Arming checks failed");
+        let mut raw = mavlink::MAVLinkV2MessageRaw::new();
+        raw.serialize_message(crate::test_shared::COMMON_MSG_HEADER, &msg);
+        let raw = mavlink::MAVLinkMessageRaw::V2(raw);
+
+        let chunk = StatusTextChunk::from_raw(&raw).unwrap();
+        assert_eq!(chunk.text_bytes(), b"Arming checks failed");
+        assert_eq!(chunk.id, 0);
+
+        let mut heartbeat = mavlink::MAVLinkV2MessageRaw::new();
+        heartbeat.serialize_message(
+            crate::test_shared::COMMON_MSG_HEADER,
+            &MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg()),
+        );
+        assert!(StatusTextChunk::from_raw(&mavlink::MAVLinkMessageRaw::V2(heartbeat)).is_none());
+    }
+}
@@ -131,4 +131,62 @@ mod test_tcp_connections {
 
         server_thread.join().unwrap();
     }
+
+    /// Test whether the halves of a split TCP connection can receive and send independently.
+    #[test]
+    fn test_tcp_split() {
+        const RECEIVE_CHECK_COUNT: i32 = 5;
+
+        #[cfg(feature = "signing")]
+        let singing_cfg_server = SigningConfig::new(test_shared::SECRET_KEY, 0, true, false);
+        #[cfg(feature = "signing")]
+        let singing_cfg_client = singing_cfg_server.clone();
+
+        let msg = mavlink::common::MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server_thread = thread::spawn({
+            let msg = msg.clone();
+            move || {
+                #[allow(unused_mut)]
+                let mut server =
+                    mavlink::connect::<mavlink::common::MavMessage>("tcpin:0.0.0.0:14570")
+                        .expect("Couldn't create server");
+
+                #[cfg(feature = "signing")]
+                server.setup_signing(Some(singing_cfg_server));
+
+                let (mut receiver, mut sender) = server.split();
+                let receiver_thread = thread::spawn(move || {
+                    for _i in 0..RECEIVE_CHECK_COUNT {
+                        let (_header, msg) = receiver.recv().expect("Failed to receive message");
+                        assert!(matches!(msg, mavlink::common::MavMessage::HEARTBEAT(_)));
+                    }
+                });
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    sender.send_default(&msg).expect("Failed to send message");
+                }
+                receiver_thread.join().unwrap();
+            }
+        });
+
+        // Give some time for the server to connect
+        thread::sleep(std::time::Duration::from_millis(100));
+
+        #[allow(unused_mut)]
+        let mut client = mavlink::connect::<mavlink::common::MavMessage>("tcpout:127.0.0.1:14570")
+            .expect("Couldn't create client");
+
+        #[cfg(feature = "signing")]
+        client.setup_signing(Some(singing_cfg_client));
+
+        let (mut receiver, mut sender) = client.split();
+        for sequence in 0..RECEIVE_CHECK_COUNT {
+            sender.send_default(&msg).expect("Failed to send message");
+            let (header, received) = receiver.recv().expect("Failed to receive message");
+            assert_eq!(header.sequence, sequence as u8);
+            assert_eq!(received, msg);
+        }
+
+        server_thread.join().unwrap();
+    }
 }
@@ -0,0 +1,244 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog_edit {
+    use std::cell::RefCell;
+    use std::io::{Cursor, Write};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use mavlink::common::{MavMessage, ATTITUDE_DATA};
+    use mavlink::tlog::edit::{copy, message_ids, split, Filter, Merge, Split};
+    use mavlink::tlog::{TlogReader, TlogWriter};
+    use mavlink::{MavHeader, MavlinkVersion};
+
+    /// A signed heartbeat, whose signature is not checked when copying it
+    const HEARTBEAT_SIGNED: &[u8] = &[
+        0xfd, 0x09, 0x01, 0x00, 0xef, 0x01, 0x02, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02,
+        0x03, 0x59, 0x03, 0x03, 0xc9, 0x8b, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x27, 0x18,
+        0xb1, 0x68, 0xcc, 0xf5,
+    ];
+
+    /// Writes to the last of a list of logs
+    struct LastLog(Rc<RefCell<Vec<Vec<u8>>>>);
+
+    impl Write for LastLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().last_mut().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn record(log: &mut Vec<u8>, timestamp: u64, system_id: u8, msg: &MavMessage) {
+        log.extend_from_slice(&timestamp.to_be_bytes());
+        let header = MavHeader {
+            system_id,
+            ..crate::test_shared::COMMON_MSG_HEADER
+        };
+        mavlink::write_versioned_msg(log, MavlinkVersion::V2, header, msg).unwrap();
+    }
+
+    fn attitude(time_boot_ms: u32) -> MavMessage {
+        MavMessage::ATTITUDE(ATTITUDE_DATA {
+            time_boot_ms,
+            ..Default::default()
+        })
+    }
+
+    fn reader(log: &[u8]) -> TlogReader<Cursor<Vec<u8>>> {
+        TlogReader::new(Cursor::new(log.to_vec()))
+    }
+
+    /// Timestamps and system ids of the frames of a log
+    fn frames(log: Vec<u8>) -> Vec<(u64, u8)> {
+        reader(&log)
+            .raw_frames::<MavMessage>()
+            .map(|frame| {
+                let (timestamp, frame) = frame.unwrap();
+                (timestamp, frame.system_id())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter() {
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        let mut log = Vec::new();
+        for i in 0..10 {
+            record(&mut log, i * 100, 1, &attitude(i as u32));
+            record(&mut log, i * 100, 2, &heartbeat);
+            record(&mut log, i * 100, 3, &attitude(i as u32));
+        }
+
+        let filter = Filter {
+            excluded_messages: message_ids::<MavMessage>(&["ATTITUDE"]).unwrap(),
+            ..Default::default()
+        };
+        let mut writer = TlogWriter::new(Vec::new());
+        assert_eq!(
+            copy(
+                reader(&log).raw_frames::<MavMessage>(),
+                &filter,
+                &mut writer
+            )
+            .unwrap(),
+            10
+        );
+        assert!(frames(writer.into_inner())
+            .iter()
+            .all(|(_, system_id)| *system_id == 2));
+
+        let filter = Filter {
+            messages: message_ids::<MavMessage>(&["ATTITUDE", "HEARTBEAT"]).unwrap(),
+            excluded_systems: vec![3],
+            start: Some(200),
+            end: Some(400),
+            ..Default::default()
+        };
+        let mut writer = TlogWriter::new(Vec::new());
+        copy(
+            reader(&log).raw_frames::<MavMessage>(),
+            &filter,
+            &mut writer,
+        )
+        .unwrap();
+        assert_eq!(
+            frames(writer.into_inner()),
+            [(200, 1), (200, 2), (300, 1), (300, 2)]
+        );
+
+        assert!(message_ids::<MavMessage>(&["ATTITUDE", "NOT_A_MESSAGE"]).is_err());
+    }
+
+    #[test]
+    fn test_signed_frames_are_copied_unchanged() {
+        let mut log = 100u64.to_be_bytes().to_vec();
+        log.extend_from_slice(HEARTBEAT_SIGNED);
+        record(&mut log, 200, 1, &attitude(0));
+
+        let mut writer = TlogWriter::new(Vec::new());
+        copy(
+            reader(&log).raw_frames::<MavMessage>(),
+            &Filter::default(),
+            &mut writer,
+        )
+        .unwrap();
+        assert_eq!(writer.into_inner(), log);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        for i in 0..5 {
+            record(&mut first, i * 100, 1, &attitude(i as u32));
+            // the second station received the same frames a bit later
+            record(&mut second, i * 100 + 20, 1, &attitude(i as u32));
+            record(&mut second, i * 100 + 50, 2, &attitude(i as u32));
+        }
+        record(&mut second, 1000, 1, &attitude(0));
+
+        let merge = Merge::<_, MavMessage>::new(vec![reader(&first), reader(&second)]).unwrap();
+        let mut writer = TlogWriter::new(Vec::new());
+        assert_eq!(copy(merge, &Filter::default(), &mut writer).unwrap(), 16);
+        let merged = frames(writer.into_inner());
+        assert!(merged.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        let mut merge = Merge::<_, MavMessage>::new(vec![reader(&first), reader(&second)])
+            .unwrap()
+            .skip_duplicates(Duration::from_micros(100));
+        let mut writer = TlogWriter::new(Vec::new());
+        copy(merge.by_ref(), &Filter::default(), &mut writer).unwrap();
+        assert_eq!(merge.duplicates(), 5);
+        let merged = frames(writer.into_inner());
+        assert_eq!(merged.len(), 11);
+        assert_eq!(&merged[..4], [(0, 1), (50, 2), (100, 1), (150, 2)]);
+        // the same frame long after is not a duplicate
+        assert_eq!(merged[10], (1000, 1));
+    }
+
+    #[test]
+    fn test_split() {
+        let mut log = Vec::new();
+        for timestamp in [0, 100, 200, 10_000, 10_100, 10_200, 10_300] {
+            record(&mut log, timestamp, 1, &attitude(0));
+        }
+        let record_size = log.len() as u64 / 7;
+
+        // timestamps of the frames of each log
+        let split_log = |filter: &Filter, options: &Split| {
+            let logs = Rc::new(RefCell::new(Vec::new()));
+            let count = split(
+                reader(&log).raw_frames::<MavMessage>(),
+                filter,
+                options,
+                |index| {
+                    assert_eq!(index, logs.borrow().len());
+                    logs.borrow_mut().push(Vec::new());
+                    Ok(LastLog(logs.clone()))
+                },
+            )
+            .unwrap();
+            let logs = logs.take();
+            assert_eq!(count, logs.len());
+            logs.into_iter()
+                .map(|log| {
+                    frames(log)
+                        .into_iter()
+                        .map(|(timestamp, _)| timestamp)
+                        .collect()
+                })
+                .collect::<Vec<Vec<_>>>()
+        };
+
+        let gaps = Split {
+            max_gap: Some(Duration::from_millis(1)),
+            ..Default::default()
+        };
+        assert_eq!(
+            split_log(&Filter::default(), &gaps),
+            [vec![0, 100, 200], vec![10_000, 10_100, 10_200, 10_300]]
+        );
+        let sizes = Split {
+            max_size: Some(record_size * 3),
+            ..Default::default()
+        };
+        assert_eq!(
+            split_log(&Filter::default(), &sizes),
+            [
+                vec![0, 100, 200],
+                vec![10_000, 10_100, 10_200],
+                vec![10_300]
+            ]
+        );
+        let durations = Split {
+            max_duration: Some(Duration::from_micros(150)),
+            ..Default::default()
+        };
+        assert_eq!(
+            split_log(&Filter::default(), &durations),
+            [
+                vec![0, 100],
+                vec![200],
+                vec![10_000, 10_100],
+                vec![10_200, 10_300]
+            ]
+        );
+        let late = Filter {
+            start: Some(10_000),
+            ..Default::default()
+        };
+        assert_eq!(
+            split_log(&late, &gaps),
+            [vec![10_000, 10_100, 10_200, 10_300]]
+        );
+        let nothing = Filter {
+            systems: vec![9],
+            ..Default::default()
+        };
+        assert!(split_log(&nothing, &gaps).is_empty());
+    }
+}
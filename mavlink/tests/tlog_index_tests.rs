@@ -0,0 +1,191 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog_index {
+    use std::io::Cursor;
+
+    use mavlink::common::{MavMessage, ATTITUDE_DATA};
+    use mavlink::tlog::index::{sidecar_path, Query, TlogIndex};
+    use mavlink::tlog::TlogReader;
+    use mavlink::{MavHeader, MavlinkVersion, Message};
+
+    fn record(log: &mut Vec<u8>, timestamp: u64, system_id: u8, msg: &MavMessage) {
+        log.extend_from_slice(&timestamp.to_be_bytes());
+        let header = MavHeader {
+            system_id,
+            ..crate::test_shared::COMMON_MSG_HEADER
+        };
+        mavlink::write_versioned_msg(log, MavlinkVersion::V2, header, msg).unwrap();
+    }
+
+    fn attitude(time_boot_ms: u32) -> MavMessage {
+        MavMessage::ATTITUDE(ATTITUDE_DATA {
+            time_boot_ms,
+            ..Default::default()
+        })
+    }
+
+    /// Attitude and heartbeats of two systems every 100 ms, with some garbage in between
+    fn log() -> Vec<u8> {
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        let mut log = Vec::new();
+        for i in 0..100 {
+            let timestamp = 1_000_000 + i * 100_000;
+            for system_id in [1, 2] {
+                record(&mut log, timestamp, system_id, &attitude(i as u32));
+                record(&mut log, timestamp, system_id, &heartbeat);
+            }
+            if i == 50 {
+                log.extend_from_slice(&[0xfd, 0x09, 0x00, 0x12]);
+            }
+        }
+        log
+    }
+
+    fn times(
+        index: &TlogIndex,
+        query: &Query,
+        reader: &mut TlogReader<Cursor<Vec<u8>>>,
+    ) -> Vec<u32> {
+        index
+            .frames::<MavMessage, _>(query, reader)
+            .map(|entry| match entry.unwrap().1.msg {
+                MavMessage::ATTITUDE(attitude) => attitude.time_boot_ms,
+                msg => panic!("Unexpected message {msg:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_query() {
+        let log = log();
+        let index = TlogIndex::build::<MavMessage>(log.as_slice()).unwrap();
+        assert_eq!(index.len(), 400);
+        assert_eq!(index.indexed_len(), log.len() as u64);
+        assert_eq!(index.entries()[0].offset, 0);
+
+        let query = Query {
+            message_id: Some(30),
+            system_id: Some(1),
+            start: Some(2_000_000),
+            end: Some(7_000_000),
+            ..Default::default()
+        };
+        assert_eq!(index.query(&query).count(), 50);
+        let mut reader = TlogReader::new(Cursor::new(log.clone()));
+        assert_eq!(
+            times(&index, &query, &mut reader),
+            (10..60).collect::<Vec<_>>()
+        );
+        // the garbage in the log is not read
+        assert!(reader.corrupt_regions().is_empty());
+
+        let query = Query {
+            system_id: Some(2),
+            start: Some(10_800_000),
+            ..Default::default()
+        };
+        let entries: Vec<_> = index.query(&query).collect();
+        assert_eq!(entries.len(), 4);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.system_id == 2 && entry.timestamp >= 10_800_000));
+        assert_eq!(index.query(&Query::default()).count(), 400);
+    }
+
+    /// Test whether a log that does not start at the beginning of its reader is indexed and read correctly
+    #[test]
+    fn test_log_after_prefix() {
+        let log = log();
+        let prefix = b"not a part of the log";
+        let mut data = prefix.to_vec();
+        data.extend_from_slice(&log[..log.len() / 2]);
+
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(prefix.len() as u64);
+        let mut index = TlogIndex::build::<MavMessage>(&mut cursor).unwrap();
+        cursor.get_mut().extend_from_slice(&log[log.len() / 2..]);
+        cursor.set_position(prefix.len() as u64);
+        index.update::<MavMessage>(&mut cursor).unwrap();
+        assert_eq!(index.len(), 400);
+        assert_eq!(index.indexed_len(), log.len() as u64);
+
+        // reading backwards from the end of the log seeks the underlying reader
+        cursor.set_position(prefix.len() as u64);
+        let mut reader = TlogReader::new(cursor);
+        let query = Query {
+            message_id: Some(30),
+            system_id: Some(1),
+            ..Default::default()
+        };
+        let mut entries: Vec<_> = index.query(&query).copied().collect();
+        entries.reverse();
+        for (entry, time_boot_ms) in entries.iter().zip((0..100).rev()) {
+            let (_, frame) = reader.read_indexed::<MavMessage>(entry).unwrap();
+            let msg =
+                MavMessage::parse(MavlinkVersion::V2, frame.message_id(), frame.payload()).unwrap();
+            assert_eq!(msg, attitude(time_boot_ms));
+        }
+    }
+
+    #[test]
+    fn test_unsorted_log() {
+        let mut log = Vec::new();
+        for (timestamp, time_boot_ms) in [(300, 0), (100, 1), (200, 2), (100, 3)] {
+            record(&mut log, timestamp, 1, &attitude(time_boot_ms));
+        }
+        let index = TlogIndex::build::<MavMessage>(log.as_slice()).unwrap();
+        let query = Query {
+            start: Some(100),
+            end: Some(250),
+            ..Default::default()
+        };
+        let mut reader = TlogReader::new(Cursor::new(log));
+        assert_eq!(times(&index, &query, &mut reader), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sidecar() {
+        let path =
+            std::env::temp_dir().join(format!("mavlink-index-test-{}.tlog", std::process::id()));
+        let sidecar = sidecar_path(&path);
+        let mut log = log();
+        std::fs::write(&path, &log).unwrap();
+        let _ = std::fs::remove_file(&sidecar);
+
+        let index = TlogIndex::open::<MavMessage>(&path).unwrap();
+        assert_eq!(index.len(), 400);
+        assert_eq!(TlogIndex::load(&sidecar).unwrap(), index);
+
+        // records appended to the log are indexed, including one truncated when the index was built
+        record(&mut log, 20_000_000, 1, &attitude(1000));
+        std::fs::write(&path, &log[..log.len() - 3]).unwrap();
+        assert_eq!(TlogIndex::open::<MavMessage>(&path).unwrap().len(), 400);
+        std::fs::write(&path, &log).unwrap();
+        let index = TlogIndex::open::<MavMessage>(&path).unwrap();
+        assert_eq!(index.len(), 401);
+        assert_eq!(TlogIndex::load(&sidecar).unwrap(), index);
+
+        // a different log is indexed again
+        let mut other = Vec::new();
+        record(&mut other, 5, 3, &attitude(0));
+        std::fs::write(&path, &other).unwrap();
+        let index = TlogIndex::open::<MavMessage>(&path).unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.entries()[0].system_id, 3);
+
+        // an index that does not match its log is an error when reading frames
+        let stale = TlogIndex::build::<MavMessage>(log.as_slice()).unwrap();
+        let mut reader = TlogReader::new(std::fs::File::open(&path).unwrap());
+        let query = Query::default();
+        let mut frames = stale.frames::<MavMessage, _>(&query, &mut reader);
+        assert!(frames.next().unwrap().is_err());
+
+        std::fs::write(&sidecar, b"garbage").unwrap();
+        assert!(TlogIndex::load(&sidecar).is_err());
+        assert_eq!(TlogIndex::open::<MavMessage>(&path).unwrap().len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&sidecar).unwrap();
+    }
+}
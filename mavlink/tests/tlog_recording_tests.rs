@@ -0,0 +1,250 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog_recording {
+    use std::fs::File;
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use mavlink::common::MavMessage;
+    use mavlink::tlog::{RotatingTlogWriter, Rotation, TlogReader, TlogSink, TlogWriter};
+    use mavlink::{
+        MavConnection, MavFrame, MavHeader, MavlinkVersion, MemoryConnection, RecordingConnection,
+    };
+
+    fn heartbeat(id: u32) -> MavMessage {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavMessage::HEARTBEAT(heartbeat)
+    }
+
+    fn id(msg: &MavMessage) -> u32 {
+        match msg {
+            MavMessage::HEARTBEAT(heartbeat) => heartbeat.custom_mode,
+            _ => panic!("Unexpected message {msg:?}"),
+        }
+    }
+
+    fn log_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("mavlink-tlog-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read_ids(path: &Path) -> Vec<u32> {
+        let mut reader = TlogReader::new(File::open(path).unwrap());
+        let ids = reader
+            .frames::<MavMessage>()
+            .map(|entry| id(&entry.unwrap().1.msg))
+            .collect();
+        assert!(reader.corrupt_regions().is_empty());
+        ids
+    }
+
+    fn log_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_writer_roundtrip() {
+        let mut writer = TlogWriter::new(Vec::new());
+        for i in 0..5 {
+            let frame = MavFrame {
+                header: crate::test_shared::COMMON_MSG_HEADER,
+                msg: heartbeat(i),
+                protocol_version: MavlinkVersion::V2,
+            };
+            writer.write_message(1_000 + u64::from(i), &frame).unwrap();
+        }
+
+        let mut reader = TlogReader::new(Cursor::new(writer.into_inner()));
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap())
+            .collect();
+        assert_eq!(frames.len(), 5);
+        for (i, (timestamp, frame)) in frames.into_iter().enumerate() {
+            assert_eq!(timestamp, 1_000 + i as u64);
+            assert_eq!(frame.header, crate::test_shared::COMMON_MSG_HEADER);
+            assert_eq!(id(&frame.msg), i as u32);
+        }
+    }
+
+    /// Test whether both directions of a connection are recorded
+    #[test]
+    fn test_recording_connection() {
+        let dir = log_dir("connection");
+        let (a, b) = MemoryConnection::pair();
+        let log = RotatingTlogWriter::new(dir.join("flight.tlog"), Rotation::default()).unwrap();
+        let path = log.path().to_path_buf();
+        let a = RecordingConnection::new(Box::new(a), log);
+
+        let start = mavlink::tlog::timestamp_now();
+        for i in 0..3 {
+            a.send_default(&heartbeat(i)).unwrap();
+            assert_eq!(id(&b.recv().unwrap().1), i);
+            b.send_default(&heartbeat(10 + i)).unwrap();
+            assert_eq!(id(&a.recv().unwrap().1), 10 + i);
+        }
+        a.flush_log().unwrap();
+
+        assert_eq!(path, dir.join("flight-000.tlog"));
+        assert_eq!(read_ids(&path), vec![0, 10, 1, 11, 2, 12]);
+        let mut reader = TlogReader::new(File::open(&path).unwrap());
+        let (timestamp, _) = reader.read_raw::<MavMessage>().unwrap().unwrap();
+        assert!(timestamp >= start && timestamp <= mavlink::tlog::timestamp_now());
+    }
+
+    /// Test whether frames are recorded exactly as sent and received
+    #[test]
+    fn test_recorded_frames_unchanged() {
+        let dir = log_dir("unchanged");
+        let (a, b) = MemoryConnection::pair();
+        let log = TlogWriter::new(File::create(dir.join("flight.tlog")).unwrap());
+        let a = RecordingConnection::new(Box::new(a), log);
+        let header = MavHeader {
+            sequence: 42,
+            ..crate::test_shared::COMMON_MSG_HEADER
+        };
+
+        let mut frames = Vec::new();
+        for i in 0..3 {
+            a.send(&header, &heartbeat(i)).unwrap();
+            frames.push(MavConnection::<MavMessage>::recv_raw(&b).unwrap());
+            b.send(&header, &heartbeat(10 + i)).unwrap();
+            frames.push(MavConnection::<MavMessage>::try_recv_raw(&a).unwrap());
+        }
+        a.send_raw(&frames[1]).unwrap();
+        frames.push(MavConnection::<MavMessage>::recv_raw(&b).unwrap());
+        a.flush_log().unwrap();
+
+        let mut reader = TlogReader::new(File::open(dir.join("flight.tlog")).unwrap());
+        let recorded: Vec<_> = reader
+            .raw_frames::<MavMessage>()
+            .map(|entry| entry.unwrap().1.raw_bytes().to_vec())
+            .collect();
+        let frames: Vec<_> = frames.iter().map(|raw| raw.raw_bytes().to_vec()).collect();
+        assert_eq!(recorded, frames);
+    }
+
+    #[test]
+    fn test_rotation_by_size() {
+        let dir = log_dir("size");
+        let record_size = 8 + 21;
+        let mut log = RotatingTlogWriter::new(
+            dir.join("flight.tlog"),
+            Rotation {
+                max_size: Some(3 * record_size + 5),
+                ..Rotation::default()
+            },
+        )
+        .unwrap();
+        let mut buf = [0; mavlink::MAX_FRAME_SIZE];
+        for i in 0..10 {
+            let frame = MavFrame {
+                header: crate::test_shared::COMMON_MSG_HEADER,
+                msg: heartbeat(i),
+                protocol_version: MavlinkVersion::V2,
+            };
+            let len = frame.ser(&mut buf).unwrap();
+            assert_eq!(len as u64 + 8, record_size);
+            log.record(u64::from(i), &buf[..len]).unwrap();
+        }
+        log.flush().unwrap();
+
+        let files = log_files(&dir);
+        assert_eq!(files.len(), 4);
+        let ids: Vec<_> = files.iter().map(|file| read_ids(file)).collect();
+        assert_eq!(
+            ids,
+            vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9]]
+        );
+
+        // existing logs are not overwritten
+        let log = RotatingTlogWriter::new(dir.join("flight.tlog"), Rotation::default()).unwrap();
+        assert_eq!(log.path(), dir.join("flight-004.tlog"));
+    }
+
+    #[test]
+    fn test_rotation_by_time() {
+        let dir = log_dir("time");
+        let mut log = RotatingTlogWriter::new(
+            dir.join("flight.tlog"),
+            Rotation {
+                max_duration: Some(Duration::from_millis(20)),
+                ..Rotation::default()
+            },
+        )
+        .unwrap();
+        log.record(1, &[0xfd]).unwrap();
+        log.record(2, &[0xfd]).unwrap();
+        std::thread::sleep(Duration::from_millis(30));
+        log.record(3, &[0xfd]).unwrap();
+        log.flush().unwrap();
+
+        let sizes: Vec<_> = log_files(&dir)
+            .iter()
+            .map(|file| std::fs::metadata(file).unwrap().len())
+            .collect();
+        assert_eq!(sizes, vec![18, 9]);
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_tlog_async_recording {
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    use mavlink::common::MavMessage;
+    use mavlink::tlog::{TlogReader, TlogSink, TlogWriter};
+    use mavlink::{AsyncMavConnection, AsyncMemoryConnection, AsyncRecordingConnection};
+
+    /// Sink writing into a buffer that is kept by the test
+    struct SharedSink(Arc<Mutex<TlogWriter<Vec<u8>>>>);
+
+    impl TlogSink for SharedSink {
+        fn record(&mut self, timestamp: u64, frame: &[u8]) -> std::io::Result<()> {
+            self.0.lock().unwrap().record(timestamp, frame)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_recording_connection() {
+        let (a, b) = AsyncMemoryConnection::pair();
+        let log = Arc::new(Mutex::new(TlogWriter::new(Vec::new())));
+        let a = AsyncRecordingConnection::new(Box::new(a), SharedSink(log.clone()));
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        a.send_default(&msg).await.unwrap();
+        let (_header, received): (_, MavMessage) = b.recv().await.unwrap();
+        b.send_default(&received).await.unwrap();
+        assert_eq!(a.recv().await.unwrap().1, msg);
+
+        let (mut receive_half, mut send_half) =
+            (Box::new(a) as Box<dyn AsyncMavConnection<MavMessage> + Sync + Send>).split();
+        send_half.send_default(&msg).await.unwrap();
+        AsyncMavConnection::<MavMessage>::recv(&b).await.unwrap();
+        b.send_default(&msg).await.unwrap();
+        receive_half.recv().await.unwrap();
+
+        let buffer = log.lock().unwrap().get_ref().clone();
+        let mut reader = TlogReader::new(Cursor::new(buffer));
+        let frames: Vec<_> = reader
+            .frames::<MavMessage>()
+            .map(|entry| entry.unwrap().1.msg)
+            .collect();
+        assert_eq!(frames, vec![msg; 4]);
+    }
+}
@@ -0,0 +1,153 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog {
+    use std::io::Cursor;
+
+    use mavlink::common::MavMessage;
+    use mavlink::tlog::TlogReader;
+    use mavlink::MavlinkVersion;
+
+    fn heartbeat(id: u32) -> MavMessage {
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.custom_mode = id;
+        MavMessage::HEARTBEAT(heartbeat)
+    }
+
+    fn record(log: &mut Vec<u8>, timestamp: u64, msg: &MavMessage) {
+        log.extend_from_slice(&timestamp.to_be_bytes());
+        mavlink::write_versioned_msg(
+            log,
+            MavlinkVersion::V2,
+            crate::test_shared::COMMON_MSG_HEADER,
+            msg,
+        )
+        .unwrap();
+    }
+
+    fn ids(reader: &mut TlogReader<Cursor<Vec<u8>>>) -> Vec<(u64, u32)> {
+        reader
+            .frames::<MavMessage>()
+            .map(|entry| {
+                let (timestamp, frame) = entry.unwrap();
+                match frame.msg {
+                    MavMessage::HEARTBEAT(heartbeat) => (timestamp, heartbeat.custom_mode),
+                    msg => panic!("Unexpected message {msg:?}"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_read_log() {
+        let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/log.tlog"))
+            .expect("Failed to open log");
+        let mut reader = TlogReader::new(file);
+
+        let frames: Vec<_> = reader
+            .raw_frames::<mavlink::ardupilotmega::MavMessage>()
+            .collect::<Result<_, _>>()
+            .expect("Failed to read log");
+        assert_eq!(frames.len(), 1426);
+        assert_eq!(frames[0].0, 1_632_843_969_792_995);
+        assert!(frames.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(reader.corrupt_regions().is_empty());
+        assert_eq!(reader.position(), 64088);
+    }
+
+    #[test]
+    fn test_corrupt_regions() {
+        let mut log = Vec::new();
+        record(&mut log, 100, &heartbeat(0));
+        let garbage_start = log.len() as u64;
+        log.extend_from_slice(&[0xfd, 0x09, 0x00, 0x12, 0x34]);
+        let garbage_end = log.len() as u64;
+        record(&mut log, 200, &heartbeat(1));
+        // flip a payload bit of the third record
+        let corrupted_start = log.len() as u64;
+        record(&mut log, 300, &heartbeat(2));
+        log[corrupted_start as usize + 8 + 12] ^= 0x01;
+        let corrupted_end = log.len() as u64;
+        record(&mut log, 400, &heartbeat(3));
+        // truncated record
+        let truncated_start = log.len() as u64;
+        record(&mut log, 500, &heartbeat(4));
+        log.truncate(log.len() - 3);
+
+        let mut reader = TlogReader::new(Cursor::new(log.clone()));
+        assert_eq!(ids(&mut reader), vec![(100, 0), (200, 1), (400, 3)]);
+        assert_eq!(
+            reader.corrupt_regions(),
+            &[
+                garbage_start..garbage_end,
+                corrupted_start..corrupted_end,
+                truncated_start..log.len() as u64,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_seek_time() {
+        let mut log = Vec::new();
+        for i in 0..10 {
+            record(&mut log, 1_000 * u64::from(i), &heartbeat(i));
+        }
+        let mut reader = TlogReader::new(Cursor::new(log));
+
+        reader.seek_time::<MavMessage>(4_500).unwrap();
+        assert_eq!(reader.read_frame::<MavMessage>().unwrap().unwrap().0, 5_000);
+
+        // seeking backwards
+        reader.seek_time::<MavMessage>(2_000).unwrap();
+        assert_eq!(ids(&mut reader).first(), Some(&(2_000, 2)));
+
+        reader.seek_time::<MavMessage>(20_000).unwrap();
+        assert!(reader.read_frame::<MavMessage>().unwrap().is_none());
+    }
+
+    /// Test whether seeking is relative to the position the reader started at
+    #[test]
+    fn test_seek_after_prefix() {
+        let prefix = b"not a part of the log";
+        let mut data = prefix.to_vec();
+        for i in 0..200 {
+            record(&mut data, 1_000 * u64::from(i), &heartbeat(i));
+        }
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(prefix.len() as u64);
+        let mut reader = TlogReader::new(cursor);
+
+        assert_eq!(ids(&mut reader).len(), 200);
+        reader.seek_position(0).unwrap();
+        assert_eq!(reader.read_frame::<MavMessage>().unwrap().unwrap().0, 0);
+        reader.seek_time::<MavMessage>(150_000).unwrap();
+        assert_eq!(ids(&mut reader).first(), Some(&(150_000, 150)));
+        // the prefix is never read as a part of the log
+        assert!(reader.corrupt_regions().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_messages() {
+        let mut log = Vec::new();
+        record(&mut log, 1, &heartbeat(0));
+        // a frame of a message unknown to the dialect, its CRC can not be checked
+        log.extend_from_slice(&2u64.to_be_bytes());
+        log.extend_from_slice(&[
+            0xfd, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0xff, 0xff, 0xff, 0x00,
+        ]);
+        log.extend_from_slice(&[0x12, 0x34]);
+        record(&mut log, 3, &heartbeat(1));
+
+        let mut reader = TlogReader::new(Cursor::new(log.clone()));
+        let raw: Vec<_> = reader
+            .raw_frames::<MavMessage>()
+            .map(|entry| entry.unwrap())
+            .collect();
+        assert_eq!(raw.len(), 3);
+        assert_eq!(raw[1].1.message_id(), 0xff_ffff);
+
+        let mut reader = TlogReader::new(Cursor::new(log));
+        assert_eq!(ids(&mut reader), vec![(1, 0), (3, 1)]);
+        assert!(reader.corrupt_regions().is_empty());
+    }
+}
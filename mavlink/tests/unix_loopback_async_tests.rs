@@ -0,0 +1,95 @@
+mod test_shared;
+
+#[cfg(all(feature = "tokio-1", feature = "unix", unix, feature = "common"))]
+mod test_unix_connections {
+    use std::path::PathBuf;
+
+    use mavlink::common::MavMessage;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "mavlink-async-test-{}-{name}.sock",
+            std::process::id()
+        ))
+    }
+
+    /// Test whether messages can be exchanged in both directions over a Unix stream socket using connect_async
+    #[tokio::test]
+    async fn test_unix_stream_loopback() {
+        const RECEIVE_CHECK_COUNT: i32 = 3;
+
+        let path = socket_path("stream");
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server = tokio::spawn({
+            let address = format!("unixin:{}", path.display());
+            let msg = msg.clone();
+            async move {
+                let server = mavlink::connect_async::<MavMessage>(&address)
+                    .await
+                    .expect("Couldn't create server");
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    let (_header, received) =
+                        server.recv().await.expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    server
+                        .send_default(&received)
+                        .await
+                        .expect("Failed to send message");
+                }
+            }
+        });
+
+        // Give some time for the server to bind
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let client = mavlink::connect_async::<MavMessage>(&format!("unix:{}", path.display()))
+            .await
+            .expect("Couldn't create client");
+        for _i in 0..RECEIVE_CHECK_COUNT {
+            client
+                .send_default(&msg)
+                .await
+                .expect("Failed to send message");
+            let (_header, received) = client.recv().await.expect("Failed to receive message");
+            assert_eq!(received, msg);
+        }
+
+        server.await.unwrap();
+    }
+
+    /// Test whether a Unix datagram server replies to the client using connect_async
+    #[tokio::test]
+    async fn test_unix_datagram_loopback() {
+        const RECEIVE_CHECK_COUNT: i32 = 3;
+
+        let path = socket_path("datagram");
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server =
+            mavlink::connect_async::<MavMessage>(&format!("unixgramin:{}", path.display()))
+                .await
+                .expect("Couldn't create server");
+        let client = mavlink::connect_async::<MavMessage>(&format!("unixgram:{}", path.display()))
+            .await
+            .expect("Couldn't create client");
+
+        for sequence in 0..RECEIVE_CHECK_COUNT {
+            client
+                .send_default(&msg)
+                .await
+                .expect("Failed to send message");
+            let (header, received) = server.recv().await.expect("Failed to receive message");
+            assert_eq!(header.sequence, sequence as u8);
+            server
+                .send_default(&received)
+                .await
+                .expect("Failed to send message");
+            let (_header, received) = client.recv().await.expect("Failed to receive message");
+            assert_eq!(received, msg);
+        }
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,135 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "unix", unix, feature = "common"))]
+mod test_unix_connections {
+    use std::path::PathBuf;
+    use std::thread;
+
+    use mavlink::common::MavMessage;
+    #[cfg(feature = "signing")]
+    use mavlink::SigningConfig;
+    use mavlink::{ConnectionAddress, UnixConfig, UnixMode};
+
+    #[cfg(feature = "signing")]
+    use crate::test_shared;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mavlink-test-{}-{name}.sock", std::process::id()))
+    }
+
+    #[test]
+    fn test_parse_address() {
+        for address in [
+            "unixin:/tmp/mav.sock",
+            "unix:/tmp/mav.sock",
+            "unixgramin:/tmp/mav.sock",
+            "unixgram:/tmp/mav.sock",
+        ] {
+            let parsed = ConnectionAddress::parse_address(address).expect("Failed to parse");
+            assert!(matches!(parsed, ConnectionAddress::Unix(_)));
+            assert_eq!(parsed.to_string(), address);
+        }
+
+        let config = UnixConfig::new("/tmp/mav.sock".into(), UnixMode::DatagramOut);
+        assert_eq!(
+            ConnectionAddress::from(config).to_string(),
+            "unixgram:/tmp/mav.sock"
+        );
+    }
+
+    /// Test whether messages can be exchanged in both directions over a Unix stream socket
+    #[test]
+    fn test_unix_stream_loopback() {
+        const RECEIVE_CHECK_COUNT: i32 = 5;
+
+        #[cfg(feature = "signing")]
+        let singing_cfg_server = SigningConfig::new(test_shared::SECRET_KEY, 0, true, false);
+        #[cfg(feature = "signing")]
+        let singing_cfg_client = singing_cfg_server.clone();
+
+        let path = socket_path("stream");
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server_thread = thread::spawn({
+            let address = format!("unixin:{}", path.display());
+            let msg = msg.clone();
+            move || {
+                #[allow(unused_mut)]
+                let mut server =
+                    mavlink::connect::<MavMessage>(&address).expect("Couldn't create server");
+
+                #[cfg(feature = "signing")]
+                server.setup_signing(Some(singing_cfg_server));
+
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    let (_header, received) = server.recv().expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    server
+                        .send_default(&received)
+                        .expect("Failed to send message");
+                }
+            }
+        });
+
+        // Give some time for the server to bind
+        thread::sleep(std::time::Duration::from_millis(100));
+
+        #[allow(unused_mut)]
+        let mut client = mavlink::connect::<MavMessage>(&format!("unix:{}", path.display()))
+            .expect("Couldn't create client");
+
+        #[cfg(feature = "signing")]
+        client.setup_signing(Some(singing_cfg_client));
+
+        for sequence in 0..RECEIVE_CHECK_COUNT {
+            client.send_default(&msg).expect("Failed to send message");
+            let (header, received) = client.recv().expect("Failed to receive message");
+            assert_eq!(header.sequence, sequence as u8);
+            assert_eq!(received, msg);
+        }
+
+        server_thread.join().unwrap();
+        // the socket file is removed once the connection is accepted
+        assert!(!path.exists());
+    }
+
+    /// Test whether a Unix datagram server replies to the client, also through split halves
+    #[test]
+    fn test_unix_datagram_loopback() {
+        const RECEIVE_CHECK_COUNT: i32 = 5;
+
+        let path = socket_path("datagram");
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server = mavlink::connect::<MavMessage>(&format!("unixgramin:{}", path.display()))
+            .expect("Couldn't create server");
+        assert!(path.exists());
+
+        let server_thread = thread::spawn({
+            let msg = msg.clone();
+            move || {
+                let (mut receiver, mut sender) = server.split();
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    let (_header, received) = receiver.recv().expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    sender
+                        .send_default(&received)
+                        .expect("Failed to send message");
+                }
+            }
+        });
+
+        let client = mavlink::connect::<MavMessage>(&format!("unixgram:{}", path.display()))
+            .expect("Couldn't create client");
+        for sequence in 0..RECEIVE_CHECK_COUNT {
+            client.send_default(&msg).expect("Failed to send message");
+            let (header, received) = client.recv().expect("Failed to receive message");
+            assert_eq!(header.sequence, sequence as u8);
+            assert_eq!(received, msg);
+        }
+
+        server_thread.join().unwrap();
+        // dropping the server removes its socket file
+        assert!(!path.exists());
+    }
+}
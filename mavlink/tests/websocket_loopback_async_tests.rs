@@ -0,0 +1,80 @@
+mod test_shared;
+
+#[cfg(all(feature = "tokio-1", feature = "websocket", feature = "common"))]
+mod test_websocket_connections {
+    use mavlink::common::MavMessage;
+
+    /// Test whether messages can be exchanged in both directions over a WebSocket using connect_async
+    #[tokio::test]
+    async fn test_websocket_loopback() {
+        const RECEIVE_CHECK_COUNT: i32 = 3;
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server = tokio::spawn({
+            let msg = msg.clone();
+            async move {
+                let server = mavlink::connect_async::<MavMessage>("wsin:127.0.0.1:14590")
+                    .await
+                    .expect("Couldn't create server");
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    let (_header, received) =
+                        server.recv().await.expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    server
+                        .send_default(&received)
+                        .await
+                        .expect("Failed to send message");
+                }
+            }
+        });
+
+        // Give some time for the server to bind
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let client = mavlink::connect_async::<MavMessage>("ws://127.0.0.1:14590/mavlink")
+            .await
+            .expect("Couldn't create client");
+        for sequence in 0..RECEIVE_CHECK_COUNT {
+            client
+                .send_default(&msg)
+                .await
+                .expect("Failed to send message");
+            let (header, received) = client.recv().await.expect("Failed to receive message");
+            assert_eq!(header.sequence, sequence as u8);
+            assert_eq!(received, msg);
+        }
+
+        server.await.unwrap();
+    }
+
+    /// Test whether a blocking client can talk to an async server
+    #[tokio::test]
+    async fn test_websocket_blocking_client() {
+        const RECEIVE_CHECK_COUNT: i32 = 3;
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let client = std::thread::spawn({
+            let msg = msg.clone();
+            move || {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                let client = mavlink::connect::<MavMessage>("ws://127.0.0.1:14591")
+                    .expect("Couldn't create client");
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    client.send_default(&msg).expect("Failed to send message");
+                }
+            }
+        });
+
+        let server = mavlink::connect_async::<MavMessage>("wsin:127.0.0.1:14591")
+            .await
+            .expect("Couldn't create server");
+        for _i in 0..RECEIVE_CHECK_COUNT {
+            let (_header, received) = server.recv().await.expect("Failed to receive message");
+            assert_eq!(received, msg);
+        }
+
+        client.join().unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+mod test_shared;
+
+#[cfg(all(feature = "websocket", feature = "common"))]
+mod test_websocket_connections {
+    use std::thread;
+
+    use mavlink::common::MavMessage;
+    #[cfg(feature = "signing")]
+    use mavlink::SigningConfig;
+    use mavlink::{ConnectionAddress, WebSocketConfig, WebSocketMode};
+
+    #[cfg(feature = "signing")]
+    use crate::test_shared;
+
+    #[test]
+    fn test_parse_address() {
+        for address in [
+            "wsin:0.0.0.0:8080",
+            "ws://127.0.0.1:8080/mavlink",
+            "wss://example.com/mavlink",
+        ] {
+            let parsed = ConnectionAddress::parse_address(address).expect("Failed to parse");
+            assert!(matches!(parsed, ConnectionAddress::WebSocket(_)));
+            assert_eq!(parsed.to_string(), address);
+        }
+
+        // the slashes of the URL are optional
+        let parsed = ConnectionAddress::parse_address("ws:127.0.0.1:8080").unwrap();
+        assert_eq!(parsed.to_string(), "ws://127.0.0.1:8080");
+
+        let config = WebSocketConfig::new("127.0.0.1:8080".to_owned(), WebSocketMode::WsIn);
+        assert_eq!(
+            ConnectionAddress::from(config).to_string(),
+            "wsin:127.0.0.1:8080"
+        );
+    }
+
+    /// Test whether messages can be exchanged in both directions over a WebSocket
+    #[test]
+    fn test_websocket_loopback() {
+        const RECEIVE_CHECK_COUNT: i32 = 5;
+
+        #[cfg(feature = "signing")]
+        let singing_cfg_server = SigningConfig::new(test_shared::SECRET_KEY, 0, true, false);
+        #[cfg(feature = "signing")]
+        let singing_cfg_client = singing_cfg_server.clone();
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server_thread = thread::spawn({
+            let msg = msg.clone();
+            move || {
+                #[allow(unused_mut)]
+                let mut server = mavlink::connect::<MavMessage>("wsin:127.0.0.1:14580")
+                    .expect("Couldn't create server");
+
+                #[cfg(feature = "signing")]
+                server.setup_signing(Some(singing_cfg_server));
+
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    let (_header, received) = server.recv().expect("Failed to receive message");
+                    assert_eq!(received, msg);
+                    server
+                        .send_default(&received)
+                        .expect("Failed to send message");
+                }
+            }
+        });
+
+        // Give some time for the server to bind
+        thread::sleep(std::time::Duration::from_millis(100));
+
+        #[allow(unused_mut)]
+        let mut client = mavlink::connect::<MavMessage>("ws://127.0.0.1:14580/mavlink")
+            .expect("Couldn't create client");
+
+        #[cfg(feature = "signing")]
+        client.setup_signing(Some(singing_cfg_client));
+
+        for sequence in 0..RECEIVE_CHECK_COUNT {
+            client.send_default(&msg).expect("Failed to send message");
+            let (header, received) = client.recv().expect("Failed to receive message");
+            assert_eq!(header.sequence, sequence as u8);
+            assert_eq!(received, msg);
+        }
+
+        server_thread.join().unwrap();
+    }
+
+    /// Test whether a split WebSocket connection can send while the receive half waits for a message
+    #[test]
+    fn test_websocket_split() {
+        const RECEIVE_CHECK_COUNT: i32 = 5;
+
+        let msg = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let server_thread = thread::spawn({
+            let msg = msg.clone();
+            move || {
+                let server = mavlink::connect::<MavMessage>("wsin:127.0.0.1:14581")
+                    .expect("Couldn't create server");
+                let (mut receiver, mut sender) = server.split();
+                let receiver_thread = thread::spawn(move || {
+                    for _i in 0..RECEIVE_CHECK_COUNT {
+                        let (_header, received) =
+                            receiver.recv().expect("Failed to receive message");
+                        assert_eq!(received, msg);
+                    }
+                });
+                // the receiver is waiting while these messages are sent
+                for _i in 0..RECEIVE_CHECK_COUNT {
+                    sender
+                        .send_default(&MavMessage::HEARTBEAT(
+                            crate::test_shared::get_heartbeat_msg(),
+                        ))
+                        .expect("Failed to send message");
+                }
+                receiver_thread.join().unwrap();
+            }
+        });
+
+        // Give some time for the server to bind
+        thread::sleep(std::time::Duration::from_millis(100));
+
+        let client =
+            mavlink::connect::<MavMessage>("ws://127.0.0.1:14581").expect("Couldn't create client");
+        for _i in 0..RECEIVE_CHECK_COUNT {
+            let (_header, received) = client.recv().expect("Failed to receive message");
+            assert_eq!(received, msg);
+        }
+        assert!(client.try_recv().is_err());
+        for _i in 0..RECEIVE_CHECK_COUNT {
+            client.send_default(&msg).expect("Failed to send message");
+        }
+
+        server_thread.join().unwrap();
+    }
+}